@@ -1,5 +1,10 @@
+use std::{any::Any, cell::RefCell};
+
 pub trait Actor {
+    type Message;
+
     fn handle_messages(&mut self);
+    fn handle_message(&mut self, message: Self::Message);
 }
 
 pub type ActorResult<T> = Result<T, ActorError>;
@@ -12,4 +17,37 @@ pub enum ActorError {
     BadRequest,
     ComponentNotFound,
     ActorHasBeenDropped,
+    /// A blocking request was sent to the actor driven by the current thread,
+    /// which can never be answered since the thread is waiting on itself.
+    WouldDeadlock,
+    /// The request depends on something that is still being prepared, such as a chunk
+    /// that is being generated. Retrying once it is ready will succeed.
+    Pending,
+    /// Preparing something the request depends on failed, such as a chunk generator that
+    /// panicked. Retrying starts over.
+    GenerationFailed,
+}
+
+thread_local! {
+    static CURRENT_ACTOR: RefCell<Option<Box<dyn Any>>> = const { RefCell::new(None) };
+}
+
+/// Marks the current thread as the one driving the actor identified by `id`.
+/// Only the first actor to enter a thread is recorded.
+pub fn enter_actor<T: Any>(id: impl FnOnce() -> T) {
+    CURRENT_ACTOR.with_borrow_mut(|current| {
+        if current.is_none() {
+            *current = Some(Box::new(id()));
+        }
+    });
+}
+
+/// Returns true if the current thread is driving an actor whose identity matches `f`.
+pub fn is_current_actor<T: Any>(f: impl FnOnce(&T) -> bool) -> bool {
+    CURRENT_ACTOR.with_borrow(|current| {
+        current
+            .as_ref()
+            .and_then(|id| id.downcast_ref::<T>())
+            .is_some_and(f)
+    })
 }
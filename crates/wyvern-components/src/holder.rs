@@ -6,8 +6,13 @@ pub trait DataComponentHolder {
     fn component_map(&self) -> &DataComponentMap;
     fn component_map_mut(&mut self) -> &mut DataComponentMap;
 
+    /// Called after `set` or `with` changes a component, so holders that cache
+    /// data derived from their components can refresh it.
+    fn components_changed(&mut self) {}
+
     fn set<T: 'static + ComponentElement>(&mut self, kind: DataComponentType<T>, value: T) {
         self.component_map_mut().set(kind, value);
+        self.components_changed();
     }
 
     fn with<T: 'static + ComponentElement>(mut self, kind: DataComponentType<T>, value: T) -> Self
@@ -15,6 +20,7 @@ pub trait DataComponentHolder {
         Self: Sized,
     {
         self.component_map_mut().set(kind, value);
+        self.components_changed();
         self
     }
 
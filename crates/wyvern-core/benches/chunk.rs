@@ -0,0 +1,42 @@
+#![feature(test)]
+
+extern crate test;
+
+use test::Bencher;
+use wyvern_components::DataComponentHolder;
+use wyvern_core::{
+    blocks::{BlockComponents, BlockState, Blocks},
+    dimension::chunk::Chunk,
+};
+use wyvern_values::IVec3;
+
+#[bench]
+fn create_chunk(b: &mut Bencher) {
+    b.iter(|| Chunk::new(-4, 20));
+}
+
+#[bench]
+fn create_tall_chunk(b: &mut Bencher) {
+    b.iter(|| Chunk::new(-32, 32));
+}
+
+#[bench]
+fn fill_chunk(b: &mut Bencher) {
+    let state = BlockState::new(Blocks::GRASS_BLOCK).with(BlockComponents::SNOWY, false);
+    let mut chunk = Chunk::new(-4, 20);
+    b.iter(|| {
+        for y in -64..320 {
+            for z in 0..16 {
+                for x in 0..16 {
+                    chunk.set_block_at(IVec3::new(x, y, z), &state);
+                }
+            }
+        }
+    });
+}
+
+#[bench]
+fn clone_block_states(b: &mut Bencher) {
+    let state = BlockState::new(Blocks::OAK_STAIRS);
+    b.iter(|| (0..4096).map(|_| state.clone()).collect::<Vec<_>>());
+}
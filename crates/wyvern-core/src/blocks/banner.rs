@@ -0,0 +1,140 @@
+use std::str::FromStr;
+
+use wyvern_components::DataComponentHolder;
+use wyvern_datatypes::{
+    compound,
+    nbt::{Nbt, NbtArray},
+};
+use wyvern_values::Id;
+
+use crate::item::{ItemComponents, ItemStack};
+
+use super::{BlockComponents, BlockState, DyeColor};
+
+/// A single pattern layer on a banner.
+/// The pattern should be a key in the `minecraft:banner_pattern` registry.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BannerLayer {
+    pub pattern: Id,
+    pub color: DyeColor,
+}
+
+/// Builds banner block states and items with a base color and layered patterns.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Banner {
+    base: DyeColor,
+    layers: Vec<BannerLayer>,
+}
+
+impl Banner {
+    pub fn new(base: DyeColor) -> Banner {
+        Banner {
+            base,
+            layers: Vec::new(),
+        }
+    }
+
+    /// Copies the base color and patterns of a banner item.
+    /// Returns `None` if the item is not a banner.
+    pub fn from_item(item: &ItemStack) -> Option<Banner> {
+        let base = DyeColor::from_str(item.kind().path().strip_suffix("_banner")?).ok()?;
+        Some(Banner {
+            base,
+            layers: item
+                .get(ItemComponents::BANNER_PATTERNS)
+                .unwrap_or_default(),
+        })
+    }
+
+    /// Adds a pattern layer on top of the existing layers.
+    pub fn pattern(mut self, pattern: Id, color: DyeColor) -> Self {
+        self.layers.push(BannerLayer { pattern, color });
+        self
+    }
+
+    pub fn base(&self) -> &DyeColor {
+        &self.base
+    }
+
+    pub fn layers(&self) -> &[BannerLayer] {
+        &self.layers
+    }
+
+    /// Creates a standing banner block with this banner's patterns.
+    pub fn block_state(&self) -> BlockState {
+        self.with_patterns(BlockState::new(Id::new(
+            "minecraft",
+            &format!("{}_banner", self.base.to_string()),
+        )))
+    }
+
+    /// Creates a wall banner block with this banner's patterns.
+    pub fn wall_block_state(&self) -> BlockState {
+        self.with_patterns(BlockState::new(Id::new(
+            "minecraft",
+            &format!("{}_wall_banner", self.base.to_string()),
+        )))
+    }
+
+    /// Creates a banner item with this banner's patterns.
+    pub fn item(&self) -> ItemStack {
+        ItemStack::new(Id::new(
+            "minecraft",
+            &format!("{}_banner", self.base.to_string()),
+        ))
+        .with(ItemComponents::BANNER_PATTERNS, self.layers.clone())
+    }
+
+    fn with_patterns(&self, state: BlockState) -> BlockState {
+        let mut patterns = NbtArray::new();
+        for layer in &self.layers {
+            let _ = patterns.push(compound! {
+                pattern: layer.pattern.into_string().as_str(),
+                color: layer.color.to_string().as_str()
+            });
+        }
+        state.with(
+            BlockComponents::CUSTOM_DATA,
+            Nbt::Compound(compound! {
+                patterns: patterns
+            }),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wyvern_datatypes::{compound, list, nbt::Nbt};
+    use wyvern_values::{IVec3, id};
+
+    use crate::{blocks::DyeColor, dimension::chunk::Chunk};
+
+    use super::Banner;
+
+    #[test]
+    fn placed_banner_keeps_patterns() {
+        let banner = Banner::new(DyeColor::White)
+            .pattern(id![minecraft:stripe_top], DyeColor::Red)
+            .pattern(id![minecraft:border], DyeColor::LightBlue);
+        let mut chunk = Chunk::new(0, 1);
+        chunk.set_block_at(IVec3::new(4, 8, 2), &banner.wall_block_state());
+
+        let entities = chunk.protocol_block_entities();
+        assert_eq!(entities.len(), 1);
+        assert_eq!(
+            chunk.block_entity_data(IVec3::new(4, 8, 2)),
+            Some(Nbt::Compound(compound! {
+                patterns: list![
+                    compound! {
+                        pattern: "minecraft:stripe_top",
+                        color: "red"
+                    },
+                    compound! {
+                        pattern: "minecraft:border",
+                        color: "light_blue"
+                    }
+                ]
+            }))
+        );
+    }
+}
@@ -55,6 +55,7 @@ generate_block_components! {
     pub const WATERLOGGED: bool = id![minecraft:waterlogged], "waterlogged";
     pub const POWERED: bool = id![minecraft:powered], "powered";
     pub const OPEN: bool = id![minecraft:open], "open";
+    pub const LIT: bool = id![minecraft:lit], "lit";
 
     pub const AXIS: Axis = id![minecraft:axis], "axis";
     pub const FACING: BlockDirection = id![minecraft:facing], "facing";
@@ -0,0 +1,184 @@
+use std::sync::LazyLock;
+
+use wyvern_values::Id;
+
+use crate::dimension::heightmap::HeightmapKind;
+
+use super::{BlockComponents, BlockState, INTERNED_STATES};
+
+pub const MAX_LIGHT: u8 = 15;
+
+/// How much light a block gives off and how much it absorbs.
+struct LightProperties {
+    emission: u8,
+    opacity: u8,
+}
+
+fn emission_of(block: &Id) -> u8 {
+    match block.path() {
+        "glowstone"
+        | "sea_lantern"
+        | "jack_o_lantern"
+        | "lantern"
+        | "shroomlight"
+        | "beacon"
+        | "conduit"
+        | "lava"
+        | "fire"
+        | "end_gateway"
+        | "end_portal"
+        | "campfire"
+        | "redstone_lamp"
+        | "ochre_froglight"
+        | "verdant_froglight"
+        | "pearlescent_froglight" => 15,
+        "torch" | "wall_torch" | "end_rod" => 14,
+        "furnace" | "blast_furnace" | "smoker" => 13,
+        "nether_portal" => 11,
+        "soul_torch" | "soul_wall_torch" | "soul_lantern" | "soul_fire" | "soul_campfire"
+        | "crying_obsidian" => 10,
+        "redstone_ore" | "deepslate_redstone_ore" => 9,
+        "redstone_torch"
+        | "redstone_wall_torch"
+        | "enchanting_table"
+        | "ender_chest"
+        | "glow_lichen" => 7,
+        "sea_pickle" | "sculk_catalyst" => 6,
+        "magma_block" | "candle" => 3,
+        "brewing_stand" | "brown_mushroom" | "dragon_egg" | "end_portal_frame" | "sculk_sensor" => {
+            1
+        }
+        path if path.ends_with("_candle") => 3,
+        _ => 0,
+    }
+}
+
+/// Blocks that are full cubes or otherwise block movement, but still let light through.
+const TRANSPARENT_PATTERNS: &[&str] = &[
+    "glass",
+    "_slab",
+    "_stairs",
+    "fence",
+    "_wall",
+    "_pane",
+    "iron_bars",
+    "_door",
+    "_trapdoor",
+    "chest",
+    "_bed",
+    "_carpet",
+    "_head",
+    "_skull",
+    "potted_",
+    "flower_pot",
+    "cactus",
+    "barrier",
+    "lantern",
+    "campfire",
+    "chain",
+    "beacon",
+    "hopper",
+    "anvil",
+    "cauldron",
+    "enchanting_table",
+    "slime_block",
+    "honey_block",
+    "scaffolding",
+    "bell",
+    "lectern",
+    "brewing_stand",
+    "end_rod",
+    "sea_pickle",
+    "conduit",
+    "daylight_detector",
+];
+
+/// Blocks that let light through, but dim it a little more than air does.
+fn is_filtering(block: &Id) -> bool {
+    let path = block.path();
+    matches!(
+        path,
+        "water" | "lava" | "bubble_column" | "ice" | "frosted_ice"
+    ) || path.ends_with("_leaves")
+}
+
+fn opacity_of(state: u32, block: &Id) -> u8 {
+    if is_filtering(block) {
+        1
+    } else if !HeightmapKind::MotionBlocking.includes(state)
+        || TRANSPARENT_PATTERNS
+            .iter()
+            .any(|pattern| block.path().contains(pattern))
+    {
+        0
+    } else {
+        MAX_LIGHT
+    }
+}
+
+/// Light properties of every protocol block state, indexed by protocol id.
+static LIGHT_PROPERTIES: LazyLock<Vec<LightProperties>> = LazyLock::new(|| {
+    INTERNED_STATES
+        .iter()
+        .enumerate()
+        .map(|(id, state)| {
+            let lit = state.components.get(BlockComponents::LIT).unwrap_or(true);
+            LightProperties {
+                emission: if lit { emission_of(&state.block) } else { 0 },
+                opacity: opacity_of(id as u32, &state.block),
+            }
+        })
+        .collect()
+});
+
+/// Returns the light level a block with the given protocol id gives off.
+pub(crate) fn light_emission(state: u32) -> u8 {
+    LIGHT_PROPERTIES
+        .get(state as usize)
+        .map_or(0, |properties| properties.emission)
+}
+
+/// Returns how many levels light loses passing through a block with the given protocol id,
+/// on top of the one level it loses with every step.
+pub(crate) fn light_opacity(state: u32) -> u8 {
+    LIGHT_PROPERTIES
+        .get(state as usize)
+        .map_or(0, |properties| properties.opacity)
+}
+
+impl BlockState {
+    /// Returns the light level this block gives off, from 0 to 15.
+    pub fn light_emission(&self) -> u8 {
+        light_emission(self.protocol_id() as u32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wyvern_components::DataComponentHolder;
+
+    use crate::blocks::{BlockComponents, BlockState, Blocks};
+
+    use super::light_opacity;
+
+    #[test]
+    fn emission_follows_lit_property() {
+        assert_eq!(BlockState::new(Blocks::GLOWSTONE).light_emission(), 15);
+        assert_eq!(BlockState::new(Blocks::STONE).light_emission(), 0);
+        let furnace = BlockState::new(Blocks::FURNACE);
+        assert_eq!(furnace.light_emission(), 0);
+        assert_eq!(
+            furnace.with(BlockComponents::LIT, true).light_emission(),
+            13
+        );
+    }
+
+    #[test]
+    fn opacity_of_common_blocks() {
+        let opacity = |state: BlockState| light_opacity(state.protocol_id() as u32);
+        assert_eq!(opacity(BlockState::new(Blocks::AIR)), 0);
+        assert_eq!(opacity(BlockState::new(Blocks::GLASS)), 0);
+        assert_eq!(opacity(BlockState::new(Blocks::WATER)), 1);
+        assert_eq!(opacity(BlockState::new(Blocks::STONE)), 15);
+    }
+}
@@ -2,14 +2,22 @@ mod properties;
 pub use properties::*;
 mod components;
 pub use components::*;
+mod banner;
+pub use banner::*;
+mod light;
+pub use light::*;
+mod skull;
 mod structure;
 pub use structure::*;
+mod transform;
+pub use transform::*;
 
 use datafix::serialization::{CodecAdapters, CodecOps, DefaultCodec, MapCodecBuilder};
 use wyvern_components::{DataComponentHolder, DataComponentMap};
 
 use std::collections::{BTreeMap, HashSet};
-use std::sync::LazyLock;
+use std::fmt::Debug;
+use std::sync::{Arc, LazyLock};
 
 use voxidian_protocol::autogenerated::block_states::{
     BLOCK_STATE_DEFAULTS, BLOCK_STATE_TO_ID, BLOCK_STATES, ID_TO_BLOCK_STATE,
@@ -28,36 +36,115 @@ pub static BLOCK_STATE_KEYS: LazyLock<HashSet<Id>> = LazyLock::new(|| {
 
 pub struct Block {}
 
-#[derive(Debug, Clone, PartialEq)]
-pub struct BlockState {
+/// The block and properties of every protocol block state, indexed by protocol id.
+pub(crate) static INTERNED_STATES: LazyLock<Vec<InternedState>> = LazyLock::new(|| {
+    let len = ID_TO_BLOCK_STATE
+        .keys()
+        .copied()
+        .max()
+        .map_or(0, |max| max as usize + 1);
+    (0..len)
+        .map(|id| {
+            let state = ID_TO_BLOCK_STATE
+                .get(&(id as i32))
+                .unwrap_or(ID_TO_BLOCK_STATE.get(&0).unwrap());
+            InternedState {
+                block: state.id.clone().into(),
+                components: array_to_components(&state.properties),
+            }
+        })
+        .collect()
+});
+
+pub(crate) struct InternedState {
     pub(crate) block: Id,
     pub(crate) components: DataComponentMap,
 }
 
+fn interned(state: u32) -> &'static InternedState {
+    &INTERNED_STATES[state as usize]
+}
+
+fn lookup_state(block: &Id, components: &DataComponentMap) -> Option<u32> {
+    BLOCK_STATE_TO_ID
+        .get(&ProtocolState {
+            id: block.clone().into(),
+            properties: components_to_array(components),
+        })
+        .map(|id| *id as u32)
+}
+
+/// A block and its properties. Block states are stored as their protocol id, so cloning one
+/// is cheap; a component map is only carried when it differs from the protocol state's,
+/// such as when `CUSTOM_DATA` is attached.
+#[derive(Clone)]
+pub struct BlockState {
+    pub(crate) state: u32,
+    pub(crate) components: Option<Arc<DataComponentMap>>,
+}
+
 impl<O: CodecOps> DefaultCodec<O> for BlockState {
     fn codec() -> impl datafix::serialization::Codec<Self, O> {
         MapCodecBuilder::new()
-            .field(Id::codec().field_of("Name", |state: &BlockState| &state.block))
+            .field(Id::codec().field_of("Name", |state: &BlockState| state.name()))
             .field(
                 BTreeMap::codec()
                     .xmap(array_to_components, components_to_array)
                     .default_field_of(
                         "Properties",
-                        |state: &BlockState| &state.components,
+                        |state: &BlockState| state.component_map(),
                         DataComponentMap::new,
                     ),
             )
-            .build(|block, components| BlockState { block, components })
+            .build(BlockState::from_parts)
     }
 }
 
 impl DataComponentHolder for BlockState {
     fn component_map(&self) -> &DataComponentMap {
-        &self.components
+        match &self.components {
+            Some(components) => components,
+            None => &interned(self.state).components,
+        }
     }
 
     fn component_map_mut(&mut self) -> &mut DataComponentMap {
-        &mut self.components
+        let state = self.state;
+        Arc::make_mut(
+            self.components
+                .get_or_insert_with(|| Arc::new(interned(state).components.clone())),
+        )
+    }
+
+    fn components_changed(&mut self) {
+        let Some(components) = &self.components else {
+            return;
+        };
+        let Some(state) = lookup_state(self.name(), components) else {
+            return;
+        };
+        self.state = state;
+        if interned(state).components == **components {
+            self.components = None;
+        }
+    }
+}
+
+impl PartialEq for BlockState {
+    fn eq(&self, other: &Self) -> bool {
+        match (&self.components, &other.components) {
+            (None, None) => self.state == other.state,
+            _ => self.name() == other.name() && self.component_map() == other.component_map(),
+        }
+    }
+}
+
+impl Debug for BlockState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BlockState")
+            .field("block", self.name())
+            .field("components", self.component_map())
+            .finish()
     }
 }
 
@@ -69,32 +156,54 @@ impl BlockState {
             .into()
     }
 
+    fn from_parts(block: Id, components: DataComponentMap) -> Self {
+        let mut state = BlockState::new(block);
+        state.components = Some(Arc::new(components));
+        state.components_changed();
+        state
+    }
+
     pub fn name(&self) -> &Id {
-        &self.block
+        &interned(self.state).block
+    }
+
+    /// Returns true if this is any kind of air.
+    pub fn is_air(&self) -> bool {
+        let name = self.name();
+        name == &Blocks::AIR || name == &Blocks::CAVE_AIR || name == &Blocks::VOID_AIR
     }
 
     pub fn protocol_id(&self) -> i32 {
-        *BLOCK_STATE_TO_ID.get(&self.into()).unwrap_or(&0)
+        match &self.components {
+            None => self.state as i32,
+            Some(components) => lookup_state(self.name(), components).unwrap_or(0) as i32,
+        }
     }
 
     pub fn from_protocol_id(id: i32) -> Self {
-        Self::from(
-            ID_TO_BLOCK_STATE
-                .get(&id)
-                .unwrap_or(ID_TO_BLOCK_STATE.get(&0).unwrap()),
-        )
+        let state = usize::try_from(id)
+            .ok()
+            .filter(|id| *id < INTERNED_STATES.len())
+            .unwrap_or(0);
+        BlockState {
+            state: state as u32,
+            components: None,
+        }
     }
 
     pub fn id_is_valid(&self) -> bool {
-        BLOCK_STATE_DEFAULTS.contains_key(&self.block.clone().into())
+        BLOCK_STATE_DEFAULTS.contains_key(&self.name().clone().into())
     }
 }
 
 impl From<&ProtocolState> for BlockState {
     fn from(value: &ProtocolState) -> Self {
-        BlockState {
-            block: value.id.clone().into(),
-            components: components::array_to_components(&value.properties),
+        match BLOCK_STATE_TO_ID.get(value) {
+            Some(id) => BlockState::from_protocol_id(*id),
+            None => BlockState::from_parts(
+                value.id.clone().into(),
+                components::array_to_components(&value.properties),
+            ),
         }
     }
 }
@@ -102,11 +211,44 @@ impl From<&ProtocolState> for BlockState {
 impl From<&BlockState> for ProtocolState {
     fn from(value: &BlockState) -> Self {
         ProtocolState {
-            id: value.block.clone().into(),
-            properties: components::components_to_array(&value.components),
+            id: value.name().clone().into(),
+            properties: components::components_to_array(value.component_map()),
         }
     }
 }
 
 pub struct Blocks;
 wyvern_macros::generate_blocks_types!();
+
+#[cfg(test)]
+mod tests {
+    use wyvern_components::DataComponentHolder;
+    use wyvern_datatypes::nbt::Nbt;
+
+    use super::{BlockComponents, BlockState, Blocks};
+
+    #[test]
+    fn property_changes_stay_interned() {
+        let snowy = BlockState::new(Blocks::GRASS_BLOCK).with(BlockComponents::SNOWY, true);
+        assert!(snowy.components.is_none());
+        assert_eq!(snowy.get(BlockComponents::SNOWY), Ok(true));
+        assert_eq!(BlockState::from_protocol_id(snowy.protocol_id()), snowy);
+
+        let plain = snowy.clone().with(BlockComponents::SNOWY, false);
+        assert_eq!(plain, BlockState::new(Blocks::GRASS_BLOCK));
+        assert_ne!(plain.protocol_id(), snowy.protocol_id());
+    }
+
+    #[test]
+    fn custom_data_is_kept() {
+        let state =
+            BlockState::new(Blocks::OAK_SIGN).with(BlockComponents::CUSTOM_DATA, Nbt::Byte(1));
+        assert!(state.components.is_some());
+        assert_eq!(state.name(), &Blocks::OAK_SIGN);
+        assert_eq!(
+            state.protocol_id(),
+            BlockState::new(Blocks::OAK_SIGN).protocol_id()
+        );
+        assert_eq!(state.get(BlockComponents::CUSTOM_DATA), Ok(Nbt::Byte(1)));
+    }
+}
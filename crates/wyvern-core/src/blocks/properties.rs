@@ -79,3 +79,23 @@ make_enum! {
         Top as "top",
         Bottom as "bottom",
 }
+
+make_enum! {
+    DyeColor =>
+        White as "white",
+        Orange as "orange",
+        Magenta as "magenta",
+        LightBlue as "light_blue",
+        Yellow as "yellow",
+        Lime as "lime",
+        Pink as "pink",
+        Gray as "gray",
+        LightGray as "light_gray",
+        Cyan as "cyan",
+        Purple as "purple",
+        Blue as "blue",
+        Brown as "brown",
+        Green as "green",
+        Red as "red",
+        Black as "black",
+}
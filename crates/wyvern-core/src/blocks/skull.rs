@@ -0,0 +1,77 @@
+use wyvern_components::DataComponentHolder;
+use wyvern_datatypes::{
+    compound, list,
+    nbt::{Nbt, NbtCompound},
+};
+
+use crate::entities::PlayerSkinData;
+
+use super::{BlockComponents, BlockState, Blocks};
+
+impl BlockState {
+    /// Creates a player head block rendering the provided base64-encoded texture payload.
+    pub fn player_head(texture: &str) -> BlockState {
+        BlockState::new(Blocks::PLAYER_HEAD).with_skull_texture(texture)
+    }
+
+    /// Sets the texture of a player head (or player wall head) to the provided
+    /// base64-encoded texture payload.
+    pub fn with_skull_texture(self, texture: &str) -> BlockState {
+        self.with(
+            BlockComponents::CUSTOM_DATA,
+            skull_profile(compound! {
+                name: "textures",
+                value: texture
+            }),
+        )
+    }
+
+    /// Sets the texture of a player head (or player wall head) to the provided skin,
+    /// such as one fetched with `Player::get_skin_for_uuid`.
+    pub fn with_skull_skin(self, skin: &PlayerSkinData) -> BlockState {
+        self.with(
+            BlockComponents::CUSTOM_DATA,
+            skull_profile(compound! {
+                name: "textures",
+                value: skin.texture.as_str(),
+                signature: skin.signature.as_str()
+            }),
+        )
+    }
+}
+
+fn skull_profile(property: NbtCompound) -> Nbt {
+    Nbt::Compound(compound! {
+        profile: compound! {
+            properties: list![property]
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use wyvern_datatypes::{compound, list, nbt::Nbt};
+    use wyvern_values::IVec3;
+
+    use crate::{blocks::BlockState, dimension::chunk::Chunk};
+
+    #[test]
+    fn placed_head_keeps_profile() {
+        let mut chunk = Chunk::new(0, 1);
+        chunk.set_block_at(IVec3::new(1, 2, 3), &BlockState::player_head("abc"));
+
+        let entities = chunk.protocol_block_entities();
+        assert_eq!(entities.len(), 1);
+        assert_eq!(
+            chunk.block_entity_data(IVec3::new(1, 2, 3)),
+            Some(Nbt::Compound(compound! {
+                profile: compound! {
+                    properties: list![compound! {
+                        name: "textures",
+                        value: "abc"
+                    }]
+                }
+            }))
+        );
+    }
+}
@@ -7,8 +7,9 @@ use std::{
     time::Duration,
 };
 
-use datafix::serialization::{
-    Codec, CodecAdapters, CodecOps, Codecs, DefaultCodec, MapCodecBuilder,
+use datafix::{
+    result::DataResult,
+    serialization::{Codec, CodecAdapters, CodecOps, Codecs, DefaultCodec, MapCodecBuilder},
 };
 use voxidian_protocol::{
     packet::PacketBuf,
@@ -16,12 +17,19 @@ use voxidian_protocol::{
     value::{Nbt as PtcNbt, NbtElement},
 };
 use wyvern_actors::ActorResult;
-use wyvern_datatypes::nbt::VxNbtOps;
+use wyvern_components::DataComponentHolder;
+use wyvern_datatypes::nbt::{Nbt, NbtCompound, NbtOps, VxNbtOps};
 use wyvern_values::IVec3;
 
-use crate::{dimension::Dimension, runtime::Runtime};
+use crate::{
+    dimension::{Dimension, Region},
+    runtime::Runtime,
+};
+
+use super::{BlockComponents, BlockState, Blocks, Mirror, Rotation};
 
-use super::BlockState;
+/// The data version written into captured structures, matching the supported game version.
+pub const STRUCTURE_DATA_VERSION: i32 = 4189;
 
 #[derive(Debug, Clone)]
 pub struct Structure {
@@ -58,11 +66,32 @@ impl<O: CodecOps> DefaultCodec<O> for Structure {
     }
 }
 
+/// How a structure is transformed when placed. Blocks are mirrored before being rotated, both
+/// around the origin the structure is placed at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StructurePlaceOptions {
+    pub rotation: Rotation,
+    pub mirror: Mirror,
+    /// Whether air in the structure replaces the blocks already in the dimension.
+    pub include_air: bool,
+}
+
+impl Default for StructurePlaceOptions {
+    fn default() -> Self {
+        StructurePlaceOptions {
+            rotation: Rotation::None,
+            mirror: Mirror::None,
+            include_air: true,
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct StructureBlock {
     pos: IVec3,
     state: i32,
-    nbt: (),
+    /// The block entity data of this block.
+    nbt: Option<Nbt>,
 }
 
 impl<O: CodecOps> DefaultCodec<O> for StructureBlock {
@@ -70,17 +99,91 @@ impl<O: CodecOps> DefaultCodec<O> for StructureBlock {
         MapCodecBuilder::new()
             .field(ivec3_codec().field_of("pos", |s: &StructureBlock| &s.pos))
             .field(i32::codec().field_of("state", |s: &StructureBlock| &s.state))
-            .field(Codecs::unit().default_field_of("nbt", |s: &StructureBlock| &s.nbt, || ()))
+            .field(
+                Nbt::codec()
+                    .xmap(block_data, block_data_nbt)
+                    .default_field_of("nbt", |s: &StructureBlock| &s.nbt, || None),
+            )
             .build(|pos, state, nbt| StructureBlock { pos, state, nbt })
     }
 }
 
 impl Structure {
+    /// Captures every block between two corners, both inclusive, into a structure whose
+    /// origin is the lowest corner. The region is clamped to the height of the dimension.
+    /// Structure voids are left out, and custom data attached to a block is kept as its
+    /// block entity data.
+    pub fn capture(dimension: &Dimension, from: IVec3, to: IVec3) -> ActorResult<Structure> {
+        let (min_y, max_y) = dimension.y_range()?;
+        let region = Region::new(from, to).clamp_y(min_y, max_y);
+        let origin = region.map_or(from.min(to), |region| region.min);
+        let mut structure = Structure {
+            size: region.map_or(IVec3::ZERO, |region| region.max - region.min + IVec3::ONE),
+            blocks: Vec::new(),
+            palette: Vec::new(),
+            entities: (),
+            data_version: STRUCTURE_DATA_VERSION,
+        };
+
+        let mut palette = HashMap::new();
+        for (position, block_state) in dimension.get_region_blocks(from, to)? {
+            if block_state.name() == &Blocks::STRUCTURE_VOID {
+                continue;
+            }
+            let protocol_id = block_state.protocol_id();
+            let state = *palette.entry(protocol_id).or_insert_with(|| {
+                structure
+                    .palette
+                    .push(BlockState::from_protocol_id(protocol_id));
+                structure.palette.len() as i32 - 1
+            });
+            structure.blocks.push(StructureBlock {
+                pos: position - origin,
+                state,
+                nbt: block_state.get(BlockComponents::CUSTOM_DATA).ok(),
+            });
+        }
+        Ok(structure)
+    }
+
+    /// Encodes this structure into the compound stored in `.nbt` structure files.
+    pub fn to_nbt(&self) -> DataResult<Nbt> {
+        Structure::codec().encode_start(&NbtOps, self)
+    }
+
+    /// Decodes a structure from the compound stored in `.nbt` structure files.
+    pub fn from_nbt(nbt: &Nbt) -> DataResult<Structure> {
+        Structure::codec().decode_start(&NbtOps, nbt)
+    }
+
     pub fn place(&self, dim: Dimension, base_position: IVec3) -> ActorResult<()> {
+        self.place_with(dim, base_position, StructurePlaceOptions::default())
+    }
+
+    /// Places this structure with its origin at `base_position`, mirroring and rotating both
+    /// the positions of its blocks and their directional properties.
+    pub fn place_with(
+        &self,
+        dim: Dimension,
+        base_position: IVec3,
+        options: StructurePlaceOptions,
+    ) -> ActorResult<()> {
+        let palette = self
+            .palette
+            .iter()
+            .map(|state| state.mirrored(options.mirror).rotated(options.rotation))
+            .collect::<Vec<_>>();
+
         for block in &self.blocks {
-            let new_pos = base_position + block.pos;
-            let block_state = &self.palette[block.state as usize];
-            dim.set_block(new_pos, block_state.clone())?;
+            let mut block_state = palette[block.state as usize].clone();
+            if !options.include_air && block_state.is_air() {
+                continue;
+            }
+            if let Some(data) = &block.nbt {
+                block_state.set(BlockComponents::CUSTOM_DATA, data.clone());
+            }
+            let offset = options.rotation.apply(options.mirror.apply(block.pos));
+            dim.set_block(base_position + offset, block_state)?;
         }
         Ok(())
     }
@@ -98,6 +201,19 @@ impl Structure {
     }
 }
 
+/// Reads the block entity data of a structure block, where an empty compound means it has none.
+fn block_data(nbt: &Nbt) -> Option<Nbt> {
+    match nbt {
+        Nbt::Compound(data) if data.is_empty() => None,
+        data => Some(data.clone()),
+    }
+}
+
+fn block_data_nbt(data: &Option<Nbt>) -> Nbt {
+    data.clone()
+        .unwrap_or_else(|| Nbt::Compound(NbtCompound::new()))
+}
+
 fn ivec3_codec<O: CodecOps>() -> impl Codec<IVec3, O> {
     i32::codec().list_of().xmap(
         |vec| IVec3::new(vec[0], vec[1], vec[2]),
@@ -190,3 +306,58 @@ impl StructureSplitter {
         );
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use datafix::serialization::{Codec, DefaultCodec};
+    use wyvern_datatypes::nbt::{Nbt, NbtCompound, VxNbtOps};
+    use wyvern_values::IVec3;
+
+    use crate::blocks::{BlockState, Blocks};
+
+    use super::{STRUCTURE_DATA_VERSION, Structure, StructureBlock};
+
+    #[test]
+    fn block_entity_data_survives_round_trip() {
+        let mut data = NbtCompound::new();
+        data.set("CustomName", Nbt::String("Loot".into()));
+        let structure = Structure {
+            size: IVec3::new(2, 1, 1),
+            blocks: vec![
+                StructureBlock {
+                    pos: IVec3::ZERO,
+                    state: 0,
+                    nbt: None,
+                },
+                StructureBlock {
+                    pos: IVec3::X,
+                    state: 1,
+                    nbt: Some(Nbt::Compound(data.clone())),
+                },
+            ],
+            palette: vec![
+                BlockState::new(Blocks::STONE),
+                BlockState::new(Blocks::CHEST),
+            ],
+            entities: (),
+            data_version: STRUCTURE_DATA_VERSION,
+        };
+
+        let decoded = Structure::from_nbt(&structure.to_nbt().unwrap()).unwrap();
+        assert_eq!(decoded.size, structure.size);
+        assert_eq!(decoded.palette, structure.palette);
+        assert_eq!(decoded.blocks[0].nbt, None);
+        assert_eq!(decoded.blocks[1].nbt, Some(Nbt::Compound(data.clone())));
+        assert_eq!(decoded.blocks[1].pos, IVec3::X);
+
+        // Split structures are written with the codec directly rather than `to_nbt`.
+        let encoded = Structure::codec()
+            .encode_start(&VxNbtOps, &structure)
+            .unwrap();
+        let decoded = Structure::codec()
+            .decode_start(&VxNbtOps, &encoded)
+            .unwrap();
+        assert_eq!(decoded.blocks[0].nbt, None);
+        assert_eq!(decoded.blocks[1].nbt, Some(Nbt::Compound(data)));
+    }
+}
@@ -0,0 +1,246 @@
+use wyvern_components::{DataComponentHolder, DataComponentType};
+use wyvern_values::IVec3;
+
+use super::{Axis, BlockComponents, BlockDirection, BlockState, StairShape};
+
+/// A rotation around the y axis, in steps of 90 degrees.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Rotation {
+    #[default]
+    None,
+    Clockwise90,
+    Clockwise180,
+    CounterClockwise90,
+}
+
+/// A reflection across a vertical plane.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Mirror {
+    #[default]
+    None,
+    /// Swaps north and south.
+    LeftRight,
+    /// Swaps east and west.
+    FrontBack,
+}
+
+impl Rotation {
+    /// Returns how many clockwise quarter turns this rotation makes.
+    fn quarter_turns(self) -> i32 {
+        match self {
+            Rotation::None => 0,
+            Rotation::Clockwise90 => 1,
+            Rotation::Clockwise180 => 2,
+            Rotation::CounterClockwise90 => 3,
+        }
+    }
+
+    /// Rotates a position around the origin, seen from above.
+    pub fn apply(self, pos: IVec3) -> IVec3 {
+        match self {
+            Rotation::None => pos,
+            Rotation::Clockwise90 => IVec3::new(-pos[2], pos[1], pos[0]),
+            Rotation::Clockwise180 => IVec3::new(-pos[0], pos[1], -pos[2]),
+            Rotation::CounterClockwise90 => IVec3::new(pos[2], pos[1], -pos[0]),
+        }
+    }
+}
+
+impl Mirror {
+    /// Reflects a position across the plane through the origin.
+    pub fn apply(self, pos: IVec3) -> IVec3 {
+        match self {
+            Mirror::None => pos,
+            Mirror::LeftRight => IVec3::new(pos[0], pos[1], -pos[2]),
+            Mirror::FrontBack => IVec3::new(-pos[0], pos[1], pos[2]),
+        }
+    }
+}
+
+impl BlockDirection {
+    pub fn rotate(&self, rotation: Rotation) -> BlockDirection {
+        let Some(idx) = HORIZONTAL.iter().position(|dir| dir == self) else {
+            return self.clone();
+        };
+        HORIZONTAL[(idx + rotation.quarter_turns() as usize) % 4].clone()
+    }
+
+    pub fn mirror(&self, mirror: Mirror) -> BlockDirection {
+        match (mirror, self) {
+            (Mirror::LeftRight, BlockDirection::North) => BlockDirection::South,
+            (Mirror::LeftRight, BlockDirection::South) => BlockDirection::North,
+            (Mirror::FrontBack, BlockDirection::East) => BlockDirection::West,
+            (Mirror::FrontBack, BlockDirection::West) => BlockDirection::East,
+            _ => self.clone(),
+        }
+    }
+}
+
+impl Axis {
+    pub fn rotate(&self, rotation: Rotation) -> Axis {
+        match (rotation.quarter_turns() % 2, self) {
+            (1, Axis::X) => Axis::Z,
+            (1, Axis::Z) => Axis::X,
+            _ => self.clone(),
+        }
+    }
+}
+
+impl StairShape {
+    /// Swaps left and right, as a reflection does.
+    pub fn mirror(&self) -> StairShape {
+        match self {
+            StairShape::Straight => StairShape::Straight,
+            StairShape::InnerLeft => StairShape::InnerRight,
+            StairShape::InnerRight => StairShape::InnerLeft,
+            StairShape::OuterLeft => StairShape::OuterRight,
+            StairShape::OuterRight => StairShape::OuterLeft,
+        }
+    }
+}
+
+const HORIZONTAL: [BlockDirection; 4] = [
+    BlockDirection::North,
+    BlockDirection::East,
+    BlockDirection::South,
+    BlockDirection::West,
+];
+
+/// Returns the property of blocks that connect to their neighbours, such as fences and panes,
+/// for the given direction.
+fn connection(dir: &BlockDirection) -> Option<DataComponentType<bool>> {
+    match dir {
+        BlockDirection::North => Some(BlockComponents::FACING_NORTH),
+        BlockDirection::East => Some(BlockComponents::FACING_EAST),
+        BlockDirection::South => Some(BlockComponents::FACING_SOUTH),
+        BlockDirection::West => Some(BlockComponents::FACING_WEST),
+        _ => None,
+    }
+}
+
+impl BlockState {
+    /// Returns this block state with its facing, axis, connections and banner rotation turned
+    /// by `rotation`.
+    pub fn rotated(&self, rotation: Rotation) -> BlockState {
+        if rotation == Rotation::None {
+            return self.clone();
+        }
+        let mut state = self.clone();
+        if let Ok(facing) = state.get(BlockComponents::FACING) {
+            state.set(BlockComponents::FACING, facing.rotate(rotation));
+        }
+        if let Ok(axis) = state.get(BlockComponents::AXIS) {
+            state.set(BlockComponents::AXIS, axis.rotate(rotation));
+        }
+        if let Ok(banner_rotation) = state.get(BlockComponents::BANNER_ROTATION) {
+            state.set(
+                BlockComponents::BANNER_ROTATION,
+                (banner_rotation + rotation.quarter_turns() * 4).rem_euclid(16),
+            );
+        }
+        self.map_connections(state, |dir| dir.rotate(rotation))
+    }
+
+    /// Returns this block state reflected by `mirror`, swapping the handedness of stairs.
+    pub fn mirrored(&self, mirror: Mirror) -> BlockState {
+        if mirror == Mirror::None {
+            return self.clone();
+        }
+        let mut state = self.clone();
+        if let Ok(facing) = state.get(BlockComponents::FACING) {
+            state.set(BlockComponents::FACING, facing.mirror(mirror));
+        }
+        if let Ok(shape) = state.get(BlockComponents::STAIR_SHAPE) {
+            state.set(BlockComponents::STAIR_SHAPE, shape.mirror());
+        }
+        if let Ok(banner_rotation) = state.get(BlockComponents::BANNER_ROTATION) {
+            let axis = match mirror {
+                Mirror::LeftRight => 8,
+                _ => 16,
+            };
+            state.set(
+                BlockComponents::BANNER_ROTATION,
+                (axis - banner_rotation).rem_euclid(16),
+            );
+        }
+        self.map_connections(state, |dir| dir.mirror(mirror))
+    }
+
+    /// Moves the horizontal connections of `self` to the directions given by `map`, writing
+    /// them into `state`.
+    fn map_connections(
+        &self,
+        mut state: BlockState,
+        map: impl Fn(&BlockDirection) -> BlockDirection,
+    ) -> BlockState {
+        for dir in &HORIZONTAL {
+            let (Some(from), Some(to)) = (connection(dir), connection(&map(dir))) else {
+                continue;
+            };
+            if let Ok(connected) = self.get(from) {
+                state.set(to, connected);
+            }
+        }
+        state
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wyvern_components::DataComponentHolder;
+    use wyvern_values::IVec3;
+
+    use crate::blocks::{Axis, BlockComponents, BlockDirection, BlockState, Blocks, StairShape};
+
+    use super::{Mirror, Rotation};
+
+    #[test]
+    fn rotates_positions_clockwise() {
+        let north = IVec3::new(0, 0, -1);
+        assert_eq!(Rotation::Clockwise90.apply(north), IVec3::new(1, 0, 0));
+        assert_eq!(Rotation::Clockwise180.apply(north), IVec3::new(0, 0, 1));
+        assert_eq!(
+            Rotation::CounterClockwise90.apply(north),
+            IVec3::new(-1, 0, 0)
+        );
+        assert_eq!(Mirror::LeftRight.apply(north), IVec3::new(0, 0, 1));
+    }
+
+    #[test]
+    fn rotates_and_mirrors_properties() {
+        let stairs = BlockState::new(Blocks::OAK_STAIRS)
+            .with(BlockComponents::FACING, BlockDirection::North)
+            .with(BlockComponents::STAIR_SHAPE, StairShape::InnerLeft);
+        let rotated = stairs.rotated(Rotation::Clockwise90);
+        assert_eq!(
+            rotated.get(BlockComponents::FACING),
+            Ok(BlockDirection::East)
+        );
+        assert_eq!(
+            rotated.get(BlockComponents::STAIR_SHAPE),
+            Ok(StairShape::InnerLeft)
+        );
+
+        let mirrored = stairs.mirrored(Mirror::LeftRight);
+        assert_eq!(
+            mirrored.get(BlockComponents::FACING),
+            Ok(BlockDirection::South)
+        );
+        assert_eq!(
+            mirrored.get(BlockComponents::STAIR_SHAPE),
+            Ok(StairShape::InnerRight)
+        );
+
+        let log = BlockState::new(Blocks::OAK_LOG).with(BlockComponents::AXIS, Axis::X);
+        assert_eq!(
+            log.rotated(Rotation::CounterClockwise90)
+                .get(BlockComponents::AXIS),
+            Ok(Axis::Z)
+        );
+
+        let fence = BlockState::new(Blocks::OAK_FENCE).with(BlockComponents::FACING_NORTH, true);
+        let fence = fence.rotated(Rotation::Clockwise90);
+        assert_eq!(fence.get(BlockComponents::FACING_EAST), Ok(true));
+        assert_eq!(fence.get(BlockComponents::FACING_NORTH), Ok(false));
+    }
+}
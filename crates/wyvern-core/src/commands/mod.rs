@@ -0,0 +1,552 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use voxidian_protocol::{
+    packet::s2c::play::{
+        CommandNode as PtcCommandNode, CommandNodeKind as PtcCommandNodeKind, CommandParser,
+        CommandStringKind, CommandSuggestion, CommandSuggestionsS2CPlayPacket,
+        CommandsS2CPlayPacket,
+    },
+    value::{Identifier, VarInt},
+};
+use wyvern_datatypes::text::{Text, TextColor};
+use wyvern_values::IVec3;
+
+use crate::{
+    actors::{ActorError, ActorResult},
+    events::{BoxedFuture, PlayerCommandEvent},
+    player::{Player, PlayerComponents},
+    runtime::Runtime,
+    server::Server,
+};
+
+mod parser;
+pub(crate) use parser::*;
+mod vanilla;
+pub use vanilla::VanillaCommands;
+
+pub type CommandExecutor = Arc<dyn Fn(CommandContext) -> BoxedFuture + Send + Sync>;
+pub type SuggestionProvider = Arc<dyn Fn(&CommandContext) -> Vec<String> + Send + Sync>;
+
+/// Controls how much of the input a string argument consumes.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StringKind {
+    /// A single word, ending at the next space.
+    SingleWord,
+    /// A single word, or a phrase surrounded by double quotes.
+    QuotablePhrase,
+    /// The rest of the input, including any spaces.
+    GreedyPhrase,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ArgumentType {
+    Integer { min: Option<i32>, max: Option<i32> },
+    String(StringKind),
+    BlockPos,
+    Player,
+}
+
+impl ArgumentType {
+    pub fn integer() -> ArgumentType {
+        ArgumentType::Integer {
+            min: None,
+            max: None,
+        }
+    }
+
+    pub fn integer_range(min: i32, max: i32) -> ArgumentType {
+        ArgumentType::Integer {
+            min: Some(min),
+            max: Some(max),
+        }
+    }
+
+    pub fn word() -> ArgumentType {
+        ArgumentType::String(StringKind::SingleWord)
+    }
+
+    pub fn string() -> ArgumentType {
+        ArgumentType::String(StringKind::QuotablePhrase)
+    }
+
+    pub fn greedy_string() -> ArgumentType {
+        ArgumentType::String(StringKind::GreedyPhrase)
+    }
+}
+
+/// A parsed and resolved argument value, passed to command executors.
+#[derive(Debug, Clone)]
+pub enum CommandArgument {
+    Integer(i32),
+    String(String),
+    BlockPos(IVec3),
+    Player(Player),
+}
+
+#[derive(Clone)]
+pub(crate) enum CommandNodeKind {
+    Literal(String),
+    Argument(String, ArgumentType),
+}
+
+/// A node in a command tree. Commands are built by chaining literal and argument nodes
+/// together with [`CommandNode::then`], and marking the nodes that can be run with
+/// [`CommandNode::executes`].
+#[derive(Clone)]
+pub struct CommandNode {
+    pub(crate) kind: CommandNodeKind,
+    pub(crate) children: Vec<CommandNode>,
+    pub(crate) executor: Option<CommandExecutor>,
+    pub(crate) suggestions: Option<SuggestionProvider>,
+}
+
+impl CommandNode {
+    pub fn literal(name: &str) -> CommandNode {
+        CommandNode {
+            kind: CommandNodeKind::Literal(name.to_string()),
+            children: Vec::new(),
+            executor: None,
+            suggestions: None,
+        }
+    }
+
+    pub fn argument(name: &str, kind: ArgumentType) -> CommandNode {
+        CommandNode {
+            kind: CommandNodeKind::Argument(name.to_string(), kind),
+            children: Vec::new(),
+            executor: None,
+            suggestions: None,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        match &self.kind {
+            CommandNodeKind::Literal(name) => name,
+            CommandNodeKind::Argument(name, _) => name,
+        }
+    }
+
+    pub fn then(mut self, child: CommandNode) -> Self {
+        self.children.push(child);
+        self
+    }
+
+    pub fn executes<
+        F: 'static + Future<Output = ActorResult<()>> + Send + Sync,
+        N: 'static + Fn(CommandContext) -> F + Send + Sync,
+    >(
+        mut self,
+        f: N,
+    ) -> Self {
+        self.executor = Some(Arc::new(move |ctx| Box::pin(f(ctx))));
+        self
+    }
+
+    /// Sets the function used to produce tab-completions for this argument.
+    /// The context holds the arguments that were typed before this one.
+    pub fn suggestions<F: 'static + Fn(&CommandContext) -> Vec<String> + Send + Sync>(
+        mut self,
+        f: F,
+    ) -> Self {
+        self.suggestions = Some(Arc::new(f));
+        self
+    }
+}
+
+/// Who a command is being run by.
+#[derive(Debug, Clone)]
+pub enum CommandSender {
+    Player(Player),
+    /// The server itself, such as commands typed into the console or run by scheduled tasks.
+    /// The console is allowed to run every command.
+    Console,
+}
+
+impl CommandSender {
+    /// Returns the player running the command, or `None` for the console.
+    pub fn player(&self) -> Option<&Player> {
+        match self {
+            CommandSender::Player(player) => Some(player),
+            CommandSender::Console => None,
+        }
+    }
+
+    pub fn is_console(&self) -> bool {
+        matches!(self, CommandSender::Console)
+    }
+
+    /// Sends a message to the player running the command, or logs it for the console.
+    pub fn send_message(&self, message: Text) -> ActorResult<()> {
+        match self {
+            CommandSender::Player(player) => player.send_message(message),
+            CommandSender::Console => {
+                log::info!("{}", message.to_plain());
+                Ok(())
+            }
+        }
+    }
+
+    /// Returns the block relative coordinates are resolved against.
+    fn origin(&self) -> IVec3 {
+        self.player()
+            .and_then(|player| player.get(PlayerComponents::POSITION).ok())
+            .map(|position| position.floor().as_ivec3())
+            .unwrap_or_default()
+    }
+}
+
+/// The outcome of running a command.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommandResult {
+    /// Whether the command was parsed and its executor finished without an error.
+    pub success: bool,
+    /// Every message sent back to the sender while the command ran, including errors.
+    pub messages: Vec<Text>,
+}
+
+/// The context a command is executed in, holding whoever ran it and the values of any
+/// arguments that were parsed.
+#[derive(Debug, Clone)]
+pub struct CommandContext {
+    pub sender: CommandSender,
+    pub input: String,
+    pub(crate) arguments: HashMap<String, CommandArgument>,
+    pub(crate) output: Arc<Mutex<Vec<Text>>>,
+}
+
+impl CommandContext {
+    /// Returns the player who ran the command, failing with [`ActorError::BadRequest`] if it
+    /// was run by the console.
+    pub fn sender_player(&self) -> ActorResult<Player> {
+        self.sender.player().cloned().ok_or(ActorError::BadRequest)
+    }
+
+    /// Sends a message to whoever ran the command, recording it in the command's result.
+    pub fn reply(&self, message: Text) -> ActorResult<()> {
+        self.output.lock().unwrap().push(message.clone());
+        self.sender.send_message(message)
+    }
+
+    pub fn argument(&self, name: &str) -> Option<&CommandArgument> {
+        self.arguments.get(name)
+    }
+
+    pub fn integer(&self, name: &str) -> ActorResult<i32> {
+        match self.arguments.get(name) {
+            Some(CommandArgument::Integer(value)) => Ok(*value),
+            _ => Err(ActorError::BadRequest),
+        }
+    }
+
+    pub fn string(&self, name: &str) -> ActorResult<String> {
+        match self.arguments.get(name) {
+            Some(CommandArgument::String(value)) => Ok(value.clone()),
+            _ => Err(ActorError::BadRequest),
+        }
+    }
+
+    pub fn block_pos(&self, name: &str) -> ActorResult<IVec3> {
+        match self.arguments.get(name) {
+            Some(CommandArgument::BlockPos(value)) => Ok(*value),
+            _ => Err(ActorError::BadRequest),
+        }
+    }
+
+    pub fn player(&self, name: &str) -> ActorResult<Player> {
+        match self.arguments.get(name) {
+            Some(CommandArgument::Player(value)) => Ok(value.clone()),
+            _ => Err(ActorError::BadRequest),
+        }
+    }
+}
+
+/// All of the commands registered on a server.
+#[derive(Clone, Default)]
+pub struct CommandTree {
+    pub(crate) commands: Vec<CommandNode>,
+}
+
+impl CommandTree {
+    pub(crate) fn register(&mut self, command: CommandNode) {
+        self.commands.retain(|x| x.name() != command.name());
+        self.commands.push(command);
+    }
+
+    pub(crate) fn contains(&self, name: &str) -> bool {
+        self.commands.iter().any(|x| x.name() == name)
+    }
+
+    /// Runs a command typed by a player. Commands that aren't registered are passed on to
+    /// [`PlayerCommandEvent`] if anything is listening for it.
+    pub(crate) fn dispatch(self: Arc<Self>, player: Player, input: String) {
+        Runtime::spawn_task(async move {
+            let root = input.split(' ').next().unwrap_or_default();
+            if !self.contains(root) {
+                let server = Server::get()?;
+                if !server.event_bus()?.on_command.is_empty() {
+                    server.spawn_event(PlayerCommandEvent {
+                        player,
+                        command: input,
+                    })?;
+                    return Ok(());
+                }
+            }
+            self.execute(CommandSender::Player(player), input).await?;
+            Ok(())
+        });
+    }
+
+    /// Parses and runs a command on behalf of `sender`, sending any errors back to them.
+    pub(crate) async fn execute(
+        &self,
+        sender: CommandSender,
+        input: String,
+    ) -> ActorResult<CommandResult> {
+        let failure = |message: Text| -> ActorResult<CommandResult> {
+            sender.send_message(message.clone())?;
+            Ok(CommandResult {
+                success: false,
+                messages: vec![message],
+            })
+        };
+
+        let root = input.split(' ').next().unwrap_or_default();
+        if !self.contains(root) {
+            let error =
+                CommandSyntaxError::new("Unknown or incomplete command, see below for error", 0);
+            return failure(command_error(&error.with_input(&input)));
+        }
+
+        let parsed = match parse(&self.commands, &input) {
+            Ok(parsed) => parsed,
+            Err(err) => return failure(command_error(&err.with_input(&input))),
+        };
+
+        let mut arguments = HashMap::new();
+        for (name, argument) in parsed.arguments {
+            match resolve_argument(&sender, argument) {
+                Ok(argument) => {
+                    arguments.insert(name, argument);
+                }
+                Err(message) => return failure(command_error_text(message)),
+            }
+        }
+
+        let output = Arc::new(Mutex::new(Vec::new()));
+        let result = (parsed.executor)(CommandContext {
+            sender,
+            input,
+            arguments,
+            output: output.clone(),
+        })
+        .await;
+        let messages = std::mem::take(&mut *output.lock().unwrap());
+        Ok(CommandResult {
+            success: result.is_ok(),
+            messages,
+        })
+    }
+
+    pub(crate) fn suggest(self: Arc<Self>, player: Player, id: VarInt, input: String) {
+        Runtime::spawn_task(async move {
+            let input = input.strip_prefix('/').unwrap_or(&input).to_string();
+            let targets = completion_targets(&self.commands, &input);
+            let Some(start) = targets.iter().map(|x| x.start).max() else {
+                return Ok(());
+            };
+            let partial = input[start..].to_lowercase();
+
+            let mut matches = Vec::new();
+            for target in targets.into_iter().filter(|x| x.start == start) {
+                let candidates = match &target.node.kind {
+                    CommandNodeKind::Literal(name) => vec![name.clone()],
+                    CommandNodeKind::Argument(_, _) => {
+                        let Some(provider) = &target.node.suggestions else {
+                            continue;
+                        };
+                        let sender = CommandSender::Player(player.clone());
+                        let ctx = CommandContext {
+                            input: input.clone(),
+                            arguments: target
+                                .arguments
+                                .into_iter()
+                                .filter_map(|(name, argument)| {
+                                    resolve_argument(&sender, argument)
+                                        .ok()
+                                        .map(|argument| (name, argument))
+                                })
+                                .collect(),
+                            sender,
+                            output: Arc::default(),
+                        };
+                        provider(&ctx)
+                    }
+                };
+                matches.extend(
+                    candidates
+                        .into_iter()
+                        .filter(|x| x.to_lowercase().starts_with(&partial))
+                        .map(|text| CommandSuggestion {
+                            text,
+                            tooltip: None,
+                        }),
+                );
+            }
+
+            let (start, length) = suggestion_range(&input, start);
+            player.write_packet(CommandSuggestionsS2CPlayPacket {
+                id,
+                start: VarInt::from(start),
+                length: VarInt::from(length),
+                matches: matches.into(),
+            })
+        });
+    }
+
+    pub(crate) fn to_packet(&self) -> CommandsS2CPlayPacket {
+        let mut nodes = vec![PtcCommandNode {
+            kind: PtcCommandNodeKind::Root,
+            executable: false,
+            redirect: None,
+            children: Vec::new().into(),
+        }];
+        let children = self
+            .commands
+            .iter()
+            .map(|x| flatten_node(x, &mut nodes))
+            .collect::<Vec<_>>();
+        nodes[0].children = children.into();
+
+        CommandsS2CPlayPacket {
+            nodes: nodes.into(),
+            root_index: VarInt::from(0),
+        }
+    }
+}
+
+fn flatten_node(node: &CommandNode, nodes: &mut Vec<PtcCommandNode>) -> VarInt {
+    let index = nodes.len();
+    nodes.push(PtcCommandNode {
+        kind: match &node.kind {
+            CommandNodeKind::Literal(name) => PtcCommandNodeKind::Literal { name: name.clone() },
+            CommandNodeKind::Argument(name, kind) => PtcCommandNodeKind::Argument {
+                name: name.clone(),
+                parser: match kind {
+                    ArgumentType::Integer { min, max } => CommandParser::Integer {
+                        min: *min,
+                        max: *max,
+                    },
+                    ArgumentType::String(kind) => CommandParser::String(match kind {
+                        StringKind::SingleWord => CommandStringKind::SingleWord,
+                        StringKind::QuotablePhrase => CommandStringKind::QuotablePhrase,
+                        StringKind::GreedyPhrase => CommandStringKind::GreedyPhrase,
+                    }),
+                    ArgumentType::BlockPos => CommandParser::BlockPos,
+                    ArgumentType::Player => CommandParser::Entity {
+                        single: true,
+                        players_only: true,
+                    },
+                },
+                suggestions: node
+                    .suggestions
+                    .as_ref()
+                    .map(|_| Identifier::new_const("minecraft", "ask_server")),
+            },
+        },
+        executable: node.executor.is_some(),
+        redirect: None,
+        children: Vec::new().into(),
+    });
+    let children = node
+        .children
+        .iter()
+        .map(|x| flatten_node(x, nodes))
+        .collect::<Vec<_>>();
+    nodes[index].children = children.into();
+    VarInt::from(index as i32)
+}
+
+fn resolve_argument(
+    sender: &CommandSender,
+    argument: ParsedArgument,
+) -> Result<CommandArgument, String> {
+    match argument {
+        ParsedArgument::Integer(value) => Ok(CommandArgument::Integer(value)),
+        ParsedArgument::String(value) => Ok(CommandArgument::String(value)),
+        ParsedArgument::BlockPos(coords) => {
+            let origin = sender.origin();
+            Ok(CommandArgument::BlockPos(IVec3::new(
+                coords[0].resolve(origin[0]),
+                coords[1].resolve(origin[1]),
+                coords[2].resolve(origin[2]),
+            )))
+        }
+        ParsedArgument::Player(name) => {
+            let players = Server::get()
+                .and_then(|x| x.players())
+                .map_err(|_| "No player was found".to_string())?;
+            players
+                .into_iter()
+                .find(|x| x.username().is_ok_and(|username| username == name))
+                .map(CommandArgument::Player)
+                .ok_or_else(|| "No player was found".to_string())
+        }
+    }
+}
+
+fn command_error(error: &CommandSyntaxError) -> Text {
+    command_error_text(error.to_string())
+}
+
+fn command_error_text(message: String) -> Text {
+    Text::literal(message).with_color(TextColor::new(255, 85, 85))
+}
+
+/// Returns where the text replaced by suggestions starts and how long it is, given the byte
+/// index it starts at in the input without its leading slash. The client counts both in UTF-16
+/// code units, including the slash.
+fn suggestion_range(input: &str, start: usize) -> (i32, i32) {
+    (
+        input[..start].encode_utf16().count() as i32 + 1,
+        input[start..].encode_utf16().count() as i32,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use wyvern_datatypes::text::Text;
+
+    use super::{CommandNode, CommandSender, CommandTree, suggestion_range};
+
+    #[test]
+    fn suggestion_ranges_count_utf16_units() {
+        assert_eq!(suggestion_range("tp abc", 3), (4, 3));
+        assert_eq!(suggestion_range("msg héllo wo", 11), (11, 2));
+        assert_eq!(suggestion_range("say 😀 x", 9), (8, 1));
+        assert_eq!(suggestion_range("say 😀", 4), (5, 2));
+    }
+
+    #[test]
+    fn console_commands_report_their_output() {
+        let mut tree = CommandTree::default();
+        tree.register(
+            CommandNode::literal("ping")
+                .executes(|ctx| async move { ctx.reply(Text::literal("pong")) }),
+        );
+
+        let result =
+            futures::executor::block_on(tree.execute(CommandSender::Console, "ping".into()))
+                .unwrap();
+        assert!(result.success);
+        assert_eq!(result.messages, vec![Text::literal("pong")]);
+
+        let result =
+            futures::executor::block_on(tree.execute(CommandSender::Console, "pong".into()))
+                .unwrap();
+        assert!(!result.success);
+        assert_eq!(result.messages.len(), 1);
+    }
+}
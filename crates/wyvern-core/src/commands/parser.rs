@@ -0,0 +1,482 @@
+use std::fmt::Display;
+
+use super::{ArgumentType, CommandExecutor, CommandNode, CommandNodeKind, StringKind};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Coordinate {
+    pub(crate) relative: bool,
+    pub(crate) value: i32,
+}
+
+impl Coordinate {
+    pub(crate) fn resolve(&self, origin: i32) -> i32 {
+        if self.relative {
+            origin + self.value
+        } else {
+            self.value
+        }
+    }
+}
+
+/// An argument as it was read from the input, before it is resolved against the server.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum ParsedArgument {
+    Integer(i32),
+    String(String),
+    BlockPos([Coordinate; 3]),
+    Player(String),
+}
+
+pub(crate) struct ParsedCommand {
+    pub(crate) executor: CommandExecutor,
+    pub(crate) arguments: Vec<(String, ParsedArgument)>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct CommandSyntaxError {
+    pub(crate) message: String,
+    pub(crate) cursor: usize,
+    pub(crate) context: String,
+}
+
+impl CommandSyntaxError {
+    pub(crate) fn new(message: &str, cursor: usize) -> CommandSyntaxError {
+        CommandSyntaxError {
+            message: message.to_string(),
+            cursor,
+            context: String::new(),
+        }
+    }
+
+    /// Attaches the input leading up to the error, shown after the message like vanilla does.
+    pub(crate) fn with_input(mut self, input: &str) -> CommandSyntaxError {
+        let end = input.floor_char_boundary(self.cursor);
+        let start = input[..end]
+            .char_indices()
+            .rev()
+            .nth(9)
+            .map_or(0, |(index, _)| index);
+        self.context = format!(
+            "{}{}",
+            if start > 0 { "..." } else { "" },
+            &input[start..end]
+        );
+        self
+    }
+}
+
+impl Display for CommandSyntaxError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}\n{}<--[HERE]", self.message, self.context)
+    }
+}
+
+pub(crate) struct StringReader<'a> {
+    pub(crate) input: &'a str,
+    pub(crate) cursor: usize,
+}
+
+impl<'a> StringReader<'a> {
+    fn remaining(&self) -> &'a str {
+        &self.input[self.cursor..]
+    }
+
+    fn at_end(&self) -> bool {
+        self.cursor >= self.input.len()
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.remaining().chars().next()
+    }
+
+    fn error(&self, message: &str) -> CommandSyntaxError {
+        CommandSyntaxError::new(message, self.cursor)
+    }
+
+    fn read_unquoted(&mut self) -> &'a str {
+        let start = self.cursor;
+        let len = self.remaining().find(' ').unwrap_or(self.remaining().len());
+        self.cursor += len;
+        &self.input[start..self.cursor]
+    }
+
+    fn read_quoted(&mut self) -> Result<String, CommandSyntaxError> {
+        self.cursor += 1;
+        let mut result = String::new();
+        let mut escaped = false;
+        while let Some(c) = self.peek() {
+            self.cursor += c.len_utf8();
+            if escaped {
+                result.push(c);
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == '"' {
+                return Ok(result);
+            } else {
+                result.push(c);
+            }
+        }
+        Err(self.error("Unclosed quoted string"))
+    }
+
+    fn read_integer(&mut self) -> Result<i32, CommandSyntaxError> {
+        let start = self.cursor;
+        let word = self.read_unquoted();
+        if word.is_empty() {
+            return Err(CommandSyntaxError::new("Expected integer", start));
+        }
+        word.parse()
+            .map_err(|_| CommandSyntaxError::new(&format!("Invalid integer '{}'", word), start))
+    }
+
+    fn read_coordinate(&mut self) -> Result<Coordinate, CommandSyntaxError> {
+        let start = self.cursor;
+        let word = self.read_unquoted();
+        let (relative, number) = match word.strip_prefix('~') {
+            Some(rest) => (true, rest),
+            None => (false, word),
+        };
+        if !relative && number.is_empty() {
+            return Err(CommandSyntaxError::new(
+                "Incomplete (expected 3 coordinates)",
+                start,
+            ));
+        }
+        let value = if number.is_empty() {
+            0
+        } else {
+            number
+                .parse()
+                .map_err(|_| CommandSyntaxError::new("Expected integer", start))?
+        };
+        Ok(Coordinate { relative, value })
+    }
+
+    fn skip_separator(&mut self) -> Result<(), CommandSyntaxError> {
+        if self.peek() == Some(' ') {
+            self.cursor += 1;
+            Ok(())
+        } else {
+            Err(self.error("Incomplete (expected 3 coordinates)"))
+        }
+    }
+}
+
+impl ArgumentType {
+    pub(crate) fn parse(
+        &self,
+        reader: &mut StringReader,
+    ) -> Result<ParsedArgument, CommandSyntaxError> {
+        match self {
+            ArgumentType::Integer { min, max } => {
+                let start = reader.cursor;
+                let value = reader.read_integer()?;
+                if let Some(min) = min
+                    && value < *min
+                {
+                    return Err(CommandSyntaxError::new(
+                        &format!("Integer must not be less than {}, found {}", min, value),
+                        start,
+                    ));
+                }
+                if let Some(max) = max
+                    && value > *max
+                {
+                    return Err(CommandSyntaxError::new(
+                        &format!("Integer must not be more than {}, found {}", max, value),
+                        start,
+                    ));
+                }
+                Ok(ParsedArgument::Integer(value))
+            }
+            ArgumentType::String(StringKind::SingleWord) => {
+                Ok(ParsedArgument::String(reader.read_unquoted().to_string()))
+            }
+            ArgumentType::String(StringKind::QuotablePhrase) => {
+                if reader.peek() == Some('"') {
+                    Ok(ParsedArgument::String(reader.read_quoted()?))
+                } else {
+                    Ok(ParsedArgument::String(reader.read_unquoted().to_string()))
+                }
+            }
+            ArgumentType::String(StringKind::GreedyPhrase) => {
+                let value = reader.remaining().to_string();
+                reader.cursor = reader.input.len();
+                Ok(ParsedArgument::String(value))
+            }
+            ArgumentType::BlockPos => {
+                let x = reader.read_coordinate()?;
+                reader.skip_separator()?;
+                let y = reader.read_coordinate()?;
+                reader.skip_separator()?;
+                let z = reader.read_coordinate()?;
+                Ok(ParsedArgument::BlockPos([x, y, z]))
+            }
+            ArgumentType::Player => {
+                let start = reader.cursor;
+                let name = reader.read_unquoted();
+                if name.is_empty() {
+                    return Err(CommandSyntaxError::new("Expected player name", start));
+                }
+                Ok(ParsedArgument::Player(name.to_string()))
+            }
+        }
+    }
+}
+
+/// Parses the input against the provided root nodes, returning the executor of the
+/// deepest matching node along with the arguments read on the way there.
+pub(crate) fn parse(
+    nodes: &[CommandNode],
+    input: &str,
+) -> Result<ParsedCommand, CommandSyntaxError> {
+    parse_children(nodes, input, 0, Vec::new())
+}
+
+fn parse_children(
+    children: &[CommandNode],
+    input: &str,
+    cursor: usize,
+    arguments: Vec<(String, ParsedArgument)>,
+) -> Result<ParsedCommand, CommandSyntaxError> {
+    let mut best_error: Option<CommandSyntaxError> = None;
+
+    for child in children {
+        let result = parse_node(child, input, cursor, arguments.clone());
+        match result {
+            Ok(parsed) => return Ok(parsed),
+            Err(err) => {
+                if best_error.as_ref().is_none_or(|x| err.cursor > x.cursor) {
+                    best_error = Some(err);
+                }
+            }
+        }
+    }
+
+    Err(best_error
+        .unwrap_or_else(|| CommandSyntaxError::new("Incorrect argument for command", cursor)))
+}
+
+fn parse_node(
+    node: &CommandNode,
+    input: &str,
+    cursor: usize,
+    mut arguments: Vec<(String, ParsedArgument)>,
+) -> Result<ParsedCommand, CommandSyntaxError> {
+    let mut reader = StringReader { input, cursor };
+
+    match &node.kind {
+        CommandNodeKind::Literal(name) => {
+            if reader.read_unquoted() != name {
+                return Err(CommandSyntaxError::new(
+                    "Unknown or incomplete command, see below for error",
+                    cursor,
+                ));
+            }
+        }
+        CommandNodeKind::Argument(name, kind) => {
+            arguments.push((name.clone(), kind.parse(&mut reader)?));
+        }
+    }
+
+    if reader.at_end() {
+        return match &node.executor {
+            Some(executor) => Ok(ParsedCommand {
+                executor: executor.clone(),
+                arguments,
+            }),
+            None => Err(reader.error("Unknown or incomplete command, see below for error")),
+        };
+    }
+
+    if reader.peek() != Some(' ') {
+        return Err(
+            reader.error("Expected whitespace to end one argument, but found trailing data")
+        );
+    }
+    if node.children.is_empty() {
+        return Err(reader.error("Incorrect argument for command"));
+    }
+    parse_children(&node.children, input, reader.cursor + 1, arguments)
+}
+
+/// A node that the last token of the input could be completed to.
+pub(crate) struct CompletionTarget<'a> {
+    pub(crate) start: usize,
+    pub(crate) node: &'a CommandNode,
+    pub(crate) arguments: Vec<(String, ParsedArgument)>,
+}
+
+/// Walks the input through the command tree, returning every node that the
+/// token currently being typed could belong to.
+pub(crate) fn completion_targets<'a>(
+    nodes: &'a [CommandNode],
+    input: &str,
+) -> Vec<CompletionTarget<'a>> {
+    let mut targets = Vec::new();
+    collect_targets(nodes, input, 0, Vec::new(), &mut targets);
+    targets
+}
+
+fn collect_targets<'a>(
+    children: &'a [CommandNode],
+    input: &str,
+    cursor: usize,
+    arguments: Vec<(String, ParsedArgument)>,
+    targets: &mut Vec<CompletionTarget<'a>>,
+) {
+    for child in children {
+        let mut reader = StringReader { input, cursor };
+        let mut arguments = arguments.clone();
+        let parsed = match &child.kind {
+            CommandNodeKind::Literal(name) => reader.read_unquoted() == name,
+            CommandNodeKind::Argument(name, kind) => match kind.parse(&mut reader) {
+                Ok(value) => {
+                    arguments.push((name.clone(), value));
+                    true
+                }
+                Err(_) => false,
+            },
+        };
+
+        if parsed && reader.peek() == Some(' ') {
+            collect_targets(
+                &child.children,
+                input,
+                reader.cursor + 1,
+                arguments,
+                targets,
+            );
+        } else if (parsed && reader.at_end()) || !input[cursor..].contains(' ') {
+            arguments.retain(|(name, _)| name != child.name());
+            targets.push(CompletionTarget {
+                start: cursor,
+                node: child,
+                arguments,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::commands::{ArgumentType, CommandNode};
+
+    use super::{CommandSyntaxError, Coordinate, ParsedArgument, completion_targets, parse};
+
+    fn tree() -> Vec<CommandNode> {
+        vec![
+            CommandNode::literal("give").then(
+                CommandNode::argument("target", ArgumentType::Player).then(
+                    CommandNode::argument("amount", ArgumentType::integer_range(1, 64))
+                        .executes(|_| async { Ok(()) }),
+                ),
+            ),
+            CommandNode::literal("say").then(
+                CommandNode::argument("message", ArgumentType::greedy_string())
+                    .executes(|_| async { Ok(()) }),
+            ),
+            CommandNode::literal("tag").then(
+                CommandNode::argument("name", ArgumentType::string())
+                    .executes(|_| async { Ok(()) }),
+            ),
+            CommandNode::literal("setblock").then(
+                CommandNode::argument("pos", ArgumentType::BlockPos).executes(|_| async { Ok(()) }),
+            ),
+        ]
+    }
+
+    #[test]
+    fn parses_arguments() {
+        let parsed = parse(&tree(), "give Endistic 32").ok().unwrap();
+        assert_eq!(
+            parsed.arguments,
+            vec![
+                (
+                    "target".to_string(),
+                    ParsedArgument::Player("Endistic".to_string())
+                ),
+                ("amount".to_string(), ParsedArgument::Integer(32)),
+            ]
+        );
+
+        let parsed = parse(&tree(), "say hello there world").ok().unwrap();
+        assert_eq!(
+            parsed.arguments[0].1,
+            ParsedArgument::String("hello there world".to_string())
+        );
+
+        let parsed = parse(&tree(), "tag \"two words\"").ok().unwrap();
+        assert_eq!(
+            parsed.arguments[0].1,
+            ParsedArgument::String("two words".to_string())
+        );
+
+        let parsed = parse(&tree(), "setblock ~ 64 ~-2").ok().unwrap();
+        assert_eq!(
+            parsed.arguments[0].1,
+            ParsedArgument::BlockPos([
+                Coordinate {
+                    relative: true,
+                    value: 0
+                },
+                Coordinate {
+                    relative: false,
+                    value: 64
+                },
+                Coordinate {
+                    relative: true,
+                    value: -2
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn reports_malformed_input() {
+        let err = parse(&tree(), "give Endistic 100").err().unwrap();
+        assert_eq!(err.cursor, 14);
+        assert_eq!(err.message, "Integer must not be more than 64, found 100");
+
+        let err = parse(&tree(), "give Endistic").err().unwrap();
+        assert_eq!(err.cursor, 13);
+
+        let err = parse(&tree(), "give Endistic abc").err().unwrap();
+        assert_eq!(err.message, "Invalid integer 'abc'");
+
+        assert!(parse(&tree(), "unknown").is_err());
+    }
+
+    #[test]
+    fn error_context_respects_char_boundaries() {
+        let err = parse(&tree(), "give Ünïcödé_ñämé abc")
+            .err()
+            .unwrap()
+            .with_input("give Ünïcödé_ñämé abc");
+        assert_eq!(err.context, "...cödé_ñämé ");
+
+        let err = CommandSyntaxError::new("Unknown command", 5).with_input("日本語のコマンド");
+        assert_eq!(err.context, "日");
+    }
+
+    #[test]
+    fn finds_completion_targets() {
+        let tree = tree();
+
+        let targets = completion_targets(&tree, "gi");
+        assert_eq!(targets.len(), tree.len());
+        assert!(targets.iter().all(|x| x.start == 0));
+
+        let targets = completion_targets(&tree, "give End");
+        assert_eq!(targets.len(), 1);
+        assert_eq!(targets[0].start, 5);
+        assert_eq!(targets[0].node.name(), "target");
+        assert!(targets[0].arguments.is_empty());
+
+        let targets = completion_targets(&tree, "give Endistic ");
+        assert_eq!(targets[0].start, 14);
+        assert_eq!(targets[0].node.name(), "amount");
+        assert_eq!(targets[0].arguments.len(), 1);
+    }
+}
@@ -0,0 +1,322 @@
+use std::sync::Arc;
+
+use wyvern_components::DataComponentHolder;
+use wyvern_datatypes::{gamemode::Gamemode, text::Text, weather::Weather};
+use wyvern_values::{DVec3, IVec3, Id};
+
+use crate::{
+    actors::{ActorError, ActorResult},
+    events::BoxedFuture,
+    item::{ITEM_REGISTRY, ItemComponents, ItemStack},
+    player::{Player, PlayerComponents},
+    plugin::Plugin,
+    server::{Server, ServerBuilder},
+};
+
+use super::{ArgumentType, CommandContext, CommandNode, CommandSender, command_error_text};
+
+type PermissionCheck = Arc<dyn Fn(&Player) -> bool + Send + Sync>;
+
+/// The `OP_LEVEL` players need to run these commands by default, matching vanilla.
+const REQUIRED_OP_LEVEL: u8 = 2;
+
+/// A plugin registering a baseline of vanilla commands: `/say`, `/tp`, `/gamemode`, `/give`,
+/// `/kick`, `/time` and `/weather`.
+///
+/// The console can always run them. Players need an `OP_LEVEL` of 2 or more, unless a different
+/// check is set with [`VanillaCommands::permission`] or every player is let through with
+/// [`VanillaCommands::allow_all`]. `/time` and `/weather` change the time and weather of every
+/// dimension.
+#[derive(Clone)]
+pub struct VanillaCommands {
+    permission: PermissionCheck,
+}
+
+impl Default for VanillaCommands {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl VanillaCommands {
+    pub fn new() -> VanillaCommands {
+        VanillaCommands {
+            permission: Arc::new(|player| {
+                player.get(PlayerComponents::OP_LEVEL).unwrap_or(0) >= REQUIRED_OP_LEVEL
+            }),
+        }
+    }
+
+    /// Lets every player run these commands, regardless of their `OP_LEVEL`.
+    pub fn allow_all(self) -> Self {
+        self.permission(|_| true)
+    }
+
+    /// Sets which players are allowed to run these commands.
+    pub fn permission<F: Fn(&Player) -> bool + Send + Sync + 'static>(mut self, f: F) -> Self {
+        self.permission = Arc::new(f);
+        self
+    }
+
+    /// Wraps an executor so players without permission are told so instead of running it.
+    fn guard<F, N>(&self, f: N) -> impl Fn(CommandContext) -> BoxedFuture + Send + Sync + use<F, N>
+    where
+        F: Future<Output = ActorResult<()>> + Send + Sync + 'static,
+        N: Fn(CommandContext) -> F + Send + Sync + 'static,
+    {
+        let permission = self.permission.clone();
+        move |ctx: CommandContext| -> BoxedFuture {
+            if ctx
+                .sender
+                .player()
+                .is_some_and(|player| !permission(player))
+            {
+                return Box::pin(async move {
+                    fail(&ctx, "You do not have permission to use this command")
+                });
+            }
+            Box::pin(f(ctx))
+        }
+    }
+}
+
+impl Plugin for VanillaCommands {
+    fn build(&self, builder: ServerBuilder) -> ServerBuilder {
+        builder
+            .command("say", |node| {
+                node.then(
+                    CommandNode::argument("message", ArgumentType::greedy_string())
+                        .executes(self.guard(say)),
+                )
+            })
+            .command("tp", |node| {
+                node.then(
+                    CommandNode::argument("location", ArgumentType::BlockPos).executes(self.guard(
+                        |ctx| async move {
+                            let player = sender(&ctx)?;
+                            teleport(&ctx, &player, block_center(ctx.block_pos("location")?))
+                        },
+                    )),
+                )
+                .then(
+                    CommandNode::argument("target", ArgumentType::Player)
+                        .executes(self.guard(|ctx| async move {
+                            let player = sender(&ctx)?;
+                            let destination = ctx.player("target")?;
+                            teleport(&ctx, &player, destination.get(PlayerComponents::POSITION)?)
+                        }))
+                        .then(
+                            CommandNode::argument("location", ArgumentType::BlockPos).executes(
+                                self.guard(|ctx| async move {
+                                    let target = ctx.player("target")?;
+                                    let location = block_center(ctx.block_pos("location")?);
+                                    teleport(&ctx, &target, location)
+                                }),
+                            ),
+                        )
+                        .then(
+                            CommandNode::argument("destination", ArgumentType::Player).executes(
+                                self.guard(|ctx| async move {
+                                    let target = ctx.player("target")?;
+                                    let destination = ctx.player("destination")?;
+                                    let location = destination.get(PlayerComponents::POSITION)?;
+                                    teleport(&ctx, &target, location)
+                                }),
+                            ),
+                        ),
+                )
+            })
+            .command("gamemode", |node| {
+                node.then(
+                    CommandNode::argument("gamemode", ArgumentType::word())
+                        .suggestions(|_| words(&["survival", "creative", "adventure", "spectator"]))
+                        .executes(self.guard(gamemode))
+                        .then(
+                            CommandNode::argument("target", ArgumentType::Player)
+                                .executes(self.guard(gamemode)),
+                        ),
+                )
+            })
+            .command("give", |node| {
+                node.then(
+                    CommandNode::argument("target", ArgumentType::Player).then(
+                        CommandNode::argument("item", ArgumentType::word())
+                            .executes(self.guard(give))
+                            .then(
+                                CommandNode::argument("count", ArgumentType::integer_range(1, 64))
+                                    .executes(self.guard(give)),
+                            ),
+                    ),
+                )
+            })
+            .command("kick", |node| {
+                node.then(
+                    CommandNode::argument("target", ArgumentType::Player)
+                        .executes(self.guard(kick))
+                        .then(
+                            CommandNode::argument("reason", ArgumentType::greedy_string())
+                                .executes(self.guard(kick)),
+                        ),
+                )
+            })
+            .command("time", |node| {
+                node.then(
+                    CommandNode::literal("set").then(
+                        CommandNode::argument("time", ArgumentType::word())
+                            .suggestions(|_| words(&["day", "noon", "night", "midnight"]))
+                            .executes(self.guard(time)),
+                    ),
+                )
+            })
+            .command("weather", |node| {
+                node.then(
+                    CommandNode::argument("weather", ArgumentType::word())
+                        .suggestions(|_| words(&["clear", "rain", "thunder"]))
+                        .executes(self.guard(weather)),
+                )
+            })
+    }
+}
+
+/// Tells the sender why the command failed, failing the command.
+fn fail(ctx: &CommandContext, message: &str) -> ActorResult<()> {
+    ctx.reply(command_error_text(message.to_string()))?;
+    Err(ActorError::BadRequest)
+}
+
+fn words(words: &[&str]) -> Vec<String> {
+    words.iter().map(|word| word.to_string()).collect()
+}
+
+/// Returns the player who ran the command, failing if it was run by the console.
+fn sender(ctx: &CommandContext) -> ActorResult<Player> {
+    match ctx.sender.player() {
+        Some(player) => Ok(player.clone()),
+        None => {
+            fail(
+                ctx,
+                "A player must be given when running this from the console",
+            )?;
+            Err(ActorError::BadRequest)
+        }
+    }
+}
+
+/// Returns the player named by the `target` argument, or the sender if there isn't one.
+fn target(ctx: &CommandContext) -> ActorResult<Player> {
+    match ctx.argument("target") {
+        Some(_) => ctx.player("target"),
+        None => sender(ctx),
+    }
+}
+
+fn block_center(pos: IVec3) -> DVec3 {
+    pos.as_dvec3() + DVec3::new(0.5, 0.0, 0.5)
+}
+
+fn teleport(ctx: &CommandContext, player: &Player, location: DVec3) -> ActorResult<()> {
+    player.teleport(location)?;
+    ctx.reply(Text::literal(format!(
+        "Teleported {} to {:.1}, {:.1}, {:.1}",
+        player.username()?,
+        location[0],
+        location[1],
+        location[2]
+    )))
+}
+
+async fn say(ctx: CommandContext) -> ActorResult<()> {
+    let name = match &ctx.sender {
+        CommandSender::Player(player) => player.username()?,
+        CommandSender::Console => "Server".to_string(),
+    };
+    let message = ctx.string("message")?;
+    Server::get()?.broadcast_message(Text::literal(format!("[{name}] {message}")))
+}
+
+async fn gamemode(ctx: CommandContext) -> ActorResult<()> {
+    let mode = match ctx.string("gamemode")?.as_str() {
+        "survival" => Gamemode::Survival,
+        "creative" => Gamemode::Creative,
+        "adventure" => Gamemode::Adventure,
+        "spectator" => Gamemode::Spectator,
+        _ => return fail(&ctx, "Unknown gamemode"),
+    };
+    let player = target(&ctx)?;
+    player.set_gamemode(mode.clone())?;
+    ctx.reply(Text::literal(format!(
+        "Set {}'s gamemode to {:?}",
+        player.username()?,
+        mode
+    )))
+}
+
+async fn give(ctx: CommandContext) -> ActorResult<()> {
+    let item = Id::from_string(&ctx.string("item")?);
+    if ITEM_REGISTRY.get_entry(&item.clone().into()).is_none() {
+        return fail(&ctx, &format!("Unknown item '{item}'"));
+    }
+    let count = ctx.integer("count").unwrap_or(1);
+
+    let player = ctx.player("target")?;
+    let inventory = player.inventory()?;
+    // The hotbar is filled first, then the rest of the inventory.
+    for slot in (36..45).chain(9..36) {
+        if inventory.get_slot_async(slot).await?.kind() == Id::constant("minecraft", "air") {
+            inventory
+                .set_slot_async(
+                    slot,
+                    ItemStack::new(item.clone()).with(ItemComponents::ITEM_COUNT, count as u16),
+                )
+                .await?;
+            return ctx.reply(Text::literal(format!(
+                "Gave {count} [{item}] to {}",
+                player.username()?
+            )));
+        }
+    }
+    fail(&ctx, "The player's inventory is full")
+}
+
+async fn kick(ctx: CommandContext) -> ActorResult<()> {
+    let player = ctx.player("target")?;
+    let reason = ctx
+        .string("reason")
+        .unwrap_or_else(|_| "Kicked by an operator".to_string());
+    player.kick(Text::literal(reason.clone()))?;
+    ctx.reply(Text::literal(format!(
+        "Kicked {}: {reason}",
+        player.username()?
+    )))
+}
+
+async fn time(ctx: CommandContext) -> ActorResult<()> {
+    let input = ctx.string("time")?;
+    let time = match input.as_str() {
+        "day" => 1000,
+        "noon" => 6000,
+        "night" => 13000,
+        "midnight" => 18000,
+        other => match other.parse::<i64>() {
+            Ok(time) if time >= 0 => time,
+            _ => return fail(&ctx, &format!("Invalid time '{other}'")),
+        },
+    };
+    for dimension in Server::get()?.dimensions()? {
+        dimension.set_time(time)?;
+    }
+    ctx.reply(Text::literal(format!("Set the time to {time}")))
+}
+
+async fn weather(ctx: CommandContext) -> ActorResult<()> {
+    let weather = match ctx.string("weather")?.as_str() {
+        "clear" => Weather::Clear,
+        "rain" => Weather::Rain,
+        "thunder" => Weather::Thunder,
+        _ => return fail(&ctx, "Unknown weather"),
+    };
+    for dimension in Server::get()?.dimensions()? {
+        dimension.set_weather(weather)?;
+    }
+    ctx.reply(Text::literal(format!("Set the weather to {weather:?}")))
+}
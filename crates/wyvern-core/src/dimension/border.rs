@@ -0,0 +1,205 @@
+use std::time::{Duration, Instant};
+
+use voxidian_protocol::{
+    packet::s2c::play::{InitializeBorderS2CPlayPacket, SetBorderLerpSizeS2CPlayPacket},
+    value::{VarInt, VarLong},
+};
+use wyvern_values::{DVec2, DVec3};
+
+use crate::{actors::ActorResult, runtime::Runtime};
+
+use super::DimensionData;
+
+/// The diameter of the world border in vanilla, which is far enough out that it's never reached.
+pub const DEFAULT_BORDER_DIAMETER: f64 = 59_999_968.0;
+
+/// How far a world border can be from 0, 0 before clients stop rendering it correctly.
+const PORTAL_TELEPORT_BOUNDARY: i32 = 29_999_984;
+
+/// A square border around a dimension that players can't move past.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WorldBorder {
+    /// The x and z coordinates of the middle of the border.
+    pub center: DVec2,
+    /// The length of each side of the border.
+    pub diameter: f64,
+    /// How many blocks from the border players start seeing a red warning overlay.
+    pub warning_distance: i32,
+    /// How many seconds before a shrinking border reaches a player they start seeing the
+    /// warning overlay.
+    pub warning_time: i32,
+}
+
+impl Default for WorldBorder {
+    fn default() -> Self {
+        WorldBorder {
+            center: DVec2::new(0.0, 0.0),
+            diameter: DEFAULT_BORDER_DIAMETER,
+            warning_distance: 5,
+            warning_time: 15,
+        }
+    }
+}
+
+impl WorldBorder {
+    /// A border with the given center and diameter, and vanilla's warnings.
+    pub fn new(center: DVec2, diameter: f64) -> WorldBorder {
+        WorldBorder {
+            center,
+            diameter,
+            ..Default::default()
+        }
+    }
+
+    pub fn center(mut self, center: DVec2) -> Self {
+        self.center = center;
+        self
+    }
+
+    pub fn diameter(mut self, diameter: f64) -> Self {
+        self.diameter = diameter;
+        self
+    }
+
+    pub fn warning_distance(mut self, distance: i32) -> Self {
+        self.warning_distance = distance;
+        self
+    }
+
+    pub fn warning_time(mut self, seconds: i32) -> Self {
+        self.warning_time = seconds;
+        self
+    }
+
+    /// Returns whether a position is inside this border.
+    pub fn contains(&self, position: DVec3) -> bool {
+        self.clamp(position) == position
+    }
+
+    /// Returns the closest position to `position` that is inside this border.
+    pub fn clamp(&self, position: DVec3) -> DVec3 {
+        let radius = self.diameter.max(0.0) / 2.0;
+        DVec3::new(
+            position[0].clamp(self.center[0] - radius, self.center[0] + radius),
+            position[1],
+            position[2].clamp(self.center[1] - radius, self.center[1] + radius),
+        )
+    }
+}
+
+/// A dimension's world border, which may be moving from one diameter to another.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct BorderState {
+    /// The border once any resize finishes.
+    pub(crate) border: WorldBorder,
+    pub(crate) from_diameter: f64,
+    pub(crate) started: Instant,
+    pub(crate) duration: Duration,
+}
+
+impl BorderState {
+    pub(crate) fn new(border: WorldBorder) -> BorderState {
+        BorderState {
+            border,
+            from_diameter: border.diameter,
+            started: Instant::now(),
+            duration: Duration::ZERO,
+        }
+    }
+
+    /// Returns the diameter of the border at `now`, moving linearly between the old and new
+    /// diameters while it's resizing.
+    pub(crate) fn diameter_at(&self, now: Instant) -> f64 {
+        let elapsed = now.saturating_duration_since(self.started);
+        if elapsed >= self.duration {
+            return self.border.diameter;
+        }
+        let progress = elapsed.as_secs_f64() / self.duration.as_secs_f64();
+        self.from_diameter + (self.border.diameter - self.from_diameter) * progress
+    }
+
+    /// Returns the border as it is at `now`.
+    pub(crate) fn at(&self, now: Instant) -> WorldBorder {
+        self.border.diameter(self.diameter_at(now))
+    }
+
+    /// Returns how long is left until a resize finishes.
+    fn remaining(&self, now: Instant) -> Duration {
+        self.duration
+            .saturating_sub(now.saturating_duration_since(self.started))
+    }
+
+    /// Returns the packet that shows this border to a player who can't see it yet, including
+    /// any resize in progress.
+    pub(crate) fn initialize_packet(&self) -> InitializeBorderS2CPlayPacket {
+        let now = Instant::now();
+        InitializeBorderS2CPlayPacket {
+            x: self.border.center[0],
+            z: self.border.center[1],
+            old_diameter: self.diameter_at(now),
+            new_diameter: self.border.diameter,
+            speed: VarLong::from(self.remaining(now).as_millis() as i64),
+            portal_teleport_boundary: VarInt::from(PORTAL_TELEPORT_BOUNDARY),
+            warning_blocks: VarInt::from(self.border.warning_distance),
+            warning_time: VarInt::from(self.border.warning_time),
+        }
+    }
+}
+
+impl DimensionData {
+    /// Shows this dimension's world border to every player in it.
+    pub(crate) fn send_world_border(&self) -> ActorResult<()> {
+        let dimension = self.as_actor();
+        let state = self.world_border;
+        Runtime::spawn_task(async move {
+            dimension.broadcast(|player| player.write_packet(state.initialize_packet()))
+        });
+        Ok(())
+    }
+
+    /// Tells every player in this dimension that its world border started resizing.
+    pub(crate) fn send_world_border_resize(&self) -> ActorResult<()> {
+        let dimension = self.as_actor();
+        let state = self.world_border;
+        Runtime::spawn_task(async move {
+            dimension.broadcast(|player| {
+                player.write_packet(SetBorderLerpSizeS2CPlayPacket {
+                    old_diameter: state.from_diameter,
+                    new_diameter: state.border.diameter,
+                    speed: VarLong::from(state.duration.as_millis() as i64),
+                })
+            })
+        });
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use wyvern_values::{DVec2, DVec3};
+
+    use super::{BorderState, WorldBorder};
+
+    #[test]
+    fn borders_clamp_positions_and_resize_linearly() {
+        let border = WorldBorder::new(DVec2::new(10.0, -10.0), 20.0);
+        assert!(border.contains(DVec3::new(0.0, 64.0, -20.0)));
+        assert_eq!(
+            border.clamp(DVec3::new(25.0, 64.0, -30.0)),
+            DVec3::new(20.0, 64.0, -20.0)
+        );
+
+        let started = Instant::now();
+        let state = BorderState {
+            border,
+            from_diameter: 100.0,
+            started,
+            duration: Duration::from_secs(10),
+        };
+        assert_eq!(state.diameter_at(started), 100.0);
+        assert_eq!(state.diameter_at(started + Duration::from_secs(5)), 60.0);
+        assert_eq!(state.diameter_at(started + Duration::from_secs(20)), 20.0);
+    }
+}
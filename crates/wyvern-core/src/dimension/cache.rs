@@ -0,0 +1,216 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{Arc, Mutex},
+};
+
+use wyvern_values::{IVec2, Id};
+
+use crate::runtime::Runtime;
+
+use super::chunk::Chunk;
+
+/// Identifies a generated chunk: which generator made it, with what seed, where, and how tall
+/// its dimension is.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ChunkCacheKey {
+    pub generator: Id,
+    pub seed: u64,
+    pub pos: IVec2,
+    pub min_sections: i32,
+    pub max_sections: i32,
+}
+
+#[derive(Default)]
+struct CacheEntries {
+    chunks: HashMap<ChunkCacheKey, (Chunk, u64)>,
+    /// The keys of every cached chunk by when it was last used, oldest first.
+    order: BTreeMap<u64, ChunkCacheKey>,
+    next_use: u64,
+}
+
+/// A cache of generated chunks shared between dimensions, so dimensions that generate the same
+/// chunks, such as instances of a minigame map, copy them instead of generating them again.
+/// Once it holds `capacity` chunks, the least recently used chunk is evicted for each new one.
+///
+/// Cloning a `ChunkCache` gives another handle to the same cache.
+#[derive(Clone)]
+pub struct ChunkCache {
+    capacity: usize,
+    entries: Arc<Mutex<CacheEntries>>,
+}
+
+impl ChunkCache {
+    pub fn new(capacity: usize) -> ChunkCache {
+        ChunkCache {
+            capacity,
+            entries: Arc::new(Mutex::new(CacheEntries::default())),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().chunks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns a copy of a cached chunk, marking it as recently used.
+    pub fn get(&self, key: &ChunkCacheKey) -> Option<Chunk> {
+        let mut entries = self.entries.lock().unwrap();
+        let next_use = entries.next_use;
+        let (chunk, last_use) = entries.chunks.get_mut(key)?;
+        let chunk = chunk.clone();
+        let last_use = std::mem::replace(last_use, next_use);
+        entries.order.remove(&last_use);
+        entries.order.insert(next_use, key.clone());
+        entries.next_use += 1;
+        Some(chunk)
+    }
+
+    /// Caches a chunk, evicting the least recently used chunks if the cache is full.
+    pub fn insert(&self, key: ChunkCacheKey, chunk: Chunk) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut entries = self.entries.lock().unwrap();
+        let next_use = entries.next_use;
+        entries.next_use += 1;
+        if let Some((_, last_use)) = entries.chunks.insert(key.clone(), (chunk, next_use)) {
+            entries.order.remove(&last_use);
+        }
+        entries.order.insert(next_use, key);
+        while entries.chunks.len() > self.capacity {
+            let Some((_, oldest)) = entries.order.pop_first() else {
+                break;
+            };
+            entries.chunks.remove(&oldest);
+        }
+    }
+
+    pub fn clear(&self) {
+        let mut entries = self.entries.lock().unwrap();
+        entries.chunks.clear();
+        entries.order.clear();
+    }
+
+    /// Wraps a chunk generator so the chunks it generates are stored in this cache under
+    /// `generator` and `seed`. Use a different id or seed for generators that produce different
+    /// chunks, or they will be given each other's chunks.
+    pub fn generator(
+        &self,
+        generator: Id,
+        seed: u64,
+        function: impl Fn(&mut Chunk, i32, i32) + Send + Sync + 'static,
+    ) -> CachedGenerator {
+        CachedGenerator {
+            cache: self.clone(),
+            generator,
+            seed,
+            function: Arc::new(function),
+        }
+    }
+}
+
+/// A chunk generator whose chunks are stored in a `ChunkCache`. Set it on a dimension with
+/// `Dimension::set_cached_chunk_generator`.
+#[derive(Clone)]
+pub struct CachedGenerator {
+    cache: ChunkCache,
+    generator: Id,
+    seed: u64,
+    function: Arc<dyn Fn(&mut Chunk, i32, i32) + Send + Sync>,
+}
+
+impl CachedGenerator {
+    fn key(&self, pos: IVec2, min_sections: i32, max_sections: i32) -> ChunkCacheKey {
+        ChunkCacheKey {
+            generator: self.generator.clone(),
+            seed: self.seed,
+            pos,
+            min_sections,
+            max_sections,
+        }
+    }
+
+    /// Fills `chunk` with the cached copy of the chunk at the given chunk coordinates, running
+    /// the generator and caching its result if there isn't one.
+    pub fn generate(&self, chunk: &mut Chunk, x: i32, z: i32) {
+        let key = self.key(IVec2::new(x, z), chunk.min_sections, chunk.max_sections);
+        if let Some(cached) = self.cache.get(&key) {
+            *chunk = cached;
+            return;
+        }
+        (self.function)(chunk, x, z);
+        self.cache.insert(key, chunk.clone());
+    }
+
+    /// Generates every chunk between two chunk coordinates on the task pool and caches it, so
+    /// dimensions created later can copy them. `min_y` and `height` must match the dimension
+    /// type of the dimensions that will use them.
+    pub fn prewarm(&self, min_y: i32, height: u32, from: IVec2, to: IVec2) {
+        let (min_sections, max_sections) = (min_y / 16, (min_y + height as i32) / 16);
+        let (min, max) = (from.min(to), from.max(to));
+        for x in min[0]..=max[0] {
+            for z in min[1]..=max[1] {
+                if self
+                    .cache
+                    .entries
+                    .lock()
+                    .unwrap()
+                    .chunks
+                    .contains_key(&self.key(IVec2::new(x, z), min_sections, max_sections))
+                {
+                    continue;
+                }
+                let generator = self.clone();
+                Runtime::spawn_task(async move {
+                    let mut chunk = Chunk::new(min_sections, max_sections);
+                    generator.generate(&mut chunk, x, z);
+                    Ok(())
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wyvern_values::{IVec2, id};
+
+    use crate::dimension::chunk::Chunk;
+
+    use super::{ChunkCache, ChunkCacheKey};
+
+    fn key(x: i32) -> ChunkCacheKey {
+        ChunkCacheKey {
+            generator: id![wyvern:test],
+            seed: 0,
+            pos: IVec2::new(x, 0),
+            min_sections: 0,
+            max_sections: 1,
+        }
+    }
+
+    #[test]
+    fn least_recently_used_chunks_are_evicted() {
+        let cache = ChunkCache::new(2);
+        cache.insert(key(0), Chunk::new(0, 1));
+        cache.insert(key(1), Chunk::new(0, 1));
+        assert!(cache.get(&key(0)).is_some());
+        cache.insert(key(2), Chunk::new(0, 1));
+
+        assert_eq!(cache.len(), 2);
+        assert!(cache.get(&key(0)).is_some());
+        assert!(cache.get(&key(1)).is_none());
+        assert!(cache.get(&key(2)).is_some());
+
+        let disabled = ChunkCache::new(0);
+        disabled.insert(key(0), Chunk::new(0, 1));
+        assert!(disabled.is_empty());
+    }
+}
@@ -4,7 +4,8 @@ use voxidian_protocol::{
     packet::s2c::play::ChunkBlockEntity,
     registry::{RegEntry, Registry},
     value::{
-        ChunkSection as ProtocolSection, PaletteFormat, PalettedContainer, RawDataArray, VarInt,
+        Biome, ChunkSection as ProtocolSection, Heightmap as PtcHeightmap, Nbt as PtcNbt,
+        NbtElement, PaletteFormat, PalettedContainer, RawDataArray, VarInt,
     },
 };
 use wyvern_components::DataComponentHolder;
@@ -12,22 +13,63 @@ use wyvern_datatypes::nbt::Nbt;
 
 use crate::{
     blocks::BlockComponents,
-    server::{Server, registries::RegistryKeys},
+    server::registries::{RegistryContainer, RegistryKeys},
 };
 
-use wyvern_values::{I16Vec3, IVec3, Id, USizeVec3};
+use wyvern_values::{I16Vec3, IVec3, Id, USizeVec3, id};
 
 use crate::blocks::BlockState;
 
+use super::{
+    heightmap::{Heightmap, HeightmapKind},
+    light::ChunkLight,
+};
+
 pub static BLOCK_ENTITY_REGISTRY: LazyLock<Registry<VarInt>> =
     LazyLock::new(ChunkBlockEntity::block_entity_type_registry);
 
+/// Returns the block entity type a block creates. Every sign, hanging sign, head and banner
+/// variant shares one type; any other block uses the type named after it, if there is one.
+fn block_entity_type(block: &Id) -> Id {
+    let path = block.path();
+    let shared = if block.namespace() != "minecraft" {
+        None
+    } else if path.ends_with("_hanging_sign") {
+        Some("hanging_sign")
+    } else if path.ends_with("_sign") {
+        Some("sign")
+    } else if path.ends_with("_banner") {
+        Some("banner")
+    } else if path.ends_with("_skull") || (path.ends_with("_head") && path != "piston_head") {
+        Some("skull")
+    } else {
+        None
+    };
+    match shared {
+        Some(shared) => Id::new("minecraft", shared),
+        None => block.clone(),
+    }
+}
+
+/// A section's worth of air, cloned when a section first has a block placed in it.
+static EMPTY_BLOCKS: LazyLock<RawDataArray> = LazyLock::new(|| {
+    let mut arr = RawDataArray::new(15);
+    for _ in 0..4096 {
+        arr.push(0);
+    }
+    arr
+});
+
 #[derive(Clone, Debug)]
 pub struct Chunk {
     pub(crate) min_sections: i32,
-    pub(crate) _max_sections: i32,
+    pub(crate) max_sections: i32,
     pub(crate) sections: Vec<ChunkSection>,
     pub(crate) block_entities: HashMap<I16Vec3, VarInt>,
+    pub(crate) world_surface: Heightmap,
+    pub(crate) motion_blocking: Heightmap,
+    /// The light of this chunk, or `None` if a block changed since it was last computed.
+    pub(crate) light: Option<ChunkLight>,
 }
 
 impl Chunk {
@@ -39,12 +81,124 @@ impl Chunk {
         }
         Chunk {
             min_sections,
-            _max_sections: max_sections,
+            max_sections,
             sections: vec,
             block_entities: HashMap::new(),
+            world_surface: Heightmap::new(),
+            motion_blocking: Heightmap::new(),
+            light: None,
+        }
+    }
+
+    fn min_y(&self) -> i32 {
+        self.min_sections * 16
+    }
+
+    fn world_height(&self) -> u32 {
+        self.sections.len() as u32 * 16
+    }
+
+    fn heightmap_ref(&self, kind: HeightmapKind) -> &Heightmap {
+        match kind {
+            HeightmapKind::WorldSurface => &self.world_surface,
+            HeightmapKind::MotionBlocking => &self.motion_blocking,
+        }
+    }
+
+    fn heightmap_mut(&mut self, kind: HeightmapKind) -> &mut Heightmap {
+        match kind {
+            HeightmapKind::WorldSurface => &mut self.world_surface,
+            HeightmapKind::MotionBlocking => &mut self.motion_blocking,
+        }
+    }
+
+    /// Returns a heightmap of this chunk packed into longs the way it is sent to clients.
+    /// Each column holds one more than the height of its highest matching block above the
+    /// bottom of the dimension, or zero if it has none.
+    pub fn heightmap(&self, kind: HeightmapKind) -> Vec<u64> {
+        self.heightmap_ref(kind).pack(self.world_height())
+    }
+
+    pub(crate) fn protocol_heightmaps(&self) -> Vec<PtcHeightmap> {
+        HeightmapKind::ALL
+            .into_iter()
+            .map(|kind| {
+                self.heightmap_ref(kind)
+                    .to_protocol(kind, self.world_height())
+            })
+            .collect()
+    }
+
+    /// Returns the light of this chunk, recomputing the whole chunk if a block changed since
+    /// it was last computed.
+    pub(crate) fn light(&mut self) -> &ChunkLight {
+        if self.light.is_none() {
+            let mut blocks = Vec::with_capacity(self.sections.len() * 4096);
+            for section in &self.sections {
+                blocks.extend((0..4096).map(|idx| section.block_id_at(idx) as u32));
+            }
+            self.light = Some(ChunkLight::compute(&blocks));
+        }
+        self.light.as_ref().unwrap()
+    }
+
+    /// Returns the skylight level at a position in this chunk, from 0 to 15.
+    pub fn sky_light_at(&mut self, pos: IVec3) -> u8 {
+        let y = pos[1] - self.min_y();
+        if y < 0 {
+            return 0;
+        } else if y >= self.world_height() as i32 {
+            return 15;
+        }
+        self.light()
+            .sky_light(pos[0] as usize, y as usize, pos[2] as usize)
+    }
+
+    /// Returns the block light level at a position in this chunk, from 0 to 15.
+    pub fn block_light_at(&mut self, pos: IVec3) -> u8 {
+        let y = pos[1] - self.min_y();
+        if y < 0 || y >= self.world_height() as i32 {
+            return 0;
+        }
+        self.light()
+            .block_light(pos[0] as usize, y as usize, pos[2] as usize)
+    }
+
+    fn block_id_at(&self, pos: IVec3) -> u32 {
+        self.section_at(pos[1].div_euclid(16))
+            .map(|section| {
+                section.block_id_at(ChunkSection::index_from_pos(
+                    pos.with_y(pos[1].rem_euclid(16)).as_usizevec3(),
+                )) as u32
+            })
+            .unwrap_or(0)
+    }
+
+    /// Updates the heightmaps of the column at `pos` after the block there changed to `block`.
+    fn update_heightmaps(&mut self, pos: IVec3, block: u32) {
+        let height = (pos[1] - self.min_y() + 1) as u16;
+        for kind in HeightmapKind::ALL {
+            let current = self.heightmap_ref(kind).get(pos[0], pos[2]);
+            if kind.includes(block) {
+                if height > current {
+                    self.heightmap_mut(kind).set(pos[0], pos[2], height);
+                }
+            } else if height == current {
+                let mut below = height - 1;
+                while below > 0
+                    && !kind.includes(self.block_id_at(pos.with_y(self.min_y() + below as i32 - 1)))
+                {
+                    below -= 1;
+                }
+                self.heightmap_mut(kind).set(pos[0], pos[2], below);
+            }
         }
     }
 
+    pub(crate) fn section_at(&self, section: i32) -> Option<&ChunkSection> {
+        self.sections.get((section - self.min_sections) as usize)
+    }
+
     pub(crate) fn section_at_mut(&mut self, section: i32) -> Option<&mut ChunkSection> {
         self.sections
             .get_mut((section - self.min_sections) as usize)
@@ -53,11 +207,13 @@ impl Chunk {
     pub fn set_block_at(&mut self, pos: IVec3, block: &BlockState) {
         let section_y = pos[1].div_euclid(16);
         let local_y = pos[1].rem_euclid(16);
-        let name = block.name().clone();
+        let entity_type = block_entity_type(block.name());
         if let Some(section) = self.section_at_mut(section_y) {
             section.set_block_at(pos.with_y(local_y).as_usizevec3(), block);
+            self.update_heightmaps(pos, block.protocol_id() as u32);
+            self.light = None;
 
-            if let Some(id) = BLOCK_ENTITY_REGISTRY.get(&name.into()) {
+            if let Some(id) = BLOCK_ENTITY_REGISTRY.get(&entity_type.into()) {
                 self.block_entities.insert(pos.as_i16vec3(), *id);
             } else {
                 self.block_entities.remove(&pos.as_i16vec3());
@@ -70,13 +226,64 @@ impl Chunk {
         let local_y = pos[1].rem_euclid(16);
         if let Some(section) = self.section_at_mut(section_y) {
             section.set_block_at_by_id(pos.with_y(local_y).as_usizevec3(), block);
+            self.update_heightmaps(pos, block);
+            self.light = None;
 
-            // TODO: add this
-            // if let Some(id) = BLOCK_ENTITY_REGISTRY.get(&name) {
-            //     self.block_entities.insert(pos.as_i16vec3(), *id);
-            // } else {
-            //     self.block_entities.remove(&pos.as_i16vec3());
-            // }
+            let entity_type = block_entity_type(BlockState::from_protocol_id(block as i32).name());
+            if let Some(id) = BLOCK_ENTITY_REGISTRY.get(&entity_type.into()) {
+                self.block_entities.insert(pos.as_i16vec3(), *id);
+            } else {
+                self.block_entities.remove(&pos.as_i16vec3());
+            }
+        }
+    }
+
+    /// Returns the block entity data stored at the given position in this chunk, if any.
+    pub fn block_entity_data(&self, pos: IVec3) -> Option<Nbt> {
+        let section = self.section_at(pos[1].div_euclid(16))?;
+        section
+            .block_meta
+            .get(&pos.with_y(pos[1].rem_euclid(16)).as_usizevec3())
+            .cloned()
+    }
+
+    pub(crate) fn block_entity_at(&self, pos: IVec3) -> Option<ChunkBlockEntity> {
+        let entity_type = self.block_entities.get(&pos.as_i16vec3())?;
+        Some(ChunkBlockEntity {
+            packed_xz: (((pos[0] & 15) << 4) | (pos[2] & 15)) as u8,
+            y: pos[1] as i16,
+            entity_type: *entity_type,
+            data: protocol_nbt(self.block_entity_data(pos)),
+        })
+    }
+
+    pub(crate) fn protocol_block_entities(&self) -> Vec<ChunkBlockEntity> {
+        self.block_entities
+            .keys()
+            .filter_map(|pos| self.block_entity_at(pos.as_ivec3()))
+            .collect()
+    }
+
+    /// Sets the biome of the 4x4x4 cell containing the given position in this chunk.
+    pub fn set_biome_at(&mut self, pos: IVec3, biome: Id) {
+        if let Some(section) = self.section_at_mut(pos[1].div_euclid(16)) {
+            section.set_biome_at(pos.with_y(pos[1].rem_euclid(16)).as_usizevec3(), biome);
+        }
+    }
+
+    /// Sets the biome of a whole column of this chunk, from the bottom of the dimension to the top.
+    pub fn set_biome_column(&mut self, x: i32, z: i32, biome: Id) {
+        for section in &mut self.sections {
+            for y in (0..16).step_by(4) {
+                section.set_biome_at(USizeVec3::new(x as usize, y, z as usize), biome.clone());
+            }
+        }
+    }
+
+    pub fn biome_at(&self, pos: IVec3) -> Id {
+        match self.section_at(pos[1].div_euclid(16)) {
+            Some(section) => section.biome_at(pos.with_y(pos[1].rem_euclid(16)).as_usizevec3()),
+            None => id![minecraft:plains],
         }
     }
 
@@ -95,8 +302,14 @@ impl Chunk {
 #[derive(Clone, Debug)]
 pub(crate) struct ChunkSection {
     block_count: i16,
-    blocks: RawDataArray,
+    /// The block states of this section, or `None` if the section has only ever held air.
+    blocks: Option<RawDataArray>,
     block_meta: HashMap<USizeVec3, Nbt>,
+    /// The distinct biomes in this section. The first entry is the biome of the whole section
+    /// when `biome_cells` is `None`.
+    biome_palette: Vec<Id>,
+    /// The index into `biome_palette` of each 4x4x4 cell in this section.
+    biome_cells: Option<Box<[u8; 64]>>,
 }
 
 impl ChunkSection {
@@ -107,51 +320,56 @@ impl ChunkSection {
     pub fn empty() -> ChunkSection {
         ChunkSection {
             block_count: 0,
-            blocks: {
-                let mut arr = RawDataArray::new(15);
-                for _ in 0..4096 {
-                    arr.push(0);
-                }
-                arr
-            },
+            blocks: None,
             block_meta: HashMap::new(),
+            biome_palette: vec![id![minecraft:plains]],
+            biome_cells: None,
         }
     }
 
-    pub fn set_block_at(&mut self, pos: USizeVec3, block: &BlockState) {
-        let idx = Self::index_from_pos(pos);
-        let old_block = self.blocks.get(idx).unwrap();
+    fn block_id_at(&self, idx: usize) -> u64 {
+        self.blocks
+            .as_ref()
+            .and_then(|blocks| blocks.get(idx))
+            .unwrap_or(0)
+    }
 
-        let new_block =
-            unsafe { RegEntry::<BlockState>::new_unchecked(block.protocol_id() as u32) }.id();
+    fn set_block_id(&mut self, idx: usize, new_block: u64) {
+        if self.blocks.is_none() && new_block == 0 {
+            return;
+        }
 
+        let old_block = self.block_id_at(idx);
         if old_block == 0 && new_block != 0 {
             self.block_count += 1;
         } else if old_block != 0 && new_block == 0 {
             self.block_count -= 1;
         }
 
-        self.blocks.set(idx, new_block as u64);
+        self.blocks
+            .get_or_insert_with(|| EMPTY_BLOCKS.clone())
+            .set(idx, new_block);
+    }
+
+    pub fn set_block_at(&mut self, pos: USizeVec3, block: &BlockState) {
+        let new_block =
+            unsafe { RegEntry::<BlockState>::new_unchecked(block.protocol_id() as u32) }.id();
+        self.set_block_id(Self::index_from_pos(pos), new_block as u64);
+
         if let Ok(data) = block.get(BlockComponents::CUSTOM_DATA) {
             self.block_meta.insert(pos, data);
+        } else {
+            self.block_meta.remove(&pos);
         }
     }
 
     pub fn set_block_at_by_id(&mut self, pos: USizeVec3, new_block: u32) {
-        let idx = Self::index_from_pos(pos);
-        let old_block = self.blocks.get(idx).unwrap();
-
-        if old_block == 0 && new_block != 0 {
-            self.block_count += 1;
-        } else if old_block != 0 && new_block == 0 {
-            self.block_count -= 1;
-        }
-
-        self.blocks.set(idx, new_block as u64);
+        self.set_block_id(Self::index_from_pos(pos), new_block as u64);
+        self.block_meta.remove(&pos);
     }
 
     pub fn get_block_at(&mut self, pos: USizeVec3) -> BlockState {
-        let ptc = self.blocks.get(Self::index_from_pos(pos)).unwrap();
+        let ptc = self.block_id_at(Self::index_from_pos(pos));
         let mut state = BlockState::from_protocol_id(ptc as i32);
 
         if let Some(cdata) = self.block_meta.get(&pos) {
@@ -161,27 +379,272 @@ impl ChunkSection {
         state
     }
 
-    pub fn as_protocol_section(&self) -> ProtocolSection {
+    fn biome_cell(pos: USizeVec3) -> usize {
+        (pos[1] / 4) * 16 + (pos[2] / 4) * 4 + pos[0] / 4
+    }
+
+    pub fn set_biome_at(&mut self, pos: USizeVec3, biome: Id) {
+        let index = match self.biome_palette.iter().position(|entry| *entry == biome) {
+            Some(index) => index,
+            None => {
+                self.biome_palette.push(biome);
+                self.biome_palette.len() - 1
+            }
+        };
+        if self.biome_cells.is_none() && index == 0 {
+            return;
+        }
+
+        let cells = self.biome_cells.get_or_insert_with(|| Box::new([0; 64]));
+        cells[Self::biome_cell(pos)] = index as u8;
+
+        // Collapse back into a single biome once every cell agrees.
+        let first = cells[0];
+        if cells.iter().all(|cell| *cell == first) {
+            let biome = self.biome_palette.swap_remove(first as usize);
+            self.biome_palette = vec![biome];
+            self.biome_cells = None;
+        }
+    }
+
+    pub fn biome_at(&self, pos: USizeVec3) -> Id {
+        let index = self
+            .biome_cells
+            .as_ref()
+            .map_or(0, |cells| cells[Self::biome_cell(pos)]);
+        self.biome_palette[index as usize].clone()
+    }
+
+    /// Encodes the biomes of this section, using a single value when the whole section is
+    /// one biome, and a palette when it has few enough biomes for the client to expect one.
+    /// Biomes missing from the registry are sent as plains.
+    fn protocol_biomes(
+        &self,
+        registries: &RegistryContainer,
+    ) -> PalettedContainer<RegEntry<Biome>> {
+        let registry = registries.get(RegistryKeys::BIOME);
+        let entry = |biome: &Id| {
+            registry
+                .get_entry(biome.clone())
+                .or_else(|| registry.get_entry(id![minecraft:plains]))
+                .unwrap()
+        };
+
+        let Some(cells) = &self.biome_cells else {
+            return PalettedContainer {
+                bits_per_entry: 0,
+                format: PaletteFormat::SingleValued {
+                    entry: entry(&self.biome_palette[0]),
+                },
+            };
+        };
+
+        // Biomes missing from the registry share plains' entry, so the palette is rebuilt
+        // from the distinct entries actually sent.
+        let mut palette = Vec::new();
+        let mut ids = Vec::new();
+        let mut indices = Vec::new();
+        for biome in &self.biome_palette {
+            let entry = entry(biome);
+            let id = entry.id() as u64;
+            indices.push(match ids.iter().position(|x| *x == id) {
+                Some(index) => index,
+                None => {
+                    ids.push(id);
+                    palette.push(entry);
+                    ids.len() - 1
+                }
+            });
+        }
+
+        let bits = direct_bits(palette.len());
+        if bits <= MAX_INDIRECT_BIOME_BITS {
+            let mut data = RawDataArray::new(bits);
+            for cell in cells.iter() {
+                data.push(indices[*cell as usize] as u64);
+            }
+            return PalettedContainer {
+                bits_per_entry: bits as u8,
+                format: PaletteFormat::Indirect { palette, data },
+            };
+        }
+
+        let bits = direct_bits(registry.keys().count());
+        let mut data = RawDataArray::new(bits);
+        for cell in cells.iter() {
+            data.push(ids[indices[*cell as usize]]);
+        }
+        PalettedContainer {
+            bits_per_entry: bits as u8,
+            format: PaletteFormat::RawDirect { data },
+        }
+    }
+
+    pub fn as_protocol_section(&self, registries: &RegistryContainer) -> ProtocolSection {
         ProtocolSection {
             block_count: self.block_count,
             block_states: PalettedContainer {
                 bits_per_entry: 15,
                 format: PaletteFormat::RawDirect {
-                    data: self.blocks.clone(),
-                },
-            },
-            biomes: PalettedContainer {
-                bits_per_entry: 0,
-                format: PaletteFormat::SingleValued {
-                    entry: Server::get()
-                        .unwrap()
-                        .registries()
-                        .unwrap()
-                        .get(RegistryKeys::BIOME)
-                        .get_entry(Id::new("minecraft", "plains"))
-                        .unwrap(),
+                    data: self.blocks.clone().unwrap_or_else(|| EMPTY_BLOCKS.clone()),
                 },
             },
+            biomes: self.protocol_biomes(registries),
+        }
+    }
+}
+
+/// The most bits per entry the client reads a biome palette for. Containers needing more are
+/// encoded with registry ids directly.
+const MAX_INDIRECT_BIOME_BITS: usize = 3;
+
+/// Returns how many bits a directly encoded entry needs to fit any id in a registry of `len` entries.
+fn direct_bits(len: usize) -> usize {
+    (usize::BITS - len.saturating_sub(1).leading_zeros()).max(1) as usize
+}
+
+fn protocol_nbt(nbt: Option<Nbt>) -> PtcNbt {
+    match nbt.map(NbtElement::from) {
+        Some(NbtElement::Compound(root)) => PtcNbt {
+            name: String::new(),
+            root,
+        },
+        _ => PtcNbt::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wyvern_components::DataComponentHolder;
+    use wyvern_datatypes::{compound, list, nbt::Nbt};
+    use wyvern_values::{IVec3, USizeVec3, id};
+
+    use crate::blocks::{BlockComponents, BlockState, Blocks};
+
+    use super::{Chunk, ChunkSection, HeightmapKind, block_entity_type, direct_bits};
+
+    #[test]
+    fn placed_sign_has_text() {
+        let mut chunk = Chunk::new(0, 1);
+        let text = compound! {
+            front_text: compound! {
+                messages: list!["\"Hello\"", "\"\"", "\"\"", "\"\""]
+            }
+        };
+        let sign = BlockState::new(Blocks::OAK_SIGN)
+            .with(BlockComponents::CUSTOM_DATA, Nbt::Compound(text.clone()));
+        chunk.set_block_at(IVec3::new(3, 5, 7), &sign);
+
+        let entities = chunk.protocol_block_entities();
+        assert_eq!(entities.len(), 1);
+        assert_eq!(entities[0].packed_xz, (3 << 4) | 7);
+        assert_eq!(entities[0].y, 5);
+        assert_eq!(
+            chunk.block_entity_data(IVec3::new(3, 5, 7)),
+            Some(Nbt::Compound(text))
+        );
+
+        chunk.set_block_at(IVec3::new(3, 5, 7), &BlockState::new(Blocks::AIR));
+        assert!(chunk.protocol_block_entities().is_empty());
+        assert_eq!(chunk.block_entity_data(IVec3::new(3, 5, 7)), None);
+    }
+
+    #[test]
+    fn block_variants_share_block_entity_types() {
+        assert_eq!(
+            block_entity_type(&Blocks::OAK_WALL_SIGN),
+            id![minecraft:sign]
+        );
+        assert_eq!(
+            block_entity_type(&Blocks::BAMBOO_WALL_HANGING_SIGN),
+            id![minecraft:hanging_sign]
+        );
+        assert_eq!(
+            block_entity_type(&Blocks::WITHER_SKELETON_WALL_SKULL),
+            id![minecraft:skull]
+        );
+        assert_eq!(
+            block_entity_type(&Blocks::PLAYER_HEAD),
+            id![minecraft:skull]
+        );
+        assert_eq!(
+            block_entity_type(&Blocks::WHITE_WALL_BANNER),
+            id![minecraft:banner]
+        );
+        assert_eq!(block_entity_type(&Blocks::CHEST), id![minecraft:chest]);
+        assert_eq!(
+            block_entity_type(&Blocks::PISTON_HEAD),
+            id![minecraft:piston_head]
+        );
+    }
+
+    #[test]
+    fn empty_sections_allocate_lazily() {
+        let mut section = ChunkSection::empty();
+        section.set_block_at_by_id(USizeVec3::new(1, 2, 3), 0);
+        assert!(section.blocks.is_none());
+        assert_eq!(section.block_id_at(0), 0);
+
+        section.set_block_at_by_id(USizeVec3::new(1, 2, 3), 1);
+        assert_eq!(section.block_count, 1);
+        assert_eq!(
+            section.block_id_at(ChunkSection::index_from_pos(USizeVec3::new(1, 2, 3))),
+            1
+        );
+
+        section.set_block_at_by_id(USizeVec3::new(1, 2, 3), 0);
+        assert_eq!(section.block_count, 0);
+    }
+
+    #[test]
+    fn heightmaps_follow_placed_blocks() {
+        let mut chunk = Chunk::new(-4, 20);
+        let column = |chunk: &Chunk, kind| chunk.heightmap(kind)[0] & 0x1ff;
+
+        chunk.set_block_at(IVec3::new(0, -64, 0), &BlockState::new(Blocks::STONE));
+        chunk.set_block_at(IVec3::new(0, 10, 0), &BlockState::new(Blocks::STONE));
+        chunk.set_block_at(IVec3::new(0, 11, 0), &BlockState::new(Blocks::TORCH));
+        assert_eq!(column(&chunk, HeightmapKind::WorldSurface), 76);
+        assert_eq!(column(&chunk, HeightmapKind::MotionBlocking), 75);
+
+        chunk.set_block_at(IVec3::new(0, 11, 0), &BlockState::new(Blocks::AIR));
+        chunk.set_block_at(IVec3::new(0, 10, 0), &BlockState::new(Blocks::AIR));
+        assert_eq!(column(&chunk, HeightmapKind::WorldSurface), 1);
+        assert_eq!(column(&chunk, HeightmapKind::MotionBlocking), 1);
+
+        chunk.set_block_at(IVec3::new(0, -64, 0), &BlockState::new(Blocks::AIR));
+        assert_eq!(chunk.heightmap(HeightmapKind::WorldSurface), vec![0; 37]);
+    }
+
+    #[test]
+    fn light_is_recomputed_after_changes() {
+        let mut chunk = Chunk::new(0, 2);
+        chunk.set_block_at(IVec3::new(3, 20, 3), &BlockState::new(Blocks::STONE));
+        assert_eq!(chunk.sky_light_at(IVec3::new(3, 19, 3)), 14);
+        assert_eq!(chunk.block_light_at(IVec3::new(3, 19, 3)), 0);
+
+        chunk.set_block_at(IVec3::new(3, 18, 3), &BlockState::new(Blocks::GLOWSTONE));
+        assert_eq!(chunk.block_light_at(IVec3::new(3, 19, 3)), 14);
+        assert_eq!(chunk.sky_light_at(IVec3::new(3, 40, 3)), 15);
+    }
+
+    #[test]
+    fn biomes_collapse_to_single_value() {
+        let mut chunk = Chunk::new(0, 1);
+        chunk.set_biome_at(IVec3::new(5, 9, 2), id![minecraft:desert]);
+        assert_eq!(chunk.biome_at(IVec3::new(4, 8, 0)), id![minecraft:desert]);
+        assert_eq!(chunk.biome_at(IVec3::new(8, 8, 0)), id![minecraft:plains]);
+        assert!(chunk.sections[0].biome_cells.is_some());
+
+        for x in (0..16).step_by(4) {
+            for z in (0..16).step_by(4) {
+                chunk.set_biome_column(x, z, id![minecraft:desert]);
+            }
         }
+        assert!(chunk.sections[0].biome_cells.is_none());
+        assert_eq!(chunk.sections[0].biome_palette, vec![id![minecraft:desert]]);
+
+        assert_eq!(direct_bits(65), 7);
+        assert_eq!(direct_bits(1), 1);
     }
 }
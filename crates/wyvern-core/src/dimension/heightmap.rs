@@ -0,0 +1,199 @@
+use std::sync::LazyLock;
+
+use voxidian_protocol::value::{Heightmap as PtcHeightmap, HeightmapKind as PtcHeightmapKind};
+use wyvern_values::Id;
+
+use crate::blocks::INTERNED_STATES;
+
+/// The heightmaps a chunk keeps track of and sends to clients.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HeightmapKind {
+    /// The highest block in each column that isn't air.
+    WorldSurface,
+    /// The highest block in each column that blocks movement or holds a fluid.
+    MotionBlocking,
+}
+
+impl HeightmapKind {
+    pub(crate) const ALL: [HeightmapKind; 2] =
+        [HeightmapKind::WorldSurface, HeightmapKind::MotionBlocking];
+
+    /// Returns whether a block with the given protocol id counts towards this heightmap.
+    pub(crate) fn includes(self, block: u32) -> bool {
+        let Some(flags) = BLOCK_FLAGS.get(block as usize) else {
+            return false;
+        };
+        match self {
+            HeightmapKind::WorldSurface => flags.not_air,
+            HeightmapKind::MotionBlocking => flags.blocks_motion,
+        }
+    }
+}
+
+impl From<HeightmapKind> for PtcHeightmapKind {
+    fn from(value: HeightmapKind) -> Self {
+        match value {
+            HeightmapKind::WorldSurface => PtcHeightmapKind::WorldSurface,
+            HeightmapKind::MotionBlocking => PtcHeightmapKind::MotionBlocking,
+        }
+    }
+}
+
+struct BlockFlags {
+    not_air: bool,
+    blocks_motion: bool,
+}
+
+/// Blocks that aren't air but can be walked through. Anything else is assumed to have a
+/// collision box.
+const PASSABLE_SUFFIXES: &[&str] = &[
+    "_sapling",
+    "_button",
+    "_pressure_plate",
+    "_sign",
+    "_banner",
+    "torch",
+    "rail",
+    "_flower",
+    "_tulip",
+    "_mushroom",
+    "_roots",
+    "_vines",
+    "_vines_plant",
+    "_coral_fan",
+];
+
+const PASSABLE_BLOCKS: &[&str] = &[
+    "short_grass",
+    "tall_grass",
+    "fern",
+    "large_fern",
+    "dead_bush",
+    "dandelion",
+    "poppy",
+    "blue_orchid",
+    "allium",
+    "azure_bluet",
+    "oxeye_daisy",
+    "cornflower",
+    "lily_of_the_valley",
+    "sunflower",
+    "lilac",
+    "rose_bush",
+    "peony",
+    "wheat",
+    "carrots",
+    "potatoes",
+    "beetroots",
+    "sugar_cane",
+    "vine",
+    "ladder",
+    "lever",
+    "tripwire",
+    "tripwire_hook",
+    "redstone_wire",
+    "snow",
+    "cobweb",
+    "nether_portal",
+    "end_portal",
+    "structure_void",
+    "light",
+];
+
+fn is_air(block: &Id) -> bool {
+    matches!(block.path(), "air" | "cave_air" | "void_air")
+}
+
+fn is_passable(block: &Id) -> bool {
+    let path = block.path();
+    PASSABLE_BLOCKS.contains(&path)
+        || PASSABLE_SUFFIXES
+            .iter()
+            .any(|suffix| path.ends_with(suffix))
+}
+
+fn is_fluid(block: &Id) -> bool {
+    matches!(block.path(), "water" | "lava" | "bubble_column")
+}
+
+/// Heightmap flags of every protocol block state, indexed by protocol id.
+static BLOCK_FLAGS: LazyLock<Vec<BlockFlags>> = LazyLock::new(|| {
+    INTERNED_STATES
+        .iter()
+        .map(|state| BlockFlags {
+            not_air: !is_air(&state.block),
+            blocks_motion: is_fluid(&state.block)
+                || (!is_air(&state.block) && !is_passable(&state.block)),
+        })
+        .collect()
+});
+
+/// The height of the highest included block in each column of a chunk, stored as one more
+/// than its offset from the bottom of the dimension, with zero meaning the column is empty.
+#[derive(Clone, Debug)]
+pub(crate) struct Heightmap {
+    heights: [u16; 256],
+}
+
+impl Heightmap {
+    pub(crate) fn new() -> Heightmap {
+        Heightmap { heights: [0; 256] }
+    }
+
+    fn index(x: i32, z: i32) -> usize {
+        (z as usize) * 16 + x as usize
+    }
+
+    pub(crate) fn get(&self, x: i32, z: i32) -> u16 {
+        self.heights[Self::index(x, z)]
+    }
+
+    pub(crate) fn set(&mut self, x: i32, z: i32, height: u16) {
+        self.heights[Self::index(x, z)] = height;
+    }
+
+    /// Packs the heights into longs the way the protocol expects, using just enough bits per
+    /// entry to fit `world_height`, without letting an entry span two longs.
+    pub(crate) fn pack(&self, world_height: u32) -> Vec<u64> {
+        let bits = (u32::BITS - world_height.leading_zeros()).max(1) as usize;
+        let per_long = 64 / bits;
+        let mask = (1u64 << bits) - 1;
+
+        let mut longs = vec![0u64; self.heights.len().div_ceil(per_long)];
+        for (i, height) in self.heights.iter().enumerate() {
+            longs[i / per_long] |= (*height as u64 & mask) << ((i % per_long) * bits);
+        }
+        longs
+    }
+
+    pub(crate) fn to_protocol(&self, kind: HeightmapKind, world_height: u32) -> PtcHeightmap {
+        PtcHeightmap {
+            kind: kind.into(),
+            data: self
+                .pack(world_height)
+                .into_iter()
+                .map(|long| long as i64)
+                .collect::<Vec<_>>()
+                .into(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Heightmap;
+
+    #[test]
+    fn packs_without_spanning_longs() {
+        let mut heightmap = Heightmap::new();
+        heightmap.set(0, 0, 1);
+        heightmap.set(1, 0, 384);
+        heightmap.set(15, 15, 5);
+
+        // 384 blocks tall needs 9 bits, so each long holds 7 columns.
+        let packed = heightmap.pack(384);
+        assert_eq!(packed.len(), 37);
+        assert_eq!(packed[0], 1 | (384 << 9));
+        assert_eq!(packed[36], 5 << (9 * (255 % 7)));
+    }
+}
@@ -0,0 +1,220 @@
+use std::collections::VecDeque;
+
+use crate::blocks::{MAX_LIGHT, light_emission, light_opacity};
+
+const SECTION_VOLUME: usize = 4096;
+
+/// The sky and block light levels of every block in a chunk, computed from its block states.
+/// Light doesn't spread across chunk borders.
+#[derive(Clone, Debug)]
+pub(crate) struct ChunkLight {
+    sections: usize,
+    sky: Vec<u8>,
+    block: Vec<u8>,
+}
+
+/// Light data for a chunk in the shape of the chunk packet, covering one extra section
+/// below and above the chunk.
+pub(crate) struct ProtocolLight {
+    pub(crate) sky_mask: Vec<i64>,
+    pub(crate) block_mask: Vec<i64>,
+    pub(crate) empty_sky_mask: Vec<i64>,
+    pub(crate) empty_block_mask: Vec<i64>,
+    pub(crate) sky_arrays: Vec<Vec<u8>>,
+    pub(crate) block_arrays: Vec<Vec<u8>>,
+}
+
+fn index(x: usize, y: usize, z: usize) -> usize {
+    y * 256 + z * 16 + x
+}
+
+fn neighbours(idx: usize, height: usize) -> impl Iterator<Item = usize> {
+    let x = idx % 16;
+    let z = (idx / 16) % 16;
+    let y = idx / 256;
+    [
+        (x > 0).then(|| idx - 1),
+        (x < 15).then(|| idx + 1),
+        (z > 0).then(|| idx - 16),
+        (z < 15).then(|| idx + 16),
+        (y > 0).then(|| idx - 256),
+        (y + 1 < height).then(|| idx + 256),
+    ]
+    .into_iter()
+    .flatten()
+}
+
+/// Spreads light outwards from every queued block, losing a level with every step plus the
+/// opacity of the block it enters.
+fn propagate(levels: &mut [u8], blocks: &[u32], mut queue: VecDeque<usize>, height: usize) {
+    while let Some(idx) = queue.pop_front() {
+        let level = levels[idx];
+        if level <= 1 {
+            continue;
+        }
+        for next in neighbours(idx, height) {
+            let spread = level.saturating_sub(light_opacity(blocks[next]).max(1));
+            if spread > levels[next] {
+                levels[next] = spread;
+                queue.push_back(next);
+            }
+        }
+    }
+}
+
+/// Packs a section's light levels into the nibble array sent to clients, or returns `None`
+/// if the section is completely dark.
+fn section_nibbles(levels: &[u8]) -> Option<Vec<u8>> {
+    if levels.iter().all(|level| *level == 0) {
+        return None;
+    }
+    Some(
+        levels
+            .chunks(2)
+            .map(|pair| (pair[0] & 0xF) | ((pair[1] & 0xF) << 4))
+            .collect(),
+    )
+}
+
+fn set_bit(mask: &mut Vec<i64>, bit: usize) {
+    if mask.len() <= bit / 64 {
+        mask.resize(bit / 64 + 1, 0);
+    }
+    mask[bit / 64] |= 1 << (bit % 64);
+}
+
+impl ChunkLight {
+    /// Computes the light of a chunk from the protocol ids of its blocks, ordered by
+    /// section, then y, z and x.
+    pub(crate) fn compute(blocks: &[u32]) -> ChunkLight {
+        let sections = blocks.len() / SECTION_VOLUME;
+        let height = sections * 16;
+        let mut sky = vec![0; blocks.len()];
+        let mut block = vec![0; blocks.len()];
+
+        // Skylight falls straight down from the top of the chunk until something blocks it.
+        let mut sky_queue = VecDeque::new();
+        for z in 0..16 {
+            for x in 0..16 {
+                let mut level = MAX_LIGHT;
+                for y in (0..height).rev() {
+                    let idx = index(x, y, z);
+                    level = level.saturating_sub(light_opacity(blocks[idx]));
+                    if level == 0 {
+                        break;
+                    }
+                    sky[idx] = level;
+                    sky_queue.push_back(idx);
+                }
+            }
+        }
+        propagate(&mut sky, blocks, sky_queue, height);
+
+        let mut block_queue = VecDeque::new();
+        for (idx, state) in blocks.iter().enumerate() {
+            let emission = light_emission(*state);
+            if emission > 0 {
+                block[idx] = emission;
+                block_queue.push_back(idx);
+            }
+        }
+        propagate(&mut block, blocks, block_queue, height);
+
+        ChunkLight {
+            sections,
+            sky,
+            block,
+        }
+    }
+
+    pub(crate) fn sky_light(&self, x: usize, y: usize, z: usize) -> u8 {
+        self.sky[index(x, y, z)]
+    }
+
+    pub(crate) fn block_light(&self, x: usize, y: usize, z: usize) -> u8 {
+        self.block[index(x, y, z)]
+    }
+
+    pub(crate) fn to_protocol(&self) -> ProtocolLight {
+        let mut light = ProtocolLight {
+            sky_mask: Vec::new(),
+            block_mask: Vec::new(),
+            empty_sky_mask: Vec::new(),
+            empty_block_mask: Vec::new(),
+            sky_arrays: Vec::new(),
+            block_arrays: Vec::new(),
+        };
+
+        // The section below the chunk is dark, and the one above it is in full skylight.
+        set_bit(&mut light.empty_sky_mask, 0);
+        set_bit(&mut light.empty_block_mask, 0);
+        for section in 0..self.sections {
+            let range = section * SECTION_VOLUME..(section + 1) * SECTION_VOLUME;
+            match section_nibbles(&self.sky[range.clone()]) {
+                Some(nibbles) => {
+                    set_bit(&mut light.sky_mask, section + 1);
+                    light.sky_arrays.push(nibbles);
+                }
+                None => set_bit(&mut light.empty_sky_mask, section + 1),
+            }
+            match section_nibbles(&self.block[range]) {
+                Some(nibbles) => {
+                    set_bit(&mut light.block_mask, section + 1);
+                    light.block_arrays.push(nibbles);
+                }
+                None => set_bit(&mut light.empty_block_mask, section + 1),
+            }
+        }
+        set_bit(&mut light.sky_mask, self.sections + 1);
+        light.sky_arrays.push(vec![0xFF; SECTION_VOLUME / 2]);
+        set_bit(&mut light.empty_block_mask, self.sections + 1);
+
+        light
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::blocks::{BlockState, Blocks};
+
+    use super::{ChunkLight, SECTION_VOLUME, index};
+
+    fn id(block: wyvern_values::Id) -> u32 {
+        BlockState::new(block).protocol_id() as u32
+    }
+
+    #[test]
+    fn skylight_stops_at_opaque_blocks() {
+        let mut blocks = vec![0; SECTION_VOLUME * 2];
+        for z in 0..16 {
+            for x in 0..16 {
+                blocks[index(x, 20, z)] = id(Blocks::STONE);
+            }
+        }
+        blocks[index(4, 20, 4)] = 0;
+
+        let light = ChunkLight::compute(&blocks);
+        assert_eq!(light.sky_light(0, 25, 0), 15);
+        assert_eq!(light.sky_light(0, 20, 0), 0);
+        assert_eq!(light.sky_light(4, 10, 4), 15);
+        assert_eq!(light.sky_light(6, 10, 4), 13);
+        assert_eq!(light.sky_light(0, 0, 15), 0);
+    }
+
+    #[test]
+    fn block_light_spreads_from_emitters() {
+        let mut blocks = vec![0; SECTION_VOLUME];
+        blocks[index(8, 8, 8)] = id(Blocks::GLOWSTONE);
+
+        let light = ChunkLight::compute(&blocks);
+        assert_eq!(light.block_light(8, 8, 8), 15);
+        assert_eq!(light.block_light(8, 10, 8), 13);
+        assert_eq!(light.block_light(0, 8, 8), 7);
+
+        let protocol = light.to_protocol();
+        assert_eq!(protocol.block_mask, vec![0b010]);
+        assert_eq!(protocol.empty_block_mask, vec![0b101]);
+        assert_eq!(protocol.sky_mask, vec![0b110]);
+        assert_eq!(protocol.sky_arrays.len(), 2);
+    }
+}
@@ -1,29 +1,37 @@
 use std::{
-    collections::HashMap,
-    sync::Arc,
+    collections::{HashMap, HashSet},
+    panic::{AssertUnwindSafe, catch_unwind},
+    sync::{Arc, Mutex},
     time::{Duration, Instant},
 };
 
 use crate::{
     blocks::BlockState,
-    entities::{Entity, EntityComponents, EntityData, PlayerSkinData},
+    entities::{
+        BoundingBox, DEFAULT_PICKUP_DELAY, DespawnRule, Entity, EntityComponents, EntityData,
+        EntityInterpolation, EntitySize, EntitySpawn, MountChange, PlayerSkinData, TICK_LENGTH,
+        add_entity_packets, item_entity,
+    },
+    item::ItemStack,
+    player::Player,
     server::registries::RegistryKeys,
 };
 use chunk::{Chunk, ChunkSection};
-use flume::Sender;
+use flume::{Receiver, Sender};
+use light::ProtocolLight;
+use region::SectionUpdates;
 use voxidian_protocol::{
     packet::s2c::play::{
-        AddEntityS2CPlayPacket, BlockUpdateS2CPlayPacket, ChunkBlockEntity, PlayerActionEntry,
-        PlayerInfoUpdateS2CPlayPacket, RemoveEntitiesS2CPlayPacket, SetEntityDataS2CPlayPacket,
+        BlockEntityDataS2CPlayPacket, BlockUpdateS2CPlayPacket, ChangeDifficultyS2CPlayPacket,
+        ChunkBlockEntity, PlayerActionEntry, PlayerInfoUpdateS2CPlayPacket,
+        RemoveEntitiesS2CPlayPacket, SystemChatS2CPlayPacket,
     },
     registry::RegEntry,
-    value::{
-        Angle, BlockPos, EntityMetadata, EntityType as PtcEntityType, Nbt, ProfileProperty, Uuid,
-        VarInt,
-    },
+    value::{BlockPos, Heightmap as PtcHeightmap, ProfileProperty, Text as PtcText, Uuid, VarInt},
 };
 use wyvern_actors::Actor;
 use wyvern_components::{ComponentElement, DataComponentHolder, DataComponentMap};
+use wyvern_datatypes::{difficulty::Difficulty, gamemode::Gamemode, text::Text, weather::Weather};
 use wyvern_values::{DVec3, IVec2, IVec3, id};
 
 use crate::{events::ChunkLoadEvent, runtime::Runtime, server::Server};
@@ -31,7 +39,32 @@ use crate::{events::ChunkLoadEvent, runtime::Runtime, server::Server};
 use crate::actors::{ActorError, ActorResult};
 use wyvern_values::{Id, Vec2};
 
+mod cache;
 pub mod chunk;
+pub mod heightmap;
+pub(crate) mod light;
+mod protection;
+mod raycast;
+mod region;
+pub mod trigger;
+mod void;
+
+pub use cache::{CachedGenerator, ChunkCache, ChunkCacheKey};
+pub use protection::SpawnProtection;
+pub use raycast::{EntityRaycastHit, RaycastHit};
+pub use region::Region;
+pub(crate) use region::SectionUpdates;
+use trigger::RegionTrigger;
+pub use void::{DEFAULT_VOID_MARGIN, VoidBehavior};
+mod border;
+mod time;
+pub(crate) use border::BorderState;
+pub use border::{DEFAULT_BORDER_DIAMETER, WorldBorder};
+mod spawn;
+pub use spawn::WorldSpawn;
+mod rules;
+pub(crate) use rules::immediate_respawn_packet;
+pub use rules::{GameRule, GameRuleKeys, GameRuleType, GameRuleValue, GameRules};
 
 #[allow(dead_code)]
 #[crate::actor(Dimension, DimensionMessage)]
@@ -43,8 +76,36 @@ pub struct DimensionData {
     pub(crate) server: Option<Server>,
     pub(crate) sender: Sender<DimensionMessage>,
     pub(crate) dim_type: Id,
-    pub(crate) chunk_generator: Box<dyn Fn(&mut Chunk, i32, i32) + Send>,
+    pub(crate) chunk_generator: Arc<dyn Fn(&mut Chunk, i32, i32) + Send + Sync>,
+    pub(crate) pending_chunks: HashMap<IVec2, PendingChunk>,
     pub(crate) chunk_max: (u32, u32),
+    pub(crate) simulation_distance: Option<u32>,
+    pub(crate) default_gamemode: Option<Gamemode>,
+    pub(crate) region_triggers: HashMap<Id, RegionTrigger>,
+    /// The height players die below, or `None` to use the bottom of the dimension minus
+    /// `DEFAULT_VOID_MARGIN`.
+    pub(crate) void_height: Option<f64>,
+    pub(crate) void_behavior: VoidBehavior,
+    pub(crate) world_border: BorderState,
+    /// How many ticks this dimension has existed for.
+    pub(crate) world_age: i64,
+    /// The time of day in ticks, which keeps counting up past the end of the first day.
+    pub(crate) time: i64,
+    pub(crate) weather: Weather,
+    pub(crate) difficulty: Difficulty,
+    pub(crate) difficulty_locked: bool,
+    pub(crate) game_rules: GameRules,
+    /// Despawn rules for entity types that don't use `DespawnRule::default_for`.
+    pub(crate) despawn_rules: HashMap<Id, DespawnRule>,
+    /// Tracking ranges for entity types that don't use `default_tracking_range`.
+    pub(crate) tracking_ranges: HashMap<Id, f64>,
+    /// The entities each player in this dimension has been sent, keyed by the player's UUID.
+    pub(crate) tracked_entities: HashMap<Uuid, HashSet<Uuid>>,
+    pub(crate) spawn_protection: SpawnProtection,
+    pub(crate) world_spawn: WorldSpawn,
+    pub(crate) entity_interpolation: EntityInterpolation,
+    /// The last update between ticks that entity positions were sent for.
+    pub(crate) interpolation_step: u32,
     pub(crate) last_update: Instant,
 }
 
@@ -55,16 +116,54 @@ impl Dimension {
             dimension: self.clone(),
         }
     }
+
+    /// Runs `f` for every player in this dimension.
+    pub fn broadcast<F: FnMut(&Player) -> ActorResult<()>>(&self, mut f: F) -> ActorResult<()> {
+        let server = self.server()?;
+        for uuid in self.players()? {
+            match server.player(uuid).and_then(|player| f(&player)) {
+                Ok(()) | Err(ActorError::ActorHasBeenDropped) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+
+    /// Sends a chat message to every player in this dimension.
+    pub fn broadcast_message(&self, message: Text) -> ActorResult<()> {
+        let server = self.server()?;
+        let players = self
+            .players()?
+            .into_iter()
+            .filter_map(|uuid| server.player(uuid).ok())
+            .collect::<Vec<_>>();
+        Player::broadcast_packet(
+            &players,
+            SystemChatS2CPlayPacket {
+                content: PtcText::from(message).to_nbt(),
+                is_actionbar: false,
+            },
+        )
+    }
 }
 
 impl DimensionData {
     pub fn event_loop(mut self) {
         loop {
             self.handle_messages();
-            if Instant::now().duration_since(self.last_update) > Duration::from_millis(50) {
+            if Instant::now().duration_since(self.last_update) > TICK_LENGTH {
                 self.last_update = Instant::now();
+                self.interpolation_step = 0;
                 let _ = self.auto_apply_entity_properties();
+                let _ = self.move_passengers();
+                let _ = self.update_entity_tracking();
                 let _ = self.propogate_entity_packets();
+                let _ = self.despawn_entities();
+                let _ = self.expire_entity_effects();
+                let _ = self.tick_item_entities();
+                let _ = self.advance_time();
+            } else {
+                let _ = self.send_interpolated_positions();
             }
         }
     }
@@ -101,9 +200,9 @@ impl DimensionData {
         self.server.clone().ok_or(ActorError::ActorIsNotLoaded)
     }
 
-    #[GetChunkSection]
-    #[doc = "Returns a copy of the 16x16x16 chunk section at the provided coordinates."]
-    pub fn get_chunk_section(&mut self, position: IVec3) -> ActorResult<Option<ChunkSection>> {
+    #[TryGetChunkSection]
+    #[doc = "Returns a copy of the 16x16x16 chunk section at the provided coordinates, or `ActorError::Pending` if the chunk is still being generated."]
+    pub fn try_get_chunk_section(&mut self, position: IVec3) -> ActorResult<Option<ChunkSection>> {
         let chunk_pos = IVec2::new(position[0], position[2]);
         self.try_initialize_chunk(&chunk_pos)?;
 
@@ -122,26 +221,33 @@ impl DimensionData {
         position: IVec2,
     ) -> ActorResult<Vec<ChunkBlockEntity>> {
         match self.chunks.get(&position) {
-            Some(chunk) => {
-                let list = chunk
-                    .block_entities
-                    .iter()
-                    .map(|x| ChunkBlockEntity {
-                        packed_xz: (((x.0[0] & 15) << 4) | (x.0[2] & 15)) as u8,
-                        y: x.0[1],
-                        entity_type: *x.1,
-                        data: Nbt::new(),
-                    })
-                    .collect::<Vec<_>>();
-                Ok(list)
-            }
+            Some(chunk) => Ok(chunk.protocol_block_entities()),
             None => Ok(Vec::new()),
         }
     }
 
-    #[SetBlock]
-    #[doc = "Sets a block in this dimension at the given coordinates to the provided block state."]
-    pub fn set_block(&mut self, position: IVec3, block_state: BlockState) -> ActorResult<()> {
+    #[GetChunkHeightmaps]
+    pub fn get_chunk_heightmaps(&mut self, position: IVec2) -> ActorResult<Vec<PtcHeightmap>> {
+        match self.chunks.get(&position) {
+            Some(chunk) => Ok(chunk.protocol_heightmaps()),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    #[GetChunkLight]
+    pub(crate) fn get_chunk_light(
+        &mut self,
+        position: IVec2,
+    ) -> ActorResult<Option<ProtocolLight>> {
+        Ok(self
+            .chunks
+            .get_mut(&position)
+            .map(|chunk| chunk.light().to_protocol()))
+    }
+
+    #[TrySetBlock]
+    #[doc = "Sets a block in this dimension at the given coordinates to the provided block state, or returns `ActorError::Pending` if the chunk is still being generated."]
+    pub fn try_set_block(&mut self, position: IVec3, block_state: BlockState) -> ActorResult<()> {
         let chunk_pos = IVec2::new(position[0].div_euclid(16), position[2].div_euclid(16));
         let pos_in_chunk = IVec3::new(
             position[0].rem_euclid(16),
@@ -154,7 +260,8 @@ impl DimensionData {
         let Some(chunk) = self.chunks.get_mut(&chunk_pos) else {
             return Ok(());
         };
-        chunk.set_block_at(pos_in_chunk, &block_state.clone());
+        chunk.set_block_at(pos_in_chunk, &block_state);
+        let block_entity = chunk.block_entity_at(pos_in_chunk);
 
         let server = self.server.clone().unwrap();
         Runtime::spawn_task(async move {
@@ -167,15 +274,113 @@ impl DimensionData {
                     pos: BlockPos::new(pos[0], pos[1], pos[2]),
                     block: unsafe { RegEntry::new_unchecked(block_state.protocol_id() as u32) },
                 });
+
+                if let Some(block_entity) = &block_entity {
+                    let _ = conn.write_packet(BlockEntityDataS2CPlayPacket {
+                        pos: BlockPos::new(pos[0], pos[1], pos[2]),
+                        entity_type: block_entity.entity_type,
+                        data: block_entity.data.clone(),
+                    });
+                }
             }
             Ok(())
         });
         Ok(())
     }
 
-    #[SetBlockLoading]
-    #[doc = "Sets a block in this dimension at the given coordinates to the provided block state. Does not update for current players"]
-    pub fn set_block_loading(&mut self, position: IVec3, block_state: u32) -> ActorResult<()> {
+    #[TryFillRegion]
+    #[doc = "Sets every block between two corners to the provided block state, sending players one update per affected section, or returns `ActorError::Pending` if any of its chunks are still being generated."]
+    pub fn try_fill_region(
+        &mut self,
+        from: IVec3,
+        to: IVec3,
+        block_state: BlockState,
+    ) -> ActorResult<()> {
+        let Some(region) = self.clamp_region(Region::new(from, to))? else {
+            return Ok(());
+        };
+        self.try_initialize_region(region)?;
+
+        let state = block_state.protocol_id() as u32;
+        let mut updates = SectionUpdates::default();
+        for position in region.positions() {
+            let Some(chunk) = self.chunks.get_mut(&chunk_of(position)) else {
+                continue;
+            };
+            let pos_in_chunk = local_pos(position);
+            chunk.set_block_at(pos_in_chunk, &block_state);
+            updates.push(position, state, chunk.block_entity_at(pos_in_chunk));
+        }
+        self.send_section_updates(updates)
+    }
+
+    #[TryCloneRegion]
+    #[doc = "Copies every block between two corners so the lowest corner of the copy is at `dest`, sending players one update per affected section, or returns `ActorError::Pending` if any of the chunks involved are still being generated."]
+    pub fn try_clone_region(&mut self, from: IVec3, to: IVec3, dest: IVec3) -> ActorResult<()> {
+        let source = Region::new(from, to);
+        let offset = dest - source.min;
+        let Some(source) = self.clamp_region(source)? else {
+            return Ok(());
+        };
+        let Some(target) = self.clamp_region(source.offset(offset))? else {
+            return Ok(());
+        };
+        let source_pending = self.try_initialize_region(source);
+        self.try_initialize_region(target)?;
+        source_pending?;
+
+        // Read everything before writing so overlapping regions copy their original blocks.
+        let mut blocks = Vec::new();
+        for position in source.positions() {
+            let target_pos = position + offset;
+            if target_pos[1] < target.min[1] || target_pos[1] > target.max[1] {
+                continue;
+            }
+            if let Some(chunk) = self.chunks.get_mut(&chunk_of(position)) {
+                blocks.push((target_pos, chunk.get_block_at(local_pos(position))));
+            }
+        }
+
+        let mut updates = SectionUpdates::default();
+        for (position, block_state) in blocks {
+            let Some(chunk) = self.chunks.get_mut(&chunk_of(position)) else {
+                continue;
+            };
+            let pos_in_chunk = local_pos(position);
+            chunk.set_block_at(pos_in_chunk, &block_state);
+            updates.push(
+                position,
+                block_state.protocol_id() as u32,
+                chunk.block_entity_at(pos_in_chunk),
+            );
+        }
+        self.send_section_updates(updates)
+    }
+
+    #[TryGetRegionBlocks]
+    #[doc = "Returns a copy of every block state between two corners along with its position, or returns `ActorError::Pending` if any of its chunks are still being generated. The region is clamped to the height of the dimension."]
+    pub fn try_get_region_blocks(
+        &mut self,
+        from: IVec3,
+        to: IVec3,
+    ) -> ActorResult<Vec<(IVec3, BlockState)>> {
+        let Some(region) = self.clamp_region(Region::new(from, to))? else {
+            return Ok(Vec::new());
+        };
+        self.try_initialize_region(region)?;
+
+        Ok(region
+            .positions()
+            .filter_map(|position| {
+                let chunk = self.chunks.get_mut(&chunk_of(position))?;
+                Some((position, chunk.get_block_at(local_pos(position))))
+            })
+            .collect())
+    }
+
+    #[TrySetBlockLoading]
+    #[doc = "Sets a block in this dimension at the given coordinates to the provided block state without updating current players, or returns `ActorError::Pending` if the chunk is still being generated."]
+    pub fn try_set_block_loading(&mut self, position: IVec3, block_state: u32) -> ActorResult<()> {
         let chunk_pos = IVec2::new(position[0].div_euclid(16), position[2].div_euclid(16));
         let pos_in_chunk = IVec3::new(
             position[0].rem_euclid(16),
@@ -193,9 +398,9 @@ impl DimensionData {
         Ok(())
     }
 
-    #[GetBlock]
-    #[doc = "Returns a copy of the block state at the provided coordinates."]
-    pub fn get_block(&mut self, position: IVec3) -> ActorResult<BlockState> {
+    #[TryGetBlock]
+    #[doc = "Returns a copy of the block state at the provided coordinates, or `ActorError::Pending` if the chunk is still being generated."]
+    pub fn try_get_block(&mut self, position: IVec3) -> ActorResult<BlockState> {
         let chunk = IVec2::new(position[0].div_euclid(16), position[2].div_euclid(16));
         let pos_in_chunk = IVec3::new(
             position[0].rem_euclid(16),
@@ -223,15 +428,60 @@ impl DimensionData {
     }
 
     #[SetChunkGenerator]
-    #[doc = "Overrides the function that will be called whenever a new Chunk is generated. The default chunk generator is a no-op."]
+    #[doc = "Overrides the function that will be called whenever a new Chunk is generated. Generators run on the task pool, so they should not assume they are on the dimension's thread. The default chunk generator is a no-op."]
     pub fn set_boxed_chunk_generator(
         &mut self,
-        function: Box<dyn Fn(&mut Chunk, i32, i32) + Send>,
+        function: Arc<dyn Fn(&mut Chunk, i32, i32) + Send + Sync>,
     ) -> ActorResult<()> {
         self.chunk_generator = function;
         Ok(())
     }
 
+    #[AwaitChunk]
+    #[doc = "Starts generating the chunk at the given chunk coordinates if needed. Returns its generation job and a receiver notified once it is inserted or its generation fails, or `None` if it is already loaded."]
+    pub(crate) fn await_chunk(
+        &mut self,
+        pos: IVec2,
+    ) -> ActorResult<Option<(Arc<ChunkJob>, Receiver<ActorResult<()>>)>> {
+        match self.try_initialize_chunk(&pos) {
+            Err(ActorError::Pending) => {
+                let pending = self.pending_chunks.get_mut(&pos).unwrap();
+                let (sender, receiver) = flume::bounded(1);
+                pending.waiting.push(sender);
+                Ok(Some((pending.job.clone(), receiver)))
+            }
+            result => result.map(|_| None),
+        }
+    }
+
+    #[InsertGeneratedChunk]
+    #[doc = "Inserts a chunk produced by the chunk generator, waking everything waiting on it."]
+    pub(crate) fn insert_generated_chunk(&mut self, pos: IVec2, chunk: Chunk) -> ActorResult<()> {
+        let Some(pending) = self.pending_chunks.remove(&pos) else {
+            return Ok(());
+        };
+        self.chunks.insert(pos, chunk);
+        for sender in pending.waiting {
+            let _ = sender.try_send(Ok(()));
+        }
+
+        self.server()?.spawn_event(ChunkLoadEvent {
+            dimension: self.as_actor(),
+            pos,
+        })
+    }
+
+    #[AbandonChunk]
+    #[doc = "Forgets a chunk whose generator panicked, failing everything waiting on it with `ActorError::GenerationFailed`. The next request for the chunk generates it again."]
+    pub(crate) fn abandon_chunk(&mut self, pos: IVec2) -> ActorResult<()> {
+        if let Some(pending) = self.pending_chunks.remove(&pos) {
+            for sender in pending.waiting {
+                let _ = sender.try_send(Err(ActorError::GenerationFailed));
+            }
+        }
+        Ok(())
+    }
+
     #[GetAllEntities]
     #[doc = "Returns a handle to all of the entities present in this dimension."]
     pub fn entities(&self) -> ActorResult<Vec<Entity>> {
@@ -259,145 +509,163 @@ impl DimensionData {
             .collect())
     }
 
-    #[UniversalEntitySpawn]
-    pub(crate) fn spawn_entity_universal(
-        &mut self,
-        entity_id: i32,
-        uuid: Uuid,
-        entity_type: Id,
-        metadata: EntityMetadata,
-    ) -> ActorResult<()> {
-        let dim = self.as_actor();
-        Runtime::spawn_task(async move {
-            for conn in dim.players().unwrap_or_else(|_| Vec::new()) {
-                if conn != uuid {
-                    let conn = dim.server().unwrap().player(conn).unwrap();
-                    let _ = conn.write_packet(AddEntityS2CPlayPacket {
-                        id: entity_id.into(),
-                        uuid,
-                        kind: PtcEntityType::vanilla_registry()
-                            .get_entry(&entity_type.clone().into())
-                            .unwrap(),
-                        x: 0.0,
-                        y: 0.0,
-                        z: 0.0,
-                        pitch: Angle::of_deg(0.0),
-                        yaw: Angle::of_deg(0.0),
-                        head_yaw: Angle::of_deg(0.0),
-                        data: VarInt::from(0),
-                        vel_x: 0,
-                        vel_y: 0,
-                        vel_z: 0,
-                    });
-                    let _ = conn.write_packet(SetEntityDataS2CPlayPacket {
-                        entity: entity_id.into(),
-                        data: metadata.clone(),
-                    });
-                }
-            }
-            Ok(())
-        });
-        Ok(())
-    }
-
     #[SpawnEntity]
     #[doc = "Spawns a new entity in the dimension with the given type, returning a handle to the entity."]
     pub fn spawn_entity(&mut self, entity_type: Id) -> ActorResult<Entity> {
-        let mut uuid = Uuid::new_v4();
-        while self.entities.contains_key(&uuid) {
-            uuid = Uuid::new_v4();
-        }
+        Ok(self
+            .spawn_entities(vec![EntitySpawn::new(entity_type)])?
+            .remove(0))
+    }
 
-        let mut components = DataComponentMap::new();
-        let id = self.server.clone().unwrap().new_entity_id()?;
-        components.set(EntityComponents::ENTITY_ID, id);
-        components.set(EntityComponents::UUID, uuid);
-        components.set(EntityComponents::ENTITY_TYPE, entity_type.clone());
-        components.set(EntityComponents::POSITION, DVec3::new(0.0, 0.0, 0.0));
-        components.set(EntityComponents::DIRECTION, Vec2::new(0.0, 0.0));
-        components.set(EntityComponents::VELOCITY, DVec3::new(0.0, 0.0, 0.0));
-        components.set(EntityComponents::PLAYER_CONTROLLED, false);
+    #[SpawnEntities]
+    #[doc = "Spawns an entity for every description, returning handles to them in the same order. Entity ids are allocated with one request and players are sent every new entity from a single task, so this is much cheaper than calling `spawn_entity` in a loop. Fails with `ActorError::BadRequest`, spawning nothing, if any entity type is unknown to clients."]
+    pub fn spawn_entities(&mut self, spawns: Vec<EntitySpawn>) -> ActorResult<Vec<Entity>> {
+        let server = self.server()?;
+        let (entities, batch) = self.prepare_spawns(spawns)?;
+        Runtime::spawn_task(async move { batch.send(&server) });
+        Ok(entities)
+    }
 
-        self.entities.insert(
-            uuid,
-            EntityData {
-                last_components: DataComponentMap::new(),
-                components,
-            },
-        );
+    #[SpawnTextDisplay]
+    #[doc = "Spawns a text display showing `text` at `position`, such as for a hologram. Its scale, billboard and background can be changed with the `DISPLAY_` entity components."]
+    pub fn spawn_text_display(&mut self, position: DVec3, text: Text) -> ActorResult<Entity> {
+        Ok(self
+            .spawn_entities(vec![
+                EntitySpawn::new(id![minecraft:text_display])
+                    .position(position)
+                    .with(EntityComponents::DISPLAY_TEXT, text),
+            ])?
+            .remove(0))
+    }
 
-        let dim = self.as_actor();
+    #[SpawnItemDisplay]
+    #[doc = "Spawns an item display showing `item` at `position`."]
+    pub fn spawn_item_display(&mut self, position: DVec3, item: ItemStack) -> ActorResult<Entity> {
+        Ok(self
+            .spawn_entities(vec![
+                EntitySpawn::new(id![minecraft:item_display])
+                    .position(position)
+                    .with(EntityComponents::DISPLAY_ITEM, item),
+            ])?
+            .remove(0))
+    }
 
-        Runtime::spawn_task(async move {
-            let entity = dim.get_entity_by_id(id)?;
-            dim.spawn_entity_universal(id, uuid, entity_type, entity.generate_metadata()?)?;
-            Ok(())
-        });
+    #[SpawnBlockDisplay]
+    #[doc = "Spawns a block display showing `block` at `position`. The block's corner is at the position, rather than its center."]
+    pub fn spawn_block_display(
+        &mut self,
+        position: DVec3,
+        block: BlockState,
+    ) -> ActorResult<Entity> {
+        Ok(self
+            .spawn_entities(vec![
+                EntitySpawn::new(id![minecraft:block_display])
+                    .position(position)
+                    .with(EntityComponents::DISPLAY_BLOCK, block),
+            ])?
+            .remove(0))
+    }
 
-        Ok(Entity {
-            dimension: self.as_actor(),
-            uuid,
-        })
+    #[DropItem]
+    #[doc = "Drops `item` at `position` as an item entity moving by `velocity`, which players can pick up after `DEFAULT_PICKUP_DELAY` ticks. Item entities holding the same item merge when they're close together, and despawn after `ITEM_TIME_TO_LIVE` unless the dimension's despawn rule for items says otherwise."]
+    pub fn drop_item(
+        &mut self,
+        position: DVec3,
+        item: ItemStack,
+        velocity: DVec3,
+    ) -> ActorResult<Entity> {
+        self.drop_item_with_delay(position, item, velocity, DEFAULT_PICKUP_DELAY)
+    }
+
+    #[DropItemWithDelay]
+    #[doc = "Drops `item` like `drop_item`, but players can only pick it up after `pickup_delay` ticks."]
+    pub fn drop_item_with_delay(
+        &mut self,
+        position: DVec3,
+        item: ItemStack,
+        velocity: DVec3,
+        pickup_delay: u32,
+    ) -> ActorResult<Entity> {
+        Ok(self
+            .spawn_entities(vec![item_entity(position, item, velocity, pickup_delay)])?
+            .remove(0))
+    }
+
+    #[GetItemsIn]
+    #[doc = "Returns the item entities inside `area` that can be picked up."]
+    pub(crate) fn pickup_candidates(&self, area: BoundingBox) -> ActorResult<Vec<Uuid>> {
+        Ok(self.items_in(&area))
+    }
+
+    #[TakeFromItem]
+    #[doc = "Takes up to `max` items from an item entity, removing it once it's empty. Returns `None` if the entity is gone or can't be picked up yet."]
+    pub(crate) fn take_item_entity(
+        &mut self,
+        uuid: Uuid,
+        max: u16,
+    ) -> ActorResult<Option<ItemStack>> {
+        self.take_from_item(uuid, max)
     }
 
     #[SpawnHumanEntity]
     #[doc = "Spawns a new entity in the dimension with the given type, returning a handle to the entity."]
     pub fn spawn_human_entity(&mut self, skin: PlayerSkinData) -> ActorResult<Entity> {
-        let mut uuid = Uuid::new_v4();
-        while self.entities.contains_key(&uuid) {
-            uuid = Uuid::new_v4();
-        }
+        self.spawn_human_entity_with(skin, EntitySpawn::new(id![minecraft:player]))
+    }
 
-        let mut components = DataComponentMap::new();
-        let id = self.server.clone().unwrap().new_entity_id()?;
-        components.set(EntityComponents::ENTITY_ID, id);
-        components.set(EntityComponents::UUID, uuid);
-        components.set(EntityComponents::ENTITY_TYPE, id![minecraft:player]);
-        components.set(EntityComponents::POSITION, DVec3::new(0.0, 0.0, 0.0));
-        components.set(EntityComponents::DIRECTION, Vec2::new(0.0, 0.0));
-        components.set(EntityComponents::VELOCITY, DVec3::new(0.0, 0.0, 0.0));
-        components.set(EntityComponents::PLAYER_CONTROLLED, false);
+    #[SpawnHumanEntityWith]
+    #[doc = "Spawns a human entity with the given skin, described by `spawn` apart from its entity type. Its position, metadata, equipment and attributes are sent along with it, so players don't see it change after it appears."]
+    pub fn spawn_human_entity_with(
+        &mut self,
+        skin: PlayerSkinData,
+        mut spawn: EntitySpawn,
+    ) -> ActorResult<Entity> {
+        spawn.entity_type = id![minecraft:player];
+        let server = self.server()?;
+        let id = server.new_entity_id()?;
+        let uuid = self.unused_entity_uuid();
+        let mut components = self.spawn_components(id, uuid, spawn)?;
         components.set(EntityComponents::PLAYER_SKIN, skin.clone());
+        let packets = add_entity_packets(&components)?;
+        let observers = self.track_new_entity(uuid, &components)?;
 
         self.entities.insert(
             uuid,
             EntityData {
-                last_components: DataComponentMap::new(),
+                last_components: components.clone(),
                 components,
+                spawned_at: Instant::now(),
             },
         );
 
-        let dim = self.as_actor();
-
+        let players = self.players()?;
         Runtime::spawn_task(async move {
-            for conn in dim.players().unwrap_or_else(|_| Vec::new()) {
-                let conn = dim.server().unwrap().player(conn).unwrap();
-                let name = format!("NPC_{:?}", id);
-                let props = vec![ProfileProperty {
-                    name: "textures".to_string(),
-                    value: skin.texture.clone(),
-                    sig: Some(skin.signature.clone()),
-                }];
-                let _ = conn.write_packet(PlayerInfoUpdateS2CPlayPacket {
+            let players = players
+                .into_iter()
+                .filter_map(|uuid| server.player(uuid).ok())
+                .collect::<Vec<_>>();
+            let props = vec![ProfileProperty {
+                name: "textures".to_string(),
+                value: skin.texture,
+                sig: Some(skin.signature),
+            }];
+            Player::broadcast_packet(
+                &players,
+                PlayerInfoUpdateS2CPlayPacket {
                     actions: vec![(
                         uuid,
                         vec![PlayerActionEntry::AddPlayer {
-                            name,
+                            name: format!("NPC_{:?}", id),
                             props: props.into(),
                         }],
                     )],
-                });
-
-                let entity = dim.get_entity_by_id(id)?;
-                dim.spawn_entity_universal(
-                    id,
-                    uuid,
-                    id![minecraft:player],
-                    entity.generate_metadata()?,
-                )?;
-            }
-
-            Ok(())
+                },
+            )?;
+            let observers = observers
+                .into_iter()
+                .filter_map(|uuid| server.player(uuid).ok())
+                .collect::<Vec<_>>();
+            packets.send(&observers)
         });
 
         Ok(Entity {
@@ -415,6 +683,10 @@ impl DimensionData {
             EntityComponents::ENTITY_TYPE,
             Id::constant("minecraft", "player"),
         );
+        components.set(
+            EntityComponents::BOUNDING_BOX,
+            EntitySize::of(&id![minecraft:player]),
+        );
         components.set(EntityComponents::POSITION, DVec3::new(0.0, 0.0, 0.0));
         components.set(EntityComponents::DIRECTION, Vec2::new(0.0, 0.0));
         components.set(EntityComponents::VELOCITY, DVec3::new(0.0, 0.0, 0.0));
@@ -424,22 +696,12 @@ impl DimensionData {
             EntityData {
                 last_components: DataComponentMap::new(),
                 components,
+                spawned_at: Instant::now(),
             },
         );
 
-        let dim = self.as_actor();
-
-        Runtime::spawn_task(async move {
-            let entity = dim.get_entity_by_id(id)?;
-            dim.spawn_entity_universal(
-                id,
-                uuid,
-                id![minecraft:player],
-                entity.generate_metadata()?,
-            )?;
-            Ok(())
-        });
-
+        // Players in range are sent the new player entity on the next tick, and it's sent every
+        // entity in its own range.
         Ok(Entity {
             dimension: self.as_actor(),
             uuid,
@@ -451,14 +713,23 @@ impl DimensionData {
         let entry = self.entities.remove(&uuid);
 
         if let Some(entry) = entry {
+            self.detach_removed_entity(uuid, &entry);
             let server = self
                 .server
                 .as_ref()
                 .ok_or(ActorError::ActorDoesNotExist)?
                 .clone();
+            let trackers = self.trackers_of(&uuid);
+            for tracked in self.tracked_entities.values_mut() {
+                tracked.remove(&uuid);
+            }
+            self.tracked_entities.remove(&uuid);
 
             Runtime::spawn_task(async move {
-                for conn in server.players()? {
+                for conn in trackers {
+                    let Ok(conn) = server.player(conn) else {
+                        continue;
+                    };
                     let _ = conn.write_packet(RemoveEntitiesS2CPlayPacket {
                         entities: vec![VarInt::new(
                             entry.get(EntityComponents::ENTITY_ID).unwrap(),
@@ -518,14 +789,459 @@ impl DimensionData {
         self.chunk_max = (x, y);
         Ok(())
     }
+
+    #[GetChunkLimits]
+    #[doc = "Returns the largest chunk x and z coordinates this dimension will load."]
+    pub fn chunk_limits(&self) -> ActorResult<(u32, u32)> {
+        Ok(self.chunk_max)
+    }
+
+    #[GetSimulationDistance]
+    #[doc = "Returns the distance in chunks from a player an entity must be within to be simulated."]
+    pub fn simulation_distance(&self) -> ActorResult<Option<u32>> {
+        Ok(self.simulation_distance)
+    }
+
+    #[GetDefaultGamemode]
+    #[doc = "Returns the gamemode players joining this dimension are given, if it overrides the server default."]
+    pub fn default_gamemode(&self) -> ActorResult<Option<Gamemode>> {
+        Ok(self.default_gamemode.clone())
+    }
+
+    #[SetDefaultGamemode]
+    #[doc = "Sets the gamemode players joining this dimension are given. Handlers of `PlayerJoinEvent` that set a gamemode take priority over this."]
+    pub fn set_default_gamemode(&mut self, gamemode: Option<Gamemode>) -> ActorResult<()> {
+        self.default_gamemode = gamemode;
+        Ok(())
+    }
+
+    #[SetSimulationDistance]
+    #[doc = "Sets the distance in chunks from a player an entity must be within to be simulated. Entities outside of this range keep their state, but are frozen until a player comes close. `None` simulates all entities regardless of distance, and is the default."]
+    pub fn set_simulation_distance(&mut self, distance: Option<u32>) -> ActorResult<()> {
+        self.simulation_distance = distance;
+        Ok(())
+    }
+
+    #[GetEntityBoxes]
+    #[doc = "Returns every entity whose bounding box overlaps the box between `min` and `max`, along with its bounding box."]
+    pub(crate) fn entity_boxes(
+        &self,
+        min: DVec3,
+        max: DVec3,
+    ) -> ActorResult<Vec<(Entity, BoundingBox)>> {
+        let query = BoundingBox {
+            min: min.min(max),
+            max: min.max(max),
+        };
+        Ok(self
+            .entities
+            .iter()
+            .filter_map(|(uuid, data)| {
+                let bounding_box = data.bounding_box()?;
+                bounding_box.intersects(&query).then(|| {
+                    (
+                        Entity {
+                            dimension: self.as_actor(),
+                            uuid: *uuid,
+                        },
+                        bounding_box,
+                    )
+                })
+            })
+            .collect())
+    }
+
+    #[GetYRange]
+    #[doc = "Returns the lowest and highest y coordinates blocks can be placed at in this dimension."]
+    pub fn y_range(&self) -> ActorResult<(i32, i32)> {
+        let server = self.server.clone().unwrap();
+        let registries = server.registries()?;
+        let dim_type = registries
+            .get(RegistryKeys::DIMENSION_TYPE)
+            .get(self.dim_type.clone())
+            .ok_or(ActorError::BadRequest)?;
+        Ok((dim_type.min_y, dim_type.min_y + dim_type.height as i32 - 1))
+    }
+
+    #[GetVoidHeight]
+    #[doc = "Returns the height players fall into the void below."]
+    pub fn void_height(&self) -> ActorResult<f64> {
+        match self.void_height {
+            Some(height) => Ok(height),
+            None => Ok(self.y_range()?.0 as f64 - DEFAULT_VOID_MARGIN),
+        }
+    }
+
+    #[SetVoidHeight]
+    #[doc = "Sets the height players fall into the void below. `None` uses the bottom of the dimension minus `DEFAULT_VOID_MARGIN`."]
+    pub fn set_void_height(&mut self, height: Option<f64>) -> ActorResult<()> {
+        self.void_height = height;
+        Ok(())
+    }
+
+    #[GetVoidBehavior]
+    #[doc = "Returns what happens to players who fall below the void height."]
+    pub fn void_behavior(&self) -> ActorResult<VoidBehavior> {
+        Ok(self.void_behavior)
+    }
+
+    #[SetVoidBehavior]
+    #[doc = "Sets what happens to players who fall below the void height."]
+    pub fn set_void_behavior(&mut self, behavior: VoidBehavior) -> ActorResult<()> {
+        self.void_behavior = behavior;
+        Ok(())
+    }
+
+    #[GetWorldBorder]
+    #[doc = "Returns this dimension's world border as it is now, partway through any resize."]
+    pub fn world_border(&self) -> ActorResult<WorldBorder> {
+        Ok(self.world_border.at(Instant::now()))
+    }
+
+    #[GetWorldBorderState]
+    #[doc = "Returns this dimension's world border along with any resize in progress."]
+    pub(crate) fn world_border_state(&self) -> ActorResult<BorderState> {
+        Ok(self.world_border)
+    }
+
+    #[SetWorldBorder]
+    #[doc = "Sets this dimension's world border, stopping any resize and showing it to every player in it. Players outside it are moved back inside the next time they move."]
+    pub fn set_world_border(&mut self, border: WorldBorder) -> ActorResult<()> {
+        self.world_border = BorderState::new(border);
+        self.send_world_border()
+    }
+
+    #[ResizeWorldBorder]
+    #[doc = "Smoothly grows or shrinks this dimension's world border from its current diameter to `diameter` over `duration`, keeping its center and warnings."]
+    pub fn resize_world_border(&mut self, diameter: f64, duration: Duration) -> ActorResult<()> {
+        let now = Instant::now();
+        self.world_border = BorderState {
+            border: self.world_border.border.diameter(diameter),
+            from_diameter: self.world_border.diameter_at(now),
+            started: now,
+            duration,
+        };
+        self.send_world_border_resize()
+    }
+
+    #[GetTime]
+    #[doc = "Returns the time of day in ticks. It keeps counting up past the end of the first day, so the time within the current day is this modulo 24000."]
+    pub fn time(&self) -> ActorResult<i64> {
+        Ok(self.time)
+    }
+
+    #[SetTime]
+    #[doc = "Sets the time of day in ticks, showing it to every player in it. Time advances by one each tick unless the `doDaylightCycle` game rule is off."]
+    pub fn set_time(&mut self, time: i64) -> ActorResult<()> {
+        self.time = time;
+        self.send_time()
+    }
+
+    #[GetWorldAge]
+    #[doc = "Returns how many ticks this dimension has existed for."]
+    pub fn world_age(&self) -> ActorResult<i64> {
+        Ok(self.world_age)
+    }
+
+    #[GetWeather]
+    #[doc = "Returns the weather of this dimension, which defaults to clear."]
+    pub fn weather(&self) -> ActorResult<Weather> {
+        Ok(self.weather)
+    }
+
+    #[SetWeather]
+    #[doc = "Sets the weather of this dimension, showing it to every player in it."]
+    pub fn set_weather(&mut self, weather: Weather) -> ActorResult<()> {
+        self.weather = weather;
+        self.send_weather()
+    }
+
+    #[GetDifficulty]
+    #[doc = "Returns the difficulty of this dimension, which defaults to normal."]
+    pub fn difficulty(&self) -> ActorResult<Difficulty> {
+        Ok(self.difficulty)
+    }
+
+    #[SetDifficulty]
+    #[doc = "Sets the difficulty of this dimension, showing it to every player in it."]
+    pub fn set_difficulty(&mut self, difficulty: Difficulty) -> ActorResult<()> {
+        self.difficulty = difficulty;
+        self.send_difficulty()
+    }
+
+    #[IsDifficultyLocked]
+    #[doc = "Returns whether players are shown the difficulty as locked."]
+    pub fn is_difficulty_locked(&self) -> ActorResult<bool> {
+        Ok(self.difficulty_locked)
+    }
+
+    #[SetDifficultyLocked]
+    #[doc = "Sets whether players are shown the difficulty as locked, which greys out the difficulty button in their options."]
+    pub fn set_difficulty_locked(&mut self, locked: bool) -> ActorResult<()> {
+        self.difficulty_locked = locked;
+        self.send_difficulty()
+    }
+
+    #[GetGameRules]
+    #[doc = "Returns a copy of this dimension's game rules."]
+    pub fn game_rules(&self) -> ActorResult<GameRules> {
+        Ok(self.game_rules.clone())
+    }
+
+    #[SetGameRuleValue]
+    pub(crate) fn set_game_rule_value(
+        &mut self,
+        name: &'static str,
+        value: GameRuleValue,
+    ) -> ActorResult<()> {
+        self.game_rules.values.insert(name, value);
+        if name == GameRuleKeys::DO_IMMEDIATE_RESPAWN.name() {
+            let immediate = self.game_rules.get(GameRuleKeys::DO_IMMEDIATE_RESPAWN);
+            let dimension = self.as_actor();
+            Runtime::spawn_task(async move {
+                dimension
+                    .broadcast(|player| player.write_packet(immediate_respawn_packet(immediate)))
+            });
+        }
+        if name == GameRuleKeys::DO_DAYLIGHT_CYCLE.name() {
+            self.send_time()?;
+        }
+        Ok(())
+    }
+
+    #[GetDespawnRule]
+    #[doc = "Returns the despawn rule entities of the given type get when spawned in this dimension."]
+    pub fn despawn_rule(&self, entity_type: Id) -> ActorResult<DespawnRule> {
+        Ok(self
+            .despawn_rules
+            .get(&entity_type)
+            .copied()
+            .unwrap_or_else(|| DespawnRule::default_for(&entity_type)))
+    }
+
+    #[SetDespawnRule]
+    #[doc = "Sets the despawn rule entities of the given type get when spawned in this dimension. Entities that were already spawned keep their rule."]
+    pub fn set_despawn_rule(&mut self, entity_type: Id, rule: DespawnRule) -> ActorResult<()> {
+        self.despawn_rules.insert(entity_type, rule);
+        Ok(())
+    }
+
+    #[GetTrackingRange]
+    #[doc = "Returns how far away horizontally, in blocks, a player can be from an entity of the given type and still be sent it."]
+    pub fn tracking_range(&self, entity_type: Id) -> ActorResult<f64> {
+        Ok(self.tracking_range_of(&entity_type))
+    }
+
+    #[SetTrackingRange]
+    #[doc = "Sets how far away horizontally, in blocks, a player can be from an entity of the given type and still be sent it. Entities that leave a player's range are removed from their client, and sent again once they come back."]
+    pub fn set_tracking_range(&mut self, entity_type: Id, range: f64) -> ActorResult<()> {
+        self.tracking_ranges.insert(entity_type, range);
+        Ok(())
+    }
+
+    #[GetEntityTrackers]
+    #[doc = "Returns the players whose clients have the given entity."]
+    pub(crate) fn entity_trackers(&self, uuid: Uuid) -> ActorResult<Vec<Uuid>> {
+        Ok(self.trackers_of(&uuid))
+    }
+
+    #[GetEntityViewers]
+    #[doc = "Returns the players whose clients have the given entity, along with the player it belongs to, if any."]
+    pub(crate) fn entity_viewers(&self, uuid: Uuid) -> ActorResult<Vec<Uuid>> {
+        Ok(self.viewers_of(&uuid))
+    }
+
+    #[MountEntity]
+    #[doc = "Makes `passenger` ride `vehicle` in one step, so concurrent mounts can't lose passengers."]
+    pub(crate) fn mount_entity(
+        &mut self,
+        passenger: Uuid,
+        vehicle: Uuid,
+    ) -> ActorResult<MountChange> {
+        self.mount_passenger(passenger, vehicle)
+    }
+
+    #[DismountEntity]
+    #[doc = "Makes `passenger` stop riding its vehicle in one step, returning the vehicle it left, if any."]
+    pub(crate) fn dismount_entity(&mut self, passenger: Uuid) -> ActorResult<Option<Uuid>> {
+        Ok(self.unseat_passenger(passenger))
+    }
+
+    #[GetWorldSpawn]
+    #[doc = "Returns this dimension's spawn point, which defaults to 0, 0, 0."]
+    pub fn world_spawn(&self) -> ActorResult<WorldSpawn> {
+        Ok(self.world_spawn)
+    }
+
+    #[SetWorldSpawn]
+    #[doc = "Sets this dimension's spawn point, updating where the compasses of every player in it point."]
+    pub fn set_world_spawn(&mut self, spawn: WorldSpawn) -> ActorResult<()> {
+        self.world_spawn = spawn;
+        self.send_world_spawn()
+    }
+
+    #[GetSpawnProtection]
+    #[doc = "Returns the area around spawn where players who aren't operators can't place or break blocks."]
+    pub fn spawn_protection(&self) -> ActorResult<SpawnProtection> {
+        Ok(self.spawn_protection)
+    }
+
+    #[SetSpawnProtection]
+    #[doc = "Sets the area around spawn where players who aren't operators can't place or break blocks. A radius of 0 disables it."]
+    pub fn set_spawn_protection(&mut self, protection: SpawnProtection) -> ActorResult<()> {
+        self.spawn_protection = protection;
+        Ok(())
+    }
+
+    #[GetEntityInterpolation]
+    #[doc = "Returns how entity movement in this dimension is smoothed for clients."]
+    pub fn entity_interpolation(&self) -> ActorResult<EntityInterpolation> {
+        Ok(self.entity_interpolation)
+    }
+
+    #[SetEntityInterpolation]
+    #[doc = "Sets how entity movement in this dimension is smoothed for clients. See `EntityInterpolation` for how much bandwidth each option costs."]
+    pub fn set_entity_interpolation(
+        &mut self,
+        interpolation: EntityInterpolation,
+    ) -> ActorResult<()> {
+        self.entity_interpolation = interpolation;
+        Ok(())
+    }
+
+    #[AddRegionTrigger]
+    #[doc = "Registers a region trigger under the given id, replacing any trigger already registered under it."]
+    pub fn add_region_trigger(&mut self, id: Id, trigger: RegionTrigger) -> ActorResult<()> {
+        self.region_triggers.insert(id, trigger);
+        Ok(())
+    }
+
+    #[RemoveRegionTrigger]
+    #[doc = "Removes the region trigger registered under the given id. Players inside of it don't receive a leave event."]
+    pub fn remove_region_trigger(&mut self, id: Id) -> ActorResult<()> {
+        self.region_triggers.remove(&id);
+        Ok(())
+    }
+
+    #[GetRegionTriggersAt]
+    #[doc = "Returns every region trigger containing the provided position, along with its id."]
+    pub fn region_triggers_at(&self, position: DVec3) -> ActorResult<Vec<(Id, RegionTrigger)>> {
+        Ok(self
+            .region_triggers
+            .iter()
+            .filter(|(_, trigger)| trigger.region.contains(position))
+            .map(|(id, trigger)| (id.clone(), trigger.clone()))
+            .collect())
+    }
 }
 
 impl Dimension {
     pub fn set_chunk_generator(
         &self,
-        function: impl Fn(&mut Chunk, i32, i32) + Send + 'static,
+        function: impl Fn(&mut Chunk, i32, i32) + Send + Sync + 'static,
     ) -> ActorResult<()> {
-        self.set_boxed_chunk_generator(Box::new(function))
+        self.set_boxed_chunk_generator(Arc::new(function))
+    }
+
+    /// Sets a chunk generator whose chunks are copied from its `ChunkCache` when they've been
+    /// generated before.
+    pub fn set_cached_chunk_generator(&self, generator: CachedGenerator) -> ActorResult<()> {
+        self.set_chunk_generator(move |chunk, x, z| generator.generate(chunk, x, z))
+    }
+
+    /// Blocks until the chunk at the given chunk coordinates has been generated,
+    /// starting its generation if it has not been requested yet.
+    ///
+    /// If the generation task has not started yet, the chunk is generated on the calling thread
+    /// instead, so waiting from inside a task can't starve the task pool. Fails with
+    /// `ActorError::GenerationFailed` if the chunk generator panics.
+    pub fn load_chunk(&self, pos: IVec2) -> ActorResult<()> {
+        if let Some((job, receiver)) = self.await_chunk(pos)? {
+            job.run()?;
+            receiver
+                .recv()
+                .map_err(|_| ActorError::ActorHasBeenDropped)??;
+        }
+        Ok(())
+    }
+
+    pub async fn load_chunk_async(&self, pos: IVec2) -> ActorResult<()> {
+        if let Some((job, receiver)) = self.await_chunk_async(pos).await? {
+            job.run_async().await?;
+            receiver
+                .recv_async()
+                .await
+                .map_err(|_| ActorError::ActorHasBeenDropped)??;
+        }
+        Ok(())
+    }
+
+    /// Runs `f` until it stops returning `ActorError::Pending`, waiting for `chunk` to load in between.
+    fn with_loaded_chunk<T>(
+        &self,
+        chunk: IVec2,
+        mut f: impl FnMut() -> ActorResult<T>,
+    ) -> ActorResult<T> {
+        loop {
+            match f() {
+                Err(ActorError::Pending) => self.load_chunk(chunk)?,
+                result => return result,
+            }
+        }
+    }
+
+    /// Returns a copy of the 16x16x16 chunk section at the provided coordinates,
+    /// waiting for its chunk to be generated if needed.
+    pub fn get_chunk_section(&self, position: IVec3) -> ActorResult<Option<ChunkSection>> {
+        self.with_loaded_chunk(IVec2::new(position[0], position[2]), || {
+            self.try_get_chunk_section(position)
+        })
+    }
+
+    /// Sets a block in this dimension at the given coordinates to the provided block state,
+    /// waiting for its chunk to be generated if needed.
+    pub fn set_block(&self, position: IVec3, block_state: BlockState) -> ActorResult<()> {
+        self.with_loaded_chunk(chunk_of(position), || {
+            self.try_set_block(position, block_state.clone())
+        })
+    }
+
+    /// Sets a block in this dimension at the given coordinates to the provided block state
+    /// without updating current players, waiting for its chunk to be generated if needed.
+    pub fn set_block_loading(&self, position: IVec3, block_state: u32) -> ActorResult<()> {
+        self.with_loaded_chunk(chunk_of(position), || {
+            self.try_set_block_loading(position, block_state)
+        })
+    }
+
+    /// Returns a copy of the block state at the provided coordinates,
+    /// waiting for its chunk to be generated if needed.
+    pub fn get_block(&self, position: IVec3) -> ActorResult<BlockState> {
+        self.with_loaded_chunk(chunk_of(position), || self.try_get_block(position))
+    }
+}
+
+fn chunk_of(position: IVec3) -> IVec2 {
+    IVec2::new(position[0].div_euclid(16), position[2].div_euclid(16))
+}
+
+/// Returns the position of a block relative to the chunk it is in, keeping its y coordinate.
+fn local_pos(position: IVec3) -> IVec3 {
+    IVec3::new(
+        position[0].rem_euclid(16),
+        position[1],
+        position[2].rem_euclid(16),
+    )
+}
+
+/// Builds the packet that shows players a difficulty.
+pub(crate) fn difficulty_packet(
+    difficulty: Difficulty,
+    locked: bool,
+) -> ChangeDifficultyS2CPlayPacket {
+    ChangeDifficultyS2CPlayPacket {
+        difficulty: difficulty.id(),
+        locked,
     }
 }
 
@@ -540,13 +1256,80 @@ impl DimensionData {
             receiver: chan.1,
             sender: chan.0,
             dim_type,
-            chunk_generator: Box::new(|_, _, _| {}),
+            chunk_generator: Arc::new(|_, _, _| {}),
+            pending_chunks: HashMap::new(),
             chunk_max: (i32::MAX as u32, i32::MAX as u32),
+            simulation_distance: None,
+            default_gamemode: None,
+            region_triggers: HashMap::new(),
+            void_height: None,
+            void_behavior: VoidBehavior::default(),
+            world_border: BorderState::new(WorldBorder::default()),
+            world_age: 0,
+            time: 0,
+            weather: Weather::Clear,
+            difficulty: Difficulty::default(),
+            difficulty_locked: false,
+            game_rules: GameRules::default(),
+            despawn_rules: HashMap::new(),
+            tracking_ranges: HashMap::new(),
+            tracked_entities: HashMap::new(),
+            spawn_protection: SpawnProtection::default(),
+            world_spawn: WorldSpawn::default(),
+            entity_interpolation: EntityInterpolation::default(),
+            interpolation_step: 0,
             last_update: Instant::now(),
         }
     }
 
+    /// Shows this dimension's difficulty to every player in it.
+    fn send_difficulty(&self) -> ActorResult<()> {
+        let dimension = self.as_actor();
+        let (difficulty, locked) = (self.difficulty, self.difficulty_locked);
+        Runtime::spawn_task(async move {
+            dimension.broadcast(|player| player.write_packet(difficulty_packet(difficulty, locked)))
+        });
+        Ok(())
+    }
+
+    fn clamp_region(&self, region: Region) -> ActorResult<Option<Region>> {
+        let (min_y, max_y) = self.y_range()?;
+        Ok(region.clamp_y(min_y, max_y))
+    }
+
+    /// Starts generating every chunk in `region` that isn't loaded, returning
+    /// `ActorError::Pending` if any of them are still being generated.
+    fn try_initialize_region(&mut self, region: Region) -> ActorResult<()> {
+        let mut result = Ok(());
+        for chunk in region.chunks() {
+            match self.try_initialize_chunk(&chunk) {
+                Err(ActorError::Pending) => result = Err(ActorError::Pending),
+                Err(e) => return Err(e),
+                Ok(()) => {}
+            }
+        }
+        result
+    }
+
+    fn send_section_updates(&mut self, updates: SectionUpdates) -> ActorResult<()> {
+        let server = self.server.clone().unwrap();
+        let players = self.players()?;
+        Runtime::spawn_task(async move {
+            let players = players
+                .into_iter()
+                .filter_map(|uuid| server.player(uuid).ok())
+                .collect::<Vec<_>>();
+            updates.send(&players)
+        });
+        Ok(())
+    }
+
+    /// Ensures the chunk at `pos` is loaded, dispatching its generation onto the task pool if it
+    /// is not. Returns `ActorError::Pending` until the generated chunk has been inserted.
     pub(crate) fn try_initialize_chunk(&mut self, pos: &IVec2) -> ActorResult<()> {
+        if self.pending_chunks.contains_key(pos) {
+            return Err(ActorError::Pending);
+        }
         if !self.chunks.contains_key(pos)
             && pos[0] <= self.chunk_max.0 as i32
             && pos[1] <= self.chunk_max.1 as i32
@@ -562,15 +1345,165 @@ impl DimensionData {
             let min_sections = dim_type.min_y / 16;
             let max_sections = (dim_type.min_y + dim_type.height as i32) / 16;
 
-            let mut chunk = Chunk::new(min_sections, max_sections);
-            (self.chunk_generator)(&mut chunk, pos[0], pos[1]);
-            self.chunks.insert(*pos, chunk);
-
-            server.spawn_event(ChunkLoadEvent {
+            let generator = self.chunk_generator.clone();
+            let pos = *pos;
+            let job = Arc::new(ChunkJob {
+                pos,
                 dimension: self.as_actor(),
-                pos: *pos,
-            })?;
+                generate: Mutex::new(Some(Box::new(move || {
+                    let mut chunk = Chunk::new(min_sections, max_sections);
+                    generator(&mut chunk, pos[0], pos[1]);
+                    chunk
+                }))),
+            });
+            self.pending_chunks.insert(
+                pos,
+                PendingChunk {
+                    job: job.clone(),
+                    waiting: Vec::new(),
+                },
+            );
+            Runtime::spawn_task(async move { job.run_async().await });
+            return Err(ActorError::Pending);
         }
         Ok(())
     }
 }
+
+/// A chunk being generated, with the senders to notify once it is inserted.
+pub(crate) struct PendingChunk {
+    job: Arc<ChunkJob>,
+    waiting: Vec<Sender<ActorResult<()>>>,
+}
+
+type ChunkGenerate = Box<dyn FnOnce() -> Chunk + Send + Sync>;
+
+/// Generates a chunk and inserts it into its dimension. The job is shared between the task it
+/// was dispatched to and everything waiting on it, and whichever gets to it first runs it.
+pub(crate) struct ChunkJob {
+    pos: IVec2,
+    dimension: Dimension,
+    generate: Mutex<Option<ChunkGenerate>>,
+}
+
+impl ChunkJob {
+    /// Runs the generator if nothing has yet. A generator that panics fails with
+    /// `ActorError::GenerationFailed`, so the chunk can be abandoned instead of pending forever.
+    fn generate(&self) -> Option<ActorResult<Chunk>> {
+        let generate = self.generate.lock().unwrap().take()?;
+        Some(catch_unwind(AssertUnwindSafe(generate)).map_err(|_| {
+            log::error!("The generator for chunk {} panicked", self.pos);
+            ActorError::GenerationFailed
+        }))
+    }
+
+    pub(crate) fn run(&self) -> ActorResult<()> {
+        match self.generate() {
+            Some(Ok(chunk)) => self.dimension.insert_generated_chunk(self.pos, chunk),
+            Some(Err(err)) => {
+                self.dimension.abandon_chunk(self.pos)?;
+                Err(err)
+            }
+            None => Ok(()),
+        }
+    }
+
+    pub(crate) async fn run_async(&self) -> ActorResult<()> {
+        match self.generate() {
+            Some(Ok(chunk)) => {
+                self.dimension
+                    .insert_generated_chunk_async(self.pos, chunk)
+                    .await
+            }
+            Some(Err(err)) => {
+                self.dimension.abandon_chunk_async(self.pos).await?;
+                Err(err)
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        Arc, Mutex,
+        atomic::{AtomicUsize, Ordering},
+    };
+
+    use wyvern_actors::Actor;
+    use wyvern_values::{IVec2, id};
+
+    use crate::{actors::ActorError, server::Server};
+
+    use super::{ChunkJob, Dimension, DimensionData, DimensionMessage, PendingChunk, chunk::Chunk};
+
+    #[test]
+    fn chunk_jobs_generate_once() {
+        let (sender, receiver) = flume::unbounded();
+        let dimension = Dimension {
+            sender: sender.downgrade(),
+        };
+        let inserted = std::thread::spawn(move || {
+            let mut inserted = Vec::new();
+            for message in receiver.iter() {
+                if let DimensionMessage::InsertGeneratedChunk(pos, _, tx) = message {
+                    inserted.push(pos);
+                    let _ = tx.send(Ok(()));
+                }
+            }
+            inserted
+        });
+
+        let generated = Arc::new(AtomicUsize::new(0));
+        let counter = generated.clone();
+        let job = ChunkJob {
+            pos: IVec2::new(1, 2),
+            dimension,
+            generate: Mutex::new(Some(Box::new(move || {
+                counter.fetch_add(1, Ordering::SeqCst);
+                Chunk::new(0, 1)
+            }))),
+        };
+
+        // Both the task the job was dispatched to and a caller waiting on the chunk run it.
+        assert_eq!(job.run(), Ok(()));
+        assert_eq!(job.run(), Ok(()));
+        drop((job, sender));
+
+        assert_eq!(generated.load(Ordering::SeqCst), 1);
+        assert_eq!(inserted.join().unwrap(), vec![IVec2::new(1, 2)]);
+    }
+
+    #[test]
+    fn panicking_generators_fail_their_waiters() {
+        let (server_tx, _server_rx) = flume::unbounded();
+        let server = Server {
+            sender: server_tx.downgrade(),
+        };
+        let mut data =
+            DimensionData::new(id![minecraft:overworld], server, id![minecraft:overworld]);
+        let pos = IVec2::new(3, 4);
+        let job = Arc::new(ChunkJob {
+            pos,
+            dimension: data.as_actor(),
+            generate: Mutex::new(Some(Box::new(|| -> Chunk { panic!("generator failed") }))),
+        });
+        let (waiter, waiting) = flume::bounded(1);
+        data.pending_chunks.insert(
+            pos,
+            PendingChunk {
+                job: job.clone(),
+                waiting: vec![waiter],
+            },
+        );
+
+        let run = std::thread::spawn(move || job.run());
+        let message = data.receiver.recv().unwrap();
+        data.handle_message(message);
+
+        assert_eq!(run.join().unwrap(), Err(ActorError::GenerationFailed));
+        assert_eq!(waiting.recv(), Ok(Err(ActorError::GenerationFailed)));
+        assert!(data.pending_chunks.is_empty());
+    }
+}
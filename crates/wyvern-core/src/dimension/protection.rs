@@ -0,0 +1,42 @@
+use wyvern_values::IVec3;
+
+/// An area around a dimension's spawn where players who aren't operators can't place or break
+/// blocks. Players with an `OP_LEVEL` of 1 or more bypass it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SpawnProtection {
+    /// The block the area is centered on. Its height is ignored.
+    pub center: IVec3,
+    /// How many blocks the area reaches from the center along the x and z axes. A radius of 0
+    /// disables spawn protection, which is the default.
+    pub radius: u32,
+}
+
+impl SpawnProtection {
+    pub const fn new(center: IVec3, radius: u32) -> SpawnProtection {
+        SpawnProtection { center, radius }
+    }
+
+    /// Returns whether the block at `position` is inside the protected area.
+    pub fn protects(&self, position: IVec3) -> bool {
+        self.radius > 0
+            && (position[0] - self.center[0]).unsigned_abs() <= self.radius
+            && (position[2] - self.center[2]).unsigned_abs() <= self.radius
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wyvern_values::IVec3;
+
+    use super::SpawnProtection;
+
+    #[test]
+    fn protects_a_square_around_spawn() {
+        let protection = SpawnProtection::new(IVec3::new(10, 64, -10), 16);
+        assert!(protection.protects(IVec3::new(26, -40, -26)));
+        assert!(protection.protects(IVec3::new(-6, 300, 6)));
+        assert!(!protection.protects(IVec3::new(27, 64, -10)));
+        assert!(!protection.protects(IVec3::new(10, 64, 7)));
+        assert!(!SpawnProtection::new(IVec3::ZERO, 0).protects(IVec3::ZERO));
+    }
+}
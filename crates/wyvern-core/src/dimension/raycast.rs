@@ -0,0 +1,202 @@
+use wyvern_values::{DVec3, IVec3};
+
+use crate::{
+    actors::ActorResult,
+    blocks::{BlockDirection, BlockState},
+    entities::Entity,
+};
+
+use super::Dimension;
+
+/// The block a ray ran into.
+#[derive(Debug, Clone)]
+pub struct RaycastHit {
+    pub position: IVec3,
+    pub block: BlockState,
+    /// The face of the block the ray entered through.
+    pub face: BlockDirection,
+    /// The exact point the ray entered the block at.
+    pub point: DVec3,
+    pub distance: f64,
+}
+
+/// The entity a ray ran into.
+#[derive(Debug, Clone)]
+pub struct EntityRaycastHit {
+    pub entity: Entity,
+    /// The exact point the ray entered the entity's bounding box at.
+    pub point: DVec3,
+    pub distance: f64,
+}
+
+/// Returns every block a ray passes through in order, along with the face it entered through
+/// and how far along the ray it was entered, stopping after `max_distance`. The block the ray
+/// starts in is entered at a distance of zero through the face opposite its direction.
+fn traverse(
+    origin: DVec3,
+    direction: DVec3,
+    max_distance: f64,
+) -> impl Iterator<Item = (IVec3, BlockDirection, f64)> {
+    const FACES: [[BlockDirection; 2]; 3] = [
+        [BlockDirection::West, BlockDirection::East],
+        [BlockDirection::Down, BlockDirection::Up],
+        [BlockDirection::North, BlockDirection::South],
+    ];
+    // The face a ray stepping along an axis enters through is on the side it came from.
+    let entry_face =
+        move |axis: usize, step: i32| FACES[axis][if step > 0 { 0 } else { 1 }].clone();
+
+    let mut block = origin.floor().as_ivec3();
+    let mut step = [0; 3];
+    let mut t_max = [f64::INFINITY; 3];
+    let mut t_delta = [f64::INFINITY; 3];
+    for axis in 0..3 {
+        if direction[axis] > 0.0 {
+            step[axis] = 1;
+            t_max[axis] = (block[axis] as f64 + 1.0 - origin[axis]) / direction[axis];
+        } else if direction[axis] < 0.0 {
+            step[axis] = -1;
+            t_max[axis] = (block[axis] as f64 - origin[axis]) / direction[axis];
+        }
+        if step[axis] != 0 {
+            t_delta[axis] = 1.0 / direction[axis].abs();
+        }
+    }
+
+    let major = (0..3)
+        .max_by(|a, b| direction[*a].abs().total_cmp(&direction[*b].abs()))
+        .unwrap();
+    let mut next = Some((block, entry_face(major, step[major]), 0.0));
+
+    std::iter::from_fn(move || {
+        let current = next.take()?;
+        let axis = (0..3)
+            .min_by(|a, b| t_max[*a].total_cmp(&t_max[*b]))
+            .unwrap();
+        let t = t_max[axis];
+        if t <= max_distance && step[axis] != 0 {
+            block[axis] += step[axis];
+            t_max[axis] += t_delta[axis];
+            next = Some((block, entry_face(axis, step[axis]), t));
+        }
+        Some(current)
+    })
+}
+
+/// Returns true if rays stop at the given block. Only air and fluids let them through.
+fn stops_ray(block: &BlockState) -> bool {
+    !block.is_air() && !matches!(block.name().path(), "water" | "lava" | "bubble_column")
+}
+
+impl Dimension {
+    /// Returns every entity whose bounding box overlaps the box between `min` and `max`.
+    pub fn entities_in_box(&self, min: DVec3, max: DVec3) -> ActorResult<Vec<Entity>> {
+        Ok(self
+            .entity_boxes(min, max)?
+            .into_iter()
+            .map(|(entity, _)| entity)
+            .collect())
+    }
+
+    /// Casts a ray from `origin` in `direction`, returning the first block it runs into within
+    /// `max_distance`. Air and fluids don't stop the ray. Chunks the ray passes through are
+    /// generated if needed.
+    pub fn raycast(
+        &self,
+        origin: DVec3,
+        direction: DVec3,
+        max_distance: f64,
+    ) -> ActorResult<Option<RaycastHit>> {
+        let direction = direction.normalize_or_zero();
+        if direction == DVec3::ZERO {
+            return Ok(None);
+        }
+        for (position, face, distance) in traverse(origin, direction, max_distance) {
+            let block = self.get_block(position)?;
+            if stops_ray(&block) {
+                return Ok(Some(RaycastHit {
+                    position,
+                    block,
+                    face,
+                    point: origin + direction * distance,
+                    distance,
+                }));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Casts a ray from `origin` in `direction`, returning the first entity whose bounding box
+    /// it passes through before running into a block or reaching `max_distance`. Entities
+    /// whose bounding box contains `origin`, such as a player casting a ray from their own
+    /// eyes, are ignored.
+    pub fn raycast_entities(
+        &self,
+        origin: DVec3,
+        direction: DVec3,
+        max_distance: f64,
+    ) -> ActorResult<Option<EntityRaycastHit>> {
+        let direction = direction.normalize_or_zero();
+        if direction == DVec3::ZERO {
+            return Ok(None);
+        }
+        let max_distance = self
+            .raycast(origin, direction, max_distance)?
+            .map_or(max_distance, |hit| hit.distance);
+
+        let end = origin + direction * max_distance;
+        let mut closest: Option<EntityRaycastHit> = None;
+        for (entity, bounding_box) in self.entity_boxes(origin, end)? {
+            if bounding_box.contains(origin) {
+                continue;
+            }
+            let Some(distance) = bounding_box.ray_intersection(origin, direction) else {
+                continue;
+            };
+            if distance > max_distance
+                || closest
+                    .as_ref()
+                    .is_some_and(|closest| closest.distance <= distance)
+            {
+                continue;
+            }
+            closest = Some(EntityRaycastHit {
+                entity,
+                point: origin + direction * distance,
+                distance,
+            });
+        }
+        Ok(closest)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wyvern_values::{DVec3, IVec3};
+
+    use crate::blocks::BlockDirection;
+
+    use super::traverse;
+
+    #[test]
+    fn traverses_every_block_crossed() {
+        let blocks = traverse(DVec3::new(0.5, 0.5, 0.5), DVec3::X, 2.0).collect::<Vec<_>>();
+        assert_eq!(
+            blocks,
+            vec![
+                (IVec3::new(0, 0, 0), BlockDirection::West, 0.0),
+                (IVec3::new(1, 0, 0), BlockDirection::West, 0.5),
+                (IVec3::new(2, 0, 0), BlockDirection::West, 1.5),
+            ]
+        );
+
+        // A diagonal ray can't slip between two blocks that share only an edge.
+        let diagonal = DVec3::new(1.0, 0.0, -1.0).normalize();
+        let blocks = traverse(DVec3::new(0.5, 0.5, 0.5), diagonal, 1.5)
+            .map(|(position, face, _)| (position, face))
+            .collect::<Vec<_>>();
+        assert_eq!(blocks.len(), 3);
+        assert_eq!(blocks[1].0, IVec3::new(1, 0, 0));
+        assert_eq!(blocks[2], (IVec3::new(1, 0, -1), BlockDirection::South));
+    }
+}
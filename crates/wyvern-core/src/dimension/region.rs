@@ -0,0 +1,263 @@
+use std::collections::HashMap;
+
+use voxidian_protocol::{
+    packet::s2c::play::{
+        BlockEntityDataS2CPlayPacket, ChunkBlockEntity, SectionBlocksUpdateS2CPlayPacket,
+    },
+    value::{BlockPos, VarLong},
+};
+use wyvern_values::{DVec3, IVec2, IVec3};
+
+use crate::{
+    actors::{ActorError, ActorResult},
+    blocks::BlockState,
+    player::Player,
+};
+
+use super::Dimension;
+
+/// A box of blocks between two corners, both inclusive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Region {
+    pub(crate) min: IVec3,
+    pub(crate) max: IVec3,
+}
+
+impl Region {
+    pub fn new(a: IVec3, b: IVec3) -> Region {
+        Region {
+            min: a.min(b),
+            max: a.max(b),
+        }
+    }
+
+    /// Returns whether the block containing `position` is inside this region.
+    pub fn contains(&self, position: DVec3) -> bool {
+        let block = position.floor().as_ivec3();
+        block.cmpge(self.min).all() && block.cmple(self.max).all()
+    }
+
+    /// Shrinks this region to the y-range `min_y..=max_y`, returning `None` if nothing is left.
+    pub(crate) fn clamp_y(self, min_y: i32, max_y: i32) -> Option<Region> {
+        let min = self.min.with_y(self.min[1].max(min_y));
+        let max = self.max.with_y(self.max[1].min(max_y));
+        (min[1] <= max[1]).then_some(Region { min, max })
+    }
+
+    pub(crate) fn offset(self, by: IVec3) -> Region {
+        Region {
+            min: self.min + by,
+            max: self.max + by,
+        }
+    }
+
+    /// Returns the coordinates of every chunk this region touches.
+    pub(crate) fn chunks(self) -> impl Iterator<Item = IVec2> {
+        let (min_x, max_x) = (self.min[0].div_euclid(16), self.max[0].div_euclid(16));
+        let (min_z, max_z) = (self.min[2].div_euclid(16), self.max[2].div_euclid(16));
+        (min_x..=max_x).flat_map(move |x| (min_z..=max_z).map(move |z| IVec2::new(x, z)))
+    }
+
+    pub(crate) fn positions(self) -> impl Iterator<Item = IVec3> {
+        (self.min[0]..=self.max[0]).flat_map(move |x| {
+            (self.min[1]..=self.max[1])
+                .flat_map(move |y| (self.min[2]..=self.max[2]).map(move |z| IVec3::new(x, y, z)))
+        })
+    }
+}
+
+/// Packs section coordinates the way `SectionBlocksUpdateS2CPlayPacket` expects.
+pub(crate) fn pack_section_pos(section: IVec3) -> i64 {
+    ((section[0] as i64 & 0x3FFFFF) << 42)
+        | ((section[2] as i64 & 0x3FFFFF) << 20)
+        | (section[1] as i64 & 0xFFFFF)
+}
+
+/// Packs a block state and its position within a section into a single entry of
+/// `SectionBlocksUpdateS2CPlayPacket`.
+pub(crate) fn pack_section_block(position: IVec3, state: u32) -> i64 {
+    ((state as i64) << 12)
+        | ((position[0].rem_euclid(16) as i64) << 8)
+        | ((position[2].rem_euclid(16) as i64) << 4)
+        | position[1].rem_euclid(16) as i64
+}
+
+/// Block changes collected while editing a region, grouped by the section they happened in.
+#[derive(Default)]
+pub(crate) struct SectionUpdates {
+    sections: HashMap<IVec3, Vec<i64>>,
+    block_entities: Vec<(IVec3, ChunkBlockEntity)>,
+}
+
+impl SectionUpdates {
+    pub(crate) fn push(
+        &mut self,
+        position: IVec3,
+        state: u32,
+        block_entity: Option<ChunkBlockEntity>,
+    ) {
+        let section = IVec3::new(
+            position[0].div_euclid(16),
+            position[1].div_euclid(16),
+            position[2].div_euclid(16),
+        );
+        self.sections
+            .entry(section)
+            .or_default()
+            .push(pack_section_block(position, state));
+        if let Some(block_entity) = block_entity {
+            self.block_entities.push((position, block_entity));
+        }
+    }
+
+    /// Returns one multi-block change per affected section, and the data of any block
+    /// entities that were placed.
+    pub(crate) fn into_packets(
+        self,
+    ) -> (
+        Vec<SectionBlocksUpdateS2CPlayPacket>,
+        Vec<BlockEntityDataS2CPlayPacket>,
+    ) {
+        let sections = self
+            .sections
+            .into_iter()
+            .map(|(section, blocks)| SectionBlocksUpdateS2CPlayPacket {
+                section: pack_section_pos(section),
+                blocks: blocks
+                    .into_iter()
+                    .map(VarLong::from)
+                    .collect::<Vec<_>>()
+                    .into(),
+            })
+            .collect();
+        let block_entities = self
+            .block_entities
+            .into_iter()
+            .map(|(pos, block_entity)| BlockEntityDataS2CPlayPacket {
+                pos: BlockPos::new(pos[0], pos[1], pos[2]),
+                entity_type: block_entity.entity_type,
+                data: block_entity.data,
+            })
+            .collect();
+        (sections, block_entities)
+    }
+
+    /// Sends one multi-block change per affected section to `players`, followed by the data
+    /// of any block entities that were placed.
+    pub(crate) fn send(self, players: &[Player]) -> ActorResult<()> {
+        let (sections, block_entities) = self.into_packets();
+        for packet in sections {
+            Player::broadcast_packet(players, packet)?;
+        }
+        for packet in block_entities {
+            Player::broadcast_packet(players, packet)?;
+        }
+        Ok(())
+    }
+}
+
+impl Dimension {
+    /// Sets every block between two corners, both inclusive, to the provided block state,
+    /// waiting for the chunks involved to be generated if needed. Players are sent one update
+    /// per affected chunk section rather than one per block. The region is clamped to the
+    /// height of the dimension.
+    pub fn fill_region(&self, from: IVec3, to: IVec3, block_state: BlockState) -> ActorResult<()> {
+        loop {
+            match self.try_fill_region(from, to, block_state.clone()) {
+                Err(ActorError::Pending) => self.load_region(Region::new(from, to))?,
+                result => return result,
+            }
+        }
+    }
+
+    /// Copies every block between two corners, both inclusive, so that the lowest corner of
+    /// the copy is at `dest`. Overlapping regions are copied as they were before the copy.
+    /// Blocks that would end up outside the height of the dimension are skipped.
+    pub fn clone_region(&self, from: IVec3, to: IVec3, dest: IVec3) -> ActorResult<()> {
+        let source = Region::new(from, to);
+        loop {
+            match self.try_clone_region(from, to, dest) {
+                Err(ActorError::Pending) => {
+                    self.load_region(source)?;
+                    self.load_region(source.offset(dest - source.min))?;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Returns a copy of every block state between two corners, both inclusive, along with
+    /// its position, waiting for the chunks involved to be generated if needed. The region is
+    /// clamped to the height of the dimension.
+    pub fn get_region_blocks(
+        &self,
+        from: IVec3,
+        to: IVec3,
+    ) -> ActorResult<Vec<(IVec3, BlockState)>> {
+        loop {
+            match self.try_get_region_blocks(from, to) {
+                Err(ActorError::Pending) => self.load_region(Region::new(from, to))?,
+                result => return result,
+            }
+        }
+    }
+
+    fn load_region(&self, region: Region) -> ActorResult<()> {
+        for chunk in region.chunks() {
+            self.load_chunk(chunk)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wyvern_values::{DVec3, IVec2, IVec3};
+
+    use super::{Region, pack_section_block, pack_section_pos};
+
+    #[test]
+    fn region_spans_chunks_and_clamps() {
+        let region = Region::new(IVec3::new(17, 400, -1), IVec3::new(-3, -100, 15));
+        assert_eq!(region.min, IVec3::new(-3, -100, -1));
+        assert_eq!(
+            region.chunks().collect::<Vec<_>>(),
+            vec![
+                IVec2::new(-1, -1),
+                IVec2::new(-1, 0),
+                IVec2::new(0, -1),
+                IVec2::new(0, 0),
+                IVec2::new(1, -1),
+                IVec2::new(1, 0),
+            ]
+        );
+
+        let clamped = region.clamp_y(-64, 319).unwrap();
+        assert_eq!(clamped.min[1], -64);
+        assert_eq!(clamped.max[1], 319);
+        assert_eq!(Region::new(IVec3::ZERO, IVec3::ONE).clamp_y(5, 10), None);
+        assert_eq!(Region::new(IVec3::ZERO, IVec3::ONE).positions().count(), 8);
+    }
+
+    #[test]
+    fn contains_positions_within_blocks() {
+        let region = Region::new(IVec3::new(-1, 0, 0), IVec3::new(1, 2, 1));
+        assert!(region.contains(DVec3::new(-0.5, 0.0, 0.5)));
+        assert!(region.contains(DVec3::new(1.99, 2.99, 1.99)));
+        assert!(!region.contains(DVec3::new(2.0, 1.0, 1.0)));
+        assert!(!region.contains(DVec3::new(0.0, -0.01, 0.0)));
+    }
+
+    #[test]
+    fn packs_section_updates() {
+        assert_eq!(
+            pack_section_pos(IVec3::new(1, 2, 3)),
+            (1 << 42) | (3 << 20) | 2
+        );
+        assert_eq!(pack_section_pos(IVec3::new(0, -1, 0)), 0xFFFFF);
+        assert_eq!(
+            pack_section_block(IVec3::new(17, -1, 4), 9),
+            (9 << 12) | (1 << 8) | (4 << 4) | 15
+        );
+    }
+}
@@ -0,0 +1,158 @@
+use std::collections::HashMap;
+
+use voxidian_protocol::packet::s2c::play::{GameEvent, GameEventS2CPlayPacket};
+
+use crate::actors::ActorResult;
+
+use super::Dimension;
+
+/// The value of a game rule, which is either a boolean or an integer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameRuleValue {
+    Bool(bool),
+    Int(i32),
+}
+
+/// A type game rules can hold.
+pub trait GameRuleType: Copy {
+    fn into_value(self) -> GameRuleValue;
+    fn from_value(value: GameRuleValue) -> Option<Self>;
+}
+
+impl GameRuleType for bool {
+    fn into_value(self) -> GameRuleValue {
+        GameRuleValue::Bool(self)
+    }
+
+    fn from_value(value: GameRuleValue) -> Option<Self> {
+        match value {
+            GameRuleValue::Bool(value) => Some(value),
+            GameRuleValue::Int(_) => None,
+        }
+    }
+}
+
+impl GameRuleType for i32 {
+    fn into_value(self) -> GameRuleValue {
+        GameRuleValue::Int(self)
+    }
+
+    fn from_value(value: GameRuleValue) -> Option<Self> {
+        match value {
+            GameRuleValue::Int(value) => Some(value),
+            GameRuleValue::Bool(_) => None,
+        }
+    }
+}
+
+/// A game rule, along with the value it has until it is set.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GameRule<T: GameRuleType> {
+    name: &'static str,
+    default: T,
+}
+
+impl<T: GameRuleType> GameRule<T> {
+    pub const fn new(name: &'static str, default: T) -> GameRule<T> {
+        GameRule { name, default }
+    }
+
+    /// Returns the name of this rule, as used by vanilla's `/gamerule`.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub fn default_value(&self) -> T {
+        self.default
+    }
+}
+
+/// The game rules the server acts on. Other rules can be made with `GameRule::new` and stored
+/// the same way, for plugins to read.
+pub struct GameRuleKeys;
+
+impl GameRuleKeys {
+    /// Whether the time of day advances.
+    pub const DO_DAYLIGHT_CYCLE: GameRule<bool> = GameRule::new("doDaylightCycle", true);
+    /// Whether players keep their inventory and experience when they die. Unlike vanilla, this is
+    /// on by default, since most minigames don't want items dropped.
+    pub const KEEP_INVENTORY: GameRule<bool> = GameRule::new("keepInventory", true);
+    /// Whether players take damage from falling.
+    pub const FALL_DAMAGE: GameRule<bool> = GameRule::new("fallDamage", true);
+    /// Whether players respawn straight away instead of being shown the death screen.
+    pub const DO_IMMEDIATE_RESPAWN: GameRule<bool> = GameRule::new("doImmediateRespawn", false);
+}
+
+/// The game rules of a dimension. Rules that were never set have their default value.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GameRules {
+    pub(crate) values: HashMap<&'static str, GameRuleValue>,
+}
+
+impl GameRules {
+    pub fn new() -> GameRules {
+        GameRules::default()
+    }
+
+    pub fn get<T: GameRuleType>(&self, rule: GameRule<T>) -> T {
+        self.values
+            .get(rule.name)
+            .and_then(|value| T::from_value(*value))
+            .unwrap_or(rule.default)
+    }
+
+    pub fn set<T: GameRuleType>(&mut self, rule: GameRule<T>, value: T) {
+        self.values.insert(rule.name, value.into_value());
+    }
+
+    pub fn with<T: GameRuleType>(mut self, rule: GameRule<T>, value: T) -> Self {
+        self.set(rule, value);
+        self
+    }
+}
+
+/// Builds the packet telling players whether they respawn without a death screen.
+pub(crate) fn immediate_respawn_packet(immediate: bool) -> GameEventS2CPlayPacket {
+    GameEventS2CPlayPacket {
+        event: GameEvent::EnableRespawnScreen,
+        value: if immediate { 1.0 } else { 0.0 },
+    }
+}
+
+impl Dimension {
+    /// Returns the value of one of this dimension's game rules.
+    pub fn game_rule<T: GameRuleType>(&self, rule: GameRule<T>) -> ActorResult<T> {
+        Ok(self.game_rules()?.get(rule))
+    }
+
+    /// Sets one of this dimension's game rules, taking effect straight away.
+    pub fn set_game_rule<T: GameRuleType>(&self, rule: GameRule<T>, value: T) -> ActorResult<()> {
+        self.set_game_rule_value(rule.name, value.into_value())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{GameRuleKeys, GameRules};
+
+    #[test]
+    fn rules_fall_back_to_defaults() {
+        let rules = GameRules::new();
+        assert!(rules.get(GameRuleKeys::KEEP_INVENTORY));
+        assert!(rules.get(GameRuleKeys::FALL_DAMAGE));
+        assert!(!rules.get(GameRuleKeys::DO_IMMEDIATE_RESPAWN));
+    }
+
+    #[test]
+    fn set_rules_override_defaults() {
+        let mut rules = GameRules::new()
+            .with(GameRuleKeys::KEEP_INVENTORY, false)
+            .with(GameRuleKeys::DO_IMMEDIATE_RESPAWN, true);
+        assert!(!rules.get(GameRuleKeys::KEEP_INVENTORY));
+        assert!(rules.get(GameRuleKeys::DO_IMMEDIATE_RESPAWN));
+
+        rules.set(GameRuleKeys::KEEP_INVENTORY, true);
+        assert!(rules.get(GameRuleKeys::KEEP_INVENTORY));
+        assert!(rules.get(GameRuleKeys::DO_DAYLIGHT_CYCLE));
+    }
+}
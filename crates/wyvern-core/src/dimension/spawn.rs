@@ -0,0 +1,41 @@
+use voxidian_protocol::{packet::s2c::play::SetDefaultSpawnPositionS2CPlayPacket, value::BlockPos};
+use wyvern_values::IVec3;
+
+use crate::{actors::ActorResult, runtime::Runtime};
+
+use super::DimensionData;
+
+/// A dimension's spawn point, which compasses without a lodestone target point towards.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct WorldSpawn {
+    pub position: IVec3,
+    /// The yaw players face when they spawn, in degrees.
+    pub angle: f32,
+}
+
+impl WorldSpawn {
+    pub const fn new(position: IVec3, angle: f32) -> WorldSpawn {
+        WorldSpawn { position, angle }
+    }
+
+    /// Returns the packet that tells a client where this spawn point is.
+    pub(crate) fn to_packet(&self) -> SetDefaultSpawnPositionS2CPlayPacket {
+        SetDefaultSpawnPositionS2CPlayPacket {
+            location: BlockPos::new(self.position[0], self.position[1], self.position[2]),
+            angle: self.angle,
+        }
+    }
+}
+
+impl DimensionData {
+    /// Tells every player in this dimension where its spawn point is, so their compasses
+    /// point to it.
+    pub(crate) fn send_world_spawn(&self) -> ActorResult<()> {
+        let dimension = self.as_actor();
+        let spawn = self.world_spawn;
+        Runtime::spawn_task(async move {
+            dimension.broadcast(|player| player.write_packet(spawn.to_packet()))
+        });
+        Ok(())
+    }
+}
@@ -0,0 +1,65 @@
+use crate::{actors::ActorResult, runtime::Runtime};
+
+use super::{DimensionData, GameRuleKeys, GameRules};
+
+/// How many ticks pass between the time being sent to players. Clients advance the time on
+/// their own in between, so this only corrects drift.
+const TIME_SYNC_INTERVAL: i64 = 20;
+
+/// Returns the world age and time of day one tick after `world_age` and `time`. The time of day
+/// only advances if `doDaylightCycle` is on.
+fn next_tick(rules: &GameRules, world_age: i64, time: i64) -> (i64, i64) {
+    if rules.get(GameRuleKeys::DO_DAYLIGHT_CYCLE) {
+        (world_age + 1, time + 1)
+    } else {
+        (world_age + 1, time)
+    }
+}
+
+impl DimensionData {
+    /// Advances the world age, and the time of day unless `doDaylightCycle` is off, by one tick.
+    pub(crate) fn advance_time(&mut self) -> ActorResult<()> {
+        (self.world_age, self.time) = next_tick(&self.game_rules, self.world_age, self.time);
+        if self.world_age % TIME_SYNC_INTERVAL == 0 {
+            self.send_time()?;
+        }
+        Ok(())
+    }
+
+    /// Shows this dimension's time to every player in it who isn't shown a time of their own.
+    pub(crate) fn send_time(&self) -> ActorResult<()> {
+        let dimension = self.as_actor();
+        let (world_age, time) = (self.world_age, self.time);
+        let increasing = self.game_rules.get(GameRuleKeys::DO_DAYLIGHT_CYCLE);
+        Runtime::spawn_task(async move {
+            dimension.broadcast(|player| player.show_dimension_time(world_age, time, increasing))
+        });
+        Ok(())
+    }
+
+    /// Shows this dimension's weather to every player in it who isn't shown weather of their own.
+    pub(crate) fn send_weather(&self) -> ActorResult<()> {
+        let dimension = self.as_actor();
+        let weather = self.weather;
+        Runtime::spawn_task(async move {
+            dimension.broadcast(|player| player.show_dimension_weather(weather))
+        });
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dimension::{GameRuleKeys, GameRules};
+
+    use super::next_tick;
+
+    #[test]
+    fn daylight_cycle_rule_stops_the_time_of_day() {
+        let mut rules = GameRules::new();
+        assert_eq!(next_tick(&rules, 100, 6000), (101, 6001));
+
+        rules.set(GameRuleKeys::DO_DAYLIGHT_CYCLE, false);
+        assert_eq!(next_tick(&rules, 100, 6000), (101, 6000));
+    }
+}
@@ -0,0 +1,32 @@
+use std::time::Duration;
+
+use super::Region;
+
+/// A region of a dimension that fires `PlayerEnterRegionEvent` when a player walks into it
+/// and `PlayerLeaveRegionEvent` when they walk back out, such as a portal or a kill zone.
+#[derive(Debug, Clone)]
+pub struct RegionTrigger {
+    pub(crate) region: Region,
+    pub(crate) cooldown: Duration,
+}
+
+impl RegionTrigger {
+    pub fn new(region: Region) -> RegionTrigger {
+        RegionTrigger {
+            region,
+            cooldown: Duration::ZERO,
+        }
+    }
+
+    /// Sets how long after a player enters this region they have to wait before entering it
+    /// again fires another event. A player still standing inside once the cooldown is over
+    /// enters it again.
+    pub fn cooldown(mut self, cooldown: Duration) -> RegionTrigger {
+        self.cooldown = cooldown;
+        self
+    }
+
+    pub fn region(&self) -> Region {
+        self.region
+    }
+}
@@ -0,0 +1,20 @@
+/// How far below the bottom of a dimension its void height is, unless set explicitly.
+pub const DEFAULT_VOID_MARGIN: f64 = 64.0;
+
+/// What happens to players who fall below the void height of their dimension.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum VoidBehavior {
+    /// Players take this much `minecraft:out_of_world` damage every tick they spend below the
+    /// void height, on top of the `PlayerVoidEvent` fired when they first fall below it.
+    Damage(f32),
+    /// Only a `PlayerVoidEvent` is fired when a player falls below the void height.
+    EventOnly,
+    /// Nothing happens.
+    Disabled,
+}
+
+impl Default for VoidBehavior {
+    fn default() -> Self {
+        VoidBehavior::Damage(4.0)
+    }
+}
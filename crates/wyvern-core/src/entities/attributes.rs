@@ -6,11 +6,36 @@ use voxidian_protocol::{
 };
 
 use wyvern_components::{DataComponentHolder, DataComponentMap, DataComponentType};
-use wyvern_values::{Id, Registry};
+use wyvern_values::{Id, Registry, id};
+
+use crate::item::{AttributeModifier, AttributeOperation};
 
 pub static ATTRIBUTES: LazyLock<Registry<AttributeType>> =
     LazyLock::new(|| AttributeType::vanilla_registry().into());
 
+/// The smallest `minecraft:scale` clients accept.
+pub const MIN_SCALE: f64 = 0.0625;
+/// The largest `minecraft:scale` clients accept.
+pub const MAX_SCALE: f64 = 16.0;
+
+/// Clamps an attribute to the range clients accept for it.
+fn clamp_attribute(attribute: &Id, value: f64) -> f64 {
+    if *attribute == id![minecraft:scale] {
+        value.clamp(MIN_SCALE, MAX_SCALE)
+    } else {
+        value
+    }
+}
+
+/// Returns how much an entity with these attributes is scaled up by, from `MIN_SCALE` to
+/// `MAX_SCALE`.
+pub(crate) fn scale_of(attributes: Option<&AttributeContainer>) -> f64 {
+    let scale = attributes
+        .and_then(|attributes| attributes.get(Attributes::SCALE).ok())
+        .unwrap_or(1.0);
+    clamp_attribute(Attributes::SCALE.name(), scale)
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct AttributeContainer {
     #[allow(unused)]
@@ -42,7 +67,7 @@ impl AttributeContainer {
             if let Some(entry) = ATTRIBUTES.get_entry(attr.0.clone()) {
                 properties.push(Attribute {
                     id: entry,
-                    value: *float,
+                    value: clamp_attribute(attr.0, *float),
                     mods: LengthPrefixVec::new(),
                 });
             }
@@ -54,6 +79,71 @@ impl AttributeContainer {
     }
 }
 
+/// Returns the value a player's attribute has when nothing sets it.
+pub(crate) fn default_attribute_value(attribute: &Id) -> f64 {
+    match attribute {
+        x if *x == id![minecraft:max_health] => 20.0,
+        x if *x == id![minecraft:attack_damage] => 1.0,
+        x if *x == id![minecraft:attack_speed] => 4.0,
+        x if *x == id![minecraft:movement_speed] => 0.1,
+        x if *x == id![minecraft:flying_speed] => 0.4,
+        x if *x == id![minecraft:sneaking_speed] => 0.3,
+        x if *x == id![minecraft:block_interaction_range] => 4.5,
+        x if *x == id![minecraft:entity_interaction_range] => 3.0,
+        x if *x == id![minecraft:block_break_speed] => 1.0,
+        x if *x == id![minecraft:submerged_mining_speed] => 0.2,
+        x if *x == id![minecraft:step_height] => 0.6,
+        x if *x == id![minecraft:jump_strength] => 0.42,
+        x if *x == id![minecraft:gravity] => 0.08,
+        x if *x == id![minecraft:safe_fall_distance] => 3.0,
+        x if *x == id![minecraft:fall_damage_multiplier] => 1.0,
+        x if *x == id![minecraft:scale] => 1.0,
+        x if *x == id![minecraft:burning_time] => 1.0,
+        x if *x == id![minecraft:follow_range] => 32.0,
+        _ => 0.0,
+    }
+}
+
+/// Returns `base` changed by every modifier in `modifiers`: first adding the `AddValue`
+/// amounts, then the base multiplied by the `AddMultipliedBase` amounts, then multiplying by
+/// one plus each `AddMultipliedTotal` amount.
+fn apply_modifiers<'a>(base: f64, modifiers: impl Iterator<Item = &'a AttributeModifier>) -> f64 {
+    let modifiers = modifiers.collect::<Vec<_>>();
+    let of = |operation| {
+        modifiers
+            .iter()
+            .filter(move |x| x.operation == operation)
+            .map(|x| x.amount)
+    };
+    let value = base + of(AttributeOperation::AddValue).sum::<f64>();
+    let value = value + base * of(AttributeOperation::AddMultipliedBase).sum::<f64>();
+    of(AttributeOperation::AddMultipliedTotal).fold(value, |value, amount| value * (1.0 + amount))
+}
+
+impl AttributeContainer {
+    /// Returns these attributes with `modifiers` applied. Attributes that aren't set start from
+    /// the value they have for a player by default.
+    pub fn with_modifiers(&self, modifiers: &[AttributeModifier]) -> AttributeContainer {
+        let mut container = self.clone();
+        let mut attributes: Vec<&Id> = Vec::new();
+        for modifier in modifiers {
+            if !attributes.contains(&&modifier.attribute) {
+                attributes.push(&modifier.attribute);
+            }
+        }
+        for attribute in attributes {
+            let kind = DataComponentType::<f64>::new(attribute.clone());
+            let base = self
+                .get(kind.clone())
+                .unwrap_or_else(|_| default_attribute_value(attribute));
+            let value =
+                apply_modifiers(base, modifiers.iter().filter(|x| x.attribute == *attribute));
+            container.set(kind, value);
+        }
+        container
+    }
+}
+
 impl Default for AttributeContainer {
     fn default() -> Self {
         Self::new()
@@ -62,3 +152,65 @@ impl Default for AttributeContainer {
 
 pub struct Attributes;
 wyvern_macros::generate_attrs_types!();
+
+#[cfg(test)]
+mod tests {
+    use wyvern_components::DataComponentHolder;
+
+    use crate::item::{AttributeModifier, AttributeOperation, AttributeSlot};
+
+    use super::{ATTRIBUTES, AttributeContainer, Attributes, MAX_SCALE};
+
+    #[test]
+    fn modifiers_stack_in_order() {
+        let modifier = |amount, operation| {
+            AttributeModifier::new(
+                Attributes::ATTACK_DAMAGE.name().clone(),
+                amount,
+                operation,
+                AttributeSlot::Mainhand,
+            )
+        };
+        let container = AttributeContainer::new()
+            .with(Attributes::ATTACK_DAMAGE, 2.0)
+            .with_modifiers(&[
+                modifier(0.5, AttributeOperation::AddMultipliedTotal),
+                modifier(4.0, AttributeOperation::AddValue),
+                modifier(1.0, AttributeOperation::AddMultipliedBase),
+            ]);
+        assert_eq!(container.get(Attributes::ATTACK_DAMAGE), Ok(12.0));
+
+        let health = AttributeModifier::new(
+            Attributes::MAX_HEALTH.name().clone(),
+            0.5,
+            AttributeOperation::AddMultipliedBase,
+            AttributeSlot::Armor,
+        );
+        let container = AttributeContainer::new().with_modifiers(&[health]);
+        assert_eq!(container.get(Attributes::MAX_HEALTH), Ok(30.0));
+    }
+
+    #[test]
+    fn scale_is_sent_clamped() {
+        let scale = ATTRIBUTES
+            .get_entry(Attributes::SCALE.name().clone())
+            .unwrap();
+        let value = |container: AttributeContainer| {
+            container
+                .into_packet(1)
+                .properties
+                .iter()
+                .find(|x| x.id == scale)
+                .map(|x| x.value)
+        };
+        assert_eq!(value(AttributeContainer::new()), None);
+        assert_eq!(
+            value(AttributeContainer::new().with(Attributes::SCALE, 2.5)),
+            Some(2.5)
+        );
+        assert_eq!(
+            value(AttributeContainer::new().with(Attributes::SCALE, 100.0)),
+            Some(MAX_SCALE)
+        );
+    }
+}
@@ -0,0 +1,125 @@
+use std::time::{Duration, Instant};
+
+use super::{DamageSource, Entity};
+
+/// How long damage is remembered for deciding who killed a player.
+pub const DAMAGE_HISTORY_DURATION: Duration = Duration::from_secs(10);
+
+/// A single hit a player took.
+#[derive(Debug, Clone)]
+pub struct DamageRecord {
+    pub source: DamageSource,
+    pub amount: f32,
+    pub at: Instant,
+}
+
+/// The damage a player took recently, oldest first. Hits older than
+/// `DAMAGE_HISTORY_DURATION` are forgotten.
+#[derive(Debug, Clone, Default)]
+pub struct DamageHistory {
+    records: Vec<DamageRecord>,
+}
+
+impl DamageHistory {
+    pub fn records(&self) -> &[DamageRecord] {
+        &self.records
+    }
+
+    /// Returns the attacker of the most recent hit that had one.
+    pub fn killer(&self) -> Option<&Entity> {
+        self.records
+            .iter()
+            .rev()
+            .find_map(|record| record.source.attacker.as_ref())
+    }
+
+    /// Returns every other entity that hit the player, most recent first.
+    pub fn assisters(&self) -> Vec<&Entity> {
+        let killer = self.killer().map(Entity::uuid);
+        let mut assisters: Vec<&Entity> = Vec::new();
+        for attacker in self
+            .records
+            .iter()
+            .rev()
+            .filter_map(|record| record.source.attacker.as_ref())
+        {
+            if Some(attacker.uuid()) != killer && !assisters.iter().any(|x| x.uuid == attacker.uuid)
+            {
+                assisters.push(attacker);
+            }
+        }
+        assisters
+    }
+
+    /// Returns the total damage dealt by `attacker`.
+    pub fn damage_by(&self, attacker: &Entity) -> f32 {
+        self.records
+            .iter()
+            .filter(|record| {
+                record
+                    .source
+                    .attacker
+                    .as_ref()
+                    .is_some_and(|x| x.uuid == attacker.uuid)
+            })
+            .map(|record| record.amount)
+            .sum()
+    }
+
+    pub(crate) fn record(&mut self, source: DamageSource, amount: f32, now: Instant) {
+        self.expire(now);
+        self.records.push(DamageRecord {
+            source,
+            amount,
+            at: now,
+        });
+    }
+
+    pub(crate) fn expire(&mut self, now: Instant) {
+        self.records
+            .retain(|record| now.duration_since(record.at) < DAMAGE_HISTORY_DURATION);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use voxidian_protocol::value::Uuid;
+
+    use crate::{
+        dimension::Dimension,
+        entities::{DamageSource, Entity},
+    };
+
+    use super::DamageHistory;
+
+    #[test]
+    fn killer_and_assisters_come_from_recent_hits() {
+        let (sender, _receiver) = flume::unbounded();
+        let entity = || Entity {
+            dimension: Dimension {
+                sender: sender.downgrade(),
+            },
+            uuid: Uuid::new_v4(),
+        };
+        let (old, first, second) = (entity(), entity(), entity());
+        let start = Instant::now();
+
+        let mut history = DamageHistory::default();
+        history.record(DamageSource::player_attack(old.clone()), 4.0, start);
+        let start = start + Duration::from_secs(11);
+        history.record(DamageSource::player_attack(first.clone()), 3.0, start);
+        history.record(DamageSource::player_attack(second.clone()), 2.0, start);
+        history.record(DamageSource::player_attack(first.clone()), 3.0, start);
+        history.record(DamageSource::generic(), 1.0, start);
+
+        assert_eq!(history.records().len(), 4);
+        assert_eq!(history.killer().map(Entity::uuid), Some(first.uuid()));
+        let assisters = history.assisters();
+        assert_eq!(assisters.len(), 1);
+        assert_eq!(assisters[0].uuid(), second.uuid());
+        assert_eq!(history.damage_by(&first), 6.0);
+        assert_eq!(history.damage_by(&old), 0.0);
+    }
+}
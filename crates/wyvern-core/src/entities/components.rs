@@ -1,11 +1,15 @@
 use voxidian_protocol::value::Uuid;
 use wyvern_components::{DataComponentHolder, DataComponentMap, DataComponentType};
 
-use crate::item::ItemStack;
+use crate::{blocks::BlockState, item::ItemStack};
 
-use wyvern_values::{DVec3, Id, Vec2, id};
+use wyvern_datatypes::text::Text;
 
-use super::EntityData;
+use wyvern_values::{DVec3, Id, Vec2, Vec3, id};
+
+use super::{
+    ActiveEffects, AttributeContainer, Billboard, DespawnRule, EntityData, EntitySize, Follow,
+};
 
 pub struct EntityComponents;
 
@@ -14,6 +18,8 @@ impl EntityComponents {
         DataComponentType::new(id![minecraft:entity_type]);
     pub const POSITION: DataComponentType<DVec3> = DataComponentType::new(id![minecraft:position]);
     pub const DIRECTION: DataComponentType<Vec2> = DataComponentType::new(id![minecraft:direction]);
+    /// The yaw of the entity's head in degrees. Without it, the head faces the same way as the body.
+    pub const HEAD_YAW: DataComponentType<f32> = DataComponentType::new(id![minecraft:head_yaw]);
     pub const UUID: DataComponentType<Uuid> = DataComponentType::new(id![minecraft:uuid]);
     pub const ENTITY_ID: DataComponentType<i32> = DataComponentType::new(id![minecraft:entity_id]);
 
@@ -28,6 +34,56 @@ impl EntityComponents {
     pub const GRAVITY_ENABLED: DataComponentType<bool> =
         DataComponentType::new(id![minecraft:gravity]);
     pub const DRAG_ENABLED: DataComponentType<bool> = DataComponentType::new(id![minecraft:drag]);
+    /// How much the entity speeds up downwards each tick while `GRAVITY_ENABLED` is on, in blocks
+    /// per tick. Defaults to the vanilla value for its entity type, see `default_gravity`.
+    pub const GRAVITY: DataComponentType<f64> =
+        DataComponentType::new(id![minecraft:gravity_strength]);
+    /// The fraction of its velocity the entity keeps each tick while `PHYSICS_ENABLED` is on.
+    /// Defaults to the vanilla value for its entity type, see `default_drag`.
+    pub const DRAG: DataComponentType<f64> = DataComponentType::new(id![minecraft:drag_factor]);
+
+    /// The size of the entity's bounding box. Set when the entity is spawned from the default
+    /// size of its entity type.
+    pub const BOUNDING_BOX: DataComponentType<EntitySize> =
+        DataComponentType::new(id![minecraft:bounding_box]);
+
+    /// The UUID of the entity this entity is targeting. Set through `Entity::set_target`.
+    pub const TARGET: DataComponentType<Option<Uuid>> =
+        DataComponentType::new(id![minecraft:target]);
+    /// The entity this entity is walking towards. Set through `Entity::follow`.
+    pub const FOLLOWING: DataComponentType<Option<Follow>> =
+        DataComponentType::new(id![minecraft:following]);
+
+    /// The UUID of the entity this entity is riding. Set through `Entity::mount`.
+    pub const VEHICLE: DataComponentType<Option<Uuid>> =
+        DataComponentType::new(id![minecraft:vehicle]);
+    /// The UUIDs of the entities riding this entity. Set through `Entity::mount`.
+    pub const PASSENGERS: DataComponentType<Vec<Uuid>> =
+        DataComponentType::new(id![minecraft:passengers]);
+
+    /// When this entity despawns on its own. Set when the entity is spawned from the despawn
+    /// rule of its entity type in its dimension.
+    pub const DESPAWN_RULE: DataComponentType<DespawnRule> =
+        DataComponentType::new(id![minecraft:despawn_rule]);
+
+    pub const HEALTH: DataComponentType<f32> = DataComponentType::new(id![minecraft:health]);
+    pub const ATTRIBUTES: DataComponentType<AttributeContainer> =
+        DataComponentType::new(id![minecraft:attributes]);
+    /// The status effects the entity has. Set through `Entity::add_effect`.
+    pub const EFFECTS: DataComponentType<ActiveEffects> =
+        DataComponentType::new(id![minecraft:effects]);
+
+    pub const CUSTOM_NAME: DataComponentType<Text> =
+        DataComponentType::new(id![minecraft:custom_name]);
+    pub const CUSTOM_NAME_VISIBLE: DataComponentType<bool> =
+        DataComponentType::new(id![minecraft:custom_name_visible]);
+    pub const GLOWING: DataComponentType<bool> = DataComponentType::new(id![minecraft:glowing]);
+    pub const INVISIBLE: DataComponentType<bool> = DataComponentType::new(id![minecraft:invisible]);
+    /// Whether the entity is crouching. Kept in sync with `PlayerComponents::SNEAKING` for players.
+    pub const SNEAKING: DataComponentType<bool> = DataComponentType::new(id![minecraft:sneaking]);
+    /// Whether the entity is sprinting. Kept in sync with `PlayerComponents::SPRINTING` for
+    /// players.
+    pub const SPRINTING: DataComponentType<bool> = DataComponentType::new(id![minecraft:sprinting]);
 
     pub const MAINHAND_ITEM: DataComponentType<ItemStack> =
         DataComponentType::new(id![minecraft:equipment/mainhand]);
@@ -43,6 +99,31 @@ impl EntityComponents {
         DataComponentType::new(id![minecraft:equipment/leggings]);
     pub const BOOTS_ITEM: DataComponentType<ItemStack> =
         DataComponentType::new(id![minecraft:equipment/boots]);
+
+    /// The item an item entity shows and is picked up as. Set through `Dimension::drop_item`.
+    pub const ITEM: DataComponentType<ItemStack> = DataComponentType::new(id![minecraft:item]);
+    /// How many more ticks until players can pick up an item entity.
+    pub const PICKUP_DELAY: DataComponentType<u32> =
+        DataComponentType::new(id![minecraft:pickup_delay]);
+
+    /// How much a display entity is stretched along each axis.
+    pub const DISPLAY_SCALE: DataComponentType<Vec3> =
+        DataComponentType::new(id![minecraft:display/scale]);
+    /// How a display entity turns to face the players looking at it.
+    pub const DISPLAY_BILLBOARD: DataComponentType<Billboard> =
+        DataComponentType::new(id![minecraft:display/billboard]);
+    /// The ARGB color behind a text display's text. Defaults to `DEFAULT_TEXT_BACKGROUND`.
+    pub const DISPLAY_BACKGROUND: DataComponentType<i32> =
+        DataComponentType::new(id![minecraft:display/background]);
+    /// The text shown by a text display.
+    pub const DISPLAY_TEXT: DataComponentType<Text> =
+        DataComponentType::new(id![minecraft:display/text]);
+    /// The item shown by an item display.
+    pub const DISPLAY_ITEM: DataComponentType<ItemStack> =
+        DataComponentType::new(id![minecraft:display/item]);
+    /// The block shown by a block display.
+    pub const DISPLAY_BLOCK: DataComponentType<BlockState> =
+        DataComponentType::new(id![minecraft:display/block]);
 }
 
 impl DataComponentHolder for EntityData {
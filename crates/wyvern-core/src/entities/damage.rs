@@ -0,0 +1,188 @@
+use voxidian_protocol::{packet::s2c::play::DamageEventS2CPlayPacket, value::VarInt};
+use wyvern_components::DataComponentHolder;
+use wyvern_values::{DVec3, Id, Uuid, id};
+
+use crate::{
+    actors::{ActorError, ActorResult},
+    dimension::Dimension,
+    events::{EntityDamageEvent, EntityDeathEvent},
+    player::{Player, PlayerComponents},
+    server::registries::RegistryKeys,
+};
+
+use super::{AttributeContainer, Attributes, Entity, EntityComponents};
+
+pub const DEFAULT_MAX_HEALTH: f32 = 20.0;
+
+/// Describes what caused an entity to take damage.
+#[derive(Debug, Clone)]
+pub struct DamageSource {
+    /// The `minecraft:damage_type` registry entry used for the hurt effects and death message.
+    pub kind: Id,
+    /// The entity responsible for the damage, such as the shooter of an arrow.
+    pub attacker: Option<Entity>,
+    pub position: Option<DVec3>,
+}
+
+impl DamageSource {
+    pub fn new(kind: Id) -> DamageSource {
+        DamageSource {
+            kind,
+            attacker: None,
+            position: None,
+        }
+    }
+
+    pub fn generic() -> DamageSource {
+        DamageSource::new(id![minecraft:generic])
+    }
+
+    pub fn player_attack(attacker: Entity) -> DamageSource {
+        DamageSource::new(id![minecraft:player_attack]).attacker(attacker)
+    }
+
+    pub fn mob_attack(attacker: Entity) -> DamageSource {
+        DamageSource::new(id![minecraft:mob_attack]).attacker(attacker)
+    }
+
+    pub fn attacker(mut self, attacker: Entity) -> Self {
+        self.attacker = Some(attacker);
+        self
+    }
+
+    pub fn position(mut self, position: DVec3) -> Self {
+        self.position = Some(position);
+        self
+    }
+}
+
+/// Returns the health remaining after taking `amount` damage, clamped between zero and `max`.
+pub(crate) fn apply_damage(health: f32, max: f32, amount: f32) -> f32 {
+    (health.min(max) - amount).clamp(0.0, max)
+}
+
+fn max_health(attributes: ActorResult<AttributeContainer>) -> f32 {
+    attributes
+        .and_then(|attributes| attributes.get(Attributes::MAX_HEALTH))
+        .map(|max| max as f32)
+        .unwrap_or(DEFAULT_MAX_HEALTH)
+}
+
+/// Plays the hurt animation and sound of an entity for every player that can see it.
+fn broadcast_damage(
+    dimension: &Dimension,
+    uuid: Uuid,
+    entity_id: i32,
+    source: &DamageSource,
+) -> ActorResult<()> {
+    let server = dimension.server()?;
+    let source_type = server
+        .registries()?
+        .get(RegistryKeys::DAMAGE_TYPE)
+        .get_entry(source.kind.clone())
+        .ok_or(ActorError::BadRequest)?;
+    // Entity ids in this packet are offset by one, with zero meaning no entity.
+    let source_id = match &source.attacker {
+        Some(attacker) => attacker.get(EntityComponents::ENTITY_ID)? + 1,
+        None => 0,
+    };
+    let players = dimension
+        .entity_viewers(uuid)?
+        .into_iter()
+        .filter_map(|uuid| server.player(uuid).ok())
+        .collect::<Vec<_>>();
+
+    Player::broadcast_packet(
+        &players,
+        DamageEventS2CPlayPacket {
+            entity_id: VarInt::new(entity_id),
+            source_type,
+            source_cause_id: VarInt::new(source_id),
+            source_direct_id: VarInt::new(source_id),
+            source_pos: source.position.map(|pos| (pos[0], pos[1], pos[2])),
+        },
+    )
+}
+
+fn fire_damage_events(
+    entity: Entity,
+    amount: f32,
+    source: DamageSource,
+    killed: bool,
+) -> ActorResult<()> {
+    let server = entity.dimension().server()?;
+    server.spawn_event(EntityDamageEvent {
+        entity: entity.clone(),
+        amount,
+        source: source.clone(),
+    })?;
+    if killed {
+        server.spawn_event(EntityDeathEvent { entity, source })?;
+    }
+    Ok(())
+}
+
+impl Entity {
+    /// Damages this entity, playing its hurt effects and firing an `EntityDamageEvent`.
+    /// An `EntityDeathEvent` is fired if this brings its health to zero.
+    pub fn damage(&self, amount: f32, source: DamageSource) -> ActorResult<()> {
+        if let Some(player) = self.as_player() {
+            return player.damage(amount, source);
+        }
+
+        let max = max_health(self.get(EntityComponents::ATTRIBUTES));
+        let health = self.get(EntityComponents::HEALTH).unwrap_or(max);
+        let remaining = apply_damage(health, max, amount);
+        self.set(EntityComponents::HEALTH, remaining)?;
+
+        broadcast_damage(
+            self.dimension(),
+            self.uuid,
+            self.get(EntityComponents::ENTITY_ID)?,
+            &source,
+        )?;
+        fire_damage_events(
+            self.clone(),
+            amount,
+            source,
+            health > 0.0 && remaining <= 0.0,
+        )
+    }
+}
+
+impl Player {
+    /// Damages this player, syncing their health bar, recording the hit in their damage history
+    /// and firing an `EntityDamageEvent`.
+    /// An `EntityDeathEvent` and a `PlayerDeathEvent` are fired if this brings their health to
+    /// zero.
+    pub fn damage(&self, amount: f32, source: DamageSource) -> ActorResult<()> {
+        let max = max_health(self.effective_attributes());
+        let mut health = self.get(PlayerComponents::HEALTH)?;
+        let previous = health.health;
+        health.health = apply_damage(previous, max, amount);
+        self.set(PlayerComponents::HEALTH, health)?;
+        self.record_damage(source.clone(), amount)?;
+
+        let entity = self.as_entity()?;
+        broadcast_damage(entity.dimension(), entity.uuid, self.entity_id()?, &source)?;
+        let killed = previous > 0.0 && health.health <= 0.0;
+        fire_damage_events(entity, amount, source, killed)?;
+        if killed {
+            self.handle_death()?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::apply_damage;
+
+    #[test]
+    fn damage_is_clamped() {
+        assert_eq!(apply_damage(20.0, 20.0, 5.0), 15.0);
+        assert_eq!(apply_damage(3.0, 20.0, 5.0), 0.0);
+        assert_eq!(apply_damage(40.0, 20.0, 5.0), 15.0);
+        assert_eq!(apply_damage(10.0, 20.0, -30.0), 20.0);
+    }
+}
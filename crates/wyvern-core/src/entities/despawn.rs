@@ -0,0 +1,195 @@
+use std::time::{Duration, Instant};
+
+use voxidian_protocol::value::Uuid;
+use wyvern_components::DataComponentHolder;
+use wyvern_values::{DVec3, Id, id};
+
+use crate::{
+    actors::ActorResult, dimension::DimensionData, events::EntityDespawnEvent, runtime::Runtime,
+};
+
+use super::{Entity, EntityComponents};
+
+/// How long dropped items last before despawning.
+pub const ITEM_TIME_TO_LIVE: Duration = Duration::from_secs(5 * 60);
+/// How long projectiles last before despawning.
+pub const PROJECTILE_TIME_TO_LIVE: Duration = Duration::from_secs(60);
+
+/// When an entity is removed from its dimension without being removed by hand.
+///
+/// Entities get the rule of their entity type when spawned, which can be changed per dimension
+/// with `Dimension::set_despawn_rule`. A single entity's rule can be overridden by setting its
+/// `EntityComponents::DESPAWN_RULE`.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct DespawnRule {
+    /// How long the entity lasts after being spawned.
+    pub time_to_live: Option<Duration>,
+    /// How far away the closest player can be before the entity despawns.
+    pub player_range: Option<f64>,
+}
+
+impl DespawnRule {
+    /// A rule that never despawns the entity.
+    pub const fn never() -> DespawnRule {
+        DespawnRule {
+            time_to_live: None,
+            player_range: None,
+        }
+    }
+
+    pub const fn time_to_live(mut self, time_to_live: Duration) -> Self {
+        self.time_to_live = Some(time_to_live);
+        self
+    }
+
+    pub const fn player_range(mut self, range: f64) -> Self {
+        self.player_range = Some(range);
+        self
+    }
+
+    /// Returns the rule entities of a type get unless their dimension has one set. Dropped items
+    /// and projectiles despawn after a while, and everything else is kept.
+    pub fn default_for(entity_type: &Id) -> DespawnRule {
+        if *entity_type == id![minecraft:item] {
+            return DespawnRule::never().time_to_live(ITEM_TIME_TO_LIVE);
+        }
+        let projectiles = [
+            id![minecraft:arrow],
+            id![minecraft:spectral_arrow],
+            id![minecraft:trident],
+            id![minecraft:snowball],
+            id![minecraft:egg],
+            id![minecraft:ender_pearl],
+            id![minecraft:experience_bottle],
+            id![minecraft:splash_potion],
+            id![minecraft:lingering_potion],
+            id![minecraft:fireball],
+            id![minecraft:small_fireball],
+            id![minecraft:wind_charge],
+        ];
+        if projectiles.contains(entity_type) {
+            return DespawnRule::never().time_to_live(PROJECTILE_TIME_TO_LIVE);
+        }
+        DespawnRule::never()
+    }
+
+    /// Returns why an entity of the given age should despawn, if it should.
+    pub(crate) fn check(
+        &self,
+        age: Duration,
+        closest_player: Option<f64>,
+    ) -> Option<DespawnReason> {
+        if self.time_to_live.is_some_and(|ttl| age >= ttl) {
+            return Some(DespawnReason::TimeToLive);
+        }
+        let range = self.player_range?;
+        closest_player
+            .is_none_or(|distance| distance > range)
+            .then_some(DespawnReason::NoPlayerNearby)
+    }
+}
+
+/// Why an entity despawned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DespawnReason {
+    /// The entity outlived its time to live.
+    TimeToLive,
+    /// No player was within the entity's player range.
+    NoPlayerNearby,
+}
+
+impl DimensionData {
+    /// Removes every entity whose despawn rule says it should be, firing an
+    /// `EntityDespawnEvent` for each.
+    pub(crate) fn despawn_entities(&mut self) -> ActorResult<()> {
+        let players = self
+            .entities
+            .values()
+            .filter(|x| x.get(EntityComponents::PLAYER_CONTROLLED).unwrap_or(false))
+            .filter_map(|x| x.get(EntityComponents::POSITION).ok())
+            .collect::<Vec<_>>();
+        let now = Instant::now();
+
+        let despawned = self
+            .entities
+            .iter()
+            .filter_map(|(uuid, entity)| {
+                let rule = entity.get(EntityComponents::DESPAWN_RULE).ok()?;
+                let position = entity
+                    .get(EntityComponents::POSITION)
+                    .unwrap_or(DVec3::ZERO);
+                let closest = players
+                    .iter()
+                    .map(|player| player.distance(position))
+                    .min_by(f64::total_cmp);
+                let reason = rule.check(now.duration_since(entity.spawned_at), closest)?;
+                let entity_type = entity.get(EntityComponents::ENTITY_TYPE).ok()?;
+                Some((*uuid, entity_type, position, reason))
+            })
+            .collect::<Vec<(Uuid, Id, DVec3, DespawnReason)>>();
+        if despawned.is_empty() {
+            return Ok(());
+        }
+
+        let server = self.server.clone();
+        for (uuid, _, _, _) in &despawned {
+            self.remove_entity(*uuid)?;
+        }
+        let dimension = self.as_actor();
+        Runtime::spawn_task(async move {
+            let Some(server) = server else {
+                return Ok(());
+            };
+            for (uuid, entity_type, position, reason) in despawned {
+                server.spawn_event(EntityDespawnEvent {
+                    entity: Entity {
+                        dimension: dimension.clone(),
+                        uuid,
+                    },
+                    entity_type,
+                    position,
+                    reason,
+                })?;
+            }
+            Ok(())
+        });
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use wyvern_values::id;
+
+    use super::{DespawnReason, DespawnRule, ITEM_TIME_TO_LIVE};
+
+    #[test]
+    fn items_despawn_after_five_minutes() {
+        let rule = DespawnRule::default_for(&id![minecraft:item]);
+        assert_eq!(rule.check(Duration::from_secs(299), None), None);
+        assert_eq!(
+            rule.check(ITEM_TIME_TO_LIVE, None),
+            Some(DespawnReason::TimeToLive)
+        );
+        assert_eq!(
+            DespawnRule::default_for(&id![minecraft:zombie]),
+            DespawnRule::never()
+        );
+    }
+
+    #[test]
+    fn player_range_needs_a_close_player() {
+        let rule = DespawnRule::never().player_range(32.0);
+        assert_eq!(rule.check(Duration::ZERO, Some(10.0)), None);
+        assert_eq!(
+            rule.check(Duration::ZERO, Some(40.0)),
+            Some(DespawnReason::NoPlayerNearby)
+        );
+        assert_eq!(
+            rule.check(Duration::ZERO, None),
+            Some(DespawnReason::NoPlayerNearby)
+        );
+    }
+}
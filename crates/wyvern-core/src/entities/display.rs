@@ -0,0 +1,27 @@
+/// How a display entity turns to face the players looking at it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Billboard {
+    /// Keeps the direction the entity was given.
+    #[default]
+    Fixed,
+    /// Turns left and right to face the player, but doesn't tilt up or down.
+    Vertical,
+    /// Tilts up and down to face the player, but doesn't turn left or right.
+    Horizontal,
+    /// Always faces the player.
+    Center,
+}
+
+impl Billboard {
+    pub(crate) fn protocol_id(&self) -> u8 {
+        match self {
+            Billboard::Fixed => 0,
+            Billboard::Vertical => 1,
+            Billboard::Horizontal => 2,
+            Billboard::Center => 3,
+        }
+    }
+}
+
+/// The background color text displays have in vanilla, a translucent black.
+pub const DEFAULT_TEXT_BACKGROUND: i32 = 0x40000000;
@@ -0,0 +1,367 @@
+use std::time::Instant;
+
+use voxidian_protocol::{
+    packet::s2c::play::{RemoveMobEffectS2CPlayPacket, UpdateMobEffectS2CPlayPacket},
+    value::{MobEffect, VarInt},
+};
+use wyvern_components::DataComponentHolder;
+use wyvern_values::{Id, Uuid, id};
+
+use crate::{
+    actors::{ActorError, ActorResult},
+    dimension::{Dimension, DimensionData},
+    item::{AttributeModifier, AttributeOperation, AttributeSlot},
+    player::Player,
+    runtime::Runtime,
+};
+
+use super::{Attributes, Entity, EntityComponents, TICK_LENGTH};
+
+/// A status effect, such as `minecraft:speed`, given to an entity.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Effect {
+    /// The `minecraft:mob_effect` registry entry of the effect.
+    pub id: Id,
+    /// The level of the effect, starting at 0 for level I.
+    pub amplifier: u8,
+    /// How many ticks the effect lasts, or `Effect::INFINITE`.
+    pub duration: u32,
+    /// Whether the effect looks like it came from a beacon, with fainter particles.
+    pub ambient: bool,
+    pub particles: bool,
+}
+
+impl Effect {
+    /// A duration for effects that never run out.
+    pub const INFINITE: u32 = u32::MAX;
+
+    pub fn new(id: Id, amplifier: u8, duration: u32) -> Effect {
+        Effect {
+            id,
+            amplifier,
+            duration,
+            ambient: false,
+            particles: true,
+        }
+    }
+
+    pub fn ambient(mut self, ambient: bool) -> Self {
+        self.ambient = ambient;
+        self
+    }
+
+    pub fn particles(mut self, particles: bool) -> Self {
+        self.particles = particles;
+        self
+    }
+}
+
+/// An effect an entity has, and when it runs out.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ActiveEffect {
+    pub effect: Effect,
+    expires_at: Option<Instant>,
+}
+
+impl ActiveEffect {
+    fn new(effect: Effect, now: Instant) -> ActiveEffect {
+        ActiveEffect {
+            expires_at: (effect.duration != Effect::INFINITE)
+                .then(|| now + TICK_LENGTH * effect.duration),
+            effect,
+        }
+    }
+
+    /// Returns how many ticks are left on the effect, rounded up, or `Effect::INFINITE`.
+    pub fn remaining_ticks(&self, now: Instant) -> u32 {
+        self.expires_at.map_or(Effect::INFINITE, |expiry| {
+            let left = expiry.saturating_duration_since(now);
+            left.as_nanos().div_ceil(TICK_LENGTH.as_nanos()) as u32
+        })
+    }
+}
+
+/// The effects an entity has.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ActiveEffects {
+    effects: Vec<ActiveEffect>,
+}
+
+impl ActiveEffects {
+    pub fn get(&self, id: &Id) -> Option<&ActiveEffect> {
+        self.effects.iter().find(|x| x.effect.id == *id)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &ActiveEffect> {
+        self.effects.iter()
+    }
+
+    /// Gives the effect, unless one of the same kind is already active with a higher amplifier,
+    /// or the same amplifier and at least as long left. Returns whether the effect was given.
+    pub(crate) fn add(&mut self, effect: Effect, now: Instant) -> bool {
+        let active = ActiveEffect::new(effect, now);
+        match self
+            .effects
+            .iter_mut()
+            .find(|x| x.effect.id == active.effect.id)
+        {
+            Some(current) => {
+                let stronger = active.effect.amplifier > current.effect.amplifier;
+                let longer = active.effect.amplifier == current.effect.amplifier
+                    && active.remaining_ticks(now) > current.remaining_ticks(now);
+                if !stronger && !longer {
+                    return false;
+                }
+                *current = active;
+            }
+            None => self.effects.push(active),
+        }
+        true
+    }
+
+    /// Removes an effect, returning whether it was active.
+    pub(crate) fn remove(&mut self, id: &Id) -> bool {
+        let len = self.effects.len();
+        self.effects.retain(|x| x.effect.id != *id);
+        self.effects.len() != len
+    }
+
+    /// Removes effects that have run out, returning their ids.
+    pub(crate) fn expire(&mut self, now: Instant) -> Vec<Id> {
+        let mut expired = Vec::new();
+        self.effects.retain(|x| {
+            let done = x.expires_at.is_some_and(|expiry| expiry <= now);
+            if done {
+                expired.push(x.effect.id.clone());
+            }
+            !done
+        });
+        expired
+    }
+
+    /// Returns the attribute modifiers the active effects apply, such as extra movement speed
+    /// from `minecraft:speed`.
+    pub(crate) fn attribute_modifiers(&self) -> Vec<AttributeModifier> {
+        self.effects
+            .iter()
+            .filter_map(|active| {
+                let level = active.effect.amplifier as f64 + 1.0;
+                let (attribute, amount, operation) = match &active.effect.id {
+                    x if *x == id![minecraft:speed] => (
+                        Attributes::MOVEMENT_SPEED,
+                        0.2 * level,
+                        AttributeOperation::AddMultipliedTotal,
+                    ),
+                    x if *x == id![minecraft:slowness] => (
+                        Attributes::MOVEMENT_SPEED,
+                        -0.15 * level,
+                        AttributeOperation::AddMultipliedTotal,
+                    ),
+                    x if *x == id![minecraft:strength] => (
+                        Attributes::ATTACK_DAMAGE,
+                        3.0 * level,
+                        AttributeOperation::AddValue,
+                    ),
+                    x if *x == id![minecraft:weakness] => (
+                        Attributes::ATTACK_DAMAGE,
+                        -4.0 * level,
+                        AttributeOperation::AddValue,
+                    ),
+                    x if *x == id![minecraft:health_boost] => (
+                        Attributes::MAX_HEALTH,
+                        4.0 * level,
+                        AttributeOperation::AddValue,
+                    ),
+                    _ => return None,
+                };
+                Some(
+                    AttributeModifier::new(
+                        attribute.into_name(),
+                        amount,
+                        operation,
+                        AttributeSlot::Any,
+                    )
+                    .id(active.effect.id.clone()),
+                )
+            })
+            .collect()
+    }
+
+    /// Returns whether `minecraft:regeneration` heals its holder this tick, given how many ticks
+    /// the holder has existed for.
+    pub(crate) fn regenerates(&self, tick: u64) -> bool {
+        self.get(&id![minecraft:regeneration])
+            .is_some_and(|active| tick % (50u64 >> active.effect.amplifier.min(5)).max(1) == 0)
+    }
+}
+
+/// Returns the packet that shows an entity having an effect.
+pub(crate) fn effect_packet(
+    entity_id: i32,
+    active: &ActiveEffect,
+    now: Instant,
+) -> ActorResult<UpdateMobEffectS2CPlayPacket> {
+    let remaining = active.remaining_ticks(now);
+    Ok(UpdateMobEffectS2CPlayPacket {
+        entity_id: VarInt::new(entity_id),
+        effect_id: MobEffect::vanilla_registry()
+            .get_entry(&active.effect.id.clone().into())
+            .ok_or(ActorError::BadRequest)?,
+        amplifier: VarInt::new(active.effect.amplifier as i32),
+        duration: VarInt::new(if remaining == Effect::INFINITE {
+            -1
+        } else {
+            remaining as i32
+        }),
+        flags: active.effect.ambient as u8 | (active.effect.particles as u8) << 1 | 0x04,
+    })
+}
+
+/// Returns the packet that removes an effect from an entity.
+pub(crate) fn remove_effect_packet(
+    entity_id: i32,
+    id: &Id,
+) -> ActorResult<RemoveMobEffectS2CPlayPacket> {
+    Ok(RemoveMobEffectS2CPlayPacket {
+        entity_id: VarInt::new(entity_id),
+        effect_id: MobEffect::vanilla_registry()
+            .get_entry(&id.clone().into())
+            .ok_or(ActorError::BadRequest)?,
+    })
+}
+
+/// Sends an entity's effect packets to every player that can see it from another task.
+pub(crate) fn broadcast_effect_packets(
+    dimension: Dimension,
+    entity: Uuid,
+    updates: Vec<UpdateMobEffectS2CPlayPacket>,
+    removals: Vec<RemoveMobEffectS2CPlayPacket>,
+) {
+    Runtime::spawn_task(async move {
+        let server = dimension.server()?;
+        let players = dimension
+            .entity_viewers(entity)?
+            .into_iter()
+            .filter_map(|uuid| server.player(uuid).ok())
+            .collect::<Vec<_>>();
+        for packet in updates {
+            Player::broadcast_packet(&players, packet)?;
+        }
+        for packet in removals {
+            Player::broadcast_packet(&players, packet)?;
+        }
+        Ok(())
+    });
+}
+
+impl Entity {
+    /// Gives this entity an effect, following the same rules as vanilla when it already has
+    /// the effect: a higher amplifier or, at the same amplifier, a longer duration wins.
+    pub fn add_effect(&self, effect: Effect) -> ActorResult<()> {
+        if let Some(player) = self.as_player() {
+            return player.add_effect(effect);
+        }
+        let now = Instant::now();
+        let mut effects = self.get(EntityComponents::EFFECTS).unwrap_or_default();
+        let id = effect.id.clone();
+        if !effects.add(effect, now) {
+            return Ok(());
+        }
+        let packet = effect_packet(
+            self.get(EntityComponents::ENTITY_ID)?,
+            effects.get(&id).unwrap(),
+            now,
+        )?;
+        self.set(EntityComponents::EFFECTS, effects)?;
+        broadcast_effect_packets(self.dimension.clone(), self.uuid, vec![packet], Vec::new());
+        Ok(())
+    }
+
+    /// Removes an effect from this entity, if it has it.
+    pub fn remove_effect(&self, id: Id) -> ActorResult<()> {
+        if let Some(player) = self.as_player() {
+            return player.remove_effect(id);
+        }
+        let mut effects = self.get(EntityComponents::EFFECTS).unwrap_or_default();
+        if !effects.remove(&id) {
+            return Ok(());
+        }
+        let packet = remove_effect_packet(self.get(EntityComponents::ENTITY_ID)?, &id)?;
+        self.set(EntityComponents::EFFECTS, effects)?;
+        broadcast_effect_packets(self.dimension.clone(), self.uuid, Vec::new(), vec![packet]);
+        Ok(())
+    }
+
+    /// Returns the effects this entity has.
+    pub fn effects(&self) -> ActorResult<ActiveEffects> {
+        if let Some(player) = self.as_player() {
+            return player.effects();
+        }
+        Ok(self.get(EntityComponents::EFFECTS).unwrap_or_default())
+    }
+}
+
+impl DimensionData {
+    /// Removes effects that have run out from entities, telling players about it.
+    pub(crate) fn expire_entity_effects(&mut self) -> ActorResult<()> {
+        let now = Instant::now();
+        let dimension = self.as_actor();
+        for (uuid, entity) in self.entities.iter_mut() {
+            let Ok(mut effects) = entity.get(EntityComponents::EFFECTS) else {
+                continue;
+            };
+            let expired = effects.expire(now);
+            if expired.is_empty() {
+                continue;
+            }
+            let entity_id = entity.get(EntityComponents::ENTITY_ID)?;
+            let removals = expired
+                .iter()
+                .map(|id| remove_effect_packet(entity_id, id))
+                .collect::<ActorResult<Vec<_>>>()?;
+            entity.set(EntityComponents::EFFECTS, effects);
+            broadcast_effect_packets(dimension.clone(), *uuid, Vec::new(), removals);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use wyvern_values::id;
+
+    use super::{ActiveEffects, Effect};
+
+    #[test]
+    fn stronger_or_longer_effects_win() {
+        let now = Instant::now();
+        let mut effects = ActiveEffects::default();
+        assert!(effects.add(Effect::new(id![minecraft:speed], 1, 100), now));
+        assert!(!effects.add(Effect::new(id![minecraft:speed], 0, 1000), now));
+        assert!(!effects.add(Effect::new(id![minecraft:speed], 1, 50), now));
+        assert!(effects.add(Effect::new(id![minecraft:speed], 1, 200), now));
+        assert!(effects.add(Effect::new(id![minecraft:speed], 2, 10), now));
+
+        let speed = effects.get(&id![minecraft:speed]).unwrap();
+        assert_eq!(speed.effect.amplifier, 2);
+        assert_eq!(speed.remaining_ticks(now), 10);
+
+        effects.add(
+            Effect::new(id![minecraft:glowing], 0, Effect::INFINITE),
+            now,
+        );
+        let later = now + Duration::from_secs(1);
+        assert_eq!(effects.expire(later), vec![id![minecraft:speed]]);
+        assert_eq!(
+            effects
+                .get(&id![minecraft:glowing])
+                .unwrap()
+                .remaining_ticks(later),
+            Effect::INFINITE
+        );
+        assert!(effects.remove(&id![minecraft:glowing]));
+        assert!(!effects.remove(&id![minecraft:glowing]));
+    }
+}
@@ -0,0 +1,158 @@
+use voxidian_protocol::value::Uuid;
+use wyvern_values::{DVec3, DVec3Ext};
+
+use crate::{actors::ActorResult, player::itf::eye_height};
+
+use super::{Entity, EntityComponents};
+
+/// How an entity follows another with `Entity::follow`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FollowOptions {
+    /// How close the follower gets before it stops, in blocks.
+    pub distance: f64,
+    /// How fast the follower moves, in blocks per tick.
+    pub speed: f64,
+}
+
+impl Default for FollowOptions {
+    fn default() -> Self {
+        FollowOptions {
+            distance: 2.0,
+            speed: 0.25,
+        }
+    }
+}
+
+impl FollowOptions {
+    pub fn new() -> FollowOptions {
+        FollowOptions::default()
+    }
+
+    pub fn distance(mut self, distance: f64) -> Self {
+        self.distance = distance;
+        self
+    }
+
+    pub fn speed(mut self, speed: f64) -> Self {
+        self.speed = speed;
+        self
+    }
+}
+
+/// The entity an entity is following, and how. Set through `Entity::follow`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Follow {
+    pub target: Uuid,
+    pub options: FollowOptions,
+}
+
+/// Returns the velocity that moves an entity at `position` towards `target`, keeping its
+/// vertical velocity so it still falls and steps. It stops moving horizontally once it's
+/// within the follow distance.
+fn follow_velocity(
+    position: DVec3,
+    target: DVec3,
+    velocity: DVec3,
+    options: FollowOptions,
+) -> DVec3 {
+    let offset = (target - position).with_y(0.0);
+    let distance = offset.length();
+    if distance <= options.distance {
+        return DVec3::new(0.0, velocity[1], 0.0);
+    }
+    let horizontal = offset / distance * options.speed.min(distance - options.distance);
+    horizontal.with_y(velocity[1])
+}
+
+impl Entity {
+    /// Makes this entity walk towards `target` every tick until it's within the follow
+    /// distance, replacing anything it was following before. Following stops on its own once
+    /// the target is removed or leaves the dimension. Physics is enabled for the entity if it
+    /// wasn't configured, so it can move.
+    pub fn follow(&self, target: &Entity, options: FollowOptions) -> ActorResult<()> {
+        if self.get(EntityComponents::PHYSICS_ENABLED).is_err() {
+            self.set(EntityComponents::PHYSICS_ENABLED, true)?;
+        }
+        self.set(
+            EntityComponents::FOLLOWING,
+            Some(Follow {
+                target: target.uuid,
+                options,
+            }),
+        )
+    }
+
+    /// Returns the entity this entity is following, if any.
+    pub fn following(&self) -> ActorResult<Option<Entity>> {
+        Ok(self
+            .get(EntityComponents::FOLLOWING)
+            .ok()
+            .flatten()
+            .map(|follow| self.dimension.get_entity(follow.target)))
+    }
+
+    /// Stops this entity following anything, leaving it standing where it is.
+    pub fn stop_following(&self) -> ActorResult<()> {
+        if self
+            .get(EntityComponents::FOLLOWING)
+            .ok()
+            .flatten()
+            .is_none()
+        {
+            return Ok(());
+        }
+        self.set(EntityComponents::FOLLOWING, None)?;
+        let velocity = self.get(EntityComponents::VELOCITY).unwrap_or(DVec3::ZERO);
+        self.set(
+            EntityComponents::VELOCITY,
+            DVec3::new(0.0, velocity[1], 0.0),
+        )
+    }
+
+    /// Steers this entity towards the entity it's following, if any, for this tick.
+    pub(crate) fn steer_towards_followed(&self) -> ActorResult<()> {
+        let Some(follow) = self.get(EntityComponents::FOLLOWING).ok().flatten() else {
+            return Ok(());
+        };
+        let target = self.dimension.get_entity(follow.target);
+        let Ok(target_position) = target.get(EntityComponents::POSITION) else {
+            return self.stop_following();
+        };
+
+        let position = self.get(EntityComponents::POSITION)?;
+        let velocity = self.get(EntityComponents::VELOCITY).unwrap_or(DVec3::ZERO);
+        self.set(
+            EntityComponents::VELOCITY,
+            follow_velocity(position, target_position, velocity, follow.options),
+        )?;
+
+        let sneaking = target.get(EntityComponents::SNEAKING).unwrap_or(false);
+        self.look_at(target_position.with_y(target_position[1] + eye_height(sneaking)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wyvern_values::DVec3;
+
+    use super::{FollowOptions, follow_velocity};
+
+    #[test]
+    fn followers_stop_within_distance() {
+        let options = FollowOptions::new().distance(2.0).speed(0.5);
+        let velocity = DVec3::new(0.0, -0.1, 0.0);
+
+        assert_eq!(
+            follow_velocity(DVec3::ZERO, DVec3::new(10.0, 5.0, 0.0), velocity, options),
+            DVec3::new(0.5, -0.1, 0.0)
+        );
+        assert_eq!(
+            follow_velocity(DVec3::ZERO, DVec3::new(2.25, 0.0, 0.0), velocity, options),
+            DVec3::new(0.25, -0.1, 0.0)
+        );
+        assert_eq!(
+            follow_velocity(DVec3::ZERO, DVec3::new(0.0, 0.0, 1.5), velocity, options),
+            DVec3::new(0.0, -0.1, 0.0)
+        );
+    }
+}
@@ -0,0 +1,183 @@
+use wyvern_values::{DVec3, Id};
+
+use crate::actors::ActorResult;
+
+use super::{Entity, EntityComponents, EntityData, scale_of};
+
+/// An axis-aligned box between two corners.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoundingBox {
+    pub min: DVec3,
+    pub max: DVec3,
+}
+
+impl BoundingBox {
+    /// Returns the box of an entity of the given width and height standing at `position`.
+    pub fn at_feet(position: DVec3, width: f64, height: f64) -> BoundingBox {
+        let half = width / 2.0;
+        BoundingBox {
+            min: DVec3::new(position[0] - half, position[1], position[2] - half),
+            max: DVec3::new(position[0] + half, position[1] + height, position[2] + half),
+        }
+    }
+
+    pub fn contains(&self, point: DVec3) -> bool {
+        point.cmpge(self.min).all() && point.cmple(self.max).all()
+    }
+
+    /// Returns true if this box and `other` overlap, including if they only touch.
+    pub fn intersects(&self, other: &BoundingBox) -> bool {
+        self.min.cmple(other.max).all() && self.max.cmpge(other.min).all()
+    }
+
+    /// Returns how far `point` is from the closest point of this box, or zero if it's inside.
+    pub fn distance_to(&self, point: DVec3) -> f64 {
+        point.clamp(self.min, self.max).distance(point)
+    }
+
+    /// Returns how far along the ray starting at `origin` and going in the normalized
+    /// `direction` it first enters this box, or `None` if it misses it.
+    pub fn ray_intersection(&self, origin: DVec3, direction: DVec3) -> Option<f64> {
+        let mut near = f64::NEG_INFINITY;
+        let mut far = f64::INFINITY;
+        for axis in 0..3 {
+            if direction[axis] == 0.0 {
+                if origin[axis] < self.min[axis] || origin[axis] > self.max[axis] {
+                    return None;
+                }
+                continue;
+            }
+            let a = (self.min[axis] - origin[axis]) / direction[axis];
+            let b = (self.max[axis] - origin[axis]) / direction[axis];
+            near = near.max(a.min(b));
+            far = far.min(a.max(b));
+        }
+        (near <= far && far >= 0.0).then_some(near.max(0.0))
+    }
+}
+
+/// The width and height of an entity's bounding box, centered horizontally on its position
+/// and extending upwards from its feet.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EntitySize {
+    pub width: f64,
+    pub height: f64,
+}
+
+impl EntitySize {
+    pub fn new(width: f64, height: f64) -> EntitySize {
+        EntitySize { width, height }
+    }
+
+    /// Returns the default size of an entity type. Types not listed are assumed to be the
+    /// size of a player.
+    pub fn of(entity_type: &Id) -> EntitySize {
+        let (width, height) = default_size(entity_type);
+        EntitySize { width, height }
+    }
+
+    /// Returns the box of an entity of this size standing at `position`.
+    pub fn at(&self, position: DVec3) -> BoundingBox {
+        BoundingBox::at_feet(position, self.width, self.height)
+    }
+
+    /// Returns this size multiplied by `scale`.
+    pub fn scaled(&self, scale: f64) -> EntitySize {
+        EntitySize {
+            width: self.width * scale,
+            height: self.height * scale,
+        }
+    }
+}
+
+fn default_size(entity_type: &Id) -> (f64, f64) {
+    match entity_type.path() {
+        "item" | "experience_orb" | "snowball" | "egg" | "ender_pearl" | "potion" => (0.25, 0.25),
+        "arrow" | "spectral_arrow" | "trident" | "fireball" | "small_fireball" => (0.5, 0.5),
+        "chicken" => (0.4, 0.7),
+        "pig" | "spider" => (0.9, 0.9),
+        "cow" | "mooshroom" => (0.9, 1.4),
+        "sheep" => (0.9, 1.3),
+        "wolf" | "fox" => (0.6, 0.85),
+        "cat" | "ocelot" => (0.6, 0.7),
+        "horse" | "donkey" | "mule" => (1.3964844, 1.6),
+        "zombie" | "husk" | "drowned" | "zombie_villager" | "villager" | "witch" => (0.6, 1.95),
+        "skeleton" | "stray" | "piglin" => (0.6, 1.99),
+        "creeper" => (0.6, 1.7),
+        "enderman" => (0.6, 2.9),
+        "iron_golem" => (1.4, 2.7),
+        "armor_stand" => (0.5, 1.975),
+        "slime" | "magma_cube" => (0.52, 0.52),
+        "bat" => (0.5, 0.9),
+        "boat" | "chest_boat" => (1.375, 0.5625),
+        "minecart" => (0.98, 0.7),
+        "tnt" | "falling_block" => (0.98, 0.98),
+        "text_display" | "item_display" | "block_display" | "marker" => (0.0, 0.0),
+        _ => (0.6, 1.8),
+    }
+}
+
+impl Entity {
+    /// Returns the box this entity takes up, based on its `BOUNDING_BOX` size.
+    pub fn bounding_box(&self) -> ActorResult<BoundingBox> {
+        let position = self.get(EntityComponents::POSITION)?;
+        Ok(self.size()?.at(position))
+    }
+
+    /// Returns the size of this entity's bounding box, falling back to the default size of its
+    /// entity type. Both are multiplied by the entity's `minecraft:scale` attribute.
+    pub fn size(&self) -> ActorResult<EntitySize> {
+        let size = match self.get(EntityComponents::BOUNDING_BOX) {
+            Ok(size) => size,
+            Err(_) => EntitySize::of(&self.get(EntityComponents::ENTITY_TYPE)?),
+        };
+        let attributes = self.get(EntityComponents::ATTRIBUTES).ok();
+        Ok(size.scaled(scale_of(attributes.as_ref())))
+    }
+
+    pub fn set_size(&self, size: EntitySize) -> ActorResult<()> {
+        self.set(EntityComponents::BOUNDING_BOX, size)
+    }
+}
+
+impl EntityData {
+    /// Returns the box this entity takes up, or `None` if it has no position yet.
+    pub(crate) fn bounding_box(&self) -> Option<BoundingBox> {
+        let position = self.components.get(EntityComponents::POSITION).ok()?;
+        let size = match self.components.get(EntityComponents::BOUNDING_BOX) {
+            Ok(size) => size,
+            Err(_) => EntitySize::of(&self.components.get(EntityComponents::ENTITY_TYPE).ok()?),
+        };
+        let attributes = self.components.get(EntityComponents::ATTRIBUTES).ok();
+        Some(size.scaled(scale_of(attributes.as_ref())).at(position))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wyvern_values::DVec3;
+
+    use super::BoundingBox;
+
+    #[test]
+    fn ray_enters_box() {
+        let bb = BoundingBox::at_feet(DVec3::new(0.0, 0.0, 5.0), 1.0, 2.0);
+        assert_eq!(
+            bb.ray_intersection(DVec3::new(0.0, 1.0, 0.0), DVec3::Z),
+            Some(4.5)
+        );
+        assert_eq!(
+            bb.ray_intersection(DVec3::new(0.0, 1.0, 0.0), DVec3::NEG_Z),
+            None
+        );
+        assert_eq!(
+            bb.ray_intersection(DVec3::new(0.0, 3.0, 0.0), DVec3::Z),
+            None
+        );
+        assert!(bb.contains(DVec3::new(0.2, 1.9, 5.4)));
+        assert!(bb.intersects(&BoundingBox::at_feet(DVec3::new(0.9, 1.9, 5.0), 1.0, 1.0)));
+        assert!(!bb.intersects(&BoundingBox::at_feet(DVec3::new(0.0, 2.1, 5.0), 1.0, 1.0)));
+        assert_eq!(bb.distance_to(DVec3::new(0.0, 1.0, 0.5)), 4.0);
+        assert_eq!(bb.distance_to(DVec3::new(0.0, 1.0, 5.0)), 0.0);
+    }
+}
@@ -0,0 +1,149 @@
+use std::time::Duration;
+
+use wyvern_components::DataComponentHolder;
+use wyvern_values::DVec3;
+
+use crate::{actors::ActorResult, dimension::DimensionData, player::Player, runtime::Runtime};
+
+use super::{EntityComponents, head_yaw, rotation_packets};
+
+/// How long a dimension tick lasts.
+pub(crate) const TICK_LENGTH: Duration = Duration::from_millis(50);
+
+/// How entity movement is smoothed for clients.
+///
+/// Entity positions are sent once per dimension tick by default, so entities visibly jump
+/// between positions when ticks run late or irregularly. Sending velocity costs nothing, since
+/// the position packet has room for it either way. Sending more updates per tick costs up to
+/// that many times the movement packets for entities moving under physics.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EntityInterpolation {
+    /// Whether entities' velocity is sent with their position, so clients keep them moving
+    /// until the next update arrives.
+    pub send_velocity: bool,
+    /// How many position updates are sent per tick for entities moving under physics. Every
+    /// update after the first places the entity where its velocity will have taken it by then.
+    pub updates_per_tick: u32,
+}
+
+impl Default for EntityInterpolation {
+    fn default() -> Self {
+        EntityInterpolation {
+            send_velocity: false,
+            updates_per_tick: 1,
+        }
+    }
+}
+
+impl EntityInterpolation {
+    pub const fn send_velocity(mut self, send_velocity: bool) -> Self {
+        self.send_velocity = send_velocity;
+        self
+    }
+
+    /// Sets how many position updates are sent per tick. Values below 1 are treated as 1.
+    pub const fn updates_per_tick(mut self, updates: u32) -> Self {
+        self.updates_per_tick = if updates == 0 { 1 } else { updates };
+        self
+    }
+
+    /// Returns which update of the current tick is due after `elapsed` time has passed since
+    /// the tick started.
+    fn step_at(&self, elapsed: Duration) -> u32 {
+        let progress = elapsed.as_secs_f64() / TICK_LENGTH.as_secs_f64();
+        (progress * self.updates_per_tick as f64) as u32
+    }
+}
+
+/// Returns where an entity at `position` moving by `velocity` every tick is after `step` of
+/// `updates` updates in a tick.
+fn extrapolate(position: DVec3, velocity: DVec3, step: u32, updates: u32) -> DVec3 {
+    position + velocity * (step as f64 / updates as f64)
+}
+
+impl DimensionData {
+    /// Sends the extrapolated positions of entities moving under physics if an update between
+    /// ticks is due.
+    pub(crate) fn send_interpolated_positions(&mut self) -> ActorResult<()> {
+        let interpolation = self.entity_interpolation;
+        let step = interpolation.step_at(self.last_update.elapsed());
+        if step <= self.interpolation_step || step >= interpolation.updates_per_tick {
+            return Ok(());
+        }
+        self.interpolation_step = step;
+
+        let packets = self
+            .entities
+            .iter()
+            .filter(|(_, x)| !x.get(EntityComponents::PLAYER_CONTROLLED).unwrap_or(false))
+            .filter(|(_, x)| x.get(EntityComponents::PHYSICS_ENABLED).unwrap_or(false))
+            .filter_map(|(uuid, entity)| {
+                let velocity = entity
+                    .get(EntityComponents::VELOCITY)
+                    .ok()
+                    .filter(|velocity| *velocity != DVec3::ZERO)?;
+                let position = extrapolate(
+                    entity.get(EntityComponents::POSITION).ok()?,
+                    velocity,
+                    step,
+                    interpolation.updates_per_tick,
+                );
+                let (sync, _) = rotation_packets(
+                    entity.get(EntityComponents::ENTITY_ID).ok()?,
+                    position,
+                    entity.get(EntityComponents::DIRECTION).ok()?,
+                    head_yaw(&entity.components),
+                    if interpolation.send_velocity {
+                        velocity
+                    } else {
+                        DVec3::ZERO
+                    },
+                );
+                let trackers = self.trackers_of(uuid);
+                (!trackers.is_empty()).then_some((trackers, sync))
+            })
+            .collect::<Vec<_>>();
+        if packets.is_empty() {
+            return Ok(());
+        }
+
+        let server = self.server()?;
+        Runtime::spawn_task(async move {
+            for (trackers, packet) in packets {
+                let players = trackers
+                    .into_iter()
+                    .filter_map(|uuid| server.player(uuid).ok())
+                    .collect::<Vec<_>>();
+                Player::broadcast_packet(&players, packet)?;
+            }
+            Ok(())
+        });
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use wyvern_values::DVec3;
+
+    use super::{EntityInterpolation, extrapolate};
+
+    #[test]
+    fn updates_are_spread_over_the_tick() {
+        let interpolation = EntityInterpolation::default().updates_per_tick(4);
+        assert_eq!(interpolation.step_at(Duration::from_millis(10)), 0);
+        assert_eq!(interpolation.step_at(Duration::from_millis(26)), 2);
+        assert_eq!(
+            EntityInterpolation::default()
+                .updates_per_tick(0)
+                .updates_per_tick,
+            1
+        );
+        assert_eq!(
+            extrapolate(DVec3::ZERO, DVec3::new(4.0, 0.0, -2.0), 1, 4),
+            DVec3::new(1.0, 0.0, -0.5)
+        );
+    }
+}
@@ -0,0 +1,236 @@
+use std::collections::HashSet;
+
+use voxidian_protocol::value::Uuid;
+use wyvern_components::DataComponentHolder;
+use wyvern_values::{DVec3, id};
+
+use crate::{
+    actors::ActorResult,
+    dimension::DimensionData,
+    inventory::{max_stack_size, stackable},
+    item::{ItemComponents, ItemStack},
+};
+
+use super::{BoundingBox, EntityComponents, EntitySpawn};
+
+/// How many ticks pass before an item dropped with `Dimension::drop_item` can be picked up.
+pub const DEFAULT_PICKUP_DELAY: u32 = 10;
+/// How many ticks pass before an item a player threw can be picked up, so they don't pick it
+/// straight back up.
+pub const PLAYER_DROP_PICKUP_DELAY: u32 = 40;
+/// How close item entities have to be to merge into one.
+const MERGE_DISTANCE: f64 = 0.75;
+
+/// Returns the description of an item entity holding `item`.
+pub(crate) fn item_entity(
+    position: DVec3,
+    item: ItemStack,
+    velocity: DVec3,
+    pickup_delay: u32,
+) -> EntitySpawn {
+    EntitySpawn::new(id![minecraft:item])
+        .position(position)
+        .velocity(velocity)
+        .with(EntityComponents::ITEM, item)
+        .with(EntityComponents::PICKUP_DELAY, pickup_delay)
+        .with(EntityComponents::PHYSICS_ENABLED, true)
+}
+
+fn count(item: &ItemStack) -> u16 {
+    item.get(ItemComponents::ITEM_COUNT).unwrap_or(1)
+}
+
+/// Returns the stack two stacks merge into, or `None` if they are different items or wouldn't
+/// fit in one stack.
+fn merged(a: &ItemStack, b: &ItemStack) -> Option<ItemStack> {
+    if !stackable(a, b) {
+        return None;
+    }
+    let total = count(a) as u32 + count(b) as u32;
+    (total <= max_stack_size(a) as u32)
+        .then(|| a.clone().with(ItemComponents::ITEM_COUNT, total as u16))
+}
+
+/// An item entity as seen by the merging pass of `tick_item_entities`.
+struct DroppedItem {
+    uuid: Uuid,
+    position: DVec3,
+    item: ItemStack,
+    pickup_delay: u32,
+    changed: bool,
+}
+
+/// Merges dropped items holding the same item that are close together, keeping the longer
+/// pickup delay of each pair. Returns the items that were merged into another one.
+fn merge_nearby(items: &mut [DroppedItem]) -> HashSet<Uuid> {
+    let mut merged_away = HashSet::new();
+    for i in 0..items.len() {
+        if merged_away.contains(&items[i].uuid) {
+            continue;
+        }
+        for j in (i + 1)..items.len() {
+            if merged_away.contains(&items[j].uuid)
+                || items[i].position.distance(items[j].position) > MERGE_DISTANCE
+            {
+                continue;
+            }
+            if let Some(stack) = merged(&items[i].item, &items[j].item) {
+                merged_away.insert(items[j].uuid);
+                items[i].item = stack;
+                items[i].pickup_delay = items[i].pickup_delay.max(items[j].pickup_delay);
+                items[i].changed = true;
+            }
+        }
+    }
+    merged_away
+}
+
+impl DimensionData {
+    /// Counts down the pickup delays of item entities, and merges item entities holding the
+    /// same item that are close together into one.
+    pub(crate) fn tick_item_entities(&mut self) -> ActorResult<()> {
+        let mut items = Vec::new();
+        for (uuid, entity) in &mut self.entities {
+            let Ok(item) = entity.components.get(EntityComponents::ITEM) else {
+                continue;
+            };
+            let mut pickup_delay = entity
+                .components
+                .get(EntityComponents::PICKUP_DELAY)
+                .unwrap_or(0);
+            if pickup_delay > 0 {
+                pickup_delay -= 1;
+                entity
+                    .components
+                    .set(EntityComponents::PICKUP_DELAY, pickup_delay);
+            }
+            if let Ok(position) = entity.components.get(EntityComponents::POSITION) {
+                items.push(DroppedItem {
+                    uuid: *uuid,
+                    position,
+                    item,
+                    pickup_delay,
+                    changed: false,
+                });
+            }
+        }
+
+        let merged_away = merge_nearby(&mut items);
+        for item in items {
+            if item.changed
+                && let Some(entity) = self.entities.get_mut(&item.uuid)
+            {
+                entity.components.set(EntityComponents::ITEM, item.item);
+                entity
+                    .components
+                    .set(EntityComponents::PICKUP_DELAY, item.pickup_delay);
+            }
+        }
+        for uuid in merged_away {
+            self.remove_entity(uuid)?;
+        }
+        Ok(())
+    }
+
+    /// Returns the item entities inside `area` that can be picked up.
+    pub(crate) fn items_in(&self, area: &BoundingBox) -> Vec<Uuid> {
+        self.entities
+            .iter()
+            .filter(|(_, entity)| entity.components.contains_type(&EntityComponents::ITEM))
+            .filter(|(_, entity)| {
+                entity
+                    .components
+                    .get(EntityComponents::PICKUP_DELAY)
+                    .unwrap_or(0)
+                    == 0
+            })
+            .filter(|(_, entity)| {
+                entity
+                    .bounding_box()
+                    .is_some_and(|bounds| bounds.intersects(area))
+            })
+            .map(|(uuid, _)| *uuid)
+            .collect()
+    }
+
+    /// Takes up to `max` items from an item entity, removing it once it's empty. Returns `None`
+    /// if the entity is gone or can't be picked up yet.
+    pub(crate) fn take_from_item(
+        &mut self,
+        uuid: Uuid,
+        max: u16,
+    ) -> ActorResult<Option<ItemStack>> {
+        let Some(entity) = self.entities.get_mut(&uuid) else {
+            return Ok(None);
+        };
+        let Ok(item) = entity.components.get(EntityComponents::ITEM) else {
+            return Ok(None);
+        };
+        if max == 0
+            || entity
+                .components
+                .get(EntityComponents::PICKUP_DELAY)
+                .unwrap_or(0)
+                > 0
+        {
+            return Ok(None);
+        }
+
+        let available = count(&item);
+        let taken = available.min(max);
+        if taken == available {
+            self.remove_entity(uuid)?;
+        } else {
+            entity.components.set(
+                EntityComponents::ITEM,
+                item.clone()
+                    .with(ItemComponents::ITEM_COUNT, available - taken),
+            );
+        }
+        Ok(Some(item.with(ItemComponents::ITEM_COUNT, taken)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use voxidian_protocol::value::Uuid;
+    use wyvern_components::DataComponentHolder;
+    use wyvern_values::{DVec3, id};
+
+    use crate::item::{ItemComponents, ItemStack};
+
+    use super::{DroppedItem, merge_nearby, merged};
+
+    #[test]
+    fn only_identical_stacks_that_fit_merge() {
+        let stone = |count: u16| {
+            ItemStack::new(id![minecraft:stone]).with(ItemComponents::ITEM_COUNT, count)
+        };
+
+        assert_eq!(
+            merged(&stone(20), &stone(30)).map(|x| x.get(ItemComponents::ITEM_COUNT)),
+            Some(Ok(50))
+        );
+        assert!(merged(&stone(40), &stone(30)).is_none());
+        assert!(merged(&stone(1), &ItemStack::new(id![minecraft:dirt])).is_none());
+    }
+
+    #[test]
+    fn merging_keeps_the_longer_pickup_delay() {
+        let dropped = |x: f64, pickup_delay: u32| DroppedItem {
+            uuid: Uuid::new_v4(),
+            position: DVec3::new(x, 64.0, 0.0),
+            item: ItemStack::new(id![minecraft:stone]).with(ItemComponents::ITEM_COUNT, 1),
+            pickup_delay,
+            changed: false,
+        };
+        let mut items = [dropped(0.0, 0), dropped(0.5, 39), dropped(5.0, 0)];
+
+        let merged_away = merge_nearby(&mut items);
+        assert_eq!(merged_away.len(), 1);
+        assert!(merged_away.contains(&items[1].uuid));
+        assert_eq!(items[0].pickup_delay, 39);
+        assert_eq!(items[0].item.get(ItemComponents::ITEM_COUNT), Ok(2));
+        assert!(!items[2].changed);
+    }
+}
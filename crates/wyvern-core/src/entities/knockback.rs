@@ -0,0 +1,103 @@
+use wyvern_components::DataComponentHolder;
+use wyvern_values::DVec3;
+
+use crate::actors::ActorResult;
+
+use super::{Attributes, Entity, EntityComponents};
+
+/// The most upwards velocity knockback can give an entity.
+const MAX_VERTICAL_KNOCKBACK: f64 = 0.4;
+
+/// Computes the velocity of an entity at `position` after being knocked back away from `source`.
+/// Half of the existing velocity is kept, matching vanilla.
+pub(crate) fn knockback_velocity(
+    velocity: DVec3,
+    position: DVec3,
+    source: DVec3,
+    strength: f64,
+) -> DVec3 {
+    if strength <= 0.0 {
+        return velocity;
+    }
+
+    let away = (position - source).with_y(0.0);
+    let away = if away.length_squared() < 1.0e-8 {
+        DVec3::ZERO
+    } else {
+        away.normalize() * strength
+    };
+
+    DVec3::new(
+        velocity[0] / 2.0 + away[0],
+        (velocity[1] / 2.0 + strength).min(MAX_VERTICAL_KNOCKBACK),
+        velocity[2] / 2.0 + away[2],
+    )
+}
+
+impl Entity {
+    /// Knocks this entity back horizontally away from `source`, scaled by its knockback resistance.
+    /// Entities without physics configured have it enabled so the knockback moves them.
+    pub fn apply_knockback(&self, source: DVec3, strength: f32) -> ActorResult<()> {
+        let resistance = self
+            .get(EntityComponents::ATTRIBUTES)
+            .and_then(|attributes| attributes.get(Attributes::KNOCKBACK_RESISTANCE))
+            .unwrap_or(0.0)
+            .clamp(0.0, 1.0);
+        let strength = strength as f64 * (1.0 - resistance);
+        let position = self.get(EntityComponents::POSITION)?;
+
+        if let Some(player) = self.as_player() {
+            return player.set_velocity(knockback_velocity(
+                player.velocity()?,
+                position,
+                source,
+                strength,
+            ));
+        }
+
+        let velocity = self.get(EntityComponents::VELOCITY).unwrap_or(DVec3::ZERO);
+        if self.get(EntityComponents::PHYSICS_ENABLED).is_err() {
+            self.set(EntityComponents::PHYSICS_ENABLED, true)?;
+        }
+        self.set(
+            EntityComponents::VELOCITY,
+            knockback_velocity(velocity, position, source, strength),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wyvern_values::DVec3;
+
+    use super::knockback_velocity;
+
+    #[test]
+    fn knocks_away_from_source() {
+        let velocity = knockback_velocity(
+            DVec3::ZERO,
+            DVec3::new(0.0, 0.0, 0.0),
+            DVec3::new(-3.0, 5.0, 0.0),
+            0.4,
+        );
+        assert!((velocity[0] - 0.4).abs() < 1.0e-9);
+        assert!((velocity[1] - 0.4).abs() < 1.0e-9);
+        assert_eq!(velocity[2], 0.0);
+    }
+
+    #[test]
+    fn keeps_half_of_existing_velocity() {
+        let velocity = knockback_velocity(
+            DVec3::new(1.0, 0.0, -1.0),
+            DVec3::ZERO,
+            DVec3::new(0.0, 0.0, 1.0),
+            0.2,
+        );
+        assert!((velocity[0] - 0.5).abs() < 1.0e-9);
+        assert!((velocity[1] - 0.2).abs() < 1.0e-9);
+        assert!((velocity[2] + 0.7).abs() < 1.0e-9);
+
+        let resisted = knockback_velocity(DVec3::new(1.0, 0.0, 0.0), DVec3::ZERO, DVec3::X, 0.0);
+        assert_eq!(resisted, DVec3::new(1.0, 0.0, 0.0));
+    }
+}
@@ -0,0 +1,170 @@
+use voxidian_protocol::value::{EntityMetadata, MetadataEntry, Text as PtcText, VarInt};
+use wyvern_components::DataComponentMap;
+use wyvern_values::{Id, id};
+
+use super::{DEFAULT_TEXT_BACKGROUND, EntityComponents};
+
+const FLAGS_INDEX: u8 = 0;
+const CUSTOM_NAME_INDEX: u8 = 2;
+const CUSTOM_NAME_VISIBLE_INDEX: u8 = 3;
+const POSE_INDEX: u8 = 6;
+
+const CROUCHING_FLAG: u8 = 0x02;
+const SPRINTING_FLAG: u8 = 0x08;
+const INVISIBLE_FLAG: u8 = 0x20;
+const GLOWING_FLAG: u8 = 0x40;
+
+const STANDING_POSE: i32 = 0;
+const CROUCHING_POSE: i32 = 5;
+
+const PLAYER_SKIN_PARTS_INDEX: u8 = 17;
+
+const ITEM_INDEX: u8 = 8;
+
+const DISPLAY_SCALE_INDEX: u8 = 12;
+const DISPLAY_BILLBOARD_INDEX: u8 = 15;
+/// The index of the text, item or block a display entity shows, depending on its type.
+const DISPLAYED_INDEX: u8 = 23;
+const TEXT_DISPLAY_BACKGROUND_INDEX: u8 = 25;
+
+/// Returns the metadata an entity is shown with when it is added to a client.
+pub(crate) fn spawn_metadata(components: &DataComponentMap) -> EntityMetadata {
+    let mut meta = EntityMetadata::new();
+    if components
+        .get(EntityComponents::ENTITY_TYPE)
+        .is_ok_and(|entity_type| entity_type == id![minecraft:player])
+    {
+        meta.insert_raw_entry(PLAYER_SKIN_PARTS_INDEX, MetadataEntry::Byte(255));
+    }
+    component_metadata(components, &mut meta, |id| components.contains(id));
+    meta
+}
+
+/// Writes the metadata entries backed by entity components into `meta`, only including
+/// indices whose components satisfy `changed`. Returns true if any entries were written.
+pub(crate) fn component_metadata(
+    components: &DataComponentMap,
+    meta: &mut EntityMetadata,
+    changed: impl Fn(&Id) -> bool,
+) -> bool {
+    let mut written = false;
+
+    if changed(EntityComponents::GLOWING.name())
+        || changed(EntityComponents::INVISIBLE.name())
+        || changed(EntityComponents::SNEAKING.name())
+        || changed(EntityComponents::SPRINTING.name())
+    {
+        let mut flags = 0;
+        if components.get(EntityComponents::SNEAKING).unwrap_or(false) {
+            flags |= CROUCHING_FLAG;
+        }
+        if components.get(EntityComponents::SPRINTING).unwrap_or(false) {
+            flags |= SPRINTING_FLAG;
+        }
+        if components.get(EntityComponents::INVISIBLE).unwrap_or(false) {
+            flags |= INVISIBLE_FLAG;
+        }
+        if components.get(EntityComponents::GLOWING).unwrap_or(false) {
+            flags |= GLOWING_FLAG;
+        }
+        meta.insert_raw_entry(FLAGS_INDEX, MetadataEntry::Byte(flags));
+        written = true;
+    }
+
+    if changed(EntityComponents::SNEAKING.name()) {
+        let pose = if components.get(EntityComponents::SNEAKING).unwrap_or(false) {
+            CROUCHING_POSE
+        } else {
+            STANDING_POSE
+        };
+        meta.insert_raw_entry(POSE_INDEX, MetadataEntry::Pose(VarInt::new(pose)));
+        written = true;
+    }
+
+    if changed(EntityComponents::CUSTOM_NAME.name()) {
+        let name = components
+            .get(EntityComponents::CUSTOM_NAME)
+            .ok()
+            .map(|name| PtcText::from(name).to_nbt());
+        meta.insert_raw_entry(
+            CUSTOM_NAME_INDEX,
+            MetadataEntry::OptionalTextComponent(name),
+        );
+        written = true;
+    }
+
+    if changed(EntityComponents::CUSTOM_NAME_VISIBLE.name()) {
+        let visible = components
+            .get(EntityComponents::CUSTOM_NAME_VISIBLE)
+            .unwrap_or(false);
+        meta.insert_raw_entry(CUSTOM_NAME_VISIBLE_INDEX, MetadataEntry::Boolean(visible));
+        written = true;
+    }
+
+    if changed(EntityComponents::ITEM.name())
+        && let Ok(item) = components.get(EntityComponents::ITEM)
+    {
+        meta.insert_raw_entry(ITEM_INDEX, MetadataEntry::Slot(item.into()));
+        written = true;
+    }
+
+    if changed(EntityComponents::DISPLAY_SCALE.name())
+        && let Ok(scale) = components.get(EntityComponents::DISPLAY_SCALE)
+    {
+        meta.insert_raw_entry(
+            DISPLAY_SCALE_INDEX,
+            MetadataEntry::Vector3(scale[0], scale[1], scale[2]),
+        );
+        written = true;
+    }
+
+    if changed(EntityComponents::DISPLAY_BILLBOARD.name())
+        && let Ok(billboard) = components.get(EntityComponents::DISPLAY_BILLBOARD)
+    {
+        meta.insert_raw_entry(
+            DISPLAY_BILLBOARD_INDEX,
+            MetadataEntry::Byte(billboard.protocol_id()),
+        );
+        written = true;
+    }
+
+    if changed(EntityComponents::DISPLAY_BACKGROUND.name()) {
+        let background = components
+            .get(EntityComponents::DISPLAY_BACKGROUND)
+            .unwrap_or(DEFAULT_TEXT_BACKGROUND);
+        meta.insert_raw_entry(
+            TEXT_DISPLAY_BACKGROUND_INDEX,
+            MetadataEntry::Int(background),
+        );
+        written = true;
+    }
+
+    if changed(EntityComponents::DISPLAY_TEXT.name())
+        && let Ok(text) = components.get(EntityComponents::DISPLAY_TEXT)
+    {
+        meta.insert_raw_entry(
+            DISPLAYED_INDEX,
+            MetadataEntry::TextComponent(PtcText::from(text).to_nbt()),
+        );
+        written = true;
+    }
+
+    if changed(EntityComponents::DISPLAY_ITEM.name())
+        && let Ok(item) = components.get(EntityComponents::DISPLAY_ITEM)
+    {
+        meta.insert_raw_entry(DISPLAYED_INDEX, MetadataEntry::Slot(item.into()));
+        written = true;
+    }
+
+    if changed(EntityComponents::DISPLAY_BLOCK.name())
+        && let Ok(block) = components.get(EntityComponents::DISPLAY_BLOCK)
+    {
+        meta.insert_raw_entry(
+            DISPLAYED_INDEX,
+            MetadataEntry::BlockState(VarInt::new(block.protocol_id())),
+        );
+        written = true;
+    }
+
+    written
+}
@@ -1,19 +1,54 @@
-use std::sync::Arc;
+use std::{sync::Arc, time::Instant};
 
 use dyn_clone::clone_box;
-use voxidian_protocol::value::{EntityMetadata, MetadataEntry, Uuid};
+use voxidian_protocol::value::{EntityMetadata, Uuid};
 use wyvern_components::{ComponentElement, DataComponentMap, DataComponentType};
 
 use crate::{
     actors::{ActorError, ActorResult},
     dimension::Dimension,
+    player::{Player, itf::eye_height},
 };
-use wyvern_values::{Id, id};
+use wyvern_values::{DVec3, DVec3Ext, Id, id};
 
 mod components;
 pub use components::*;
 mod attributes;
 pub use attributes::*;
+mod combat;
+pub use combat::*;
+mod damage;
+pub use damage::*;
+mod despawn;
+pub use despawn::*;
+mod display;
+pub use display::*;
+mod follow;
+pub use follow::{Follow, FollowOptions};
+mod effects;
+pub use effects::*;
+mod hitbox;
+pub use hitbox::*;
+mod interpolation;
+mod items;
+pub use interpolation::EntityInterpolation;
+pub(crate) use interpolation::TICK_LENGTH;
+pub(crate) use items::item_entity;
+pub use items::{DEFAULT_PICKUP_DELAY, PLAYER_DROP_PICKUP_DELAY};
+mod knockback;
+pub use knockback::*;
+mod metadata;
+mod motion;
+pub(crate) use metadata::*;
+pub use motion::{default_drag, default_gravity};
+mod passengers;
+pub(crate) use passengers::MountChange;
+mod spawn;
+mod step;
+mod target;
+pub use spawn::*;
+mod tracking;
+pub use tracking::default_tracking_range;
 mod update;
 pub use update::*;
 
@@ -32,6 +67,40 @@ impl Entity {
         &self.dimension
     }
 
+    /// Returns true if this entity is controlled by a connected player.
+    pub fn is_player(&self) -> ActorResult<bool> {
+        Ok(self.get(EntityComponents::ENTITY_TYPE)? == id![minecraft:player])
+    }
+
+    /// Returns the player controlling this entity, or `None` if it isn't a player.
+    pub fn as_player(&self) -> Option<Player> {
+        if !self.is_player().unwrap_or(false) {
+            return None;
+        }
+        self.dimension.server().ok()?.player(self.uuid).ok()
+    }
+
+    /// Turns this entity's head to face `target`, looking from its eyes, while its body stays put.
+    /// Entities that aren't players are assumed to have the eye height of a player, which is lower
+    /// while they're sneaking.
+    pub fn look_at(&self, target: DVec3) -> ActorResult<()> {
+        if let Some(player) = self.as_player() {
+            return player.look_at(target);
+        }
+        let position = self.get(EntityComponents::POSITION)?;
+        let sneaking = self.get(EntityComponents::SNEAKING).unwrap_or(false);
+        let eyes = position.with_y(position[1] + eye_height(sneaking));
+        let rotation = (target - eyes).to_rotation();
+        let direction = self.get(EntityComponents::DIRECTION)?;
+        self.set(EntityComponents::DIRECTION, direction.with_y(rotation[1]))?;
+        self.set(EntityComponents::HEAD_YAW, rotation[0])
+    }
+
+    /// Turns this entity's head without turning its body.
+    pub fn set_head_yaw(&self, yaw: f32) -> ActorResult<()> {
+        self.set(EntityComponents::HEAD_YAW, yaw)
+    }
+
     pub fn remove(&self) -> ActorResult<()> {
         self.dimension.remove_entity(self.uuid)?;
         Ok(())
@@ -61,11 +130,26 @@ impl Entity {
     }
 
     pub fn generate_metadata(&self) -> ActorResult<EntityMetadata> {
-        let mut meta = EntityMetadata::new();
-        if self.get(EntityComponents::ENTITY_TYPE)? == id![minecraft:player] {
-            meta.insert_raw_entry(17, MetadataEntry::Byte(255));
+        let mut components = DataComponentMap::new();
+        components.set(
+            EntityComponents::ENTITY_TYPE,
+            self.get(EntityComponents::ENTITY_TYPE)?,
+        );
+        if let Ok(name) = self.get(EntityComponents::CUSTOM_NAME) {
+            components.set(EntityComponents::CUSTOM_NAME, name);
         }
-        Ok(meta)
+        for flag in [
+            EntityComponents::CUSTOM_NAME_VISIBLE,
+            EntityComponents::GLOWING,
+            EntityComponents::INVISIBLE,
+            EntityComponents::SNEAKING,
+            EntityComponents::SPRINTING,
+        ] {
+            if let Ok(value) = self.get(flag.clone()) {
+                components.set(flag, value);
+            }
+        }
+        Ok(spawn_metadata(&components))
     }
 }
 
@@ -76,7 +160,123 @@ pub struct EntityType;
 pub struct EntityData {
     pub(crate) last_components: DataComponentMap,
     pub(crate) components: DataComponentMap,
+    pub(crate) spawned_at: Instant,
 }
 
 pub struct Entities;
 wyvern_macros::generate_entity_types!();
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use flume::Receiver;
+    use voxidian_protocol::value::Uuid;
+    use wyvern_components::ComponentElement;
+    use wyvern_values::id;
+
+    use crate::{
+        actors::ActorError,
+        dimension::{Dimension, DimensionMessage},
+        player::{Player, PlayerMessage},
+        server::{Server, ServerMessage},
+    };
+
+    use super::Entity;
+
+    /// Answers messages from `receiver` on a new thread until every sender is dropped. Requests
+    /// the handler ignores are answered with `ActorHasBeenDropped`.
+    pub(super) fn stub_actor<M: Send + 'static>(
+        receiver: Receiver<M>,
+        handler: impl Fn(M) + Send + 'static,
+    ) {
+        std::thread::spawn(move || {
+            for message in receiver.iter() {
+                handler(message);
+            }
+        });
+    }
+
+    #[test]
+    fn player_and_entity_handles_convert() {
+        let uuid = Uuid::new_v4();
+        let (server_tx, server_rx) = flume::unbounded();
+        let (dimension_tx, dimension_rx) = flume::unbounded();
+        let (player_tx, player_rx) = flume::unbounded();
+        let server = Server {
+            sender: server_tx.downgrade(),
+        };
+        let dimension = Dimension {
+            sender: dimension_tx.downgrade(),
+        };
+        let player = Player {
+            sender: player_tx.downgrade(),
+        };
+
+        let found_player = player.clone();
+        stub_actor(server_rx, move |message| {
+            if let ServerMessage::GetPlayerByUuid(requested, tx) = message {
+                let _ = tx.send(if requested == uuid {
+                    Ok(found_player.clone())
+                } else {
+                    Err(ActorError::BadRequest)
+                });
+            }
+        });
+        stub_actor(dimension_rx, move |message| match message {
+            DimensionMessage::GetEntityComponent(_, component, tx) => {
+                let _ = tx.send(if component == id![minecraft:entity_type] {
+                    Ok(Arc::new(id![minecraft:player]) as Arc<dyn ComponentElement>)
+                } else {
+                    Err(ActorError::ComponentNotFound)
+                });
+            }
+            DimensionMessage::GetServer(tx) => {
+                let _ = tx.send(Ok(server.clone()));
+            }
+            _ => {}
+        });
+        let player_dimension = dimension.clone();
+        stub_actor(player_rx, move |message| match message {
+            PlayerMessage::GetDimension(tx) => {
+                let _ = tx.send(Ok(player_dimension.clone()));
+            }
+            PlayerMessage::GetComponent(_, tx) => {
+                let _ = tx.send(Ok(Arc::new(uuid) as Arc<dyn ComponentElement>));
+            }
+            _ => {}
+        });
+
+        let entity = player.as_entity().unwrap();
+        assert_eq!(*entity.uuid(), uuid);
+        let entity_dimension = entity.dimension.sender.upgrade().unwrap();
+        assert!(entity_dimension.same_channel(&dimension_tx));
+
+        let entity = Entity { dimension, uuid };
+        assert_eq!(entity.is_player(), Ok(true));
+        let found = entity.as_player().unwrap().sender.upgrade().unwrap();
+        assert!(found.same_channel(&player_tx));
+        drop((server_tx, dimension_tx, player_tx));
+    }
+
+    #[test]
+    fn dropped_handles_do_not_convert() {
+        let (sender, receiver) = flume::unbounded();
+        let entity = Entity {
+            dimension: Dimension {
+                sender: sender.downgrade(),
+            },
+            uuid: Uuid::new_v4(),
+        };
+        drop((sender, receiver));
+        assert!(entity.as_player().is_none());
+        assert_eq!(entity.is_player(), Err(ActorError::ActorHasBeenDropped));
+
+        let (sender, receiver) = flume::unbounded();
+        let player = Player {
+            sender: sender.downgrade(),
+        };
+        drop((sender, receiver));
+        assert!(player.as_entity().is_err());
+    }
+}
@@ -0,0 +1,70 @@
+use wyvern_values::Id;
+
+use crate::actors::ActorResult;
+
+use super::{Entity, EntityComponents};
+
+/// Returns how much an entity of the given type speeds up downwards each tick while gravity is
+/// enabled, in blocks per tick, matching vanilla.
+pub fn default_gravity(entity_type: &Id) -> f64 {
+    match entity_type.path() {
+        "fireball" | "small_fireball" | "dragon_fireball" | "wither_skull" | "wind_charge"
+        | "breeze_wind_charge" | "shulker_bullet" => 0.0,
+        "snowball" | "egg" | "ender_pearl" | "experience_orb" => 0.03,
+        "arrow" | "spectral_arrow" | "trident" | "potion" | "experience_bottle" => 0.05,
+        "item" | "falling_block" | "tnt" | "minecart" | "boat" | "chest_boat" => 0.04,
+        _ => 0.08,
+    }
+}
+
+/// Returns the fraction of its velocity an entity of the given type keeps each tick while
+/// moving under physics, matching vanilla's drag in the air.
+pub fn default_drag(entity_type: &Id) -> f64 {
+    match entity_type.path() {
+        "arrow" | "spectral_arrow" | "trident" | "snowball" | "egg" | "ender_pearl" | "potion"
+        | "experience_bottle" => 0.99,
+        "item" | "falling_block" | "tnt" | "experience_orb" => 0.98,
+        "fireball" | "small_fireball" | "dragon_fireball" | "wither_skull" => 0.95,
+        _ => 0.91,
+    }
+}
+
+impl Entity {
+    /// Returns how much this entity speeds up downwards each tick while `GRAVITY_ENABLED` is on,
+    /// from its `GRAVITY` component or the default for its entity type.
+    pub fn gravity(&self) -> ActorResult<f64> {
+        match self.get(EntityComponents::GRAVITY) {
+            Ok(gravity) => Ok(gravity),
+            Err(_) => Ok(default_gravity(&self.get(EntityComponents::ENTITY_TYPE)?)),
+        }
+    }
+
+    /// Returns the fraction of its velocity this entity keeps each tick while moving under
+    /// physics, from its `DRAG` component or the default for its entity type. Entities with
+    /// `DRAG_ENABLED` off keep all of it.
+    pub fn drag(&self) -> ActorResult<f64> {
+        if self.get(EntityComponents::DRAG_ENABLED) == Ok(false) {
+            return Ok(1.0);
+        }
+        match self.get(EntityComponents::DRAG) {
+            Ok(drag) => Ok(drag),
+            Err(_) => Ok(default_drag(&self.get(EntityComponents::ENTITY_TYPE)?)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wyvern_values::id;
+
+    use super::{default_drag, default_gravity};
+
+    #[test]
+    fn projectiles_fall_slower_than_mobs() {
+        assert_eq!(default_gravity(&id![minecraft:zombie]), 0.08);
+        assert_eq!(default_gravity(&id![minecraft:arrow]), 0.05);
+        assert_eq!(default_gravity(&id![minecraft:fireball]), 0.0);
+        assert_eq!(default_drag(&id![minecraft:item]), 0.98);
+        assert_eq!(default_drag(&id![minecraft:zombie]), 0.91);
+    }
+}
@@ -0,0 +1,423 @@
+use std::collections::{HashMap, HashSet};
+
+use voxidian_protocol::{
+    packet::s2c::play::SetPassengersS2CPlayPacket,
+    value::{Uuid, VarInt},
+};
+use wyvern_components::DataComponentHolder;
+use wyvern_values::{DVec3, DVec3Ext};
+
+use crate::{
+    actors::{ActorError, ActorResult},
+    dimension::DimensionData,
+    events::{EntityDismountEvent, EntityMountEvent},
+    player::{Player, PlayerComponents},
+    runtime::Runtime,
+};
+
+use super::{BoundingBox, Entity, EntityComponents, EntityData};
+
+impl Entity {
+    /// Returns the entity this entity is riding, if any.
+    pub fn vehicle(&self) -> ActorResult<Option<Entity>> {
+        Ok(self
+            .get(EntityComponents::VEHICLE)
+            .ok()
+            .flatten()
+            .map(|uuid| self.dimension.get_entity(uuid)))
+    }
+
+    /// Returns the entities riding this entity, in the order they mounted it.
+    pub fn passengers(&self) -> ActorResult<Vec<Entity>> {
+        Ok(self
+            .get(EntityComponents::PASSENGERS)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|uuid| self.dimension.get_entity(uuid))
+            .collect())
+    }
+
+    /// Makes this entity ride `vehicle`, dismounting it from anything it was riding first, and
+    /// fires an `EntityMountEvent`. Both entities must be in the same dimension, and an entity
+    /// can't ride itself or anything riding it.
+    pub fn mount(&self, vehicle: &Entity) -> ActorResult<()> {
+        let server = self.dimension.server()?;
+        let MountChange::Mounted { left } = self.dimension.mount_entity(self.uuid, vehicle.uuid)?
+        else {
+            return Ok(());
+        };
+
+        if let Some(left) = left {
+            let left = self.dimension.get_entity(left);
+            left.send_passengers()?;
+            server.spawn_event(EntityDismountEvent {
+                entity: self.clone(),
+                vehicle: left,
+            })?;
+        }
+        vehicle.send_passengers()?;
+
+        server.spawn_event(EntityMountEvent {
+            entity: self.clone(),
+            vehicle: vehicle.clone(),
+        })
+    }
+
+    /// Makes this entity stop riding its vehicle, placing it on top of the vehicle, and fires an
+    /// `EntityDismountEvent`. Does nothing if it isn't riding anything.
+    pub fn dismount(&self) -> ActorResult<()> {
+        let Some(vehicle) = self.dimension.dismount_entity(self.uuid)? else {
+            return Ok(());
+        };
+        let vehicle = self.dimension.get_entity(vehicle);
+        vehicle.send_passengers()?;
+
+        self.place_after_dismount(top_of(&vehicle.bounding_box()?))?;
+
+        self.dimension.server()?.spawn_event(EntityDismountEvent {
+            entity: self.clone(),
+            vehicle,
+        })
+    }
+
+    /// Makes `passenger` ride this entity. The same as `passenger.mount(self)`.
+    pub fn add_passenger(&self, passenger: &Entity) -> ActorResult<()> {
+        passenger.mount(self)
+    }
+
+    /// Makes `passenger` stop riding this entity. Does nothing if it isn't riding this entity.
+    pub fn remove_passenger(&self, passenger: &Entity) -> ActorResult<()> {
+        if passenger.get(EntityComponents::VEHICLE).ok().flatten() != Some(self.uuid) {
+            return Ok(());
+        }
+        passenger.dismount()
+    }
+
+    /// Removes every passenger from this entity.
+    pub fn eject_passengers(&self) -> ActorResult<()> {
+        for passenger in self.passengers()? {
+            passenger.dismount()?;
+        }
+        Ok(())
+    }
+
+    /// Moves a passenger that stopped riding to `position`. Players are teleported, since their
+    /// client stopped sending its position while riding.
+    fn place_after_dismount(&self, position: DVec3) -> ActorResult<()> {
+        match self.as_player() {
+            Some(player) => player.teleport(position),
+            None => self.set(EntityComponents::POSITION, position),
+        }
+    }
+
+    /// Tells every player in the dimension who is riding this entity.
+    fn send_passengers(&self) -> ActorResult<()> {
+        let mut ids = Vec::new();
+        for passenger in self.passengers()? {
+            ids.push(passenger.get(EntityComponents::ENTITY_ID)?);
+        }
+        let packet = passengers_packet(self.get(EntityComponents::ENTITY_ID)?, ids);
+        let server = self.dimension.server()?;
+        let players = self
+            .dimension
+            .entity_viewers(self.uuid)?
+            .into_iter()
+            .filter_map(|uuid| server.player(uuid).ok())
+            .collect::<Vec<_>>();
+        Player::broadcast_packet(&players, packet)
+    }
+}
+
+/// Returns the point in the middle of the top of a box, where passengers are left when they stop
+/// riding.
+fn top_of(bounding_box: &BoundingBox) -> DVec3 {
+    ((bounding_box.min + bounding_box.max) / 2.0).with_y(bounding_box.max[1])
+}
+
+pub(crate) fn passengers_packet(
+    vehicle_id: i32,
+    passenger_ids: Vec<i32>,
+) -> SetPassengersS2CPlayPacket {
+    SetPassengersS2CPlayPacket {
+        vehicle: VarInt::new(vehicle_id),
+        passengers: passenger_ids
+            .into_iter()
+            .map(VarInt::new)
+            .collect::<Vec<_>>()
+            .into(),
+    }
+}
+
+/// Returns the entity id of every vehicle a player starting to track `added` has to be sent the
+/// passengers of, along with the ids of those passengers. These are the added entities that have
+/// passengers and the vehicles the added entities ride, since the client only learns who rides
+/// what from these lists.
+pub(crate) fn riding_lists(
+    entities: &HashMap<Uuid, EntityData>,
+    added: &HashSet<Uuid>,
+) -> Vec<(i32, Vec<i32>)> {
+    let entity_id = |uuid: &Uuid| entities.get(uuid)?.get(EntityComponents::ENTITY_ID).ok();
+    let vehicles = added
+        .iter()
+        .flat_map(|uuid| {
+            let vehicle = entities
+                .get(uuid)
+                .and_then(|entity| entity.get(EntityComponents::VEHICLE).ok().flatten());
+            [Some(*uuid), vehicle]
+        })
+        .flatten()
+        .collect::<HashSet<_>>();
+
+    vehicles
+        .into_iter()
+        .filter_map(|vehicle| {
+            let passengers = entities
+                .get(&vehicle)?
+                .get(EntityComponents::PASSENGERS)
+                .unwrap_or_default();
+            if passengers.is_empty() {
+                return None;
+            }
+            Some((
+                entity_id(&vehicle)?,
+                passengers.iter().filter_map(entity_id).collect(),
+            ))
+        })
+        .collect()
+}
+
+/// What happened when an entity was made to ride another.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum MountChange {
+    /// The entity was already riding the vehicle, so nothing changed.
+    AlreadyRiding,
+    /// The entity now rides the vehicle, having left the vehicle it was riding before, if any.
+    Mounted { left: Option<Uuid> },
+}
+
+impl DimensionData {
+    /// Makes `passenger` ride `vehicle`, taking it off its old vehicle first. Fails if either
+    /// entity doesn't exist, or if `vehicle` is `passenger` or is riding it.
+    pub(crate) fn mount_passenger(
+        &mut self,
+        passenger: Uuid,
+        vehicle: Uuid,
+    ) -> ActorResult<MountChange> {
+        if !self.entities.contains_key(&passenger) {
+            return Err(ActorError::BadRequest);
+        }
+        let mut below = Some(vehicle);
+        while let Some(uuid) = below {
+            if uuid == passenger {
+                return Err(ActorError::BadRequest);
+            }
+            below = self
+                .entities
+                .get(&uuid)
+                .ok_or(ActorError::BadRequest)?
+                .get(EntityComponents::VEHICLE)
+                .ok()
+                .flatten();
+        }
+
+        let riding = self.entities[&passenger]
+            .get(EntityComponents::VEHICLE)
+            .ok()
+            .flatten();
+        if riding == Some(vehicle) {
+            return Ok(MountChange::AlreadyRiding);
+        }
+        let left = self.unseat_passenger(passenger);
+
+        let vehicle_data = self.entities.get_mut(&vehicle).unwrap();
+        let mut passengers = vehicle_data
+            .get(EntityComponents::PASSENGERS)
+            .unwrap_or_default();
+        passengers.push(passenger);
+        vehicle_data.set(EntityComponents::PASSENGERS, passengers);
+        self.entities
+            .get_mut(&passenger)
+            .unwrap()
+            .set(EntityComponents::VEHICLE, Some(vehicle));
+        Ok(MountChange::Mounted { left })
+    }
+
+    /// Takes `passenger` off the vehicle it is riding, returning that vehicle.
+    pub(crate) fn unseat_passenger(&mut self, passenger: Uuid) -> Option<Uuid> {
+        let entity = self.entities.get_mut(&passenger)?;
+        let vehicle = entity.get(EntityComponents::VEHICLE).ok().flatten()?;
+        entity.set(EntityComponents::VEHICLE, None);
+        if let Some(vehicle) = self.entities.get_mut(&vehicle) {
+            let mut passengers = vehicle
+                .get(EntityComponents::PASSENGERS)
+                .unwrap_or_default();
+            passengers.retain(|uuid| *uuid != passenger);
+            vehicle.set(EntityComponents::PASSENGERS, passengers);
+        }
+        Some(vehicle)
+    }
+
+    /// Moves every passenger onto the top of its vehicle, starting from the entities that aren't
+    /// riding anything, so passengers of passengers follow too. Players riding something have
+    /// their position updated as well, since their client stops sending it.
+    pub(crate) fn move_passengers(&mut self) -> ActorResult<()> {
+        let mut vehicles = self
+            .entities
+            .iter()
+            .filter(|(_, entity)| {
+                entity
+                    .get(EntityComponents::VEHICLE)
+                    .ok()
+                    .flatten()
+                    .is_none()
+                    && !entity
+                        .get(EntityComponents::PASSENGERS)
+                        .unwrap_or_default()
+                        .is_empty()
+            })
+            .map(|(uuid, _)| *uuid)
+            .collect::<Vec<_>>();
+
+        let mut players = Vec::new();
+        while let Some(vehicle) = vehicles.pop() {
+            let Some(vehicle) = self.entities.get(&vehicle) else {
+                continue;
+            };
+            let Some(seat) = vehicle.bounding_box().map(|x| top_of(&x)) else {
+                continue;
+            };
+            for uuid in vehicle
+                .get(EntityComponents::PASSENGERS)
+                .unwrap_or_default()
+            {
+                let Some(passenger) = self.entities.get_mut(&uuid) else {
+                    continue;
+                };
+                if passenger.get(EntityComponents::POSITION).ok() != Some(seat) {
+                    passenger.set(EntityComponents::POSITION, seat);
+                    if passenger
+                        .get(EntityComponents::PLAYER_CONTROLLED)
+                        .unwrap_or(false)
+                    {
+                        players.push((uuid, seat));
+                    }
+                }
+                vehicles.push(uuid);
+            }
+        }
+
+        if !players.is_empty() {
+            let server = self.server()?;
+            Runtime::spawn_task(async move {
+                for (uuid, seat) in players {
+                    if let Ok(player) = server.player(uuid) {
+                        player.set(PlayerComponents::POSITION, seat)?;
+                    }
+                }
+                Ok(())
+            });
+        }
+        Ok(())
+    }
+
+    /// Detaches an entity that was just removed from its vehicle and passengers. Passengers are
+    /// left where the vehicle was and get an `EntityDismountEvent`. Clients forget about
+    /// passengers of removed entities on their own, so no packets are needed.
+    pub(crate) fn detach_removed_entity(&mut self, uuid: Uuid, removed: &EntityData) {
+        if let Ok(Some(vehicle)) = removed.get(EntityComponents::VEHICLE)
+            && let Some(vehicle) = self.entities.get_mut(&vehicle)
+        {
+            let mut passengers = vehicle
+                .get(EntityComponents::PASSENGERS)
+                .unwrap_or_default();
+            passengers.retain(|passenger| *passenger != uuid);
+            vehicle.set(EntityComponents::PASSENGERS, passengers);
+        }
+
+        let passengers = removed
+            .get(EntityComponents::PASSENGERS)
+            .unwrap_or_default();
+        if passengers.is_empty() {
+            return;
+        }
+        let position = removed.bounding_box().map(|x| top_of(&x));
+        for passenger in &passengers {
+            if let Some(passenger) = self.entities.get_mut(passenger) {
+                passenger.set(EntityComponents::VEHICLE, None);
+                if let Some(position) = position
+                    && !passenger
+                        .get(EntityComponents::PLAYER_CONTROLLED)
+                        .unwrap_or(false)
+                {
+                    passenger.set(EntityComponents::POSITION, position);
+                }
+            }
+        }
+
+        let dimension = self.as_actor();
+        Runtime::spawn_task(async move {
+            let vehicle = dimension.get_entity(uuid);
+            for passenger in passengers {
+                let passenger = dimension.get_entity(passenger);
+                if let Some(position) = position
+                    && let Some(player) = passenger.as_player()
+                {
+                    player.teleport(position)?;
+                }
+                dimension.server()?.spawn_event(EntityDismountEvent {
+                    entity: passenger,
+                    vehicle: vehicle.clone(),
+                })?;
+            }
+            Ok(())
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::{HashMap, HashSet},
+        time::Instant,
+    };
+
+    use voxidian_protocol::value::Uuid;
+    use wyvern_components::DataComponentMap;
+
+    use super::{EntityComponents, EntityData, riding_lists};
+
+    fn entity(id: i32, vehicle: Option<Uuid>, passengers: Vec<Uuid>) -> EntityData {
+        let components = DataComponentMap::new()
+            .with(EntityComponents::ENTITY_ID, id)
+            .with(EntityComponents::VEHICLE, vehicle)
+            .with(EntityComponents::PASSENGERS, passengers);
+        EntityData {
+            last_components: components.clone(),
+            components,
+            spawned_at: Instant::now(),
+        }
+    }
+
+    #[test]
+    fn newly_tracked_entities_are_sent_with_their_riders() {
+        let (boat, rider, bystander) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let entities = HashMap::from([
+            (boat, entity(1, None, vec![rider])),
+            (rider, entity(2, Some(boat), Vec::new())),
+            (bystander, entity(3, None, Vec::new())),
+        ]);
+
+        // Starting to track either the vehicle or its passenger sends the vehicle's passengers.
+        for added in [boat, rider] {
+            assert_eq!(
+                riding_lists(&entities, &HashSet::from([added])),
+                vec![(1, vec![2])]
+            );
+        }
+        assert_eq!(
+            riding_lists(&entities, &HashSet::from([boat, rider])),
+            vec![(1, vec![2])]
+        );
+        assert!(riding_lists(&entities, &HashSet::from([bystander])).is_empty());
+    }
+}
@@ -0,0 +1,451 @@
+use std::time::Instant;
+
+use voxidian_protocol::{
+    packet::s2c::play::{
+        AddEntityS2CPlayPacket, EntityEquipmentPart, EquipmentSlot, SetEntityDataS2CPlayPacket,
+        SetEquipmentS2CPlayPacket, UpdateAttributesS2CPlayPacket,
+    },
+    value::{Angle, EntityType as PtcEntityType, Uuid, VarInt},
+};
+use wyvern_components::{
+    ComponentElement, DataComponentMap, DataComponentPatch, DataComponentType,
+};
+use wyvern_values::{DVec3, Id, Vec2};
+
+use crate::{
+    actors::{ActorError, ActorResult},
+    dimension::DimensionData,
+    item::ItemStack,
+    player::Player,
+    server::Server,
+};
+
+use super::{DespawnRule, Entity, EntityComponents, EntityData, EntitySize, spawn_metadata};
+
+/// Describes an entity to spawn with `Dimension::spawn_entities`.
+#[derive(Debug, Clone)]
+pub struct EntitySpawn {
+    pub entity_type: Id,
+    pub position: DVec3,
+    pub direction: Vec2,
+    pub velocity: DVec3,
+    /// Extra components the entity is spawned with, such as its custom name. These override
+    /// the components every entity starts with.
+    pub components: DataComponentMap,
+}
+
+impl EntitySpawn {
+    pub fn new(entity_type: Id) -> EntitySpawn {
+        EntitySpawn {
+            entity_type,
+            position: DVec3::ZERO,
+            direction: Vec2::ZERO,
+            velocity: DVec3::ZERO,
+            components: DataComponentMap::new(),
+        }
+    }
+
+    pub fn position(mut self, position: DVec3) -> Self {
+        self.position = position;
+        self
+    }
+
+    pub fn direction(mut self, direction: Vec2) -> Self {
+        self.direction = direction;
+        self
+    }
+
+    pub fn velocity(mut self, velocity: DVec3) -> Self {
+        self.velocity = velocity;
+        self
+    }
+
+    pub fn with<T: 'static + ComponentElement>(
+        mut self,
+        component: DataComponentType<T>,
+        value: T,
+    ) -> Self {
+        self.components.set(component, value);
+        self
+    }
+
+    /// Gives the entity an item to hold or wear, such as `EntityComponents::MAINHAND_ITEM`.
+    /// It is sent along with the entity, so observers never see it without the item.
+    pub fn equipment(self, slot: DataComponentType<ItemStack>, item: ItemStack) -> Self {
+        self.with(slot, item)
+    }
+}
+
+/// The components holding an entity's equipment, and the slots they are shown in.
+const EQUIPMENT_SLOTS: [(DataComponentType<ItemStack>, EquipmentSlot); 7] = [
+    (EntityComponents::MAINHAND_ITEM, EquipmentSlot::Mainhand),
+    (EntityComponents::OFFHAND_ITEM, EquipmentSlot::Offhand),
+    (EntityComponents::BODY_ITEM, EquipmentSlot::Body),
+    (EntityComponents::HELMET_ITEM, EquipmentSlot::Helmet),
+    (EntityComponents::CHESTPLATE_ITEM, EquipmentSlot::Chestplate),
+    (EntityComponents::LEGGINGS_ITEM, EquipmentSlot::Leggings),
+    (EntityComponents::BOOTS_ITEM, EquipmentSlot::Boots),
+];
+
+/// The packets that show a new entity to a client, with everything it was spawned with.
+pub(crate) struct SpawnPackets {
+    add: AddEntityS2CPlayPacket,
+    data: SetEntityDataS2CPlayPacket,
+    equipment: Option<SetEquipmentS2CPlayPacket>,
+    attributes: Option<UpdateAttributesS2CPlayPacket>,
+}
+
+impl SpawnPackets {
+    /// Sends the entity to every player given, skipping players that have disconnected.
+    pub(crate) fn send(self, players: &[Player]) -> ActorResult<()> {
+        Player::broadcast_packet(players, self.add)?;
+        Player::broadcast_packet(players, self.data)?;
+        if let Some(equipment) = self.equipment {
+            Player::broadcast_packet(players, equipment)?;
+        }
+        if let Some(attributes) = self.attributes {
+            Player::broadcast_packet(players, attributes)?;
+        }
+        Ok(())
+    }
+}
+
+/// New entities to send to players, each with the players that are tracking it.
+pub(crate) struct SpawnBatch {
+    entities: Vec<(SpawnPackets, Vec<Uuid>)>,
+}
+
+impl SpawnBatch {
+    /// Sends every entity to the players tracking it, encoding each packet once.
+    pub(crate) fn send(self, server: &Server) -> ActorResult<()> {
+        for (packets, observers) in self.entities {
+            let players = observers
+                .into_iter()
+                .filter_map(|uuid| server.player(uuid).ok())
+                .collect::<Vec<_>>();
+            packets.send(&players)?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the packets that add an entity with the given components to a client, including
+/// its metadata, equipment and attributes, so none of them have to be sent afterwards. Fails with
+/// `ActorError::BadRequest` if clients don't know the entity's type.
+pub(crate) fn add_entity_packets(components: &DataComponentMap) -> ActorResult<SpawnPackets> {
+    let id = components.get(EntityComponents::ENTITY_ID)?;
+    let entity_type = components.get(EntityComponents::ENTITY_TYPE)?;
+    let position = components.get(EntityComponents::POSITION)?;
+    let direction = components.get(EntityComponents::DIRECTION)?;
+
+    let parts = EQUIPMENT_SLOTS
+        .into_iter()
+        .filter_map(|(component, slot)| {
+            components
+                .get(component)
+                .ok()
+                .map(|item| EntityEquipmentPart {
+                    slot,
+                    item: item.into(),
+                })
+        })
+        .collect::<Vec<_>>();
+
+    Ok(SpawnPackets {
+        add: AddEntityS2CPlayPacket {
+            id: id.into(),
+            uuid: components.get(EntityComponents::UUID)?,
+            kind: PtcEntityType::vanilla_registry()
+                .get_entry(&entity_type.into())
+                .ok_or(ActorError::BadRequest)?,
+            x: position[0],
+            y: position[1],
+            z: position[2],
+            pitch: Angle::of_deg(direction[1]),
+            yaw: Angle::of_deg(direction[0]),
+            head_yaw: Angle::of_deg(direction[0]),
+            data: VarInt::from(0),
+            vel_x: 0,
+            vel_y: 0,
+            vel_z: 0,
+        },
+        data: SetEntityDataS2CPlayPacket {
+            entity: id.into(),
+            data: spawn_metadata(components),
+        },
+        equipment: (!parts.is_empty()).then(|| SetEquipmentS2CPlayPacket {
+            entity_id: id.into(),
+            parts,
+        }),
+        attributes: components
+            .get(EntityComponents::ATTRIBUTES)
+            .ok()
+            .map(|attributes| attributes.into_packet(id)),
+    })
+}
+
+/// Returns the equipment of an entity that changed in `patch`, to send to clients that already
+/// have it. Emptied slots are sent as air.
+pub(crate) fn changed_equipment(
+    components: &DataComponentMap,
+    patch: &DataComponentPatch,
+) -> Vec<EntityEquipmentPart> {
+    EQUIPMENT_SLOTS
+        .into_iter()
+        .filter(|(component, _)| {
+            patch.added_fields().contains_type(component)
+                || patch.removed_fields().contains(component.name())
+        })
+        .map(|(component, slot)| EntityEquipmentPart {
+            slot,
+            item: components
+                .get(component)
+                .unwrap_or_else(|_| ItemStack::air())
+                .into(),
+        })
+        .collect()
+}
+
+impl DimensionData {
+    /// Adds an entity for every description, allocating their ids with one request. Returns
+    /// handles to them in the same order, along with the batch that shows them to players.
+    ///
+    /// Every entity's packets are built before any of them is added, so a description clients
+    /// can't be sent fails the whole batch instead of spawning part of it.
+    pub(crate) fn prepare_spawns(
+        &mut self,
+        spawns: Vec<EntitySpawn>,
+    ) -> ActorResult<(Vec<Entity>, SpawnBatch)> {
+        let ids = self.server()?.new_entity_ids(spawns.len())?;
+        let mut prepared = Vec::with_capacity(spawns.len());
+        for (spawn, id) in spawns.into_iter().zip(ids) {
+            let uuid = self.unused_entity_uuid();
+            let components = self.spawn_components(id, uuid, spawn)?;
+            let packets = add_entity_packets(&components)?;
+            prepared.push((uuid, components, packets));
+        }
+
+        let mut entities = Vec::with_capacity(prepared.len());
+        let mut batch = SpawnBatch {
+            entities: Vec::with_capacity(prepared.len()),
+        };
+        for (uuid, components, packets) in prepared {
+            let observers = self.track_new_entity(uuid, &components)?;
+            batch.entities.push((packets, observers));
+            // Players are sent everything the entity spawns with, so none of it needs to be
+            // sent again as a change on the next tick.
+            self.entities.insert(
+                uuid,
+                EntityData {
+                    last_components: components.clone(),
+                    components,
+                    spawned_at: Instant::now(),
+                },
+            );
+            entities.push(Entity {
+                dimension: self.as_actor(),
+                uuid,
+            });
+        }
+        Ok((entities, batch))
+    }
+
+    /// Returns a UUID no entity in this dimension has.
+    pub(crate) fn unused_entity_uuid(&self) -> Uuid {
+        let mut uuid = Uuid::new_v4();
+        while self.entities.contains_key(&uuid) {
+            uuid = Uuid::new_v4();
+        }
+        uuid
+    }
+
+    /// Returns the components of a new, non-player entity described by `spawn`.
+    pub(crate) fn spawn_components(
+        &mut self,
+        entity_id: i32,
+        uuid: Uuid,
+        mut spawn: EntitySpawn,
+    ) -> ActorResult<DataComponentMap> {
+        let mut components = DataComponentMap::new();
+        components.set(EntityComponents::ENTITY_ID, entity_id);
+        components.set(EntityComponents::UUID, uuid);
+        components.set(EntityComponents::ENTITY_TYPE, spawn.entity_type.clone());
+        components.set(
+            EntityComponents::BOUNDING_BOX,
+            EntitySize::of(&spawn.entity_type),
+        );
+        components.set(EntityComponents::POSITION, spawn.position);
+        components.set(EntityComponents::DIRECTION, spawn.direction);
+        components.set(EntityComponents::VELOCITY, spawn.velocity);
+        components.set(EntityComponents::PLAYER_CONTROLLED, false);
+        let despawn_rule = self.despawn_rule(spawn.entity_type)?;
+        if despawn_rule != DespawnRule::never() {
+            components.set(EntityComponents::DESPAWN_RULE, despawn_rule);
+        }
+        components
+            .inner_mut()
+            .extend(spawn.components.inner_mut().drain());
+        Ok(components)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use voxidian_protocol::value::Uuid;
+    use wyvern_components::{DataComponentMap, DataComponentPatch};
+    use wyvern_values::{DVec3, Vec2, id};
+
+    use crate::{
+        actors::ActorError,
+        dimension::DimensionData,
+        entities::{AttributeContainer, EntityComponents, EntityData, tests::stub_actor},
+        item::ItemStack,
+        player::{Player, PlayerMessage},
+        server::{Server, ServerMessage},
+    };
+
+    use super::{EntitySpawn, SpawnPackets, add_entity_packets, changed_equipment};
+
+    fn zombie() -> DataComponentMap {
+        let mut components = DataComponentMap::new();
+        components.set(EntityComponents::ENTITY_ID, 12);
+        components.set(EntityComponents::UUID, Uuid::new_v4());
+        components.set(EntityComponents::ENTITY_TYPE, id![minecraft:zombie]);
+        components.set(EntityComponents::POSITION, DVec3::new(0.0, 64.0, 0.0));
+        components.set(EntityComponents::DIRECTION, Vec2::ZERO);
+        components
+    }
+
+    /// Returns how many packets `SpawnPackets::send` sends to each player.
+    fn packet_count(packets: &SpawnPackets) -> usize {
+        2 + packets.equipment.is_some() as usize + packets.attributes.is_some() as usize
+    }
+
+    #[test]
+    fn bare_entities_spawn_with_two_packets() {
+        let packets = add_entity_packets(&zombie()).unwrap();
+        assert_eq!(packet_count(&packets), 2);
+    }
+
+    #[test]
+    fn equipment_and_attributes_are_sent_with_the_spawn() {
+        let mut components = zombie();
+        components.set(
+            EntityComponents::MAINHAND_ITEM,
+            ItemStack::new(id![minecraft:iron_sword]),
+        );
+        components.set(
+            EntityComponents::HELMET_ITEM,
+            ItemStack::new(id![minecraft:iron_helmet]),
+        );
+        components.set(EntityComponents::ATTRIBUTES, AttributeContainer::new());
+
+        let packets = add_entity_packets(&components).unwrap();
+        assert_eq!(packet_count(&packets), 4);
+        assert_eq!(packets.equipment.map(|x| x.parts.len()), Some(2));
+    }
+
+    #[test]
+    fn equipment_is_only_resent_when_it_changes() {
+        let mut components = zombie();
+        components.set(
+            EntityComponents::MAINHAND_ITEM,
+            ItemStack::new(id![minecraft:iron_sword]),
+        );
+        assert!(add_entity_packets(&components).unwrap().equipment.is_some());
+
+        let next_tick = DataComponentPatch::from_maps(&components, &components);
+        assert!(changed_equipment(&components, &next_tick).is_empty());
+
+        let mut armored = components.clone();
+        armored.set(
+            EntityComponents::HELMET_ITEM,
+            ItemStack::new(id![minecraft:iron_helmet]),
+        );
+        let patch = DataComponentPatch::from_maps(&components, &armored);
+        assert_eq!(changed_equipment(&armored, &patch).len(), 1);
+
+        let mut unarmed = armored.clone();
+        unarmed
+            .inner_mut()
+            .remove(EntityComponents::MAINHAND_ITEM.name());
+        let patch = DataComponentPatch::from_maps(&armored, &unarmed);
+        assert_eq!(changed_equipment(&unarmed, &patch).len(), 1);
+    }
+
+    #[test]
+    fn batched_spawns_make_one_id_request_and_send_two_packets_per_entity() {
+        let observer = Uuid::new_v4();
+        let (server_tx, server_rx) = flume::unbounded();
+        let (player_tx, player_rx) = flume::unbounded();
+        let (requests_tx, requests_rx) = flume::unbounded();
+        let (packets_tx, packets_rx) = flume::unbounded();
+        let server = Server {
+            sender: server_tx.downgrade(),
+        };
+        let player = Player {
+            sender: player_tx.downgrade(),
+        };
+
+        stub_actor(server_rx, move |message| match message {
+            ServerMessage::NewEntityIds(count, tx) => {
+                let _ = requests_tx.send(count);
+                let _ = tx.send(Ok(100..100 + count as i32));
+            }
+            ServerMessage::GetPlayerByUuid(uuid, tx) => {
+                if uuid == observer {
+                    let _ = tx.send(Ok(player.clone()));
+                }
+            }
+            _ => {}
+        });
+        stub_actor(player_rx, move |message| {
+            if let PlayerMessage::SendPacketBuf(_, tx) = message {
+                let _ = packets_tx.send(());
+                let _ = tx.send(Ok(()));
+            }
+        });
+
+        let mut dimension = DimensionData::new(
+            id![minecraft:overworld],
+            server.clone(),
+            id![minecraft:overworld],
+        );
+        let components = DataComponentMap::new()
+            .with(EntityComponents::PLAYER_CONTROLLED, true)
+            .with(EntityComponents::POSITION, DVec3::new(0.0, 64.0, 0.0));
+        dimension.entities.insert(
+            observer,
+            EntityData {
+                last_components: components.clone(),
+                components,
+                spawned_at: Instant::now(),
+            },
+        );
+
+        let spawns = (0..10)
+            .map(|x| {
+                EntitySpawn::new(id![minecraft:zombie]).position(DVec3::new(x as f64, 64.0, 0.0))
+            })
+            .collect();
+        let (entities, batch) = dimension.prepare_spawns(spawns).unwrap();
+        batch.send(&server).unwrap();
+
+        assert_eq!(entities.len(), 10);
+        assert_eq!(requests_rx.try_iter().collect::<Vec<_>>(), vec![10]);
+        assert_eq!(packets_rx.try_iter().count(), 20);
+
+        let unknown = vec![
+            EntitySpawn::new(id![minecraft:zombie]),
+            EntitySpawn::new(id![minecraft:not_an_entity]),
+        ];
+        assert!(matches!(
+            dimension.prepare_spawns(unknown),
+            Err(ActorError::BadRequest)
+        ));
+        assert_eq!(dimension.entities.len(), 11);
+        drop((server_tx, player_tx));
+    }
+}
@@ -0,0 +1,69 @@
+use wyvern_values::{DVec3, IVec3};
+
+use crate::actors::ActorResult;
+
+use super::{Attributes, Entity, EntityComponents, default_attribute_value};
+
+impl Entity {
+    /// Returns how tall a block this entity can walk up onto without jumping, from its
+    /// `minecraft:step_height` attribute. Defaults to 0.6, which is enough for slabs but not
+    /// full blocks.
+    pub fn step_height(&self) -> ActorResult<f64> {
+        Ok(self
+            .get(EntityComponents::ATTRIBUTES)
+            .ok()
+            .and_then(|attributes| attributes.get(Attributes::STEP_HEIGHT).ok())
+            .unwrap_or_else(|| default_attribute_value(Attributes::STEP_HEIGHT.name())))
+    }
+}
+
+/// Returns where an entity whose move to `to` was blocked by a solid block ends up if it steps
+/// up onto that block, or `None` if the block is taller than `step_height` or there isn't room
+/// above it for an entity `height` blocks tall.
+pub(crate) fn step_up(
+    to: DVec3,
+    step_height: f64,
+    height: f64,
+    mut is_solid: impl FnMut(IVec3) -> ActorResult<bool>,
+) -> ActorResult<Option<DVec3>> {
+    let block = to.floor().as_ivec3();
+    let top = block[1] as f64 + 1.0;
+    if top - to[1] > step_height {
+        return Ok(None);
+    }
+    let head = (top + height).ceil() as i32;
+    for y in block[1] + 1..head {
+        if is_solid(IVec3::new(block[0], y, block[2]))? {
+            return Ok(None);
+        }
+    }
+    Ok(Some(DVec3::new(to[0], top, to[2])))
+}
+
+#[cfg(test)]
+mod tests {
+    use wyvern_values::{DVec3, IVec3};
+
+    use crate::actors::ActorResult;
+
+    use super::step_up;
+
+    #[test]
+    fn entities_step_over_ledges_up_to_their_step_height() {
+        // A one block tall ledge at x = 1, on a floor whose top layer is y = 63.
+        let is_solid = |block: IVec3| -> ActorResult<bool> {
+            Ok(block[1] < 64 || (block[0] >= 1 && block[1] == 64))
+        };
+        let blocked = DVec3::new(1.2, 64.0, 0.5);
+
+        assert_eq!(step_up(blocked, 0.6, 1.8, is_solid), Ok(None));
+        assert_eq!(
+            step_up(blocked, 1.0, 1.8, is_solid),
+            Ok(Some(DVec3::new(1.2, 65.0, 0.5)))
+        );
+
+        let ceiling =
+            |block: IVec3| -> ActorResult<bool> { Ok(is_solid(block)? || block[1] == 66) };
+        assert_eq!(step_up(blocked, 1.0, 1.8, ceiling), Ok(None));
+    }
+}
@@ -0,0 +1,52 @@
+use std::sync::{Arc, Mutex};
+
+use crate::{actors::ActorResult, events::EntityTargetEvent, runtime::Runtime};
+
+use super::{Entity, EntityComponents};
+
+impl Entity {
+    /// Returns the entity this entity is targeting, if any. Targets are always in the same
+    /// dimension as the entity targeting them.
+    pub fn target(&self) -> ActorResult<Option<Entity>> {
+        Ok(self
+            .get(EntityComponents::TARGET)
+            .ok()
+            .flatten()
+            .map(|uuid| self.dimension.get_entity(uuid)))
+    }
+
+    /// Changes the target of this entity, firing an `EntityTargetEvent` if it would change.
+    /// The target is changed once every handler has finished, unless one of them cancelled it,
+    /// and handlers can pick a different target instead.
+    pub fn set_target(&self, target: Option<Entity>) -> ActorResult<()> {
+        let old_target = self.target()?;
+        if old_target.as_ref().map(Entity::uuid) == target.as_ref().map(Entity::uuid) {
+            return Ok(());
+        }
+
+        let server = self.dimension.server()?;
+        let entity = self.clone();
+        Runtime::spawn_server_task(Some(server.clone()), async move {
+            let new_target = Arc::new(Mutex::new(target));
+            let cancelled = Arc::new(Mutex::new(false));
+            server
+                .fire_event_async(EntityTargetEvent {
+                    entity: entity.clone(),
+                    old_target,
+                    new_target: new_target.clone(),
+                    cancelled: cancelled.clone(),
+                })
+                .await?;
+            if *cancelled.lock().unwrap() {
+                return Ok(());
+            }
+            let target = new_target.lock().unwrap().take();
+            entity.set(
+                EntityComponents::TARGET,
+                target.as_ref().map(|target| *target.uuid()),
+            )?;
+            Ok(())
+        });
+        Ok(())
+    }
+}
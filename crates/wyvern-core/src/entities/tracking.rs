@@ -0,0 +1,429 @@
+use std::collections::HashSet;
+
+use voxidian_protocol::{
+    packet::s2c::play::{
+        AddEntityS2CPlayPacket, RemoveEntitiesS2CPlayPacket, SetEntityDataS2CPlayPacket,
+    },
+    value::{Angle, EntityType as PtcEntityType, Uuid, VarInt},
+};
+
+use wyvern_components::DataComponentMap;
+use wyvern_values::{DVec3, Id};
+
+use crate::{
+    actors::{ActorError, ActorResult},
+    dimension::DimensionData,
+    player::Player,
+    runtime::Runtime,
+};
+
+use super::{
+    Entity, EntityComponents, add_entity_packets,
+    passengers::{passengers_packet, riding_lists},
+    update::{head_angle, rotation_packets},
+};
+
+/// Returns how far away horizontally, in blocks, a player can be from an entity of the given
+/// type and still be sent it, matching vanilla. Can be changed per dimension with
+/// `Dimension::set_tracking_range`.
+pub fn default_tracking_range(entity_type: &Id) -> f64 {
+    let chunks = match entity_type.path() {
+        "player" => 32,
+        "marker" => 0,
+        "item" | "experience_orb" => 6,
+        "arrow" | "spectral_arrow" | "trident" | "snowball" | "egg" | "ender_pearl" | "potion"
+        | "experience_bottle" | "eye_of_ender" | "firework_rocket" | "fireball"
+        | "small_fireball" | "dragon_fireball" | "wither_skull" | "shulker_bullet"
+        | "llama_spit" | "wind_charge" | "fishing_bobber" => 4,
+        "minecart"
+        | "chest_minecart"
+        | "furnace_minecart"
+        | "hopper_minecart"
+        | "tnt_minecart"
+        | "spawner_minecart"
+        | "command_block_minecart"
+        | "boat"
+        | "chest_boat" => 8,
+        "zombie" | "husk" | "drowned" | "zombie_villager" | "skeleton" | "stray" | "creeper"
+        | "spider" | "cave_spider" | "enderman" | "witch" | "slime" | "magma_cube" | "blaze"
+        | "piglin" | "zombified_piglin" | "silverfish" | "phantom" => 8,
+        _ => 10,
+    };
+    chunks as f64 * 16.0
+}
+
+/// Returns whether an entity at `position` is close enough horizontally to an observer at
+/// `observer` to be sent to them.
+fn in_tracking_range(observer: DVec3, position: DVec3, range: f64) -> bool {
+    let dx = position[0] - observer[0];
+    let dz = position[2] - observer[2];
+    dx * dx + dz * dz <= range * range
+}
+
+/// Returns the entities an observer should have on their client, given each entity's UUID,
+/// position and tracking range.
+fn entities_in_range(
+    observer: DVec3,
+    entities: impl IntoIterator<Item = (Uuid, DVec3, f64)>,
+) -> HashSet<Uuid> {
+    entities
+        .into_iter()
+        .filter(|(_, position, range)| in_tracking_range(observer, *position, *range))
+        .map(|(uuid, _, _)| uuid)
+        .collect()
+}
+
+impl Entity {
+    /// Removes this entity for every player that has it on their client, then adds it back
+    /// under the same id with its current position and metadata. Used when a player respawns so
+    /// observers don't keep seeing their dead body.
+    pub(crate) fn respawn_for_observers(&self) -> ActorResult<()> {
+        let id = self.get(EntityComponents::ENTITY_ID)?;
+        let entity_type = self.get(EntityComponents::ENTITY_TYPE)?;
+        let position = self.get(EntityComponents::POSITION)?;
+        let direction = self.get(EntityComponents::DIRECTION)?;
+        let head_yaw = self.get(EntityComponents::HEAD_YAW).unwrap_or(direction[0]);
+        let metadata = self.generate_metadata()?;
+
+        let server = self.dimension.server()?;
+        let observers = self
+            .dimension
+            .entity_trackers(self.uuid)?
+            .into_iter()
+            .filter_map(|uuid| server.player(uuid).ok())
+            .collect::<Vec<_>>();
+
+        // Removing an id the client doesn't know about is harmless, so observers end up with
+        // exactly one copy whether or not they saw the death.
+        Player::broadcast_packet(
+            &observers,
+            RemoveEntitiesS2CPlayPacket {
+                entities: vec![VarInt::new(id)].into(),
+            },
+        )?;
+        Player::broadcast_packet(
+            &observers,
+            AddEntityS2CPlayPacket {
+                id: id.into(),
+                uuid: self.uuid,
+                kind: PtcEntityType::vanilla_registry()
+                    .get_entry(&entity_type.clone().into())
+                    .ok_or(ActorError::BadRequest)?,
+                x: position[0],
+                y: position[1],
+                z: position[2],
+                pitch: Angle::of_deg(direction[1]),
+                yaw: Angle::of_deg(direction[0]),
+                head_yaw: Angle::of_deg(head_angle(head_yaw)),
+                data: VarInt::from(0),
+                vel_x: 0,
+                vel_y: 0,
+                vel_z: 0,
+            },
+        )?;
+        Player::broadcast_packet(
+            &observers,
+            SetEntityDataS2CPlayPacket {
+                entity: id.into(),
+                data: metadata,
+            },
+        )?;
+        let (_, head) = rotation_packets(id, position, direction, head_yaw, DVec3::ZERO);
+        Player::broadcast_packet(&observers, head)?;
+        Ok(())
+    }
+}
+
+impl DimensionData {
+    /// Returns how far away horizontally a player can be from entities of the given type and
+    /// still be sent them.
+    pub(crate) fn tracking_range_of(&self, entity_type: &Id) -> f64 {
+        self.tracking_ranges
+            .get(entity_type)
+            .copied()
+            .unwrap_or_else(|| default_tracking_range(entity_type))
+    }
+
+    /// Returns the UUID and position of every player in this dimension.
+    fn observers(&self) -> Vec<(Uuid, DVec3)> {
+        self.entities
+            .iter()
+            .filter(|(_, entity)| {
+                entity
+                    .components
+                    .get(EntityComponents::PLAYER_CONTROLLED)
+                    .unwrap_or(false)
+            })
+            .filter_map(|(uuid, entity)| {
+                Some((
+                    *uuid,
+                    entity.components.get(EntityComponents::POSITION).ok()?,
+                ))
+            })
+            .collect()
+    }
+
+    /// Returns the players in range of a new entity with the given components, recording that
+    /// they are about to be sent it.
+    pub(crate) fn track_new_entity(
+        &mut self,
+        uuid: Uuid,
+        components: &DataComponentMap,
+    ) -> ActorResult<Vec<Uuid>> {
+        let position = components.get(EntityComponents::POSITION)?;
+        let range = self.tracking_range_of(&components.get(EntityComponents::ENTITY_TYPE)?);
+        let observers = self
+            .observers()
+            .into_iter()
+            .filter(|(observer, _)| *observer != uuid)
+            .filter(|(_, observer)| in_tracking_range(*observer, position, range))
+            .map(|(observer, _)| observer)
+            .collect::<Vec<_>>();
+        for observer in &observers {
+            self.tracked_entities
+                .entry(*observer)
+                .or_default()
+                .insert(uuid);
+        }
+        Ok(observers)
+    }
+
+    /// Returns the players whose clients have the given entity.
+    pub(crate) fn trackers_of(&self, uuid: &Uuid) -> Vec<Uuid> {
+        self.tracked_entities
+            .iter()
+            .filter(|(_, tracked)| tracked.contains(uuid))
+            .map(|(player, _)| *player)
+            .collect()
+    }
+
+    /// Returns the players who should be sent changes to an entity: those whose clients have it,
+    /// along with the player it belongs to, if any.
+    pub(crate) fn viewers_of(&self, uuid: &Uuid) -> Vec<Uuid> {
+        let mut viewers = self.trackers_of(uuid);
+        if self.entities.get(uuid).is_some_and(|entity| {
+            entity
+                .components
+                .get(EntityComponents::PLAYER_CONTROLLED)
+                .unwrap_or(false)
+        }) {
+            viewers.push(*uuid);
+        }
+        viewers
+    }
+
+    /// Sends every player the entities that came into their tracking range since the last
+    /// tick, and removes the ones that left it. Entities that were removed from the dimension
+    /// have already been removed from clients.
+    pub(crate) fn update_entity_tracking(&mut self) -> ActorResult<()> {
+        let server = self.server()?;
+        let observers = self.observers();
+        self.tracked_entities
+            .retain(|player, _| observers.iter().any(|(observer, _)| observer == player));
+
+        for (player, observer) in observers {
+            let in_range = entities_in_range(
+                observer,
+                self.entities
+                    .iter()
+                    .filter(|(uuid, _)| **uuid != player)
+                    .filter_map(|(uuid, entity)| {
+                        let position = entity.components.get(EntityComponents::POSITION).ok()?;
+                        let entity_type =
+                            entity.components.get(EntityComponents::ENTITY_TYPE).ok()?;
+                        Some((*uuid, position, self.tracking_range_of(&entity_type)))
+                    }),
+            );
+            let tracked = self.tracked_entities.entry(player).or_default();
+            tracked.retain(|uuid| self.entities.contains_key(uuid));
+
+            let removed = tracked
+                .difference(&in_range)
+                .filter_map(|uuid| {
+                    self.entities
+                        .get(uuid)?
+                        .components
+                        .get(EntityComponents::ENTITY_ID)
+                        .ok()
+                })
+                .map(VarInt::new)
+                .collect::<Vec<_>>();
+            let added = in_range
+                .difference(tracked)
+                .copied()
+                .collect::<HashSet<_>>();
+            let riding = riding_lists(&self.entities, &added);
+            let added = added
+                .iter()
+                .filter_map(|uuid| add_entity_packets(&self.entities.get(uuid)?.components).ok())
+                .collect::<Vec<_>>();
+            *tracked = in_range;
+
+            if removed.is_empty() && added.is_empty() {
+                continue;
+            }
+            let server = server.clone();
+            Runtime::spawn_task(async move {
+                let player = server.player(player)?;
+                if !removed.is_empty() {
+                    player.write_packet(RemoveEntitiesS2CPlayPacket {
+                        entities: removed.into(),
+                    })?;
+                }
+                let players = [player];
+                for packets in added {
+                    packets.send(&players)?;
+                }
+                // Passengers are sent once every entity they refer to has been added.
+                for (vehicle, passengers) in riding {
+                    Player::broadcast_packet(&players, passengers_packet(vehicle, passengers))?;
+                }
+                Ok(())
+            });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, sync::Arc};
+
+    use voxidian_protocol::{
+        packet::{
+            PacketBuf, PrefixedPacketEncode,
+            s2c::play::{AddEntityS2CPlayPacket, RemoveEntitiesS2CPlayPacket},
+        },
+        value::{Angle, EntityType as PtcEntityType, Uuid, VarInt},
+    };
+    use wyvern_components::DataComponentMap;
+    use wyvern_values::{DVec3, Vec2, id};
+
+    use crate::{
+        dimension::{Dimension, DimensionMessage},
+        entities::{Entity, EntityComponents, tests::stub_actor},
+        player::{Player, PlayerMessage},
+        server::{Server, ServerMessage},
+    };
+
+    use super::{default_tracking_range, entities_in_range};
+
+    /// Returns the id a packet is prefixed with when encoded.
+    fn packet_id(packet: impl PrefixedPacketEncode) -> u8 {
+        let mut buf = PacketBuf::new();
+        packet.encode_prefixed(&mut buf).unwrap();
+        buf.as_slice()[0]
+    }
+
+    #[test]
+    fn respawned_entity_appears_once() {
+        let uuid = Uuid::new_v4();
+        let observer = Uuid::new_v4();
+        let mut components = DataComponentMap::new();
+        components.set(EntityComponents::ENTITY_ID, 7);
+        components.set(EntityComponents::ENTITY_TYPE, id![minecraft:player]);
+        components.set(EntityComponents::POSITION, DVec3::new(0.0, 64.0, 0.0));
+        components.set(EntityComponents::DIRECTION, Vec2::ZERO);
+
+        let (server_tx, server_rx) = flume::unbounded();
+        let (dimension_tx, dimension_rx) = flume::unbounded();
+        let (player_tx, player_rx) = flume::unbounded();
+        let (packets_tx, packets_rx) = flume::unbounded();
+        let server = Server {
+            sender: server_tx.downgrade(),
+        };
+        let dimension = Dimension {
+            sender: dimension_tx.downgrade(),
+        };
+        let player = Player {
+            sender: player_tx.downgrade(),
+        };
+
+        stub_actor(server_rx, move |message| {
+            if let ServerMessage::GetPlayerByUuid(requested, tx) = message {
+                if requested == observer {
+                    let _ = tx.send(Ok(player.clone()));
+                }
+            }
+        });
+        stub_actor(dimension_rx, move |message| match message {
+            DimensionMessage::GetEntityComponent(_, component, tx) => {
+                if let Some(value) = components.inner().get(&component) {
+                    let _ = tx.send(Ok(Arc::clone(value)));
+                }
+            }
+            DimensionMessage::GetEntityTrackers(_, tx) => {
+                let _ = tx.send(Ok(vec![observer]));
+            }
+            DimensionMessage::GetServer(tx) => {
+                let _ = tx.send(Ok(server.clone()));
+            }
+            _ => {}
+        });
+        stub_actor(player_rx, move |message| {
+            if let PlayerMessage::SendPacketBuf(buf, tx) = message {
+                let _ = packets_tx.send(buf.as_slice()[0]);
+                let _ = tx.send(Ok(()));
+            }
+        });
+
+        let entity = Entity { dimension, uuid };
+        entity.respawn_for_observers().unwrap();
+        drop((server_tx, dimension_tx, player_tx));
+        let sent = packets_rx.iter().collect::<Vec<_>>();
+
+        let removed = packet_id(RemoveEntitiesS2CPlayPacket {
+            entities: vec![VarInt::new(7)].into(),
+        });
+        let added = packet_id(AddEntityS2CPlayPacket {
+            id: 7.into(),
+            uuid,
+            kind: PtcEntityType::vanilla_registry()
+                .get_entry(&id![minecraft:player].into())
+                .unwrap(),
+            x: 0.0,
+            y: 0.0,
+            z: 0.0,
+            pitch: Angle::of_deg(0.0),
+            yaw: Angle::of_deg(0.0),
+            head_yaw: Angle::of_deg(0.0),
+            data: VarInt::from(0),
+            vel_x: 0,
+            vel_y: 0,
+            vel_z: 0,
+        });
+        assert_eq!(sent.iter().filter(|id| **id == removed).count(), 1);
+        assert_eq!(sent.iter().filter(|id| **id == added).count(), 1);
+        let remove_at = sent.iter().position(|id| *id == removed);
+        let add_at = sent.iter().position(|id| *id == added);
+        assert!(remove_at < add_at);
+    }
+
+    #[test]
+    fn entities_are_only_tracked_within_their_range() {
+        let item = Uuid::new_v4();
+        let player = Uuid::new_v4();
+        let near_item = Uuid::new_v4();
+        let tracked = entities_in_range(
+            DVec3::new(0.0, 64.0, 0.0),
+            [
+                (
+                    item,
+                    DVec3::new(120.0, 64.0, 0.0),
+                    default_tracking_range(&id![minecraft:item]),
+                ),
+                (
+                    player,
+                    DVec3::new(120.0, 64.0, 120.0),
+                    default_tracking_range(&id![minecraft:player]),
+                ),
+                (
+                    near_item,
+                    DVec3::new(30.0, 0.0, -30.0),
+                    default_tracking_range(&id![minecraft:item]),
+                ),
+            ],
+        );
+        assert_eq!(tracked, HashSet::from([player, near_item]));
+    }
+}
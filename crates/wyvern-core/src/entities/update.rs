@@ -1,56 +1,117 @@
+use std::collections::HashMap;
+
 use voxidian_protocol::{
     packet::s2c::play::{
         EntityEquipmentPart, EntityPositionSyncS2CPlayPacket, EquipmentSlot,
-        RotateHeadS2CPlayPacket, SetEquipmentS2CPlayPacket,
+        RotateHeadS2CPlayPacket, SetEntityDataS2CPlayPacket, SetEquipmentS2CPlayPacket,
     },
-    value::Angle,
+    value::{Angle, EntityMetadata, Uuid},
 };
-use wyvern_components::DataComponentPatch;
+use wyvern_components::{DataComponentMap, DataComponentPatch};
 
 use crate::{
-    actors::ActorResult, blocks::Blocks, dimension::DimensionData, entities::Entity,
-    runtime::Runtime, server::Server,
+    actors::ActorResult,
+    blocks::Blocks,
+    dimension::DimensionData,
+    entities::{Entity, EntityData},
+    runtime::Runtime,
+    server::Server,
 };
-use wyvern_values::DVec3;
+use wyvern_values::{DVec3, IVec2, Vec2};
 
-use super::{Dimension, EntityComponents};
+use super::{
+    Dimension, EntityComponents, component_metadata, spawn::changed_equipment, step::step_up,
+};
 
 impl DimensionData {
     pub fn propogate_entity_packets(&mut self) -> ActorResult<()> {
-        let players = self.players()?.clone();
+        let send_velocity = self.entity_interpolation.send_velocity;
         for entity in &mut self.entities {
+            // Only players with the entity on their client are sent changes to it, along with
+            // the player it belongs to, if any.
+            let mut players = self
+                .tracked_entities
+                .iter()
+                .filter(|(_, tracked)| tracked.contains(entity.0))
+                .map(|(player, _)| *player)
+                .collect::<Vec<_>>();
+            if entity
+                .1
+                .components
+                .get(EntityComponents::PLAYER_CONTROLLED)
+                .unwrap_or(false)
+            {
+                players.push(*entity.0);
+            }
+
             let patch =
                 DataComponentPatch::from_maps(&entity.1.last_components, &entity.1.components);
 
             let id = entity.1.components.get(EntityComponents::ENTITY_ID)?;
-            if patch
-                .added_fields()
-                .contains_type(&EntityComponents::POSITION)
-                || patch
-                    .added_fields()
-                    .contains_type(&EntityComponents::DIRECTION)
-            {
+            if movement_changed(&patch) {
                 let pos = entity.1.components.get(EntityComponents::POSITION)?;
                 let dir = entity.1.components.get(EntityComponents::DIRECTION)?;
+                let head_yaw = head_yaw(&entity.1.components);
+                let velocity = match send_velocity {
+                    true => entity
+                        .1
+                        .components
+                        .get(EntityComponents::VELOCITY)
+                        .unwrap_or(DVec3::ZERO),
+                    false => DVec3::ZERO,
+                };
                 for player in &players {
                     let player = *player;
                     Runtime::spawn_task(async move {
                         let player = Server::get()?.player(player)?;
-                        player.write_packet(EntityPositionSyncS2CPlayPacket {
-                            entity_id: id.into(),
-                            x: pos[0],
-                            y: pos[1],
-                            z: pos[2],
-                            vx: 0.0,
-                            vy: 0.0,
-                            vz: 0.0,
-                            yaw: dir[0],
-                            pitch: dir[1],
-                            on_ground: true,
-                        })?;
-                        player.write_packet(RotateHeadS2CPlayPacket {
+                        let (sync, head) = rotation_packets(id, pos, dir, head_yaw, velocity);
+                        player.write_packet(sync)?;
+                        player.write_packet(head)?;
+                        Ok(())
+                    });
+                }
+            }
+
+            if let Ok(attributes) = patch.added_fields().get(EntityComponents::ATTRIBUTES) {
+                for player in &players {
+                    let player = *player;
+                    let packet = attributes.into_packet(id);
+                    Runtime::spawn_task(async move {
+                        Server::get()?.player(player)?.write_packet(packet)?;
+                        Ok(())
+                    });
+                }
+            }
+
+            // Players and the entity's own client already have its equipment from when it was
+            // added, so it's only sent again when it changes.
+            let equipment = changed_equipment(&entity.1.components, &patch);
+            if !equipment.is_empty() {
+                for player in players.iter().filter(|player| *player != entity.0) {
+                    let player = *player;
+                    let packet = SetEquipmentS2CPlayPacket {
+                        entity_id: id.into(),
+                        parts: equipment.clone(),
+                    };
+                    Runtime::spawn_task(async move {
+                        Server::get()?.player(player)?.write_packet(packet)?;
+                        Ok(())
+                    });
+                }
+            }
+
+            let mut metadata = EntityMetadata::new();
+            if component_metadata(&entity.1.components, &mut metadata, |ty| {
+                patch.added_fields().contains(ty) || patch.removed_fields().contains(ty)
+            }) {
+                for player in &players {
+                    let player = *player;
+                    let metadata = metadata.clone();
+                    Runtime::spawn_task(async move {
+                        let player = Server::get()?.player(player)?;
+                        player.write_packet(SetEntityDataS2CPlayPacket {
                             entity: id.into(),
-                            yaw: Angle::of_deg(dir[0].rem_euclid(360.0)),
+                            data: metadata,
                         })?;
                         Ok(())
                     });
@@ -63,14 +124,14 @@ impl DimensionData {
     }
 
     pub fn auto_apply_entity_properties(&mut self) -> ActorResult<()> {
-        for entity in &self.entities {
+        for uuid in simulated_entities(&self.entities, self.simulation_distance) {
             let entity = Entity {
                 dimension: {
                     Dimension {
                         sender: self.sender.downgrade(),
                     }
                 },
-                uuid: *entity.0,
+                uuid,
             };
             let dimension = {
                 Dimension {
@@ -80,7 +141,6 @@ impl DimensionData {
 
             Runtime::spawn_task(async move {
                 entity_position(&entity, &dimension)?;
-                entity_equipment(&entity)?;
                 Ok(())
             });
         }
@@ -88,7 +148,115 @@ impl DimensionData {
     }
 }
 
+/// Returns whether a change to an entity's components moves or turns it, including looking up
+/// or down, so observers need to be sent its new position and rotation.
+pub(crate) fn movement_changed(patch: &DataComponentPatch) -> bool {
+    let added = patch.added_fields();
+    added.contains_type(&EntityComponents::POSITION)
+        || added.contains_type(&EntityComponents::DIRECTION)
+        || added.contains_type(&EntityComponents::HEAD_YAW)
+        || patch
+            .removed_fields()
+            .contains(EntityComponents::HEAD_YAW.name())
+}
+
+/// Returns the yaw of an entity's head, which follows its body unless `HEAD_YAW` is set.
+pub(crate) fn head_yaw(components: &DataComponentMap) -> f32 {
+    components
+        .get(EntityComponents::HEAD_YAW)
+        .unwrap_or_else(|_| {
+            components
+                .get(EntityComponents::DIRECTION)
+                .map(|dir| dir[0])
+                .unwrap_or(0.0)
+        })
+}
+
+/// Builds the packets that move an entity and turn its body by `dir`, and its head to `head_yaw`.
+/// Clients keep the entity moving by `velocity` every tick until its next update.
+pub(crate) fn rotation_packets(
+    id: i32,
+    pos: DVec3,
+    dir: Vec2,
+    head_yaw: f32,
+    velocity: DVec3,
+) -> (EntityPositionSyncS2CPlayPacket, RotateHeadS2CPlayPacket) {
+    (
+        EntityPositionSyncS2CPlayPacket {
+            entity_id: id.into(),
+            x: pos[0],
+            y: pos[1],
+            z: pos[2],
+            vx: velocity[0],
+            vy: velocity[1],
+            vz: velocity[2],
+            yaw: dir[0],
+            pitch: dir[1],
+            on_ground: true,
+        },
+        RotateHeadS2CPlayPacket {
+            entity: id.into(),
+            yaw: Angle::of_deg(head_angle(head_yaw)),
+        },
+    )
+}
+
+/// Returns a head yaw wrapped to between 0 and 360 degrees, the range it is sent to clients in.
+pub(crate) fn head_angle(head_yaw: f32) -> f32 {
+    head_yaw.rem_euclid(360.0)
+}
+
+/// Returns the entities that should be ticked. Players always are, and with a simulation
+/// distance set, other entities are only ticked within that many chunks of a player.
+fn simulated_entities(
+    entities: &HashMap<Uuid, EntityData>,
+    simulation_distance: Option<u32>,
+) -> Vec<Uuid> {
+    let is_player = |entity: &EntityData| {
+        entity
+            .components
+            .get(EntityComponents::PLAYER_CONTROLLED)
+            .unwrap_or(false)
+    };
+    let player_chunks = entities
+        .values()
+        .filter(|x| is_player(x))
+        .filter_map(|x| x.components.get(EntityComponents::POSITION).ok())
+        .map(chunk_of)
+        .collect::<Vec<_>>();
+
+    entities
+        .iter()
+        .filter(|(_, entity)| {
+            let Some(distance) = simulation_distance else {
+                return true;
+            };
+            if is_player(entity) {
+                return true;
+            }
+            let chunk = chunk_of(
+                entity
+                    .components
+                    .get(EntityComponents::POSITION)
+                    .unwrap_or(DVec3::ZERO),
+            );
+            player_chunks
+                .iter()
+                .any(|x| (*x - chunk).abs().max_element() <= distance as i32)
+        })
+        .map(|(uuid, _)| *uuid)
+        .collect()
+}
+
+fn chunk_of(pos: DVec3) -> IVec2 {
+    IVec2::new(
+        (pos[0].floor() as i32).div_euclid(16),
+        (pos[2].floor() as i32).div_euclid(16),
+    )
+}
+
 pub fn entity_position(entity: &Entity, dimension: &Dimension) -> ActorResult<()> {
+    entity.steer_towards_followed()?;
     if let Ok(true) = entity.get(EntityComponents::PHYSICS_ENABLED) {
         if let Ok(mut velocity) = entity.get(EntityComponents::VELOCITY) {
             let mut pos = entity.get(EntityComponents::POSITION)?;
@@ -101,11 +269,23 @@ pub fn entity_position(entity: &Entity, dimension: &Dimension) -> ActorResult<()
                 if dimension.get_block(new_pos.floor().as_ivec3())?.name() == &Blocks::AIR {
                     pos = new_pos;
                     break;
-                } else {
-                    velocity = velocity.map(|x| x / 2.0);
                 }
+                if (velocity[0] != 0.0 || velocity[2] != 0.0)
+                    && let Some(stepped) = step_up(
+                        new_pos,
+                        entity.step_height()?,
+                        entity.size()?.height,
+                        |block| Ok(dimension.get_block(block)?.name() != &Blocks::AIR),
+                    )?
+                {
+                    pos = stepped;
+                    velocity = velocity.with_y(velocity[1].max(0.0));
+                    break;
+                }
+                velocity = velocity.map(|x| x / 2.0);
             }
-            velocity = velocity.map(|x| x * 0.9);
+            let drag = entity.drag()?;
+            velocity = velocity.map(|x| x * drag);
             entity.set(EntityComponents::POSITION, pos)?;
             entity.set(EntityComponents::VELOCITY, velocity)?;
         }
@@ -114,7 +294,10 @@ pub fn entity_position(entity: &Entity, dimension: &Dimension) -> ActorResult<()
         let vel = entity
             .get(EntityComponents::VELOCITY)
             .unwrap_or(DVec3::new(0.0, 0.0, 0.0));
-        entity.set(EntityComponents::VELOCITY, vel.with_y(vel[1] - 0.08))?;
+        entity.set(
+            EntityComponents::VELOCITY,
+            vel.with_y(vel[1] - entity.gravity()?),
+        )?;
     }
     Ok(())
 }
@@ -148,15 +331,107 @@ pub fn entity_equipment(entity: &Entity) -> ActorResult<()> {
     let eid = entity.get(EntityComponents::ENTITY_ID).unwrap_or(-1);
 
     if !parts.is_empty() {
-        for player in entity.dimension().players()? {
-            let player = Server::get()?.player(player)?;
-            if player.entity_id()? != eid {
-                player.write_packet(SetEquipmentS2CPlayPacket {
+        for player in entity.dimension().entity_trackers(*entity.uuid())? {
+            Server::get()?
+                .player(player)?
+                .write_packet(SetEquipmentS2CPlayPacket {
                     entity_id: eid.into(),
                     parts: parts.clone(),
                 })?;
-            }
         }
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, time::Instant};
+
+    use voxidian_protocol::value::Uuid;
+    use wyvern_components::{DataComponentMap, DataComponentPatch};
+    use wyvern_values::{DVec3, Vec2};
+
+    use super::{
+        EntityComponents, EntityData, head_angle, head_yaw, movement_changed, rotation_packets,
+        simulated_entities,
+    };
+
+    fn entity_at(position: DVec3, player: bool) -> EntityData {
+        let components = DataComponentMap::new()
+            .with(EntityComponents::POSITION, position)
+            .with(EntityComponents::PLAYER_CONTROLLED, player);
+        EntityData {
+            last_components: components.clone(),
+            components,
+            spawned_at: Instant::now(),
+        }
+    }
+
+    #[test]
+    fn only_entities_within_simulation_distance_are_ticked() {
+        let player = Uuid::new_v4();
+        let inside = Uuid::new_v4();
+        let outside = Uuid::new_v4();
+        let entities = HashMap::from([
+            (player, entity_at(DVec3::new(8.0, 64.0, 8.0), true)),
+            // Two chunks away, at the edge of a simulation distance of 2.
+            (inside, entity_at(DVec3::new(47.0, 64.0, 8.0), false)),
+            // Three chunks away, just past it.
+            (outside, entity_at(DVec3::new(48.0, 64.0, 8.0), false)),
+        ]);
+
+        let ticked = simulated_entities(&entities, Some(2));
+        assert_eq!(ticked.len(), 2);
+        assert!(ticked.contains(&player));
+        assert!(ticked.contains(&inside));
+        assert!(!ticked.contains(&outside));
+    }
+
+    #[test]
+    fn pitch_changes_are_sent() {
+        let level = DataComponentMap::new()
+            .with(EntityComponents::POSITION, DVec3::ZERO)
+            .with(EntityComponents::DIRECTION, Vec2::new(90.0, 0.0));
+        let aiming_up = level
+            .clone()
+            .with(EntityComponents::DIRECTION, Vec2::new(90.0, -35.0));
+        assert!(movement_changed(&DataComponentPatch::from_maps(
+            &level, &aiming_up
+        )));
+        assert!(!movement_changed(&DataComponentPatch::from_maps(
+            &aiming_up, &aiming_up
+        )));
+
+        let (sync, _) = rotation_packets(
+            1,
+            DVec3::ZERO,
+            aiming_up.get(EntityComponents::DIRECTION).unwrap(),
+            head_yaw(&aiming_up),
+            DVec3::ZERO,
+        );
+        assert_eq!(sync.yaw, 90.0);
+        assert_eq!(sync.pitch, -35.0);
+    }
+
+    #[test]
+    fn head_and_body_yaw_diverge() {
+        let body = DataComponentMap::new().with(EntityComponents::DIRECTION, Vec2::new(90.0, 10.0));
+        assert_eq!(head_yaw(&body), 90.0);
+
+        let turned = body.with(EntityComponents::HEAD_YAW, -45.0);
+        assert_eq!(head_yaw(&turned), -45.0);
+
+        let (sync, head) = rotation_packets(
+            1,
+            DVec3::ZERO,
+            Vec2::new(90.0, 10.0),
+            head_yaw(&turned),
+            DVec3::ZERO,
+        );
+        assert_eq!(sync.yaw, 90.0);
+        assert_eq!(sync.pitch, 10.0);
+        assert_eq!(head.entity.as_i32(), 1);
+        assert_eq!(head_angle(head_yaw(&turned)), 315.0);
+        assert_ne!(head_angle(head_yaw(&turned)), head_angle(90.0));
+    }
+}
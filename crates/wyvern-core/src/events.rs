@@ -1,17 +1,31 @@
-use std::{fmt::Debug, pin::Pin, sync::Arc};
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
 
 use crate::{
-    actors::ActorResult, blocks::BlockState, dimension::Dimension, entities::Entity,
-    item::ItemStack, player::Player, server::Server,
+    actors::ActorResult,
+    blocks::BlockState,
+    dimension::Dimension,
+    entities::{DamageHistory, DamageSource, DespawnReason, Entity},
+    inventory::{ClickKind, MouseButton, ScreenSlot},
+    item::ItemStack,
+    player::{Player, join::DimensionChoice},
+    server::Server,
 };
 
-use wyvern_values::{DVec3, IVec2, IVec3, Id, Vec2, cell::Token};
+use wyvern_datatypes::{gamemode::Gamemode, window::InventoryKind};
+use wyvern_values::{DVec3, IVec2, IVec3, Id, Vec2};
 
 macro_rules! event_bus {
     ($($name:ident : $t:ty)*) => {
         #[derive(Default)]
         pub struct EventBus {
             $(pub(crate) $name: Vec<Arc<dyn Fn(Arc<$t>) -> BoxedFuture + Sync + Send>>,)*
+            pub(crate) stats: Mutex<HashMap<&'static str, EventStats>>,
         }
 
         $(impl crate::events::Event for $t {
@@ -23,16 +37,34 @@ macro_rules! event_bus {
             }
 
             fn dispatch(self, bus: std::sync::Arc<EventBus>) {
-                let tick1 = std::time::Instant::now();
-                let tick2 = std::time::Instant::now();
-                let time_time = tick2 - tick1;
-                let start = std::time::Instant::now();
                 let event = Arc::new(self);
                 for event_func in bus.$name.clone().into_iter() {
-                    $crate::runtime::Runtime::spawn_task(event_func(event.clone()));
+                    let bus = bus.clone();
+                    let future = event_func(event.clone());
+                    $crate::runtime::Runtime::spawn_task(async move {
+                        let start = std::time::Instant::now();
+                        let result = future.await;
+                        let elapsed = start.elapsed();
+                        bus.record_stats(std::any::type_name::<Self>(), elapsed);
+                        log::debug!("Handler for event {:?} took {:?} to execute", std::any::type_name::<Self>(), elapsed);
+                        result
+                    });
                 }
-                let end = std::time::Instant::now();
-                log::debug!("Event {:?} took {:?} to execute", std::any::type_name::<Self>(), (end - start) - (time_time));
+            }
+
+            fn dispatch_and_wait(self, bus: std::sync::Arc<EventBus>) -> BoxedFuture {
+                let event = Arc::new(self);
+                Box::pin(async move {
+                    for event_func in bus.$name.clone().into_iter() {
+                        let start = std::time::Instant::now();
+                        let result = event_func(event.clone()).await;
+                        bus.record_stats(std::any::type_name::<Self>(), start.elapsed());
+                        if let Err(err) = result {
+                            log::warn!("Handler for event {:?} failed: {:?}", std::any::type_name::<Self>(), err);
+                        }
+                    }
+                    Ok(())
+                })
             }
         })*
 
@@ -52,6 +84,7 @@ event_bus! {
     on_change_held_slot: ChangeHeldSlotEvent
     on_swap_hands: SwapHandsEvent
     on_drop_item: DropItemEvent
+    on_pickup_item: PlayerPickupItemEvent
     on_block_break: BreakBlockEvent
     on_chat: ChatMessageEvent
     on_right_click: RightClickEvent
@@ -60,6 +93,56 @@ event_bus! {
     on_left_click: PlayerLeftClickEvent
     on_load: PlayerLoadEvent
     on_respawn: PlayerRespawnEvent
+    on_player_death: PlayerDeathEvent
+    on_entity_damage: EntityDamageEvent
+    on_entity_death: EntityDeathEvent
+    on_entity_despawn: EntityDespawnEvent
+    on_entity_target: EntityTargetEvent
+    on_entity_mount: EntityMountEvent
+    on_entity_dismount: EntityDismountEvent
+    on_enter_region: PlayerEnterRegionEvent
+    on_leave_region: PlayerLeaveRegionEvent
+    on_void: PlayerVoidEvent
+    on_gamemode_change: GamemodeChangeEvent
+    on_sneak: PlayerSneakEvent
+    on_sprint: PlayerSprintEvent
+    on_jump: PlayerJumpEvent
+    on_menu_click: MenuClickEvent
+}
+
+impl EventBus {
+    pub(crate) fn record_stats(&self, event: &'static str, elapsed: Duration) {
+        self.stats
+            .lock()
+            .unwrap()
+            .entry(event)
+            .or_default()
+            .record(elapsed);
+    }
+}
+
+/// Aggregated execution times of every handler invocation for a single event type.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct EventStats {
+    pub count: u64,
+    pub total: Duration,
+    pub max: Duration,
+}
+
+impl EventStats {
+    pub fn mean(&self) -> Duration {
+        if self.count == 0 {
+            Duration::ZERO
+        } else {
+            self.total.div_f64(self.count as f64)
+        }
+    }
+
+    pub(crate) fn record(&mut self, elapsed: Duration) {
+        self.count += 1;
+        self.total += elapsed;
+        self.max = self.max.max(elapsed);
+    }
 }
 
 impl Debug for EventBus {
@@ -77,6 +160,8 @@ pub trait Event {
         f: N,
     );
     fn dispatch(self, bus: Arc<EventBus>);
+    /// Runs every handler of this event one after another, finishing once they all have.
+    fn dispatch_and_wait(self, bus: Arc<EventBus>) -> BoxedFuture;
 }
 
 pub type BoxedFuture = Pin<Box<dyn Future<Output = ActorResult<()>> + Sync + Send + 'static>>;
@@ -87,6 +172,7 @@ pub struct DimensionCreateEvent {
     pub server: Server,
 }
 
+/// Fired once a chunk has been generated and inserted into its dimension.
 #[derive(Debug, Clone)]
 pub struct ChunkLoadEvent {
     pub dimension: Dimension,
@@ -105,6 +191,60 @@ pub struct PlayerMoveEvent {
     pub new_direction: Vec2,
 }
 
+/// Fired when a player moves into a region trigger of their dimension.
+#[derive(Debug, Clone)]
+pub struct PlayerEnterRegionEvent {
+    pub player: Player,
+    pub dimension: Dimension,
+    pub region: Id,
+}
+
+/// Fired when a player moves out of a region trigger of their dimension, or leaves the
+/// dimension while inside of one.
+#[derive(Debug, Clone)]
+pub struct PlayerLeaveRegionEvent {
+    pub player: Player,
+    pub dimension: Dimension,
+    pub region: Id,
+}
+
+/// Fired when a player falls below the void height of their dimension.
+#[derive(Debug, Clone)]
+pub struct PlayerVoidEvent {
+    pub player: Player,
+    pub dimension: Dimension,
+    pub position: DVec3,
+}
+
+/// Fired after a player's gamemode changes and the client has been told about it.
+#[derive(Debug, Clone)]
+pub struct GamemodeChangeEvent {
+    pub player: Player,
+    pub old: Gamemode,
+    pub new: Gamemode,
+}
+
+/// Fired when a player starts or stops sneaking.
+#[derive(Debug, Clone)]
+pub struct PlayerSneakEvent {
+    pub player: Player,
+    pub sneaking: bool,
+}
+
+/// Fired when a player starts or stops sprinting.
+#[derive(Debug, Clone)]
+pub struct PlayerSprintEvent {
+    pub player: Player,
+    pub sprinting: bool,
+}
+
+/// Fired when a player presses the jump key. Holding it down to keep jumping doesn't fire it
+/// again, and it fires even if the player can't jump, such as in midair.
+#[derive(Debug, Clone)]
+pub struct PlayerJumpEvent {
+    pub player: Player,
+}
+
 #[derive(Debug, Clone)]
 pub struct PlayerCommandEvent {
     pub player: Player,
@@ -137,10 +277,48 @@ pub struct PlaceBlockEvent {
     pub block: BlockState,
 }
 
+/// Fired when a player clicks a slot while a screen is open, whether it belongs to the screen or
+/// to their own inventory below it. Clicking a locked slot fires this without moving any items,
+/// so locked slots can be used as buttons.
+#[derive(Debug, Clone)]
+pub struct MenuClickEvent {
+    pub player: Player,
+    pub screen: InventoryKind,
+    pub slot: ScreenSlot,
+    /// The mouse button used, or `None` for key presses.
+    pub button: Option<MouseButton>,
+    pub shift: bool,
+    pub click_kind: ClickKind,
+    pub locked: bool,
+}
+
+/// Fired when a player drops an item. Once every handler has finished, the item is dropped as
+/// an item entity that players can pick up, unless a handler cancelled the drop.
 #[derive(Debug)]
 pub struct DropItemEvent {
     pub player: Player,
     pub item: ItemStack,
+    pub(crate) cancelled: Arc<Mutex<bool>>,
+}
+
+impl DropItemEvent {
+    pub fn is_cancelled(&self) -> bool {
+        *self.cancelled.lock().unwrap()
+    }
+
+    /// Stops the item from being dropped. The item has already left the player's inventory, so
+    /// handlers that cancel the drop decide what happens to it.
+    pub fn set_cancelled(&self, cancelled: bool) {
+        *self.cancelled.lock().unwrap() = cancelled;
+    }
+}
+
+/// Fired when a player picks up an item entity, after the item is in their inventory. `item`
+/// is only what they picked up, which may be part of the entity's stack if the rest didn't fit.
+#[derive(Debug, Clone)]
+pub struct PlayerPickupItemEvent {
+    pub player: Player,
+    pub item: ItemStack,
 }
 
 #[derive(Debug, Clone)]
@@ -173,19 +351,94 @@ pub struct ChatMessageEvent {
 #[derive(Debug, Clone)]
 pub struct PlayerJoinEvent {
     pub player: Player,
-    pub new_dimension: Token<Id>,
+    pub new_dimension: DimensionChoice,
+}
+
+#[derive(Debug, Clone)]
+pub struct EntityDamageEvent {
+    pub entity: Entity,
+    pub amount: f32,
+    pub source: DamageSource,
+}
+
+#[derive(Debug, Clone)]
+pub struct EntityDeathEvent {
+    pub entity: Entity,
+    pub source: DamageSource,
+}
+
+/// Fired after an entity despawned on its own because of its despawn rule. The entity's handle
+/// no longer refers to a live entity.
+#[derive(Debug, Clone)]
+pub struct EntityDespawnEvent {
+    pub entity: Entity,
+    pub entity_type: Id,
+    pub position: DVec3,
+    pub reason: DespawnReason,
+}
+
+/// Fired when an entity is about to start targeting another entity, switch targets or stop
+/// targeting. Handlers can change the new target or cancel the change. The target is only
+/// changed once every handler has finished.
+#[derive(Debug, Clone)]
+pub struct EntityTargetEvent {
+    pub entity: Entity,
+    pub old_target: Option<Entity>,
+    pub(crate) new_target: Arc<Mutex<Option<Entity>>>,
+    pub(crate) cancelled: Arc<Mutex<bool>>,
+}
+
+impl EntityTargetEvent {
+    pub fn new_target(&self) -> Option<Entity> {
+        self.new_target.lock().unwrap().clone()
+    }
+
+    /// Replaces the target the entity will switch to. It must be in the same dimension as the
+    /// entity.
+    pub fn set_new_target(&self, target: Option<Entity>) {
+        *self.new_target.lock().unwrap() = target;
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        *self.cancelled.lock().unwrap()
+    }
+
+    /// Keeps the entity's old target.
+    pub fn set_cancelled(&self, cancelled: bool) {
+        *self.cancelled.lock().unwrap() = cancelled;
+    }
+}
+
+/// Fired after an entity starts riding another entity.
+#[derive(Debug, Clone)]
+pub struct EntityMountEvent {
+    pub entity: Entity,
+    pub vehicle: Entity,
+}
+
+/// Fired after an entity stops riding another entity, such as when a player sneaks or the
+/// vehicle is removed. If the vehicle was removed, its handle no longer refers to an entity.
+#[derive(Debug, Clone)]
+pub struct EntityDismountEvent {
+    pub entity: Entity,
+    pub vehicle: Entity,
 }
 
 #[derive(Debug, Clone)]
 pub struct PlayerAttackEntityEvent {
     pub attacker: Player,
     pub victim: Entity,
+    /// How charged the attack was, from 0 to 1, by the attacker's `ATTACK_SPEED`. Vanilla
+    /// scales damage by this and only allows critical and sweeping attacks above 0.9.
+    pub attack_strength: f32,
 }
 
 #[derive(Debug, Clone)]
 pub struct PlayerAttackPlayerEvent {
     pub attacker: Player,
     pub victim: Player,
+    /// How charged the attack was, from 0 to 1, by the attacker's `ATTACK_SPEED`.
+    pub attack_strength: f32,
 }
 
 #[derive(Debug, Clone)]
@@ -202,3 +455,26 @@ pub struct PlayerLoadEvent {
 pub struct PlayerRespawnEvent {
     pub player: Player,
 }
+
+/// Fired when a player dies. Unless their dimension has `keepInventory` on, their inventory has
+/// been emptied into the drops, which handlers can change. Once every handler has finished, each
+/// remaining drop fires a `DropItemEvent` and, unless it is cancelled, is dropped where they died.
+#[derive(Debug, Clone)]
+pub struct PlayerDeathEvent {
+    pub player: Player,
+    pub keep_inventory: bool,
+    /// The damage the player took in the moments before dying. Use `killer` and `assisters`
+    /// to attribute the kill.
+    pub damage_history: DamageHistory,
+    pub(crate) drops: Arc<Mutex<Vec<ItemStack>>>,
+}
+
+impl PlayerDeathEvent {
+    pub fn drops(&self) -> Vec<ItemStack> {
+        self.drops.lock().unwrap().clone()
+    }
+
+    pub fn set_drops(&self, drops: Vec<ItemStack>) {
+        *self.drops.lock().unwrap() = drops;
+    }
+}
@@ -0,0 +1,790 @@
+use std::{collections::HashSet, ops::Range};
+
+use wyvern_components::DataComponentHolder;
+
+use crate::item::{ItemComponents, ItemStack};
+
+/// The stack size items have when they don't set `ItemComponents::MAX_STACK_SIZE`.
+pub const DEFAULT_MAX_STACK_SIZE: u16 = 64;
+
+/// The slot index clients send when clicking outside of a window.
+const OUTSIDE_WINDOW: i16 = -999;
+
+/// The number of slots of a player's inventory shown under an open container.
+const PLAYER_SLOTS: usize = 36;
+
+/// How a player clicked a slot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClickKind {
+    Left,
+    Right,
+    ShiftLeft,
+    ShiftRight,
+    /// A number key press, with the hotbar slot from 0 to 8.
+    NumberKey(u8),
+    /// The swap hands key, pressed while hovering over the slot.
+    SwapOffhand,
+    Middle,
+    /// The drop key.
+    Drop,
+    /// The drop key while holding control, dropping the whole stack.
+    DropStack,
+    DoubleClick,
+    /// Dragging a stack over the slot.
+    Drag,
+}
+
+/// A mouse button a slot was clicked with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MouseButton {
+    Left,
+    Right,
+    Middle,
+}
+
+/// A slot clicked while a screen is open.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScreenSlot {
+    /// A slot of the screen itself, with the index used by `Player::set_screen_slot`.
+    Container(usize),
+    /// A slot of the player's own inventory shown below the screen, with the index used by
+    /// `Player::inventory`.
+    Inventory(usize),
+}
+
+impl ClickKind {
+    /// Returns the mouse button of this click, or `None` if it was a key press or a drag.
+    pub fn button(&self) -> Option<MouseButton> {
+        match self {
+            ClickKind::Left | ClickKind::ShiftLeft | ClickKind::DoubleClick => {
+                Some(MouseButton::Left)
+            }
+            ClickKind::Right | ClickKind::ShiftRight => Some(MouseButton::Right),
+            ClickKind::Middle => Some(MouseButton::Middle),
+            _ => None,
+        }
+    }
+
+    pub fn is_shift(&self) -> bool {
+        matches!(self, ClickKind::ShiftLeft | ClickKind::ShiftRight)
+    }
+
+    /// Decodes a click's mode and button, returning `None` for clicks the vanilla client never
+    /// sends.
+    pub(crate) fn decode(mode: i32, button: i8) -> Option<ClickKind> {
+        match (mode, button) {
+            (0, 0) => Some(ClickKind::Left),
+            (0, 1) => Some(ClickKind::Right),
+            (1, 0) => Some(ClickKind::ShiftLeft),
+            (1, 1) => Some(ClickKind::ShiftRight),
+            (2, 0..=8) => Some(ClickKind::NumberKey(button as u8)),
+            (2, 40) => Some(ClickKind::SwapOffhand),
+            (3, 2) => Some(ClickKind::Middle),
+            (4, 0) => Some(ClickKind::Drop),
+            (4, 1) => Some(ClickKind::DropStack),
+            (5, _) => Some(ClickKind::Drag),
+            (6, 0) => Some(ClickKind::DoubleClick),
+            _ => None,
+        }
+    }
+}
+
+/// What a player did when clicking in a window, decoded from the click's mode and button.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ClickAction {
+    /// A left or right click on a slot, or outside the window if `slot` is `None`.
+    Pickup {
+        slot: Option<usize>,
+        right: bool,
+    },
+    /// A shift click, moving the clicked stack to the other part of the window.
+    QuickMove {
+        slot: usize,
+    },
+    /// A number key press, swapping the hovered slot with a hotbar slot. `hotbar` is 40 for the
+    /// offhand.
+    Swap {
+        slot: usize,
+        hotbar: usize,
+    },
+    /// A middle click, which copies a full stack of the clicked item in creative mode.
+    Clone {
+        slot: usize,
+    },
+    /// A drop key press over a slot.
+    Throw {
+        slot: usize,
+        all: bool,
+    },
+    DragStart {
+        kind: DragKind,
+    },
+    DragAdd {
+        slot: usize,
+    },
+    DragEnd,
+    /// A double click, gathering matching items into the cursor.
+    PickupAll {
+        slot: usize,
+    },
+}
+
+impl ClickAction {
+    /// Decodes a click, returning `None` for clicks the vanilla client never sends.
+    pub(crate) fn decode(mode: i32, button: i8, slot: i16) -> Option<ClickAction> {
+        let outside = slot == OUTSIDE_WINDOW;
+        let index = usize::try_from(slot).ok();
+        match (mode, button) {
+            (0, 0 | 1) if outside => Some(ClickAction::Pickup {
+                slot: None,
+                right: button == 1,
+            }),
+            (0, 0 | 1) => Some(ClickAction::Pickup {
+                slot: Some(index?),
+                right: button == 1,
+            }),
+            (1, 0 | 1) => Some(ClickAction::QuickMove { slot: index? }),
+            (2, 0..=8 | 40) => Some(ClickAction::Swap {
+                slot: index?,
+                hotbar: button as usize,
+            }),
+            (3, 2) => Some(ClickAction::Clone { slot: index? }),
+            (4, 0 | 1) => Some(ClickAction::Throw {
+                slot: index?,
+                all: button == 1,
+            }),
+            (5, 0 | 4 | 8) if outside => Some(ClickAction::DragStart {
+                kind: match button {
+                    0 => DragKind::Split,
+                    4 => DragKind::One,
+                    _ => DragKind::Clone,
+                },
+            }),
+            (5, 1 | 5 | 9) => Some(ClickAction::DragAdd { slot: index? }),
+            (5, 2 | 6 | 10) if outside => Some(ClickAction::DragEnd),
+            (6, 0) => Some(ClickAction::PickupAll { slot: index? }),
+            _ => None,
+        }
+    }
+
+    /// Returns the slot that was clicked. Drags have no clicked slot, since they only pass over
+    /// slots.
+    pub(crate) fn clicked_slot(self) -> Option<usize> {
+        match self {
+            ClickAction::DragAdd { .. } => None,
+            action => action_slot(action),
+        }
+    }
+}
+
+/// How the cursor's stack is spread over the slots of a drag.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum DragKind {
+    /// Splits the cursor evenly between the slots.
+    Split,
+    /// Places one item in each slot.
+    One,
+    /// Places a full stack in each slot without using up the cursor, in creative mode.
+    Clone,
+}
+
+/// A drag the player has started but not finished yet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Drag {
+    kind: DragKind,
+    slots: Vec<usize>,
+}
+
+/// The slots of an open window and the item on the cursor, which clicks are applied to.
+///
+/// Windows are laid out like the vanilla client's. For a player's own inventory, slot indices are
+/// the same as in their inventory, with the main inventory at 9 to 35 and the hotbar at 36 to 44.
+/// With a container open, its slots come first, followed by the player's main inventory and then
+/// their hotbar.
+#[derive(Debug, Clone)]
+pub(crate) struct ClickWindow {
+    pub(crate) slots: Vec<ItemStack>,
+    pub(crate) cursor: ItemStack,
+    /// The number of slots belonging to the open container, or 0 for the player's own inventory.
+    pub(crate) container_slots: usize,
+    /// The player's offhand item while a container is open, since it isn't one of the window's
+    /// slots then but can still be swapped into with the offhand key.
+    pub(crate) offhand: Option<ItemStack>,
+    pub(crate) creative: bool,
+    pub(crate) drag: Option<Drag>,
+    /// Items thrown out of the window by this click.
+    pub(crate) dropped: Vec<ItemStack>,
+    /// The slots that can't be clicked, and that items are never moved into or out of.
+    pub(crate) locked: HashSet<usize>,
+    pub(crate) result: Option<ResultLayout>,
+}
+
+/// Where the result slot of a screen is, and which slots are used up when it is taken.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ResultLayout {
+    pub(crate) slot: usize,
+    pub(crate) inputs: Range<usize>,
+    /// Whether taking the result uses up the whole first input instead of one item, like an
+    /// anvil does.
+    pub(crate) consume_whole_first: bool,
+}
+
+impl ClickWindow {
+    /// Returns the window index of a player inventory slot, if it is shown in this window.
+    pub(crate) fn window_index(&self, inventory_slot: usize) -> Option<usize> {
+        if self.container_slots == 0 {
+            return Some(inventory_slot);
+        }
+        match inventory_slot {
+            9..45 => Some(self.container_slots + inventory_slot - 9),
+            _ => None,
+        }
+    }
+
+    /// Returns the player inventory slot a window index refers to, or `None` if it belongs to
+    /// the open container.
+    pub(crate) fn inventory_slot(&self, index: usize) -> Option<usize> {
+        if self.container_slots == 0 {
+            return Some(index);
+        }
+        index
+            .checked_sub(self.container_slots)
+            .filter(|slot| *slot < PLAYER_SLOTS)
+            .map(|slot| slot + 9)
+    }
+
+    /// Applies a click. Returns `false` if the click can't be applied to this window, in which
+    /// case the window is left unchanged.
+    pub(crate) fn apply(&mut self, action: ClickAction) -> bool {
+        if action_slot(action).is_some_and(|slot| slot >= self.slots.len()) {
+            return false;
+        }
+        if action
+            .clicked_slot()
+            .is_some_and(|slot| self.locked.contains(&slot))
+        {
+            return false;
+        }
+        if let Some(layout) = self
+            .result
+            .clone()
+            .filter(|layout| action.clicked_slot() == Some(layout.slot))
+        {
+            return self.take_result(action, &layout);
+        }
+        if !matches!(
+            action,
+            ClickAction::DragStart { .. } | ClickAction::DragAdd { .. } | ClickAction::DragEnd
+        ) {
+            self.drag = None;
+        }
+
+        match action {
+            ClickAction::Pickup { slot: None, right } => {
+                if !is_empty(&self.cursor) {
+                    let amount = if right { 1 } else { count(&self.cursor) };
+                    let dropped = take(&mut self.cursor, amount);
+                    self.dropped.push(dropped);
+                }
+            }
+            ClickAction::Pickup {
+                slot: Some(slot),
+                right,
+            } => self.pickup(slot, right),
+            ClickAction::QuickMove { slot } => self.quick_move(slot),
+            ClickAction::Swap { slot, hotbar: 40 } if self.container_slots != 0 => {
+                let Some(offhand) = &mut self.offhand else {
+                    return false;
+                };
+                std::mem::swap(&mut self.slots[slot], offhand);
+            }
+            ClickAction::Swap { slot, hotbar } => {
+                let target = match hotbar {
+                    40 => Some(45),
+                    hotbar => self.window_index(36 + hotbar),
+                };
+                let Some(target) =
+                    target.filter(|target| *target < self.slots.len() && !self.is_fixed(*target))
+                else {
+                    return false;
+                };
+                self.slots.swap(slot, target);
+            }
+            ClickAction::Clone { slot } => {
+                if self.creative && is_empty(&self.cursor) && !is_empty(&self.slots[slot]) {
+                    let item = &self.slots[slot];
+                    self.cursor = with_count(item, max_stack_size(item));
+                }
+            }
+            ClickAction::Throw { slot, all } => {
+                if !is_empty(&self.slots[slot]) {
+                    let amount = if all { count(&self.slots[slot]) } else { 1 };
+                    let dropped = take(&mut self.slots[slot], amount);
+                    self.dropped.push(dropped);
+                }
+            }
+            ClickAction::DragStart { kind } => {
+                if kind == DragKind::Clone && !self.creative {
+                    return false;
+                }
+                self.drag = Some(Drag {
+                    kind,
+                    slots: Vec::new(),
+                });
+            }
+            ClickAction::DragAdd { slot } => {
+                let Some(drag) = &mut self.drag else {
+                    return false;
+                };
+                if !drag.slots.contains(&slot)
+                    && !self.is_fixed(slot)
+                    && fits(&self.slots[slot], &self.cursor)
+                {
+                    drag.slots.push(slot);
+                }
+            }
+            ClickAction::DragEnd => {
+                let Some(drag) = self.drag.take() else {
+                    return false;
+                };
+                self.finish_drag(drag);
+            }
+            ClickAction::PickupAll { .. } => self.pickup_all(),
+        }
+        true
+    }
+
+    /// Whether items can't be put into or taken out of a slot except by clicking it.
+    fn is_fixed(&self, slot: usize) -> bool {
+        self.locked.contains(&slot) || self.result.as_ref().is_some_and(|r| r.slot == slot)
+    }
+
+    /// Takes the item in the result slot, using up the inputs it was made from. Only clicks and
+    /// shift clicks can take results, and only if the whole result fits.
+    fn take_result(&mut self, action: ClickAction, layout: &ResultLayout) -> bool {
+        let result = self.slots[layout.slot].clone();
+        if is_empty(&result) {
+            return true;
+        }
+        match action {
+            ClickAction::Pickup { .. } => {
+                if is_empty(&self.cursor) {
+                    self.cursor = result;
+                } else if stackable(&self.cursor, &result)
+                    && count(&self.cursor) + count(&result) <= max_stack_size(&result)
+                {
+                    self.cursor = with_count(&self.cursor, count(&self.cursor) + count(&result));
+                } else {
+                    return true;
+                }
+            }
+            ClickAction::QuickMove { slot } => {
+                let before = self.slots.clone();
+                self.quick_move(slot);
+                if !is_empty(&self.slots[slot]) {
+                    self.slots = before;
+                    return true;
+                }
+            }
+            _ => return false,
+        }
+
+        self.slots[layout.slot] = ItemStack::air();
+        for input in layout.inputs.clone() {
+            let amount = if layout.consume_whole_first && input == layout.inputs.start {
+                count(&self.slots[input])
+            } else {
+                1
+            };
+            if !is_empty(&self.slots[input]) {
+                take(&mut self.slots[input], amount);
+            }
+        }
+        true
+    }
+
+    fn pickup(&mut self, slot: usize, right: bool) {
+        let item = &mut self.slots[slot];
+        if is_empty(&self.cursor) {
+            if !is_empty(item) {
+                let amount = if right {
+                    count(item).div_ceil(2)
+                } else {
+                    count(item)
+                };
+                self.cursor = take(item, amount);
+            }
+        } else if is_empty(item) || stackable(item, &self.cursor) {
+            let amount = if right { 1 } else { count(&self.cursor) };
+            let placed = take(&mut self.cursor, amount);
+            let rest = merge(item, placed);
+            self.cursor = merge_into_cursor(&self.cursor, rest);
+        } else {
+            std::mem::swap(item, &mut self.cursor);
+        }
+    }
+
+    fn quick_move(&mut self, slot: usize) {
+        if is_empty(&self.slots[slot]) {
+            return;
+        }
+        let (targets, reverse) = self.quick_move_targets(slot);
+        let mut item = std::mem::replace(&mut self.slots[slot], ItemStack::air());
+        let mut order: Vec<usize> = targets.filter(|target| !self.is_fixed(*target)).collect();
+        if reverse {
+            order.reverse();
+        }
+
+        // Existing stacks are topped up before empty slots are used, like in vanilla.
+        for target in order.iter().copied() {
+            if !is_empty(&self.slots[target]) && stackable(&self.slots[target], &item) {
+                item = merge(&mut self.slots[target], item);
+            }
+        }
+        for target in order.iter().copied() {
+            if is_empty(&item) {
+                break;
+            }
+            if is_empty(&self.slots[target]) {
+                item = merge(&mut self.slots[target], item);
+            }
+        }
+        self.slots[slot] = item;
+    }
+
+    /// Returns the slots a shift click moves items into, and whether they are filled last first.
+    fn quick_move_targets(&self, slot: usize) -> (std::ops::Range<usize>, bool) {
+        let len = self.slots.len();
+        if self.container_slots == 0 {
+            match slot {
+                9..36 => (36..45.min(len), false),
+                36..45 => (9..36, false),
+                _ => (9..45.min(len), false),
+            }
+        } else if slot < self.container_slots {
+            (self.container_slots..len, true)
+        } else {
+            (0..self.container_slots, false)
+        }
+    }
+
+    fn finish_drag(&mut self, drag: Drag) {
+        if drag.slots.is_empty() || is_empty(&self.cursor) {
+            return;
+        }
+        let per_slot = match drag.kind {
+            DragKind::Split => (count(&self.cursor) / drag.slots.len() as u16).max(1),
+            DragKind::One => 1,
+            DragKind::Clone => max_stack_size(&self.cursor),
+        };
+        for slot in drag.slots {
+            if is_empty(&self.cursor) {
+                break;
+            }
+            let placed = if drag.kind == DragKind::Clone {
+                with_count(&self.cursor, per_slot)
+            } else {
+                take(&mut self.cursor, per_slot)
+            };
+            let rest = merge(&mut self.slots[slot], placed);
+            if drag.kind != DragKind::Clone {
+                self.cursor = merge_into_cursor(&self.cursor, rest);
+            }
+        }
+    }
+
+    fn pickup_all(&mut self) {
+        if is_empty(&self.cursor) {
+            return;
+        }
+        let max = max_stack_size(&self.cursor);
+        // Partial stacks are taken from before full ones, like in vanilla.
+        for take_full in [false, true] {
+            for index in 0..self.slots.len() {
+                if self.is_fixed(index) {
+                    continue;
+                }
+                let remaining = max.saturating_sub(count(&self.cursor));
+                if remaining == 0 {
+                    return;
+                }
+                let item = &mut self.slots[index];
+                if is_empty(item)
+                    || !stackable(item, &self.cursor)
+                    || (count(item) >= max_stack_size(item)) != take_full
+                {
+                    continue;
+                }
+                let taken = take(item, remaining);
+                let total = count(&self.cursor) + count(&taken);
+                self.cursor = with_count(&self.cursor, total);
+            }
+        }
+    }
+}
+
+fn action_slot(action: ClickAction) -> Option<usize> {
+    match action {
+        ClickAction::Pickup { slot, .. } => slot,
+        ClickAction::QuickMove { slot }
+        | ClickAction::Swap { slot, .. }
+        | ClickAction::Clone { slot }
+        | ClickAction::Throw { slot, .. }
+        | ClickAction::DragAdd { slot }
+        | ClickAction::PickupAll { slot } => Some(slot),
+        ClickAction::DragStart { .. } | ClickAction::DragEnd => None,
+    }
+}
+
+/// Returns the most items a stack of this item can hold.
+pub fn max_stack_size(item: &ItemStack) -> u16 {
+    item.get(ItemComponents::MAX_STACK_SIZE)
+        .unwrap_or(DEFAULT_MAX_STACK_SIZE)
+        .max(1)
+}
+
+/// Returns whether two items can be in the same stack, which is when only their counts differ.
+pub fn stackable(a: &ItemStack, b: &ItemStack) -> bool {
+    with_count(a, 1) == with_count(b, 1)
+}
+
+/// Returns whether a slot holding this item counts as empty.
+pub(crate) fn is_empty(item: &ItemStack) -> bool {
+    item.kind() == ItemStack::air().kind() || count(item) == 0
+}
+
+fn count(item: &ItemStack) -> u16 {
+    item.get(ItemComponents::ITEM_COUNT).unwrap_or(1)
+}
+
+fn with_count(item: &ItemStack, count: u16) -> ItemStack {
+    item.clone().with(ItemComponents::ITEM_COUNT, count)
+}
+
+/// Whether some of the cursor's stack could be placed in a slot.
+fn fits(slot: &ItemStack, cursor: &ItemStack) -> bool {
+    is_empty(slot) || (stackable(slot, cursor) && count(slot) < max_stack_size(slot))
+}
+
+/// Takes up to `amount` items out of a stack, leaving air behind if it is used up.
+fn take(item: &mut ItemStack, amount: u16) -> ItemStack {
+    let available = count(item);
+    let amount = amount.min(available);
+    let taken = with_count(item, amount);
+    *item = if amount == available {
+        ItemStack::air()
+    } else {
+        with_count(item, available - amount)
+    };
+    taken
+}
+
+/// Moves as much of `incoming` into `slot` as fits, returning what is left over.
+fn merge(slot: &mut ItemStack, incoming: ItemStack) -> ItemStack {
+    if is_empty(&incoming) {
+        return ItemStack::air();
+    }
+    if is_empty(slot) {
+        let mut incoming = incoming;
+        let amount = count(&incoming).min(max_stack_size(&incoming));
+        *slot = take(&mut incoming, amount);
+        return incoming;
+    }
+    if !stackable(slot, &incoming) {
+        return incoming;
+    }
+    let space = max_stack_size(slot).saturating_sub(count(slot));
+    let mut incoming = incoming;
+    let moved = take(&mut incoming, space);
+    *slot = with_count(slot, count(slot) + count(&moved));
+    incoming
+}
+
+/// Puts items that didn't fit in a slot back onto the cursor.
+fn merge_into_cursor(cursor: &ItemStack, rest: ItemStack) -> ItemStack {
+    if is_empty(&rest) {
+        return cursor.clone();
+    }
+    if is_empty(cursor) {
+        return rest;
+    }
+    with_count(cursor, count(cursor) + count(&rest))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use wyvern_components::DataComponentHolder;
+    use wyvern_values::Id;
+
+    use crate::item::{ItemComponents, ItemStack};
+
+    use super::{ClickAction, ClickWindow, DragKind, ResultLayout};
+
+    fn stone(count: u16) -> ItemStack {
+        ItemStack::new(Id::constant("minecraft", "stone")).with(ItemComponents::ITEM_COUNT, count)
+    }
+
+    fn window(container_slots: usize) -> ClickWindow {
+        let len = if container_slots == 0 {
+            46
+        } else {
+            container_slots + 36
+        };
+        ClickWindow {
+            slots: vec![ItemStack::air(); len],
+            cursor: ItemStack::air(),
+            container_slots,
+            offhand: (container_slots != 0).then(ItemStack::air),
+            creative: false,
+            drag: None,
+            dropped: Vec::new(),
+            locked: HashSet::new(),
+            result: None,
+        }
+    }
+
+    fn click(window: &mut ClickWindow, mode: i32, button: i8, slot: i16) {
+        assert!(window.apply(ClickAction::decode(mode, button, slot).unwrap()));
+    }
+
+    #[test]
+    fn right_click_picks_up_half() {
+        let mut window = window(0);
+        window.slots[9] = stone(5);
+        click(&mut window, 0, 1, 9);
+        assert_eq!(window.cursor, stone(3));
+        assert_eq!(window.slots[9], stone(2));
+    }
+
+    #[test]
+    fn placing_respects_max_stack_size() {
+        let mut window = window(0);
+        window.slots[9] = stone(60);
+        window.cursor = stone(10);
+        click(&mut window, 0, 0, 9);
+        assert_eq!(window.slots[9], stone(64));
+        assert_eq!(window.cursor, stone(6));
+    }
+
+    #[test]
+    fn shift_click_tops_up_stacks_before_empty_slots() {
+        let mut window = window(0);
+        window.slots[9] = stone(20);
+        window.slots[40] = stone(60);
+        click(&mut window, 1, 0, 9);
+        assert!(window.slots[9].kind() == ItemStack::air().kind());
+        assert_eq!(window.slots[40], stone(64));
+        assert_eq!(window.slots[36], stone(16));
+    }
+
+    #[test]
+    fn shift_click_moves_between_container_and_player() {
+        let mut window = window(27);
+        window.slots[0] = stone(8);
+        click(&mut window, 1, 0, 0);
+        // Items leaving a container fill the hotbar from its last slot.
+        assert_eq!(window.slots[27 + 35], stone(8));
+
+        click(&mut window, 1, 0, 27 + 35);
+        assert_eq!(window.slots[0], stone(8));
+    }
+
+    #[test]
+    fn number_keys_swap_with_the_hotbar() {
+        let mut window = window(0);
+        window.slots[9] = stone(1);
+        click(&mut window, 2, 3, 9);
+        assert_eq!(window.slots[39], stone(1));
+    }
+
+    #[test]
+    fn offhand_key_swaps_with_a_container_open() {
+        let mut window = window(27);
+        window.slots[0] = stone(3);
+        window.offhand = Some(stone(1));
+        click(&mut window, 2, 40, 0);
+        assert_eq!(window.slots[0], stone(1));
+        assert_eq!(window.offhand, Some(stone(3)));
+    }
+
+    #[test]
+    fn split_drags_share_the_cursor_evenly() {
+        let mut window = window(0);
+        window.cursor = stone(7);
+        click(&mut window, 5, 0, -999);
+        for slot in [9, 10, 11] {
+            click(&mut window, 5, 1, slot);
+        }
+        click(&mut window, 5, 2, -999);
+        assert_eq!(window.slots[9], stone(2));
+        assert_eq!(window.slots[11], stone(2));
+        assert_eq!(window.cursor, stone(1));
+    }
+
+    #[test]
+    fn clone_drags_need_creative() {
+        let mut window = window(0);
+        window.cursor = stone(1);
+        let start = ClickAction::DragStart {
+            kind: DragKind::Clone,
+        };
+        assert!(!window.apply(start));
+        window.creative = true;
+        assert!(window.apply(start));
+    }
+
+    #[test]
+    fn locked_slots_are_left_alone() {
+        let mut window = window(9);
+        window.locked = (0..9).collect();
+        window.slots[0] = stone(1);
+        assert!(!window.apply(ClickAction::decode(0, 0, 0).unwrap()));
+        assert_eq!(window.slots[0], stone(1));
+
+        // Shift clicking from the player's inventory skips the locked container slots.
+        window.slots[9] = stone(5);
+        click(&mut window, 1, 0, 9);
+        assert_eq!(window.slots[0], stone(1));
+        assert_eq!(window.slots[9], stone(5));
+
+        window.cursor = stone(1);
+        click(&mut window, 6, 0, 10);
+        assert_eq!(window.cursor, stone(6));
+        assert_eq!(window.slots[0], stone(1));
+    }
+
+    #[test]
+    fn taking_a_result_uses_up_the_inputs() {
+        let mut window = window(10);
+        window.result = Some(ResultLayout {
+            slot: 0,
+            inputs: 1..10,
+            consume_whole_first: false,
+        });
+        window.slots[0] = stone(4);
+        window.slots[1] = stone(2);
+        window.slots[5] = stone(1);
+
+        // Clicking with a matching stack adds the result to it instead of placing the stack.
+        window.cursor = stone(1);
+        click(&mut window, 0, 0, 0);
+        assert_eq!(window.cursor, stone(5));
+        assert!(window.slots[0].kind() == ItemStack::air().kind());
+        assert_eq!(window.slots[1], stone(1));
+        assert!(window.slots[5].kind() == ItemStack::air().kind());
+    }
+
+    #[test]
+    fn double_click_gathers_partial_stacks_first() {
+        let mut window = window(0);
+        window.cursor = stone(1);
+        window.slots[9] = stone(64);
+        window.slots[10] = stone(30);
+        click(&mut window, 6, 0, 11);
+        assert_eq!(window.cursor, stone(64));
+        assert_eq!(window.slots[9], stone(31));
+        assert!(window.slots[10].kind() == ItemStack::air().kind());
+    }
+}
@@ -1,8 +1,21 @@
+mod click;
+pub(crate) use click::{ClickAction, ClickWindow, Drag, ResultLayout, is_empty};
+pub use click::{
+    ClickKind, DEFAULT_MAX_STACK_SIZE, MouseButton, ScreenSlot, max_stack_size, stackable,
+};
 mod data;
 pub use data::*;
+mod result;
+pub use result::{ResultInputs, ResultSlot};
 
 use crate::{actors::ActorResult, item::ItemStack};
 
+/// A collection of item slots.
+///
+/// These methods are synchronous. For an in-memory inventory like `DataInventory` they never
+/// block, but actor-backed inventories like `PlayerInventory` block until the actor answers.
+/// Those also provide `get_slot_async` and `set_slot_async`, which should be preferred inside
+/// tasks and event handlers so a busy actor doesn't hold up a task thread.
 pub trait Inventory {
     fn get_slot(&self, slot: usize) -> ActorResult<ItemStack>;
     fn set_slot(&mut self, slot: usize, item: ItemStack) -> ActorResult<()>;
@@ -0,0 +1,40 @@
+use std::{fmt::Debug, sync::Arc};
+
+use crate::item::ItemStack;
+
+/// What the result slot of an anvil or crafting screen is computed from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResultInputs {
+    /// The input slots, in order. For an anvil these are the left and right slot, and for a
+    /// crafting table the 3x3 grid row by row.
+    pub items: Vec<ItemStack>,
+    /// The name typed into an anvil's text field, if the player typed one.
+    pub rename: Option<String>,
+}
+
+/// Computes the contents of a screen's result slot from its inputs, such as for custom recipes.
+///
+/// Taking the result uses up one item from each input slot of a crafting table. For an anvil,
+/// the whole left slot and one item of the right slot are used up.
+#[derive(Clone)]
+pub struct ResultSlot {
+    compute: Arc<dyn Fn(&ResultInputs) -> ItemStack + Send + Sync>,
+}
+
+impl Debug for ResultSlot {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("ResultSlot { ... }")
+    }
+}
+
+impl ResultSlot {
+    pub fn new<F: Fn(&ResultInputs) -> ItemStack + Send + Sync + 'static>(f: F) -> ResultSlot {
+        ResultSlot {
+            compute: Arc::new(f),
+        }
+    }
+
+    pub(crate) fn compute(&self, inputs: &ResultInputs) -> ItemStack {
+        (self.compute)(inputs)
+    }
+}
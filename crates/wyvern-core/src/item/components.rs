@@ -1,17 +1,31 @@
+use std::collections::HashMap;
+
 use voxidian_protocol::packet::s2c::play::EquipmentSlot as PtcEquipmentSlot;
 
 use wyvern_components::DataComponentType;
+
+use crate::blocks::BannerLayer;
 use wyvern_datatypes::{nbt::NbtCompound, text::Text};
-use wyvern_values::{Id, id};
+use wyvern_values::{IVec3, Id, id};
 
 pub struct ItemComponents;
 
 impl ItemComponents {
     pub const ITEM_COUNT: DataComponentType<u16> =
         DataComponentType::new(id![minecraft:item_count]);
+    /// The most items a stack can hold. Items without it stack up to 64.
+    pub const MAX_STACK_SIZE: DataComponentType<u16> =
+        DataComponentType::new(id![minecraft:max_stack_size]);
     pub const MAX_DAMAGE: DataComponentType<i32> =
         DataComponentType::new(id![minecraft:max_damage]);
     pub const DAMAGE: DataComponentType<i32> = DataComponentType::new(id![minecraft:damage]);
+    /// The enchantments on the item and their levels, which clients show in the item's
+    /// tooltip. Enchanted items glint unless `ENCHANTMENT_GLINT_OVERRIDE` says otherwise.
+    pub const ENCHANTMENTS: DataComponentType<HashMap<Id, i32>> =
+        DataComponentType::new(id![minecraft:enchantments]);
+    /// Forces the enchantment glint on or off, whether or not the item is enchanted.
+    pub const ENCHANTMENT_GLINT_OVERRIDE: DataComponentType<bool> =
+        DataComponentType::new(id![minecraft:enchantment_glint_override]);
     pub const ITEM_MODEL: DataComponentType<Id> = DataComponentType::new(id![minecraft:item_model]);
     pub const CUSTOM_DATA: DataComponentType<NbtCompound> =
         DataComponentType::new(id![minecraft:custom_data]);
@@ -20,6 +34,32 @@ impl ItemComponents {
     pub const EQUIPPABLE: DataComponentType<EquippableComponent> =
         DataComponentType::new(id![minecraft:equippable]);
     pub const CAN_BLOCK: DataComponentType<bool> = DataComponentType::new(id![minecraft:can_block]);
+    pub const BANNER_PATTERNS: DataComponentType<Vec<BannerLayer>> =
+        DataComponentType::new(id![minecraft:banner_patterns]);
+    /// Attribute modifiers the item applies to whoever holds or wears it, which clients also
+    /// show in the item's tooltip.
+    pub const ATTRIBUTE_MODIFIERS: DataComponentType<Vec<AttributeModifier>> =
+        DataComponentType::new(id![minecraft:attribute_modifiers]);
+    /// Makes a compass point at a block instead of its dimension's `WorldSpawn`.
+    pub const LODESTONE_TARGET: DataComponentType<LodestoneTarget> =
+        DataComponentType::new(id![minecraft:lodestone_target]);
+}
+
+/// A block a compass points at, which doesn't have to be a lodestone. Compasses spin while
+/// their holder is in a different dimension.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LodestoneTarget {
+    pub dimension: Id,
+    pub position: IVec3,
+}
+
+impl LodestoneTarget {
+    pub fn new(dimension: Id, position: IVec3) -> LodestoneTarget {
+        LodestoneTarget {
+            dimension,
+            position,
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -51,6 +91,98 @@ impl From<PtcEquipmentSlot> for EquipmentSlot {
     }
 }
 
+/// How an attribute modifier changes an attribute. Modifiers are applied in the order of
+/// these variants.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AttributeOperation {
+    /// Adds the amount to the base value.
+    AddValue,
+    /// Adds the base value multiplied by the amount.
+    AddMultipliedBase,
+    /// Multiplies the value by one plus the amount.
+    AddMultipliedTotal,
+}
+
+/// Where an item has to be for its attribute modifiers to apply.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AttributeSlot {
+    Any,
+    Mainhand,
+    Offhand,
+    Hand,
+    Feet,
+    Legs,
+    Chest,
+    Head,
+    Armor,
+    Body,
+    Saddle,
+}
+
+impl AttributeSlot {
+    pub fn contains(&self, slot: &EquipmentSlot) -> bool {
+        match self {
+            AttributeSlot::Any => true,
+            AttributeSlot::Mainhand => *slot == EquipmentSlot::Mainhand,
+            AttributeSlot::Offhand => *slot == EquipmentSlot::Offhand,
+            AttributeSlot::Hand => {
+                matches!(slot, EquipmentSlot::Mainhand | EquipmentSlot::Offhand)
+            }
+            AttributeSlot::Feet => *slot == EquipmentSlot::Boots,
+            AttributeSlot::Legs => *slot == EquipmentSlot::Leggings,
+            AttributeSlot::Chest => *slot == EquipmentSlot::Chestplate,
+            AttributeSlot::Head => *slot == EquipmentSlot::Helmet,
+            AttributeSlot::Armor => matches!(
+                slot,
+                EquipmentSlot::Helmet
+                    | EquipmentSlot::Chestplate
+                    | EquipmentSlot::Leggings
+                    | EquipmentSlot::Boots
+            ),
+            AttributeSlot::Body => *slot == EquipmentSlot::Body,
+            AttributeSlot::Saddle => *slot == EquipmentSlot::Saddle,
+        }
+    }
+}
+
+/// A change to an attribute, such as `Attributes::ATTACK_DAMAGE`, made by an item.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AttributeModifier {
+    /// Identifies the modifier. Clients only apply one modifier with the same id to the same
+    /// attribute.
+    pub id: Id,
+    pub attribute: Id,
+    pub amount: f64,
+    pub operation: AttributeOperation,
+    pub slot: AttributeSlot,
+}
+
+impl AttributeModifier {
+    /// Creates a modifier whose id is made from the attribute and slot.
+    pub fn new(
+        attribute: Id,
+        amount: f64,
+        operation: AttributeOperation,
+        slot: AttributeSlot,
+    ) -> AttributeModifier {
+        AttributeModifier {
+            id: Id::new(
+                "wyvern",
+                &format!("{}_{:?}", attribute.path(), slot).to_lowercase(),
+            ),
+            attribute,
+            amount,
+            operation,
+            slot,
+        }
+    }
+
+    pub fn id(mut self, id: Id) -> Self {
+        self.id = id;
+        self
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct EquippableComponent {
     pub slot: EquipmentSlot,
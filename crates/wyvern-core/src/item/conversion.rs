@@ -1,15 +1,126 @@
 use voxidian_protocol::value::{
-    BlocksAttacksComp, CustomDataComp, DamageComp, DataComponentTypes, DataComponents,
-    EquippableComp, EquippableSlot, Identifier, ItemDamageFunction, ItemModelComp, ItemNameComp,
-    LengthPrefixVec, LoreComp, MaxDamageComp, Nbt as PtcNbt, NbtElement, RegOr, SlotData,
-    SoundEvent, Text, VarInt,
+    AttributeModifier as PtcAttributeModifier, AttributeModifiersComp,
+    AttributeOperation as PtcAttributeOperation, AttributeType, BannerPattern as PtcBannerPattern,
+    BannerPatternLayer, BannerPatternsComp, BlockPos, BlocksAttacksComp, CustomDataComp,
+    DamageComp, DataComponentTypes, DataComponents, DyeColor as PtcDyeColor, Enchantment,
+    EnchantmentGlintOverrideComp, EnchantmentsComp, EquipmentSlotGroup, EquippableComp,
+    EquippableSlot, GlobalPos, Identifier, ItemDamageFunction, ItemModelComp, ItemNameComp,
+    LengthPrefixVec, LodestoneTrackerComp, LoreComp, MaxDamageComp, Nbt as PtcNbt, NbtElement,
+    RegOr, SlotData, SoundEvent, Text, VarInt,
 };
 
 use wyvern_components::{DataComponentHolder, DataComponentMap};
 use wyvern_datatypes::nbt::Nbt;
-use wyvern_values::Id;
+use wyvern_values::{IVec3, Id};
 
-use super::{EquipmentSlot, EquippableComponent, ITEM_REGISTRY, ItemComponents, ItemStack};
+use crate::blocks::{BannerLayer, DyeColor};
+
+use super::{
+    AttributeModifier, AttributeOperation, AttributeSlot, EquipmentSlot, EquippableComponent,
+    ITEM_REGISTRY, ItemComponents, ItemStack, LodestoneTarget,
+};
+
+impl From<AttributeOperation> for PtcAttributeOperation {
+    fn from(value: AttributeOperation) -> Self {
+        match value {
+            AttributeOperation::AddValue => PtcAttributeOperation::AddValue,
+            AttributeOperation::AddMultipliedBase => PtcAttributeOperation::AddMultipliedBase,
+            AttributeOperation::AddMultipliedTotal => PtcAttributeOperation::AddMultipliedTotal,
+        }
+    }
+}
+
+impl From<PtcAttributeOperation> for AttributeOperation {
+    fn from(value: PtcAttributeOperation) -> Self {
+        match value {
+            PtcAttributeOperation::AddValue => AttributeOperation::AddValue,
+            PtcAttributeOperation::AddMultipliedBase => AttributeOperation::AddMultipliedBase,
+            PtcAttributeOperation::AddMultipliedTotal => AttributeOperation::AddMultipliedTotal,
+        }
+    }
+}
+
+impl From<DyeColor> for PtcDyeColor {
+    fn from(value: DyeColor) -> Self {
+        match value {
+            DyeColor::White => PtcDyeColor::White,
+            DyeColor::Orange => PtcDyeColor::Orange,
+            DyeColor::Magenta => PtcDyeColor::Magenta,
+            DyeColor::LightBlue => PtcDyeColor::LightBlue,
+            DyeColor::Yellow => PtcDyeColor::Yellow,
+            DyeColor::Lime => PtcDyeColor::Lime,
+            DyeColor::Pink => PtcDyeColor::Pink,
+            DyeColor::Gray => PtcDyeColor::Gray,
+            DyeColor::LightGray => PtcDyeColor::LightGray,
+            DyeColor::Cyan => PtcDyeColor::Cyan,
+            DyeColor::Purple => PtcDyeColor::Purple,
+            DyeColor::Blue => PtcDyeColor::Blue,
+            DyeColor::Brown => PtcDyeColor::Brown,
+            DyeColor::Green => PtcDyeColor::Green,
+            DyeColor::Red => PtcDyeColor::Red,
+            DyeColor::Black => PtcDyeColor::Black,
+        }
+    }
+}
+
+impl From<PtcDyeColor> for DyeColor {
+    fn from(value: PtcDyeColor) -> Self {
+        match value {
+            PtcDyeColor::White => DyeColor::White,
+            PtcDyeColor::Orange => DyeColor::Orange,
+            PtcDyeColor::Magenta => DyeColor::Magenta,
+            PtcDyeColor::LightBlue => DyeColor::LightBlue,
+            PtcDyeColor::Yellow => DyeColor::Yellow,
+            PtcDyeColor::Lime => DyeColor::Lime,
+            PtcDyeColor::Pink => DyeColor::Pink,
+            PtcDyeColor::Gray => DyeColor::Gray,
+            PtcDyeColor::LightGray => DyeColor::LightGray,
+            PtcDyeColor::Cyan => DyeColor::Cyan,
+            PtcDyeColor::Purple => DyeColor::Purple,
+            PtcDyeColor::Blue => DyeColor::Blue,
+            PtcDyeColor::Brown => DyeColor::Brown,
+            PtcDyeColor::Green => DyeColor::Green,
+            PtcDyeColor::Red => DyeColor::Red,
+            PtcDyeColor::Black => DyeColor::Black,
+        }
+    }
+}
+
+impl From<AttributeSlot> for EquipmentSlotGroup {
+    fn from(value: AttributeSlot) -> Self {
+        match value {
+            AttributeSlot::Any => EquipmentSlotGroup::Any,
+            AttributeSlot::Mainhand => EquipmentSlotGroup::MainHand,
+            AttributeSlot::Offhand => EquipmentSlotGroup::OffHand,
+            AttributeSlot::Hand => EquipmentSlotGroup::Hand,
+            AttributeSlot::Feet => EquipmentSlotGroup::Feet,
+            AttributeSlot::Legs => EquipmentSlotGroup::Legs,
+            AttributeSlot::Chest => EquipmentSlotGroup::Chest,
+            AttributeSlot::Head => EquipmentSlotGroup::Head,
+            AttributeSlot::Armor => EquipmentSlotGroup::Armor,
+            AttributeSlot::Body => EquipmentSlotGroup::Body,
+            AttributeSlot::Saddle => EquipmentSlotGroup::Saddle,
+        }
+    }
+}
+
+impl From<EquipmentSlotGroup> for AttributeSlot {
+    fn from(value: EquipmentSlotGroup) -> Self {
+        match value {
+            EquipmentSlotGroup::Any => AttributeSlot::Any,
+            EquipmentSlotGroup::MainHand => AttributeSlot::Mainhand,
+            EquipmentSlotGroup::OffHand => AttributeSlot::Offhand,
+            EquipmentSlotGroup::Hand => AttributeSlot::Hand,
+            EquipmentSlotGroup::Feet => AttributeSlot::Feet,
+            EquipmentSlotGroup::Legs => AttributeSlot::Legs,
+            EquipmentSlotGroup::Chest => AttributeSlot::Chest,
+            EquipmentSlotGroup::Head => AttributeSlot::Head,
+            EquipmentSlotGroup::Armor => AttributeSlot::Armor,
+            EquipmentSlotGroup::Body => AttributeSlot::Body,
+            EquipmentSlotGroup::Saddle => AttributeSlot::Saddle,
+        }
+    }
+}
 
 impl From<ItemStack> for SlotData {
     fn from(value: ItemStack) -> Self {
@@ -27,6 +138,47 @@ impl From<ItemStack> for SlotData {
             }));
             filtered_components.push(DataComponentTypes::MaxDamageComp);
         }
+        if let Ok(enchantments) = value.get(ItemComponents::ENCHANTMENTS) {
+            components.push(DataComponents::EnchantmentsComp(EnchantmentsComp {
+                enchantments: enchantments
+                    .into_iter()
+                    .filter_map(|(id, level)| {
+                        Enchantment::vanilla_registry()
+                            .get_entry(&id.into())
+                            .map(|entry| (entry, VarInt::new(level)))
+                    })
+                    .collect::<Vec<_>>()
+                    .into(),
+            }));
+            filtered_components.push(DataComponentTypes::EnchantmentsComp);
+        }
+        if let Ok(glint) = value.get(ItemComponents::ENCHANTMENT_GLINT_OVERRIDE) {
+            components.push(DataComponents::EnchantmentGlintOverrideComp(
+                EnchantmentGlintOverrideComp { has_glint: glint },
+            ));
+            filtered_components.push(DataComponentTypes::EnchantmentGlintOverrideComp);
+        }
+        if let Ok(modifiers) = value.get(ItemComponents::ATTRIBUTE_MODIFIERS) {
+            components.push(DataComponents::AttributeModifiersComp(
+                AttributeModifiersComp {
+                    modifiers: modifiers
+                        .into_iter()
+                        .filter_map(|modifier| {
+                            Some(PtcAttributeModifier {
+                                attribute: AttributeType::vanilla_registry()
+                                    .get_entry(&modifier.attribute.into())?,
+                                id: modifier.id.into(),
+                                amount: modifier.amount,
+                                operation: modifier.operation.into(),
+                                slot: modifier.slot.into(),
+                            })
+                        })
+                        .collect::<Vec<_>>()
+                        .into(),
+                },
+            ));
+            filtered_components.push(DataComponentTypes::AttributeModifiersComp);
+        }
         if let Ok(asset) = value.get(ItemComponents::ITEM_MODEL) {
             components.push(DataComponents::ItemModelComp(ItemModelComp {
                 asset: asset.into(),
@@ -107,6 +259,41 @@ impl From<ItemStack> for SlotData {
             }));
             filtered_components.push(DataComponentTypes::EquippableComp);
         }
+        if let Ok(layers) = value.get(ItemComponents::BANNER_PATTERNS) {
+            components.push(DataComponents::BannerPatternsComp(BannerPatternsComp {
+                layers: layers
+                    .into_iter()
+                    .map(|layer| BannerPatternLayer {
+                        // Patterns are sent inline, since their registry ids depend on the
+                        // server's registries, which aren't available here.
+                        pattern: RegOr::Or(PtcBannerPattern {
+                            translation_key: format!(
+                                "block.{}.banner.{}",
+                                layer.pattern.namespace(),
+                                layer.pattern.path()
+                            ),
+                            asset_id: layer.pattern.into(),
+                        }),
+                        color: layer.color.into(),
+                    })
+                    .collect::<Vec<_>>()
+                    .into(),
+            }));
+            filtered_components.push(DataComponentTypes::BannerPatternsComp);
+        }
+        if let Ok(target) = value.get(ItemComponents::LODESTONE_TARGET) {
+            let position = target.position;
+            components.push(DataComponents::LodestoneTrackerComp(LodestoneTrackerComp {
+                target: Some(GlobalPos {
+                    dimension: target.dimension.into(),
+                    pos: BlockPos::new(position[0], position[1], position[2]),
+                }),
+                // Tracked compasses are reset by the server when their lodestone is broken,
+                // which would clear targets that aren't lodestones.
+                tracked: false,
+            }));
+            filtered_components.push(DataComponentTypes::LodestoneTrackerComp);
+        }
 
         let count = value
             .get(ItemComponents::ITEM_COUNT)
@@ -154,6 +341,44 @@ impl From<SlotData> for ItemStack {
                 DataComponents::CustomDataComp(data) => {
                     map.set(ItemComponents::CUSTOM_DATA, data.data.root.into());
                 }
+                DataComponents::EnchantmentsComp(component) => {
+                    let enchantments = component
+                        .enchantments
+                        .iter()
+                        .filter_map(|(entry, level)| {
+                            Enchantment::vanilla_registry()
+                                .lookup(entry)
+                                .map(|enchantment| (enchantment.id.clone().into(), level.as_i32()))
+                        })
+                        .collect();
+                    map.set(ItemComponents::ENCHANTMENTS, enchantments);
+                }
+                DataComponents::EnchantmentGlintOverrideComp(component) => {
+                    map.set(
+                        ItemComponents::ENCHANTMENT_GLINT_OVERRIDE,
+                        component.has_glint,
+                    );
+                }
+                DataComponents::AttributeModifiersComp(component) => {
+                    let modifiers = component
+                        .modifiers
+                        .iter()
+                        .filter_map(|modifier| {
+                            Some(AttributeModifier {
+                                id: modifier.id.clone().into(),
+                                attribute: AttributeType::vanilla_registry()
+                                    .lookup(&modifier.attribute)?
+                                    .id
+                                    .clone()
+                                    .into(),
+                                amount: modifier.amount,
+                                operation: modifier.operation.clone().into(),
+                                slot: modifier.slot.clone().into(),
+                            })
+                        })
+                        .collect();
+                    map.set(ItemComponents::ATTRIBUTE_MODIFIERS, modifiers);
+                }
                 DataComponents::ItemModelComp(id) => {
                     map.set(ItemComponents::ITEM_MODEL, id.asset.into());
                 }
@@ -179,6 +404,35 @@ impl From<SlotData> for ItemStack {
                         },
                     );
                 }
+                DataComponents::BannerPatternsComp(component) => {
+                    let layers = component
+                        .layers
+                        .iter()
+                        .filter_map(|layer| match &layer.pattern {
+                            RegOr::Id(_) => None,
+                            RegOr::Or(pattern) => Some(BannerLayer {
+                                pattern: pattern.asset_id.clone().into(),
+                                color: layer.color.clone().into(),
+                            }),
+                        })
+                        .collect();
+                    map.set(ItemComponents::BANNER_PATTERNS, layers);
+                }
+                DataComponents::LodestoneTrackerComp(component) => {
+                    if let Some(target) = component.target {
+                        map.set(
+                            ItemComponents::LODESTONE_TARGET,
+                            LodestoneTarget {
+                                dimension: target.dimension.into(),
+                                position: IVec3::new(
+                                    target.pos.x(),
+                                    target.pos.y(),
+                                    target.pos.z(),
+                                ),
+                            },
+                        );
+                    }
+                }
                 _ => {}
             }
         }
@@ -188,3 +442,72 @@ impl From<SlotData> for ItemStack {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use voxidian_protocol::value::SlotData;
+    use wyvern_components::DataComponentHolder;
+    use wyvern_datatypes::{
+        nbt::{Nbt, NbtCompound},
+        text::Text,
+    };
+    use wyvern_values::{IVec3, id};
+
+    use crate::{
+        blocks::{Banner, DyeColor},
+        item::{ItemComponents, ItemStack, LodestoneTarget},
+    };
+
+    #[test]
+    fn lore_and_custom_data_round_trip() {
+        let mut data = NbtCompound::new();
+        data.set("kit_item", Nbt::new("starter_sword"));
+        let lore = vec![Text::literal("Given to new players")];
+        let item = ItemStack::new(id![minecraft:iron_sword])
+            .with(ItemComponents::LORE, lore.clone())
+            .with(ItemComponents::CUSTOM_DATA, data.clone());
+
+        let sent = ItemStack::from(SlotData::from(item));
+        assert_eq!(sent.get(ItemComponents::CUSTOM_DATA), Ok(data));
+        assert_eq!(sent.get(ItemComponents::LORE), Ok(lore));
+    }
+
+    #[test]
+    fn enchantments_round_trip() {
+        let enchantments = HashMap::from([
+            (id![minecraft:sharpness], 5),
+            (id![minecraft:unbreaking], 3),
+        ]);
+        let item = ItemStack::new(id![minecraft:diamond_sword])
+            .with(ItemComponents::ENCHANTMENTS, enchantments.clone());
+
+        let sent = ItemStack::from(SlotData::from(item));
+        assert_eq!(sent.get(ItemComponents::ENCHANTMENTS), Ok(enchantments));
+    }
+
+    #[test]
+    fn banner_patterns_round_trip() {
+        let item = Banner::new(DyeColor::White)
+            .pattern(id![minecraft:stripe_top], DyeColor::Red)
+            .pattern(id![minecraft:flower], DyeColor::LightBlue)
+            .item();
+
+        let sent = ItemStack::from(SlotData::from(item.clone()));
+        assert_eq!(
+            sent.get(ItemComponents::BANNER_PATTERNS),
+            item.get(ItemComponents::BANNER_PATTERNS)
+        );
+    }
+
+    #[test]
+    fn lodestone_targets_round_trip() {
+        let target = LodestoneTarget::new(id![minecraft:overworld], IVec3::new(12, 70, -340));
+        let item = ItemStack::new(id![minecraft:compass])
+            .with(ItemComponents::LODESTONE_TARGET, target.clone());
+
+        let sent = ItemStack::from(SlotData::from(item));
+        assert_eq!(sent.get(ItemComponents::LODESTONE_TARGET), Ok(target));
+    }
+}
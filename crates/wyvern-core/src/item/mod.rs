@@ -2,6 +2,8 @@ mod components;
 pub use components::*;
 use wyvern_components::{DataComponentHolder, DataComponentMap};
 mod conversion;
+mod recipe;
+pub use recipe::*;
 
 use std::sync::LazyLock;
 
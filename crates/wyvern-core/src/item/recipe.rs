@@ -0,0 +1,384 @@
+use std::collections::HashMap;
+
+use voxidian_protocol::value::{RecipeDisplay, SlotData, SlotDisplay};
+use wyvern_values::{Id, Registry, id};
+
+use crate::inventory::{is_empty, stackable};
+
+use super::{ITEM_REGISTRY, ItemStack};
+
+/// What an item in a crafting grid has to be to fill a spot in a recipe.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Ingredient {
+    /// Any item of one of these types, whatever its components.
+    AnyOf(Vec<Id>),
+    /// An item with the same type and components as this one. The count is ignored.
+    Exact(ItemStack),
+}
+
+impl Ingredient {
+    /// Any item of the given type.
+    pub fn item(id: Id) -> Ingredient {
+        Ingredient::AnyOf(vec![id])
+    }
+
+    pub fn matches(&self, item: &ItemStack) -> bool {
+        match self {
+            Ingredient::AnyOf(ids) => ids.contains(&item.kind()),
+            Ingredient::Exact(exact) => stackable(exact, item),
+        }
+    }
+
+    /// Returns how the client's recipe book shows this ingredient.
+    fn display(&self) -> SlotDisplay {
+        match self {
+            Ingredient::AnyOf(ids) => {
+                let mut items = ids
+                    .iter()
+                    .filter_map(|id| ITEM_REGISTRY.get_entry(&id.clone().into()))
+                    .map(SlotDisplay::Item)
+                    .collect::<Vec<_>>();
+                match items.len() {
+                    0 => SlotDisplay::Empty,
+                    1 => items.remove(0),
+                    _ => SlotDisplay::Composite(items.into()),
+                }
+            }
+            Ingredient::Exact(item) => SlotDisplay::ItemStack(SlotData::from(item.clone())),
+        }
+    }
+}
+
+/// A recipe whose ingredients have to be laid out in a pattern. The pattern can be placed
+/// anywhere in the grid, and mirrored left to right.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShapedRecipe {
+    pattern: Vec<Vec<char>>,
+    keys: HashMap<char, Ingredient>,
+    result: ItemStack,
+}
+
+impl ShapedRecipe {
+    /// Creates a recipe from rows of characters, where every character other than a space
+    /// stands for the ingredient set for it with `key`. The pattern shouldn't have empty rows
+    /// or columns around it.
+    pub fn new<S: AsRef<str>>(pattern: &[S], result: ItemStack) -> ShapedRecipe {
+        ShapedRecipe {
+            pattern: pattern
+                .iter()
+                .map(|row| row.as_ref().chars().collect())
+                .collect(),
+            keys: HashMap::new(),
+            result,
+        }
+    }
+
+    pub fn key(mut self, key: char, ingredient: Ingredient) -> Self {
+        self.keys.insert(key, ingredient);
+        self
+    }
+
+    fn width(&self) -> usize {
+        self.pattern.iter().map(Vec::len).max().unwrap_or(0)
+    }
+
+    /// Returns the ingredient for every spot in the pattern, row by row, with `None` for spaces
+    /// and for spots past the end of shorter rows.
+    fn cells(&self) -> Vec<Option<&Ingredient>> {
+        let width = self.width();
+        self.pattern
+            .iter()
+            .flat_map(|row| {
+                (0..width).map(|x| {
+                    row.get(x)
+                        .filter(|key| **key != ' ')
+                        .and_then(|key| self.keys.get(key))
+                })
+            })
+            .collect()
+    }
+
+    fn matches(&self, grid: &CraftingGrid) -> bool {
+        let Some((left, top, width, height)) = grid.bounds() else {
+            return false;
+        };
+        if width != self.width() || height != self.pattern.len() {
+            return false;
+        }
+        [false, true].into_iter().any(|mirrored| {
+            (0..height).all(|y| {
+                (0..width).all(|x| {
+                    let column = if mirrored { width - 1 - x } else { x };
+                    let item = grid.get(left + x, top + y);
+                    match self.pattern[y].get(column).filter(|key| **key != ' ') {
+                        Some(key) => self
+                            .keys
+                            .get(key)
+                            .is_some_and(|ingredient| ingredient.matches(item)),
+                        None => is_empty(item),
+                    }
+                })
+            })
+        })
+    }
+}
+
+/// A recipe whose ingredients can be anywhere in the grid.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShapelessRecipe {
+    ingredients: Vec<Ingredient>,
+    result: ItemStack,
+}
+
+impl ShapelessRecipe {
+    pub fn new(result: ItemStack) -> ShapelessRecipe {
+        ShapelessRecipe {
+            ingredients: Vec::new(),
+            result,
+        }
+    }
+
+    pub fn ingredient(mut self, ingredient: Ingredient) -> Self {
+        self.ingredients.push(ingredient);
+        self
+    }
+
+    fn matches(&self, grid: &CraftingGrid) -> bool {
+        let items = grid
+            .items
+            .iter()
+            .filter(|item| !is_empty(item))
+            .collect::<Vec<_>>();
+        let mut used = vec![false; self.ingredients.len()];
+        items.len() == self.ingredients.len() && self.assign(&items, &mut used)
+    }
+
+    /// Returns whether every item can be given a different ingredient it matches.
+    fn assign(&self, items: &[&ItemStack], used: &mut [bool]) -> bool {
+        let Some((item, rest)) = items.split_first() else {
+            return true;
+        };
+        for (index, ingredient) in self.ingredients.iter().enumerate() {
+            if used[index] || !ingredient.matches(item) {
+                continue;
+            }
+            used[index] = true;
+            if self.assign(rest, used) {
+                return true;
+            }
+            used[index] = false;
+        }
+        false
+    }
+}
+
+/// A crafting recipe, registered under `RegistryKeys::RECIPE`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Recipe {
+    Shaped(ShapedRecipe),
+    Shapeless(ShapelessRecipe),
+}
+
+impl Recipe {
+    pub fn result(&self) -> &ItemStack {
+        match self {
+            Recipe::Shaped(recipe) => &recipe.result,
+            Recipe::Shapeless(recipe) => &recipe.result,
+        }
+    }
+
+    pub fn matches(&self, grid: &CraftingGrid) -> bool {
+        match self {
+            Recipe::Shaped(recipe) => recipe.matches(grid),
+            Recipe::Shapeless(recipe) => recipe.matches(grid),
+        }
+    }
+
+    /// Returns how the client's recipe book shows this recipe.
+    pub(crate) fn display(&self) -> RecipeDisplay {
+        let result = SlotDisplay::ItemStack(SlotData::from(self.result().clone()));
+        let crafting_station = ITEM_REGISTRY
+            .get_entry(&id![minecraft:crafting_table].into())
+            .map_or(SlotDisplay::Empty, SlotDisplay::Item);
+        match self {
+            Recipe::Shaped(recipe) => RecipeDisplay::CraftingShaped {
+                width: (recipe.width() as i32).into(),
+                height: (recipe.pattern.len() as i32).into(),
+                ingredients: recipe
+                    .cells()
+                    .into_iter()
+                    .map(|cell| cell.map_or(SlotDisplay::Empty, Ingredient::display))
+                    .collect::<Vec<_>>()
+                    .into(),
+                result,
+                crafting_station,
+            },
+            Recipe::Shapeless(recipe) => RecipeDisplay::CraftingShapeless {
+                ingredients: recipe
+                    .ingredients
+                    .iter()
+                    .map(Ingredient::display)
+                    .collect::<Vec<_>>()
+                    .into(),
+                result,
+                crafting_station,
+            },
+        }
+    }
+}
+
+impl From<ShapedRecipe> for Recipe {
+    fn from(value: ShapedRecipe) -> Self {
+        Recipe::Shaped(value)
+    }
+}
+
+impl From<ShapelessRecipe> for Recipe {
+    fn from(value: ShapelessRecipe) -> Self {
+        Recipe::Shapeless(value)
+    }
+}
+
+/// The items in a square crafting grid, row by row.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CraftingGrid {
+    pub width: usize,
+    pub items: Vec<ItemStack>,
+}
+
+impl CraftingGrid {
+    fn get(&self, x: usize, y: usize) -> &ItemStack {
+        &self.items[y * self.width + x]
+    }
+
+    /// Returns the left, top, width and height of the smallest area holding every item.
+    fn bounds(&self) -> Option<(usize, usize, usize, usize)> {
+        let filled = self
+            .items
+            .iter()
+            .enumerate()
+            .filter(|(_, item)| !is_empty(item))
+            .map(|(index, _)| (index % self.width, index / self.width))
+            .collect::<Vec<_>>();
+        let left = filled.iter().map(|(x, _)| *x).min()?;
+        let right = filled.iter().map(|(x, _)| *x).max()?;
+        let top = filled.iter().map(|(_, y)| *y).min()?;
+        let bottom = filled.iter().map(|(_, y)| *y).max()?;
+        Some((left, top, right - left + 1, bottom - top + 1))
+    }
+
+    /// Returns the result of the first recipe in `recipes` the grid matches, or air if there
+    /// is none.
+    pub fn craft(&self, recipes: &Registry<Recipe>) -> ItemStack {
+        recipes
+            .entries()
+            .find(|(_, recipe)| recipe.matches(self))
+            .map(|(_, recipe)| recipe.result().clone())
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wyvern_components::DataComponentHolder;
+    use wyvern_datatypes::text::Text;
+    use wyvern_values::id;
+
+    use crate::item::{ItemComponents, ItemStack};
+
+    use super::{CraftingGrid, Ingredient, ShapedRecipe, ShapelessRecipe};
+
+    fn grid(width: usize, items: &[Option<ItemStack>]) -> CraftingGrid {
+        CraftingGrid {
+            width,
+            items: items
+                .iter()
+                .map(|x| x.clone().unwrap_or_default())
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn shaped_recipes_can_move_and_mirror() {
+        let stick = ItemStack::new(id![minecraft:stick]);
+        let plank = ItemStack::new(id![minecraft:oak_planks]);
+        let hoe = ShapedRecipe::new(
+            &["##", " |", " |"],
+            ItemStack::new(id![minecraft:wooden_hoe]),
+        )
+        .key('#', Ingredient::item(id![minecraft:oak_planks]))
+        .key('|', Ingredient::item(id![minecraft:stick]));
+
+        let (p, s) = (Some(plank.clone()), Some(stick.clone()));
+        assert!(hoe.matches(&grid(
+            3,
+            &[
+                None,
+                p.clone(),
+                p.clone(),
+                None,
+                None,
+                s.clone(),
+                None,
+                None,
+                s.clone()
+            ]
+        )));
+        assert!(hoe.matches(&grid(
+            3,
+            &[
+                p.clone(),
+                p.clone(),
+                None,
+                s.clone(),
+                None,
+                None,
+                s.clone(),
+                None,
+                None
+            ]
+        )));
+        assert!(!hoe.matches(&grid(
+            3,
+            &[
+                p.clone(),
+                p.clone(),
+                None,
+                None,
+                s.clone(),
+                None,
+                s.clone(),
+                None,
+                None
+            ]
+        )));
+        assert!(!hoe.matches(&grid(2, &[p.clone(), p, None, s])));
+    }
+
+    #[test]
+    fn shaped_recipe_cells_fill_short_rows() {
+        let recipe = ShapedRecipe::new(&["#|", "#"], ItemStack::new(id![minecraft:stone_axe]))
+            .key('#', Ingredient::item(id![minecraft:cobblestone]))
+            .key('|', Ingredient::item(id![minecraft:stick]));
+        let cobblestone = Ingredient::item(id![minecraft:cobblestone]);
+        let stick = Ingredient::item(id![minecraft:stick]);
+
+        assert_eq!(
+            recipe.cells(),
+            vec![Some(&cobblestone), Some(&stick), Some(&cobblestone), None]
+        );
+    }
+
+    #[test]
+    fn shapeless_recipes_respect_components() {
+        let named = ItemStack::new(id![minecraft:paper])
+            .with(ItemComponents::ITEM_NAME, Text::literal("Ticket"));
+        let recipe = ShapelessRecipe::new(ItemStack::new(id![minecraft:map]))
+            .ingredient(Ingredient::Exact(named.clone()))
+            .ingredient(Ingredient::item(id![minecraft:paper]));
+
+        let paper = Some(ItemStack::new(id![minecraft:paper]));
+        assert!(recipe.matches(&grid(2, &[paper.clone(), None, None, Some(named)])));
+        assert!(!recipe.matches(&grid(2, &[paper.clone(), None, None, paper])));
+    }
+}
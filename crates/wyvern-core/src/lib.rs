@@ -5,6 +5,7 @@ pub(crate) mod actors {
     pub use wyvern_actors::*;
 }
 pub mod blocks;
+pub mod commands;
 pub mod dimension;
 pub mod entities;
 pub mod events;
@@ -0,0 +1,50 @@
+use std::time::{Duration, Instant};
+
+use wyvern_components::DataComponentHolder;
+
+use crate::{
+    actors::ActorResult,
+    entities::{Attributes, TICK_LENGTH, default_attribute_value},
+};
+
+use super::ConnectionData;
+
+/// Returns how charged an attack is, from 0 to 1, when `since` has passed since the attacker
+/// last attacked or switched items, following vanilla's attack cooldown.
+pub(crate) fn attack_strength(since: Duration, attack_speed: f64) -> f32 {
+    let cooldown_ticks = 20.0 / attack_speed;
+    let ticks = since.as_secs_f64() / TICK_LENGTH.as_secs_f64();
+    ((ticks + 0.5) / cooldown_ticks).clamp(0.0, 1.0) as f32
+}
+
+impl ConnectionData {
+    /// Returns how charged the player's attack is, and starts their attack cooldown over.
+    pub(crate) fn take_attack_strength(&mut self) -> ActorResult<f32> {
+        let now = Instant::now();
+        let attack_speed = self
+            .effective_attributes()?
+            .get(Attributes::ATTACK_SPEED)
+            .unwrap_or_else(|_| default_attribute_value(Attributes::ATTACK_SPEED.name()));
+        let strength = match self.associated_data.last_attack_reset {
+            Some(reset) => attack_strength(now.duration_since(reset), attack_speed),
+            None => 1.0,
+        };
+        self.associated_data.last_attack_reset = Some(now);
+        Ok(strength)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::attack_strength;
+
+    #[test]
+    fn attacks_charge_with_attack_speed() {
+        assert_eq!(attack_strength(Duration::ZERO, 4.0), 0.1);
+        assert_eq!(attack_strength(Duration::from_millis(100), 4.0), 0.5);
+        assert_eq!(attack_strength(Duration::from_secs(1), 4.0), 1.0);
+        assert_eq!(attack_strength(Duration::ZERO, 100.0), 1.0);
+    }
+}
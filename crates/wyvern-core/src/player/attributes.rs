@@ -0,0 +1,39 @@
+use wyvern_components::DataComponentHolder;
+
+use crate::item::{AttributeModifier, EquipmentSlot, ItemComponents, ItemStack};
+
+use super::ConnectionData;
+
+impl ConnectionData {
+    /// Returns the items the player holds and wears, with the slot each is in.
+    fn equipment(&self) -> Vec<(EquipmentSlot, ItemStack)> {
+        [
+            (
+                EquipmentSlot::Mainhand,
+                self.associated_data.held_slot as usize,
+            ),
+            (EquipmentSlot::Offhand, 45),
+            (EquipmentSlot::Helmet, 5),
+            (EquipmentSlot::Chestplate, 6),
+            (EquipmentSlot::Leggings, 7),
+            (EquipmentSlot::Boots, 8),
+        ]
+        .into_iter()
+        .filter_map(|(slot, index)| Some((slot, self.get_inv_slot(index).ok()?)))
+        .collect()
+    }
+
+    /// Returns the attribute modifiers of every item the player holds or wears that apply in
+    /// the slot the item is in.
+    pub(crate) fn equipment_modifiers(&self) -> Vec<AttributeModifier> {
+        self.equipment()
+            .into_iter()
+            .flat_map(|(slot, item)| {
+                item.get(ItemComponents::ATTRIBUTE_MODIFIERS)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter(move |modifier| modifier.slot.contains(&slot))
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,32 @@
+use std::time::Instant;
+
+use wyvern_components::DataComponentHolder;
+
+use crate::actors::ActorResult;
+
+use super::{ConnectionData, PlayerComponents};
+
+impl ConnectionData {
+    /// Shows the player the world border of their dimension.
+    pub(crate) fn send_world_border(&mut self) -> ActorResult<()> {
+        if let Some(dimension) = self.associated_data.dimension.clone() {
+            self.write_packet(dimension.world_border_state()?.initialize_packet());
+        }
+        Ok(())
+    }
+
+    /// Moves the player back inside their dimension's world border if they moved past it.
+    pub(crate) fn enforce_world_border(&mut self) -> ActorResult<()> {
+        let Some(dimension) = self.associated_data.dimension.clone() else {
+            return Ok(());
+        };
+        let border = dimension.world_border_state()?.at(Instant::now());
+        let position = self.get(PlayerComponents::POSITION)?;
+        let clamped = border.clamp(position);
+        if clamped != position {
+            self.set(PlayerComponents::POSITION, clamped);
+            self.set(PlayerComponents::TELEPORT_POSITION, clamped);
+        }
+        Ok(())
+    }
+}
@@ -3,14 +3,22 @@ use voxidian_protocol::{
         ChunkBatchFinishedS2CPlayPacket, ChunkBatchStartS2CPlayPacket,
         LevelChunkWithLightS2CPlayPacket, SetChunkCacheCenterS2CPlayPacket,
     },
-    value::{ChunkSectionData, LengthPrefixVec, VarInt},
+    value::{ChunkSectionData, VarInt},
 };
 use wyvern_components::DataComponentHolder;
 
-use crate::{actors::ActorResult, runtime::Runtime, server::registries::RegistryKeys};
+use crate::{
+    actors::ActorResult,
+    dimension::Dimension,
+    runtime::Runtime,
+    server::{
+        Server,
+        registries::{RegistryContainer, RegistryKeys},
+    },
+};
 use wyvern_values::{IVec2, IVec3};
 
-use super::{ConnectionData, PlayerComponents};
+use super::{ConnectionData, Player, PlayerComponents};
 
 impl ConnectionData {
     pub fn send_chunks(&mut self) -> ActorResult<()> {
@@ -42,12 +50,17 @@ impl ConnectionData {
             })
             .copied()
             .collect::<Vec<_>>();
+        self.forget_unloaded_fake_blocks();
 
+        // Chunks past the dimension's limits are never generated, so they're never queued.
+        let (max_x, max_z) = dimension.chunk_limits()?;
         let mut chunks = Vec::new();
         for chunk_x in (cx - render_distance)..(cx + render_distance) {
             for chunk_z in (cz - render_distance)..(cz + render_distance) {
                 let pos = IVec2::new(chunk_x, chunk_z);
-                if !self.associated_data.loaded_chunks.contains(&pos) {
+                if !self.associated_data.loaded_chunks.contains(&pos)
+                    && within_limits(pos, max_x, max_z)
+                {
                     chunks.push(pos);
                 }
             }
@@ -59,74 +72,200 @@ impl ConnectionData {
             lhs_dist.cmp(&rhs_dist)
         });
 
+        if self.associated_data.unacknowledged_chunk_batches >= MAX_UNACKNOWLEDGED_BATCHES {
+            return Ok(());
+        }
+
+        let len = batch_len(
+            self.connected_server.chunk_batch_size()?,
+            self.connected_server.target_chunks_per_tick()?,
+            self.associated_data.client_chunks_per_tick,
+        );
+        chunks.truncate(len);
+        if chunks.is_empty() {
+            return Ok(());
+        }
+        self.associated_data
+            .loaded_chunks
+            .extend(chunks.iter().copied());
+        self.associated_data.unacknowledged_chunk_batches += 1;
+
         let player = self.as_actor();
         let server = self.connected_server.clone();
 
-        if let Some(pos) = chunks.first() {
-            let pos = *pos;
-            self.associated_data.loaded_chunks.push(pos);
+        // The chunks are marked as sent up front so the next tick doesn't send them again, and
+        // handed back if they turn out not to be.
+        Runtime::spawn_task(async move {
+            match send_batch(&player, &server, &dimension, chunk_center, &chunks).await {
+                Ok(unsent) => {
+                    if !unsent.is_empty() {
+                        player.forget_unsent_chunks(unsent, true)?;
+                    }
+                    Ok(())
+                }
+                Err(err) => {
+                    player.forget_unsent_chunks(chunks, false)?;
+                    Err(err)
+                }
+            }
+        });
 
-            Runtime::spawn_task(async move {
-                let dim_type_entry = dimension.dimension_type().unwrap();
+        Ok(())
+    }
 
-                let (min_y, max_y) = {
-                    let registries = server.registries().unwrap();
-                    let dim_type = registries
-                        .get(RegistryKeys::DIMENSION_TYPE)
-                        .get(dim_type_entry)
-                        .unwrap();
+    /// Hands back chunks from a batch that weren't sent, so they're sent again later. If the batch
+    /// itself never reached the player, it no longer counts towards the unacknowledged batches.
+    pub(crate) fn forget_chunks(&mut self, chunks: &[IVec2], batch_sent: bool) {
+        self.associated_data
+            .loaded_chunks
+            .retain(|pos| !chunks.contains(pos));
+        if !batch_sent {
+            self.associated_data.unacknowledged_chunk_batches = self
+                .associated_data
+                .unacknowledged_chunk_batches
+                .saturating_sub(1);
+        }
+    }
+}
 
-                    let min_y = dim_type.min_y;
-                    let max_y = dim_type.min_y + dim_type.height as i32;
+/// Sends a batch of chunks to a player, returning the chunks that weren't loaded yet and so
+/// weren't sent.
+async fn send_batch(
+    player: &Player,
+    server: &Server,
+    dimension: &Dimension,
+    chunk_center: IVec2,
+    chunks: &[IVec2],
+) -> ActorResult<Vec<IVec2>> {
+    let dim_type_entry = dimension.dimension_type_async().await?;
 
-                    (min_y, max_y)
-                };
+    let registries = server.registries_async().await?;
+    let (min_y, max_y) = {
+        let dim_type = registries
+            .get(RegistryKeys::DIMENSION_TYPE)
+            .get(dim_type_entry)
+            .unwrap();
 
-                let chunk_x = pos[0];
-                let chunk_z = pos[1];
+        let min_y = dim_type.min_y;
+        let max_y = dim_type.min_y + dim_type.height as i32;
 
-                let mut sections = Vec::new();
+        (min_y, max_y)
+    };
 
-                for y in (min_y..max_y).step_by(16) {
-                    let pos = IVec3::new(chunk_x, y, chunk_z);
+    let mut packets = Vec::new();
+    let mut unsent = Vec::new();
+    for pos in chunks {
+        match chunk_packet(dimension, &registries, *pos, min_y, max_y).await? {
+            Some(packet) => packets.push(packet),
+            None => unsent.push(*pos),
+        }
+    }
 
-                    let chunk = dimension.get_chunk_section(pos)?;
-                    let Some(chunk) = chunk else {
-                        return Ok(());
-                    };
-                    sections.push(chunk.as_protocol_section());
-                }
+    player.write_packet(SetChunkCacheCenterS2CPlayPacket {
+        chunk_x: chunk_center[0].into(),
+        chunk_z: chunk_center[1].into(),
+    })?;
+    player.write_packet(ChunkBatchStartS2CPlayPacket {})?;
+    let size = packets.len() as i32;
+    for packet in packets {
+        player.write_packet(packet)?;
+    }
+    player.write_packet(ChunkBatchFinishedS2CPlayPacket {
+        size: VarInt::from(size),
+    })?;
 
-                let packet = LevelChunkWithLightS2CPlayPacket {
-                    chunk_x,
-                    chunk_z,
-                    heightmaps: LengthPrefixVec::new(),
-                    data: ChunkSectionData { sections },
-                    block_entities: dimension
-                        .get_chunk_block_entities(IVec2::new(chunk_x, chunk_z))?
-                        .into(),
-                    sky_light_mask: vec![0].into(),
-                    block_light_mask: vec![0].into(),
-                    empty_sky_light_mask: vec![0].into(),
-                    empty_block_light_mask: vec![0].into(),
-                    sky_light_array: vec![].into(),
-                    block_light_array: vec![].into(),
-                };
-
-                player.write_packet(SetChunkCacheCenterS2CPlayPacket {
-                    chunk_x: chunk_center[0].into(),
-                    chunk_z: chunk_center[1].into(),
-                })?;
-                player.write_packet(ChunkBatchStartS2CPlayPacket {})?;
-                player.write_packet(packet).unwrap();
-                player.write_packet(ChunkBatchFinishedS2CPlayPacket {
-                    size: VarInt::from(1),
-                })?;
-
-                Ok(())
-            });
-        }
+    Ok(unsent)
+}
 
-        Ok(())
+/// The most chunk batches that can be sent to a player before it acknowledges any of them.
+const MAX_UNACKNOWLEDGED_BATCHES: u32 = 4;
+
+/// Returns how many chunks to put in the next batch, sending no more per batch than the server
+/// targets per tick or the client last said it could handle.
+pub(crate) fn batch_len(
+    batch_size: usize,
+    target_per_tick: f32,
+    client_per_tick: Option<f32>,
+) -> usize {
+    let per_tick = client_per_tick.map_or(target_per_tick, |client| client.min(target_per_tick));
+    (per_tick.ceil() as usize).clamp(1, batch_size.max(1))
+}
+
+/// Returns true if a chunk is within a dimension's chunk limits, matching the chunks
+/// `Dimension::max_chunks` allows to be generated.
+fn within_limits(pos: IVec2, max_x: u32, max_z: u32) -> bool {
+    i64::from(pos[0]) <= i64::from(max_x) && i64::from(pos[1]) <= i64::from(max_z)
+}
+
+async fn chunk_packet(
+    dimension: &Dimension,
+    registries: &RegistryContainer,
+    pos: IVec2,
+    min_y: i32,
+    max_y: i32,
+) -> ActorResult<Option<LevelChunkWithLightS2CPlayPacket>> {
+    dimension.load_chunk_async(pos).await?;
+
+    let mut sections = Vec::new();
+    for y in (min_y..max_y).step_by(16) {
+        let Some(section) = dimension
+            .try_get_chunk_section_async(IVec3::new(pos[0], y, pos[1]))
+            .await?
+        else {
+            return Ok(None);
+        };
+        sections.push(section.as_protocol_section(registries));
+    }
+
+    let Some(light) = dimension.get_chunk_light_async(pos).await? else {
+        return Ok(None);
+    };
+
+    Ok(Some(LevelChunkWithLightS2CPlayPacket {
+        chunk_x: pos[0],
+        chunk_z: pos[1],
+        heightmaps: dimension.get_chunk_heightmaps_async(pos).await?.into(),
+        data: ChunkSectionData { sections },
+        block_entities: dimension.get_chunk_block_entities_async(pos).await?.into(),
+        sky_light_mask: light.sky_mask.into(),
+        block_light_mask: light.block_mask.into(),
+        empty_sky_light_mask: light.empty_sky_mask.into(),
+        empty_block_light_mask: light.empty_block_mask.into(),
+        sky_light_array: light
+            .sky_arrays
+            .into_iter()
+            .map(|array| array.into())
+            .collect::<Vec<_>>()
+            .into(),
+        block_light_array: light
+            .block_arrays
+            .into_iter()
+            .map(|array| array.into())
+            .collect::<Vec<_>>()
+            .into(),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use wyvern_values::IVec2;
+
+    use super::{batch_len, within_limits};
+
+    #[test]
+    fn batch_len_respects_limits() {
+        assert_eq!(batch_len(16, 9.0, None), 9);
+        assert_eq!(batch_len(16, 32.0, None), 16);
+        assert_eq!(batch_len(16, 9.0, Some(2.5)), 3);
+        assert_eq!(batch_len(16, 9.0, Some(0.0)), 1);
+    }
+
+    #[test]
+    fn chunks_past_the_limits_are_skipped() {
+        assert!(within_limits(IVec2::new(4, 4), 4, 4));
+        assert!(within_limits(IVec2::new(-20, 0), 4, 4));
+        assert!(!within_limits(IVec2::new(5, 0), 4, 4));
+        assert!(!within_limits(IVec2::new(0, 5), 4, 4));
+        assert!(within_limits(IVec2::new(i32::MAX, 0), i32::MAX as u32, 0));
     }
 }
@@ -0,0 +1,275 @@
+use std::{collections::HashSet, ops::Range};
+
+use voxidian_protocol::{
+    packet::s2c::play::{
+        ContainerSetSlotS2CPlayPacket, ScreenWindowKind, SetCursorItemS2CPlayPacket,
+    },
+    value::VarInt,
+};
+use wyvern_components::DataComponentHolder;
+use wyvern_datatypes::{gamemode::Gamemode, window::InventoryKind};
+
+use crate::{
+    actors::{Actor, ActorResult},
+    events::MenuClickEvent,
+    inventory::{
+        ClickAction, ClickKind, ClickWindow, Inventory, ResultInputs, ResultLayout, ScreenSlot,
+        is_empty,
+    },
+    item::{CraftingGrid, ItemStack},
+    server::registries::RegistryKeys,
+};
+
+use super::{ConnectionData, PlayerComponents};
+
+/// The slot of the player's own inventory holding the result of its crafting grid.
+const CRAFTING_RESULT_SLOT: usize = 0;
+/// The slots of the player's own inventory making up its 2x2 crafting grid.
+const CRAFTING_GRID_SLOTS: Range<usize> = 1..5;
+/// The slot of the player's own inventory holding their offhand item.
+const OFFHAND_SLOT: usize = 45;
+
+impl ConnectionData {
+    /// Applies a click in the player's open window, instead of trusting the slots the client
+    /// says changed. If the client predicted something else, its window is corrected.
+    pub(crate) fn container_click(
+        &mut self,
+        slot: i16,
+        button: i8,
+        mode: i32,
+        changed_slots: Vec<(i16, ItemStack)>,
+        cursor: ItemStack,
+    ) -> ActorResult<()> {
+        let mut window = self.click_window();
+        let before = window.slots.clone();
+        let offhand_before = window.offhand.clone();
+
+        let action = ClickAction::decode(mode, button, slot);
+        let screen_click = self
+            .associated_data
+            .screen
+            .as_ref()
+            .map(|(kind, _)| *kind)
+            .zip(action.and_then(ClickAction::clicked_slot))
+            .zip(ClickKind::decode(mode, button));
+        if let Some(((screen, index), click_kind)) = screen_click {
+            let slot = match window.inventory_slot(index) {
+                Some(slot) => ScreenSlot::Inventory(slot),
+                None => ScreenSlot::Container(index),
+            };
+            self.connected_server.spawn_event(MenuClickEvent {
+                player: self.as_actor(),
+                screen,
+                slot,
+                button: click_kind.button(),
+                shift: click_kind.is_shift(),
+                click_kind,
+                locked: window.locked.contains(&index),
+            })?;
+        }
+
+        if !action.is_some_and(|action| window.apply(action)) {
+            self.associated_data.drag = None;
+            let window = self.click_window();
+            self.resync_window(&window);
+            return Ok(());
+        }
+
+        if let Some((slot, item)) = self.screen_result(&window.slots) {
+            window.slots[slot] = item;
+        }
+
+        let mut predicted = before.clone();
+        for (slot, item) in changed_slots {
+            if let Some(predicted) = usize::try_from(slot)
+                .ok()
+                .and_then(|slot| predicted.get_mut(slot))
+            {
+                *predicted = item;
+            }
+        }
+        let diverged = !same_item(&cursor, &window.cursor)
+            || predicted
+                .iter()
+                .zip(window.slots.iter())
+                .any(|(predicted, actual)| !same_item(predicted, actual));
+
+        for (index, item) in window.slots.iter().enumerate() {
+            if *item == before[index] {
+                continue;
+            }
+            match window.inventory_slot(index) {
+                Some(slot) => self
+                    .associated_data
+                    .inventory
+                    .set_slot(slot, item.clone())?,
+                None => {
+                    if let Some((_, container)) = &mut self.associated_data.screen {
+                        container.set_slot(index, item.clone())?;
+                    }
+                }
+            }
+        }
+        if window.offhand != offhand_before
+            && let Some(offhand) = &window.offhand
+        {
+            self.associated_data
+                .inventory
+                .set_slot(OFFHAND_SLOT, offhand.clone())?;
+        }
+        self.associated_data.cursor_item = window.cursor.clone();
+        self.associated_data.drag = window.drag.clone();
+
+        for item in window.dropped.drain(..) {
+            self.toss_item(item)?;
+        }
+
+        if diverged {
+            self.resync_window(&window);
+        }
+        Ok(())
+    }
+
+    /// Sends every slot of the player's open window, such as after their inventory was changed
+    /// without them clicking.
+    pub(crate) fn send_inventory(&mut self) {
+        let window = self.click_window();
+        self.resync_window(&window);
+    }
+
+    /// Returns the result slot of the open window and what it should hold. Screens with a result
+    /// callback use it, while crafting grids without one use the server's recipes.
+    fn screen_result(&self, slots: &[ItemStack]) -> Option<(usize, ItemStack)> {
+        let Some((kind, _)) = self.associated_data.screen.as_ref() else {
+            let grid = slots.get(CRAFTING_GRID_SLOTS)?.to_vec();
+            return Some((CRAFTING_RESULT_SLOT, self.craft(grid, 2)?));
+        };
+        let slot = kind.result_slot()?;
+        let items = slots.get(kind.input_slots())?.to_vec();
+        match &self.associated_data.screen_result {
+            Some(compute) => {
+                let inputs = ResultInputs {
+                    items,
+                    rename: self.associated_data.anvil_rename.clone(),
+                };
+                Some((slot, compute.compute(&inputs)))
+            }
+            None if *kind == InventoryKind::Crafting => Some((slot, self.craft(items, 3)?)),
+            None => None,
+        }
+    }
+
+    /// Returns what the first recipe matching a crafting grid of the given width makes.
+    fn craft(&self, items: Vec<ItemStack>, width: usize) -> Option<ItemStack> {
+        let registries = self.connected_server.registries().ok()?;
+        Some(CraftingGrid { width, items }.craft(registries.get(RegistryKeys::RECIPE)))
+    }
+
+    /// Recomputes the result slot of the open window and sends it to the player.
+    pub(crate) fn update_screen_result(&mut self) -> ActorResult<()> {
+        let window = self.click_window();
+        let Some((slot, item)) = self.screen_result(&window.slots) else {
+            return Ok(());
+        };
+        let window_id = match &mut self.associated_data.screen {
+            Some((_, container)) => {
+                container.set_slot(slot, item.clone())?;
+                self.associated_data.window_id as i32
+            }
+            None => {
+                self.associated_data
+                    .inventory
+                    .set_slot(slot, item.clone())?;
+                0
+            }
+        };
+        self.write_packet(ContainerSetSlotS2CPlayPacket {
+            window_id: VarInt::new(window_id),
+            state_id: VarInt::new(0),
+            slot: slot as i16,
+            slot_data: item.into(),
+        });
+        Ok(())
+    }
+
+    /// Builds the window the player currently has open, which is their own inventory if no
+    /// screen is open.
+    fn click_window(&self) -> ClickWindow {
+        let inventory = &self.associated_data.inventory;
+        let player_slot = |slot| inventory.get_slot(slot).unwrap_or_default();
+        let (slots, container_slots) = match &self.associated_data.screen {
+            Some((kind, container)) => {
+                let container_slots = ScreenWindowKind::from(*kind).container_slot_count();
+                let slots = (0..container_slots)
+                    .map(|slot| container.get_slot(slot).unwrap_or_default())
+                    .chain((9..45).map(player_slot))
+                    .collect();
+                (slots, container_slots)
+            }
+            None => ((0..46).map(player_slot).collect(), 0),
+        };
+        ClickWindow {
+            slots,
+            cursor: self.associated_data.cursor_item.clone(),
+            container_slots,
+            offhand: self
+                .associated_data
+                .screen
+                .as_ref()
+                .map(|_| player_slot(OFFHAND_SLOT)),
+            creative: self.get(PlayerComponents::GAMEMODE) == Ok(Gamemode::Creative),
+            drag: self.associated_data.drag.clone(),
+            dropped: Vec::new(),
+            locked: match self.associated_data.screen {
+                Some(_) => self.associated_data.locked_slots.clone(),
+                None => HashSet::new(),
+            },
+            result: match &self.associated_data.screen {
+                Some((kind, _)) => kind.result_slot().map(|slot| ResultLayout {
+                    slot,
+                    inputs: kind.input_slots(),
+                    consume_whole_first: *kind == InventoryKind::Anvil,
+                }),
+                None => Some(ResultLayout {
+                    slot: CRAFTING_RESULT_SLOT,
+                    inputs: CRAFTING_GRID_SLOTS,
+                    consume_whole_first: false,
+                }),
+            },
+        }
+    }
+
+    /// Sends every slot of the window and the cursor, overwriting what the client predicted.
+    fn resync_window(&mut self, window: &ClickWindow) {
+        let window_id = if self.associated_data.screen.is_some() {
+            self.associated_data.window_id as i32
+        } else {
+            0
+        };
+        for (index, item) in window.slots.iter().enumerate() {
+            self.write_packet(ContainerSetSlotS2CPlayPacket {
+                window_id: VarInt::new(window_id),
+                state_id: VarInt::new(0),
+                slot: index as i16,
+                slot_data: item.clone().into(),
+            });
+        }
+        if let Some(offhand) = &window.offhand {
+            // The offhand isn't in a container's window, so it's set in the player's inventory.
+            self.write_packet(ContainerSetSlotS2CPlayPacket {
+                window_id: VarInt::new(0),
+                state_id: VarInt::new(0),
+                slot: OFFHAND_SLOT as i16,
+                slot_data: offhand.clone().into(),
+            });
+        }
+        self.write_packet(SetCursorItemS2CPlayPacket {
+            slot_data: window.cursor.clone().into(),
+        });
+    }
+}
+
+/// Whether the client and server agree on what is in a slot.
+fn same_item(a: &ItemStack, b: &ItemStack) -> bool {
+    (is_empty(a) && is_empty(b)) || a == b
+}
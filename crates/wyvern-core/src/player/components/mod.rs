@@ -14,7 +14,7 @@ use wyvern_values::{DVec2, DVec3, Vec2, id};
 
 pub mod update;
 
-use super::Player;
+use super::{Player, PlayerInputs, scoreboard::ScoreFormat};
 
 pub struct PlayerComponents;
 
@@ -31,8 +31,43 @@ impl PlayerComponents {
         DataComponentType::new(id![minecraft:attributes]);
     pub const INPUT_FLAGS: DataComponentType<InputFlags> =
         DataComponentType::new(id![minecraft:input_flags]);
+    /// The movement keys the player is holding. Updated from the client's input, so setting it
+    /// doesn't move the player.
+    pub const INPUTS: DataComponentType<PlayerInputs> =
+        DataComponentType::new(id![minecraft:inputs]);
+    /// Whether the player is holding the sneak key. Updated from the client's input, so setting
+    /// it doesn't make the player sneak.
+    pub const SNEAKING: DataComponentType<bool> = DataComponentType::new(id![minecraft:sneaking]);
+    /// Whether the player is holding the sprint key while moving forward and not sneaking.
+    /// Updated from the client's input, so setting it doesn't make the player sprint.
+    pub const SPRINTING: DataComponentType<bool> = DataComponentType::new(id![minecraft:sprinting]);
+    /// Whether the player is standing on the ground. The client reports this with its movement,
+    /// and it's only believed if there is a solid block under the player.
+    pub const ON_GROUND: DataComponentType<bool> = DataComponentType::new(id![minecraft:on_ground]);
+    /// How far the player has fallen since they were last on the ground, in blocks. Landing
+    /// deals fall damage based on it, so setting it to 0 cancels the damage from a fall.
+    pub const FALL_DISTANCE: DataComponentType<f32> =
+        DataComponentType::new(id![minecraft:fall_distance]);
     pub const GAMEMODE: DataComponentType<Gamemode> =
         DataComponentType::new(id![minecraft:gamemode]);
+    /// Whether the player can start flying by double-tapping jump. Defaults to true in creative
+    /// and spectator, and is reset to match the gamemode whenever it changes.
+    pub const ALLOW_FLIGHT: DataComponentType<bool> =
+        DataComponentType::new(id![minecraft:allow_flight]);
+    /// Whether the player is flying. The client updates this itself when the player starts or
+    /// stops flying.
+    pub const FLYING: DataComponentType<bool> = DataComponentType::new(id![minecraft:flying]);
+    /// How fast the player flies. Defaults to 0.05.
+    pub const FLY_SPEED: DataComponentType<f32> = DataComponentType::new(id![minecraft:fly_speed]);
+    /// How fast the client thinks the player walks, which only changes their field of view.
+    /// Defaults to 0.1.
+    pub const WALK_SPEED: DataComponentType<f32> =
+        DataComponentType::new(id![minecraft:walk_speed]);
+    /// The player's operator level from 0 to 4, which is 0 unless set. Only the server uses it,
+    /// such as to let operators build inside spawn protection.
+    pub const OP_LEVEL: DataComponentType<u8> = DataComponentType::new(id![minecraft:op_level]);
+    /// The round trip time of the player's last answered keep-alive, in milliseconds.
+    pub const PING: DataComponentType<u32> = DataComponentType::new(id![minecraft:ping]);
 
     pub const TELEPORT_SYNC_SENT: DataComponentType<i32> =
         DataComponentType::new(id![minecraft:teleport_sent]);
@@ -45,6 +80,15 @@ impl PlayerComponents {
         DataComponentType::new(id![minecraft:sidebar_name]);
     pub const SIDEBAR_LINES: DataComponentType<Vec<Text>> =
         DataComponentType::new(id![minecraft:sidebar_lines]);
+    /// How the numbers beside sidebar lines are shown. Defaults to `ScoreFormat::Blank`.
+    /// Lines are ordered by their scores, so `ScoreFormat::Number` shows that ordering
+    /// rather than a meaningful value; use `ScoreFormat::Fixed` to show your own.
+    pub const SIDEBAR_NUMBER_FORMAT: DataComponentType<ScoreFormat> =
+        DataComponentType::new(id![minecraft:sidebar_number_format]);
+    /// The number format of each sidebar line. Lines without one (`None`, or past the end)
+    /// use `SIDEBAR_NUMBER_FORMAT`.
+    pub const SIDEBAR_LINE_FORMATS: DataComponentType<Vec<Option<ScoreFormat>>> =
+        DataComponentType::new(id![minecraft:sidebar_line_formats]);
 
     pub const HEALTH: DataComponentType<HealthComponent> =
         DataComponentType::new(id![minecraft:health]);
@@ -2,20 +2,27 @@ use core::f64;
 
 use voxidian_protocol::{
     packet::s2c::play::{
-        GameEvent, GameEventS2CPlayPacket, NumberFormat, ObjectiveKind, ObjectiveLocation,
-        PlayerPositionS2CPlayPacket, SetBorderCenterS2CPlayPacket, SetBorderSizeS2CPlayPacket,
-        SetBorderWarningDelayS2CPlayPacket, SetBorderWarningDistanceS2CPlayPacket,
-        SetDisplayObjectiveS2CPlayPacket, SetExperienceS2CPlayPacket, SetHealthS2CPlayPacket,
-        SetObjectiveS2CPlayPacket, SetScoreS2CPlayPacket, TeleportFlags, UpdateObjectiveAction,
+        GameEvent, GameEventS2CPlayPacket, ObjectiveKind, ObjectiveLocation,
+        PlayerAbilitiesS2CPlayPacket, PlayerActionEntry, PlayerInfoUpdateS2CPlayPacket,
+        PlayerPositionS2CPlayPacket, ResetScoreS2CPlayPacket, SetBorderCenterS2CPlayPacket,
+        SetBorderSizeS2CPlayPacket, SetBorderWarningDelayS2CPlayPacket,
+        SetBorderWarningDistanceS2CPlayPacket, SetDisplayObjectiveS2CPlayPacket,
+        SetExperienceS2CPlayPacket, SetHealthS2CPlayPacket, SetObjectiveS2CPlayPacket,
+        SetScoreS2CPlayPacket, TeleportFlags, UpdateObjectiveAction,
     },
     value::{Text as PtcText, VarInt},
 };
-use wyvern_components::{DataComponentHolder, DataComponentPatch};
+use wyvern_components::{DataComponentHolder, DataComponentMap, DataComponentPatch};
 use wyvern_datatypes::{gamemode::Gamemode, text::Text};
 
 use crate::{
-    actors::ActorResult, entities::EntityComponents, item::ItemStack, player::ConnectionData,
+    actors::ActorResult,
+    entities::EntityComponents,
+    events::GamemodeChangeEvent,
+    item::ItemStack,
+    player::{ConnectionData, scoreboard::ScoreFormat},
     runtime::Runtime,
+    server::Server,
 };
 use wyvern_values::{DVec3, id};
 
@@ -23,23 +30,32 @@ use super::{Player, PlayerComponents};
 
 impl Player {
     pub(crate) fn update_components(&mut self) -> ActorResult<()> {
+        self.tick_effects()?;
         let current_components = self.get_current_components()?;
         let last_components = self.get_saved_components()?;
         let patch = DataComponentPatch::from_maps(&last_components, &current_components);
 
-        self.update_gamemode(&patch)?;
-        self.update_sidebar(&patch)?;
+        self.update_gamemode(&patch, &last_components)?;
+        self.update_sidebar(&patch, &last_components)?;
         self.update_stats(&patch)?;
         self.update_teleport(&patch)?;
         self.update_velocity(&patch)?;
         self.update_attributes(&patch)?;
+        self.update_abilities(&patch)?;
 
         self.set_saved_components(current_components.clone())?;
+        self.pick_up_items()?;
 
         Ok(())
     }
 
-    pub(crate) fn update_gamemode(&mut self, patch: &DataComponentPatch) -> ActorResult<()> {
+    /// Tells the client about a new gamemode so it switches its abilities to match, and
+    /// shows the new gamemode to every player in the player list.
+    pub(crate) fn update_gamemode(
+        &mut self,
+        patch: &DataComponentPatch,
+        last_components: &DataComponentMap,
+    ) -> ActorResult<()> {
         if patch
             .added_fields()
             .contains_type(&PlayerComponents::GAMEMODE)
@@ -47,26 +63,79 @@ impl Player {
             let mode = patch.added_fields().get(PlayerComponents::GAMEMODE)?;
             self.write_packet(GameEventS2CPlayPacket {
                 event: GameEvent::ChangeGameMode,
-                value: match mode {
-                    Gamemode::Survival => 0.0,
-                    Gamemode::Creative => 1.0,
-                    Gamemode::Adventure => 2.0,
-                    Gamemode::Spectator => 3.0,
-                },
+                value: mode.id() as f32,
             })?;
+
+            let server = Server::get()?;
+            Player::broadcast_packet(
+                &server.players()?,
+                PlayerInfoUpdateS2CPlayPacket {
+                    actions: vec![(
+                        self.get(PlayerComponents::UUID)?,
+                        vec![PlayerActionEntry::GameMode(VarInt::from(mode.id() as i32))],
+                    )],
+                },
+            )?;
+
+            // The first gamemode a player is given when joining isn't a change.
+            if let Ok(old) = last_components.get(PlayerComponents::GAMEMODE) {
+                self.set(PlayerComponents::ALLOW_FLIGHT, default_allow_flight(&mode))?;
+                self.set(PlayerComponents::FLYING, mode == Gamemode::Spectator)?;
+                server.spawn_event(GamemodeChangeEvent {
+                    player: self.clone(),
+                    old,
+                    new: mode,
+                })?;
+            }
         }
         Ok(())
     }
 
-    pub(crate) fn update_attributes(&mut self, patch: &DataComponentPatch) -> ActorResult<()> {
-        if patch
-            .added_fields()
-            .contains_type(&PlayerComponents::ATTRIBUTES)
+    /// Sends the player's abilities whenever they or the gamemode they depend on change.
+    pub(crate) fn update_abilities(&mut self, patch: &DataComponentPatch) -> ActorResult<()> {
+        let added = patch.added_fields();
+        if !added.contains_type(&PlayerComponents::GAMEMODE)
+            && !added.contains_type(&PlayerComponents::ALLOW_FLIGHT)
+            && !added.contains_type(&PlayerComponents::FLYING)
+            && !added.contains_type(&PlayerComponents::FLY_SPEED)
+            && !added.contains_type(&PlayerComponents::WALK_SPEED)
         {
-            let container = patch.added_fields().get(PlayerComponents::ATTRIBUTES)?;
-            self.write_packet(container.into_packet(self.entity_id()?))?;
+            return Ok(());
         }
-        Ok(())
+
+        let mode = self.get(PlayerComponents::GAMEMODE)?;
+        let creative = mode == Gamemode::Creative;
+        let allow_flight = self
+            .get(PlayerComponents::ALLOW_FLIGHT)
+            .unwrap_or(default_allow_flight(&mode));
+        let flying = allow_flight
+            && self
+                .get(PlayerComponents::FLYING)
+                .unwrap_or(mode == Gamemode::Spectator);
+
+        let mut flags = 0;
+        if default_allow_flight(&mode) {
+            flags |= ABILITY_INVULNERABLE;
+        }
+        if flying {
+            flags |= ABILITY_FLYING;
+        }
+        if allow_flight {
+            flags |= ABILITY_ALLOW_FLIGHT;
+        }
+        if creative {
+            flags |= ABILITY_INSTANT_BREAK;
+        }
+        self.write_packet(PlayerAbilitiesS2CPlayPacket {
+            flags,
+            fly_speed: self.get(PlayerComponents::FLY_SPEED).unwrap_or(0.05),
+            fov_modifier: self.get(PlayerComponents::WALK_SPEED).unwrap_or(0.1),
+        })
+    }
+
+    /// Sends the player's attributes when they or the modifiers of their equipment change.
+    pub(crate) fn update_attributes(&mut self, _patch: &DataComponentPatch) -> ActorResult<()> {
+        self.sync_attributes()
     }
 
     pub(crate) fn update_teleport(&mut self, _patch: &DataComponentPatch) -> ActorResult<()> {
@@ -146,62 +215,124 @@ impl Player {
         Ok(())
     }
 
-    pub(crate) fn update_sidebar(&mut self, patch: &DataComponentPatch) -> ActorResult<()> {
-        if let Ok(sidebar_present) = patch.added_fields().get(PlayerComponents::SIDEBAR_PRESENT) {
+    pub(crate) fn update_sidebar(
+        &mut self,
+        patch: &DataComponentPatch,
+        last_components: &DataComponentMap,
+    ) -> ActorResult<()> {
+        let created = patch.added_fields().get(PlayerComponents::SIDEBAR_PRESENT);
+        if let Ok(sidebar_present) = created {
             if sidebar_present {
                 self.write_packet(SetObjectiveS2CPlayPacket {
-                    name: "wyvern_objective".into(),
+                    name: SIDEBAR_OBJECTIVE.into(),
                     action: UpdateObjectiveAction::Create {
-                        value: PtcText::from(
-                            self.get(PlayerComponents::SIDEBAR_NAME)
-                                .unwrap_or_else(|_| Text::literal("Untitled Objective")),
-                        )
-                        .to_nbt(),
+                        value: PtcText::from(self.sidebar_name()).to_nbt(),
                         kind: ObjectiveKind::Integer,
-                        format: Some(NumberFormat::Blank),
+                        format: self.sidebar_number_format().to_protocol(),
                     },
                 })?;
                 self.write_packet(SetDisplayObjectiveS2CPlayPacket {
                     to: ObjectiveLocation::Sidebar,
-                    name: "wyvern_objective".into(),
+                    name: SIDEBAR_OBJECTIVE.into(),
                 })?;
             } else {
                 self.write_packet(SetObjectiveS2CPlayPacket {
-                    name: "wyvern_objective".into(),
+                    name: SIDEBAR_OBJECTIVE.into(),
                     action: UpdateObjectiveAction::Remove,
                 })?;
             }
         }
 
-        if self.get(PlayerComponents::SIDEBAR_PRESENT).unwrap_or(false) {
-            if let Ok(sidebar_lines) = patch.added_fields().get(PlayerComponents::SIDEBAR_LINES) {
-                for (idx, line) in sidebar_lines.into_iter().enumerate() {
-                    let idx = usize::min(idx, i32::MAX as usize) as i32;
-                    self.write_packet(SetScoreS2CPlayPacket {
-                        entity_name: format!("line_{}", idx),
-                        objective_name: "wyvern_objective".into(),
-                        value: VarInt::new(i32::MAX - idx),
-                        display_name: Some(PtcText::from(line).to_nbt()),
-                        number_format: Some(NumberFormat::Blank),
-                    })?;
+        if !self.get(PlayerComponents::SIDEBAR_PRESENT).unwrap_or(false) {
+            return Ok(());
+        }
+        let created = created.unwrap_or(false);
+
+        if created
+            || patch
+                .added_fields()
+                .contains_type(&PlayerComponents::SIDEBAR_LINES)
+            || patch
+                .added_fields()
+                .contains_type(&PlayerComponents::SIDEBAR_NUMBER_FORMAT)
+            || patch
+                .added_fields()
+                .contains_type(&PlayerComponents::SIDEBAR_LINE_FORMATS)
+        {
+            // A newly created objective has no scores, so every line has to be sent.
+            let old_lines = if created {
+                Vec::new()
+            } else {
+                sidebar_lines(
+                    last_components
+                        .get(PlayerComponents::SIDEBAR_LINES)
+                        .unwrap_or_default(),
+                    last_components
+                        .get(PlayerComponents::SIDEBAR_NUMBER_FORMAT)
+                        .unwrap_or(ScoreFormat::Blank),
+                    last_components
+                        .get(PlayerComponents::SIDEBAR_LINE_FORMATS)
+                        .unwrap_or_default(),
+                )
+            };
+            let new_lines = sidebar_lines(
+                self.get(PlayerComponents::SIDEBAR_LINES)
+                    .unwrap_or_default(),
+                self.sidebar_number_format(),
+                self.get(PlayerComponents::SIDEBAR_LINE_FORMATS)
+                    .unwrap_or_default(),
+            );
+            for change in sidebar_line_changes(&old_lines, &new_lines) {
+                match change {
+                    SidebarLineChange::Set(idx, line) => {
+                        self.write_packet(SetScoreS2CPlayPacket {
+                            entity_name: sidebar_entry(idx),
+                            objective_name: SIDEBAR_OBJECTIVE.into(),
+                            value: VarInt::new(i32::MAX - idx as i32),
+                            display_name: Some(PtcText::from(line.text).to_nbt()),
+                            number_format: line.format.to_protocol(),
+                        })?;
+                    }
+                    SidebarLineChange::Remove(idx) => {
+                        self.write_packet(ResetScoreS2CPlayPacket {
+                            entity_name: sidebar_entry(idx),
+                            objective_name: Some(SIDEBAR_OBJECTIVE.into()),
+                        })?;
+                    }
                 }
-                self.write_packet(SetObjectiveS2CPlayPacket {
-                    name: "wyvern_objective".into(),
-                    action: UpdateObjectiveAction::Update {
-                        value: PtcText::from(
-                            self.get(PlayerComponents::SIDEBAR_NAME)
-                                .unwrap_or_else(|_| Text::literal("Untitled Objective")),
-                        )
-                        .to_nbt(),
-                        kind: ObjectiveKind::Integer,
-                        format: Some(NumberFormat::Blank),
-                    },
-                })?;
             }
         }
+
+        if !created
+            && (patch
+                .added_fields()
+                .contains_type(&PlayerComponents::SIDEBAR_NAME)
+                || patch
+                    .added_fields()
+                    .contains_type(&PlayerComponents::SIDEBAR_NUMBER_FORMAT))
+        {
+            self.write_packet(SetObjectiveS2CPlayPacket {
+                name: SIDEBAR_OBJECTIVE.into(),
+                action: UpdateObjectiveAction::Update {
+                    value: PtcText::from(self.sidebar_name()).to_nbt(),
+                    kind: ObjectiveKind::Integer,
+                    format: self.sidebar_number_format().to_protocol(),
+                },
+            })?;
+        }
         Ok(())
     }
 
+    fn sidebar_name(&self) -> Text {
+        self.get(PlayerComponents::SIDEBAR_NAME)
+            .unwrap_or_else(|_| Text::literal("Untitled Objective"))
+    }
+
+    fn sidebar_number_format(&self) -> ScoreFormat {
+        self.get(PlayerComponents::SIDEBAR_NUMBER_FORMAT)
+            .unwrap_or(ScoreFormat::Blank)
+    }
+
     pub(crate) fn update_stats(&mut self, patch: &DataComponentPatch) -> ActorResult<()> {
         if let Ok(health) = patch.added_fields().get(PlayerComponents::HEALTH) {
             self.write_packet(SetHealthS2CPlayPacket {
@@ -239,6 +370,73 @@ impl Player {
     }
 }
 
+/// Returns whether players in a gamemode can fly unless told otherwise. These are also the
+/// gamemodes that can't take damage.
+pub(crate) fn default_allow_flight(mode: &Gamemode) -> bool {
+    matches!(mode, Gamemode::Creative | Gamemode::Spectator)
+}
+
+const ABILITY_INVULNERABLE: u8 = 0x01;
+pub(crate) const ABILITY_FLYING: u8 = 0x02;
+const ABILITY_ALLOW_FLIGHT: u8 = 0x04;
+const ABILITY_INSTANT_BREAK: u8 = 0x08;
+
+/// The objective the sidebar helpers on `PlayerComponents` display.
+const SIDEBAR_OBJECTIVE: &str = "wyvern_objective";
+
+/// The scoreboard entry a sidebar line is stored under. Lines are ordered by giving earlier
+/// lines higher scores.
+fn sidebar_entry(idx: usize) -> String {
+    format!("line_{}", idx)
+}
+
+/// A sidebar line as it is shown to the client.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct SidebarLine {
+    pub(crate) text: Text,
+    pub(crate) format: ScoreFormat,
+}
+
+/// Pairs each sidebar line with its number format, falling back to `default` for lines
+/// without one.
+pub(crate) fn sidebar_lines(
+    lines: Vec<Text>,
+    default: ScoreFormat,
+    formats: Vec<Option<ScoreFormat>>,
+) -> Vec<SidebarLine> {
+    let mut formats = formats.into_iter();
+    lines
+        .into_iter()
+        .map(|text| SidebarLine {
+            text,
+            format: formats.next().flatten().unwrap_or_else(|| default.clone()),
+        })
+        .collect()
+}
+
+/// A change to a single line of the sidebar.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum SidebarLineChange {
+    Set(usize, SidebarLine),
+    Remove(usize),
+}
+
+/// Returns the changes needed to turn the sidebar lines the client has into `new`, so lines
+/// that didn't change aren't sent again.
+pub(crate) fn sidebar_line_changes(
+    old: &[SidebarLine],
+    new: &[SidebarLine],
+) -> Vec<SidebarLineChange> {
+    let mut changes = new
+        .iter()
+        .enumerate()
+        .filter(|(idx, line)| old.get(*idx) != Some(*line))
+        .map(|(idx, line)| SidebarLineChange::Set(idx, line.clone()))
+        .collect::<Vec<_>>();
+    changes.extend((new.len()..old.len()).map(SidebarLineChange::Remove));
+    changes
+}
+
 fn calculate_total_experience(level: i32, progress: f32) -> f32 {
     let progress = progress.clamp(0.0, 1.0);
 
@@ -283,3 +481,77 @@ impl ConnectionData {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use wyvern_datatypes::text::Text;
+
+    use crate::player::scoreboard::ScoreFormat;
+
+    use super::{SidebarLine, SidebarLineChange, sidebar_line_changes, sidebar_lines};
+
+    fn lines(texts: &[&str], formats: Vec<Option<ScoreFormat>>) -> Vec<SidebarLine> {
+        sidebar_lines(
+            texts.iter().map(|text| Text::literal(*text)).collect(),
+            ScoreFormat::Blank,
+            formats,
+        )
+    }
+
+    #[test]
+    fn only_changed_lines_are_sent() {
+        let old = lines(&["Kills: 1", "", "Deaths: 0", "example.net"], vec![]);
+        let new = lines(&["Kills: 2", "", "Deaths: 0", "example.net"], vec![]);
+
+        // Rebuilding the whole sidebar would send four scores; only one line changed.
+        assert_eq!(
+            sidebar_line_changes(&old, &new),
+            vec![SidebarLineChange::Set(0, new[0].clone())]
+        );
+        assert_eq!(sidebar_line_changes(&new, &new), vec![]);
+
+        let shorter = lines(&["Kills: 2", ""], vec![]);
+        assert_eq!(
+            sidebar_line_changes(&old, &shorter),
+            vec![
+                SidebarLineChange::Set(0, new[0].clone()),
+                SidebarLineChange::Remove(2),
+                SidebarLineChange::Remove(3),
+            ]
+        );
+    }
+
+    #[test]
+    fn line_formats_override_default() {
+        let fixed = ScoreFormat::Fixed(Text::literal("3"));
+        let old = lines(&["Kills", "Deaths"], vec![]);
+        let new = lines(&["Kills", "Deaths"], vec![Some(fixed.clone())]);
+        assert_eq!(new[0].format, fixed);
+        assert_eq!(new[1].format, ScoreFormat::Blank);
+        assert_eq!(
+            sidebar_line_changes(&old, &new),
+            vec![SidebarLineChange::Set(0, new[0].clone())]
+        );
+    }
+
+    #[test]
+    fn lines_padded_before_a_format_follow_the_default() {
+        let fixed = ScoreFormat::Fixed(Text::literal("3"));
+        let texts = ["Kills", "Deaths", "Wins"].map(Text::literal).to_vec();
+        // Setting the third line's format leaves the first two without their own.
+        let formats = vec![None, None, Some(fixed.clone())];
+
+        let old = sidebar_lines(texts.clone(), ScoreFormat::Blank, formats.clone());
+        let new = sidebar_lines(texts, ScoreFormat::Number, formats);
+        assert_eq!(new[0].format, ScoreFormat::Number);
+        assert_eq!(new[1].format, ScoreFormat::Number);
+        assert_eq!(new[2].format, fixed);
+        assert_eq!(
+            sidebar_line_changes(&old, &new),
+            vec![
+                SidebarLineChange::Set(0, new[0].clone()),
+                SidebarLineChange::Set(1, new[1].clone()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,59 @@
+use std::{collections::HashMap, time::Instant};
+
+use crate::entities::TICK_LENGTH;
+
+/// Cooldowns under arbitrary names, such as for a game's abilities.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Cooldowns {
+    expiries: HashMap<String, Instant>,
+}
+
+impl Cooldowns {
+    /// Puts `key` on cooldown for `ticks` ticks from `now`. Zero ticks clears the cooldown.
+    pub(crate) fn set(&mut self, key: String, ticks: u32, now: Instant) {
+        if ticks == 0 {
+            self.expiries.remove(&key);
+        } else {
+            self.expiries.insert(key, now + TICK_LENGTH * ticks);
+        }
+    }
+
+    /// Returns how many ticks are left on the cooldown of `key`, rounded up, forgetting any
+    /// cooldowns that have expired.
+    pub(crate) fn remaining(&mut self, key: &str, now: Instant) -> u32 {
+        self.expiries.retain(|_, expiry| *expiry > now);
+        self.expiries.get(key).map_or(0, |expiry| {
+            let left = *expiry - now;
+            left.as_nanos().div_ceil(TICK_LENGTH.as_nanos()) as u32
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::Cooldowns;
+
+    #[test]
+    fn cooldowns_expire_after_their_ticks() {
+        let start = Instant::now();
+        let mut cooldowns = Cooldowns::default();
+        cooldowns.set("dash".to_string(), 3, start);
+
+        assert_eq!(cooldowns.remaining("dash", start), 3);
+        assert_eq!(
+            cooldowns.remaining("dash", start + Duration::from_millis(101)),
+            1
+        );
+        assert_eq!(
+            cooldowns.remaining("dash", start + Duration::from_millis(150)),
+            0
+        );
+        assert_eq!(cooldowns.remaining("railgun", start), 0);
+
+        cooldowns.set("dash".to_string(), 3, start);
+        cooldowns.set("dash".to_string(), 0, start);
+        assert_eq!(cooldowns.remaining("dash", start), 0);
+    }
+}
@@ -1,8 +1,23 @@
-use std::time::Instant;
+use std::{
+    collections::{HashMap, HashSet},
+    time::{Duration, Instant},
+};
 
-use crate::{dimension::Dimension, inventory::DataInventory, item::ItemStack};
-use wyvern_datatypes::window::InventoryKind;
-use wyvern_values::IVec2;
+use crate::{
+    blocks::BlockState,
+    dimension::Dimension,
+    entities::{ActiveEffects, AttributeContainer, DamageHistory},
+    inventory::{DataInventory, Drag, ResultSlot},
+    item::ItemStack,
+};
+use wyvern_datatypes::{text::Text, weather::Weather, window::InventoryKind};
+use wyvern_values::{DVec3, IVec2, IVec3, Id};
+
+use super::{
+    cooldown::Cooldowns,
+    net::{DEFAULT_KEEP_ALIVE_INTERVAL, DEFAULT_KEEP_ALIVE_TIMEOUT},
+    triggers::RegionPresence,
+};
 
 #[derive(Debug, Clone)]
 pub struct PlayerData {
@@ -11,11 +26,25 @@ pub struct PlayerData {
     #[allow(unused)]
     pub(crate) last_chunk_position: IVec2,
     pub(crate) loaded_chunks: Vec<IVec2>,
+    /// Blocks shown to this player that differ from their dimension's.
+    pub(crate) fake_blocks: HashMap<IVec3, BlockState>,
     pub(crate) render_distance: i32,
+    /// Chunk batches sent to the client that it has not acknowledged yet.
+    pub(crate) unacknowledged_chunk_batches: u32,
+    /// How many chunks per tick the client last reported it can receive.
+    pub(crate) client_chunks_per_tick: Option<f32>,
 
     pub(crate) entity_id: i32,
+    pub(crate) protocol_version: i32,
 
     pub(crate) last_sent_keep_alive: Instant,
+    /// The id and send time of the keep-alive the client hasn't answered yet.
+    pub(crate) pending_keep_alive: Option<(u64, Instant)>,
+    pub(crate) next_keep_alive_id: u64,
+    pub(crate) keep_alive_interval: Duration,
+    pub(crate) keep_alive_timeout: Duration,
+    /// The reason this player's connection should be closed at the end of this loop.
+    pub(crate) kick: Option<Text>,
 
     pub(crate) inventory: DataInventory,
     pub(crate) screen: Option<(InventoryKind, DataInventory)>,
@@ -23,6 +52,40 @@ pub struct PlayerData {
     pub(crate) held_slot: i16,
 
     pub(crate) cursor_item: ItemStack,
+    /// The drag the player is in the middle of, if any.
+    pub(crate) drag: Option<Drag>,
+    /// The slots of the open screen the player can't take items out of or put items into.
+    pub(crate) locked_slots: HashSet<usize>,
+    /// Computes the result slot of the open screen from its inputs.
+    pub(crate) screen_result: Option<ResultSlot>,
+    /// The name the player typed into their open anvil.
+    pub(crate) anvil_rename: Option<String>,
+
+    pub(crate) cooldowns: HashMap<Id, Instant>,
+    pub(crate) action_cooldowns: Cooldowns,
+    pub(crate) damage_history: DamageHistory,
+    /// When the player last attacked or switched items, which restarts their attack cooldown.
+    pub(crate) last_attack_reset: Option<Instant>,
+    pub(crate) effects: ActiveEffects,
+    /// How many ticks the player has had effects ticked for, for effects that act every few
+    /// ticks.
+    pub(crate) effect_ticks: u64,
+    /// The attributes last sent to the client, with equipment modifiers applied.
+    pub(crate) sent_attributes: AttributeContainer,
+
+    /// The time of day shown to this player instead of their dimension's.
+    pub(crate) client_time: Option<i64>,
+    /// The weather shown to this player instead of their dimension's.
+    pub(crate) client_weather: Option<Weather>,
+
+    /// The region triggers of the current dimension this player is inside of.
+    pub(crate) region_triggers: RegionPresence,
+    /// When this player was last hurt by the void, or `None` if they are above the void height.
+    pub(crate) void_ticked_at: Option<Instant>,
+    /// The height of the player's last movement, used to work out how far they've fallen.
+    pub(crate) last_fall_y: Option<f64>,
+    /// How far the player's last movement took them, and when, used to work out their velocity.
+    pub(crate) last_movement: Option<(DVec3, Instant)>,
 }
 
 impl Default for PlayerData {
@@ -32,10 +95,19 @@ impl Default for PlayerData {
 
             last_chunk_position: IVec2::new(0, 0),
             loaded_chunks: Vec::new(),
+            fake_blocks: HashMap::new(),
 
             render_distance: 2,
+            unacknowledged_chunk_batches: 0,
+            client_chunks_per_tick: None,
             entity_id: 0,
+            protocol_version: 0,
             last_sent_keep_alive: Instant::now(),
+            pending_keep_alive: None,
+            next_keep_alive_id: 0,
+            keep_alive_interval: DEFAULT_KEEP_ALIVE_INTERVAL,
+            keep_alive_timeout: DEFAULT_KEEP_ALIVE_TIMEOUT,
+            kick: None,
 
             inventory: DataInventory::new_filled(36, ItemStack::air),
 
@@ -43,7 +115,67 @@ impl Default for PlayerData {
             held_slot: 36,
 
             cursor_item: ItemStack::air(),
+            drag: None,
+            locked_slots: HashSet::new(),
+            screen_result: None,
+            anvil_rename: None,
             window_id: 0,
+
+            cooldowns: HashMap::new(),
+            action_cooldowns: Cooldowns::default(),
+            damage_history: DamageHistory::default(),
+            last_attack_reset: None,
+            effects: ActiveEffects::default(),
+            effect_ticks: 0,
+            sent_attributes: AttributeContainer::new(),
+
+            client_time: None,
+            client_weather: None,
+
+            region_triggers: RegionPresence::default(),
+            void_ticked_at: None,
+            last_fall_y: None,
+            last_movement: None,
         }
     }
 }
+
+impl PlayerData {
+    /// Returns true if the item is still on cooldown, forgetting any cooldowns that have expired.
+    pub(crate) fn is_on_cooldown(&mut self, item: &Id) -> bool {
+        self.is_on_cooldown_at(item, Instant::now())
+    }
+
+    fn is_on_cooldown_at(&mut self, item: &Id, now: Instant) -> bool {
+        self.cooldowns.retain(|_, expiry| *expiry > now);
+        self.cooldowns.contains_key(item)
+    }
+
+    /// Returns true if using the item should be ignored, which is only the case while it is on
+    /// cooldown and the server suppresses actions of items on cooldown.
+    pub(crate) fn item_use_suppressed(&mut self, item: &Id, suppress: bool, now: Instant) -> bool {
+        suppress && self.is_on_cooldown_at(item, now)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use wyvern_values::id;
+
+    use super::PlayerData;
+
+    #[test]
+    fn item_use_is_suppressed_until_the_cooldown_expires() {
+        let mut data = PlayerData::default();
+        let item = id![minecraft:ender_pearl];
+        let now = Instant::now();
+        data.cooldowns
+            .insert(item.clone(), now + Duration::from_millis(1000));
+
+        assert!(data.item_use_suppressed(&item, true, now));
+        assert!(!data.item_use_suppressed(&item, false, now));
+        assert!(!data.item_use_suppressed(&item, true, now + Duration::from_millis(1000)));
+    }
+}
@@ -0,0 +1,164 @@
+use std::sync::{Arc, Mutex};
+
+use voxidian_protocol::{
+    packet::s2c::play::{GameEvent, GameEventS2CPlayPacket, RespawnDataKept, RespawnS2CPlayPacket},
+    registry::RegEntry,
+    value::VarInt,
+};
+use wyvern_components::DataComponentHolder;
+use wyvern_datatypes::gamemode::Gamemode;
+use wyvern_values::{DVec3, id};
+
+use crate::{
+    actors::{Actor, ActorResult},
+    dimension::GameRuleKeys,
+    entities::{ActiveEffects, PLAYER_DROP_PICKUP_DELAY},
+    events::{DropItemEvent, PlayerDeathEvent, PlayerRespawnEvent},
+    runtime::Runtime,
+};
+
+use super::{ConnectionData, HealthComponent, Player, PlayerComponents};
+
+impl Player {
+    /// Handles this player dying. Unless their dimension has `keepInventory` on, their items and
+    /// experience are taken, and whatever `PlayerDeathEvent` handlers leave in the drops is
+    /// scattered where they died. Their damage history is handed to the event and cleared for
+    /// their next life.
+    /// If their dimension has `doImmediateRespawn` on, they are respawned once the event's
+    /// handlers have finished, without seeing the death screen.
+    pub(crate) fn handle_death(&self) -> ActorResult<()> {
+        let dimension = self.dimension()?;
+        let server = dimension.server()?;
+        let keep_inventory = dimension.game_rule(GameRuleKeys::KEEP_INVENTORY)?;
+        let immediate_respawn = dimension.game_rule(GameRuleKeys::DO_IMMEDIATE_RESPAWN)?;
+        let drops = if keep_inventory {
+            Vec::new()
+        } else {
+            self.take_death_drops()?
+        };
+        let damage_history = self.take_damage_history()?;
+
+        let player = self.clone();
+        Runtime::spawn_server_task(Some(server.clone()), async move {
+            let drops = Arc::new(Mutex::new(drops));
+            server
+                .fire_event_async(PlayerDeathEvent {
+                    player: player.clone(),
+                    keep_inventory,
+                    damage_history,
+                    drops: drops.clone(),
+                })
+                .await?;
+            let drops = std::mem::take(&mut *drops.lock().unwrap());
+            let position = player.get(PlayerComponents::POSITION)?;
+            for item in drops {
+                // Scatter the drops a little, as vanilla does.
+                let velocity = DVec3::new(
+                    rand::random::<f64>() * 0.2 - 0.1,
+                    0.2,
+                    rand::random::<f64>() * 0.2 - 0.1,
+                );
+                let cancelled = Arc::new(Mutex::new(false));
+                server
+                    .fire_event_async(DropItemEvent {
+                        player: player.clone(),
+                        item: item.clone(),
+                        cancelled: cancelled.clone(),
+                    })
+                    .await?;
+                if *cancelled.lock().unwrap() {
+                    continue;
+                }
+                dimension.drop_item_with_delay(
+                    position,
+                    item,
+                    velocity,
+                    PLAYER_DROP_PICKUP_DELAY,
+                )?;
+            }
+            if immediate_respawn {
+                player.respawn()?;
+            }
+            Ok(())
+        });
+        Ok(())
+    }
+}
+
+impl ConnectionData {
+    /// Brings a dead player back to life where they died, firing a `PlayerRespawnEvent`. Does
+    /// nothing if they are alive, so a client asking to respawn after the server already
+    /// respawned them isn't respawned twice.
+    pub(crate) fn perform_respawn(&mut self) -> ActorResult<()> {
+        if self
+            .get(PlayerComponents::HEALTH)
+            .is_ok_and(|health| health.health > 0.0)
+        {
+            return Ok(());
+        }
+
+        self.write_packet(RespawnS2CPlayPacket {
+            dim: unsafe { RegEntry::new_unchecked(0) },
+            dim_name: id![wyvern:fake].into(),
+            seed: 0,
+            gamemode: Gamemode::Survival.into(),
+            is_debug: false,
+            is_flat: false,
+            death_loc: None,
+            portal_cooldown: VarInt::from(0),
+            sea_level: VarInt::from(64),
+            prev_gamemode: Gamemode::Survival.into(),
+            data_kept: RespawnDataKept {
+                keep_attributes: true,
+                keep_metadata: true,
+            },
+        });
+        self.associated_data.loaded_chunks.clear();
+        // Effects end on death, and the client has already cleared them.
+        self.associated_data.effects = ActiveEffects::default();
+
+        // The client starts the new life with an empty inventory and no
+        // experience, so show it what was kept.
+        self.associated_data.screen = None;
+        self.associated_data.locked_slots.clear();
+        self.associated_data.drag = None;
+        self.send_inventory();
+        self.last_saved_components
+            .inner_mut()
+            .remove(PlayerComponents::EXPERIENCE.name());
+
+        self.set(
+            PlayerComponents::HEALTH,
+            HealthComponent {
+                food: 20,
+                saturation: 20.0,
+                health: 20.0,
+            },
+        );
+        self.write_packet(GameEventS2CPlayPacket {
+            event: GameEvent::WaitForChunks,
+            value: 0.0,
+        });
+        // The client forgets the spawn point along with the rest of its old world.
+        self.send_world_spawn()?;
+
+        self.set(
+            PlayerComponents::TELEPORT_POSITION,
+            self.get(PlayerComponents::POSITION)? + DVec3::new(0.0, 0.1, 0.0),
+        );
+
+        // Observers still see the dead body, so replace it with a live one.
+        let uuid = self.get(PlayerComponents::UUID)?;
+        let dimension = self.associated_data.dimension.clone();
+        Runtime::spawn_task(async move {
+            if let Some(dimension) = dimension {
+                dimension.get_entity(uuid).respawn_for_observers()?;
+            }
+            Ok(())
+        });
+        self.connected_server.spawn_event(PlayerRespawnEvent {
+            player: self.as_actor(),
+        })?;
+        Ok(())
+    }
+}
@@ -0,0 +1,17 @@
+use std::time::Instant;
+
+use crate::{actors::ActorResult, entities::effect_packet};
+
+use super::ConnectionData;
+
+impl ConnectionData {
+    /// Sends the player their effects again, since respawning in a dimension clears them on the
+    /// client.
+    pub(crate) fn resend_effects(&mut self) -> ActorResult<()> {
+        let now = Instant::now();
+        for active in self.associated_data.effects.clone().iter() {
+            self.write_packet(effect_packet(self.associated_data.entity_id, active, now)?);
+        }
+        Ok(())
+    }
+}
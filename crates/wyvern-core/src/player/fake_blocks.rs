@@ -0,0 +1,71 @@
+use voxidian_protocol::{
+    packet::s2c::play::BlockUpdateS2CPlayPacket, registry::RegEntry, value::BlockPos,
+};
+use wyvern_values::{IVec2, IVec3};
+
+use crate::{
+    actors::{ActorError, ActorResult},
+    blocks::BlockState,
+    dimension::SectionUpdates,
+};
+
+use super::ConnectionData;
+
+/// Returns the packet that shows a player `block` at `position`.
+pub(crate) fn block_update(position: IVec3, block: &BlockState) -> BlockUpdateS2CPlayPacket {
+    BlockUpdateS2CPlayPacket {
+        pos: BlockPos::new(position[0], position[1], position[2]),
+        block: unsafe { RegEntry::new_unchecked(block.protocol_id() as u32) },
+    }
+}
+
+impl ConnectionData {
+    /// Sends the player block changes, one multi-block change per affected section.
+    pub(crate) fn write_section_updates(&mut self, updates: SectionUpdates) {
+        let (sections, block_entities) = updates.into_packets();
+        for packet in sections {
+            self.write_packet(packet);
+        }
+        for packet in block_entities {
+            self.write_packet(packet);
+        }
+    }
+
+    /// Stops tracking fake blocks at `positions` and sends the player the real blocks there.
+    pub(crate) fn revert_fake_blocks(&mut self, positions: Vec<IVec3>) -> ActorResult<()> {
+        let positions = positions
+            .into_iter()
+            .filter(|position| self.associated_data.fake_blocks.remove(position).is_some())
+            .collect::<Vec<_>>();
+        if positions.is_empty() {
+            return Ok(());
+        }
+        let dimension = self
+            .associated_data
+            .dimension
+            .clone()
+            .ok_or(ActorError::ActorIsNotLoaded)?;
+        let mut updates = SectionUpdates::default();
+        for position in positions {
+            updates.push(
+                position,
+                dimension.get_block(position)?.protocol_id() as u32,
+                None,
+            );
+        }
+        self.write_section_updates(updates);
+        Ok(())
+    }
+
+    /// Forgets fake blocks in chunks the player no longer has loaded, since the client drops
+    /// them with the chunk and gets the real blocks if it loads the chunk again.
+    pub(crate) fn forget_unloaded_fake_blocks(&mut self) {
+        let loaded = &self.associated_data.loaded_chunks;
+        self.associated_data.fake_blocks.retain(|position, _| {
+            loaded.contains(&IVec2::new(
+                position[0].div_euclid(16),
+                position[2].div_euclid(16),
+            ))
+        });
+    }
+}
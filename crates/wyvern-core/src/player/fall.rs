@@ -0,0 +1,142 @@
+use wyvern_components::{DataComponentHolder, DataComponentType};
+use wyvern_datatypes::gamemode::Gamemode;
+use wyvern_values::{IVec3, id};
+
+use crate::{
+    actors::{Actor, ActorResult},
+    dimension::{GameRuleKeys, GameRules},
+    entities::{Attributes, DamageSource, default_attribute_value},
+    runtime::Runtime,
+};
+
+use super::{ConnectionData, PlayerComponents};
+
+/// Returns how far a player has fallen after moving `dy` blocks up while airborne, given how far
+/// they had fallen before. This is how far they are below the highest point they reached since
+/// leaving the ground.
+fn next_fall_distance(fall_distance: f32, dy: f64) -> f32 {
+    (fall_distance - dy as f32).max(0.0)
+}
+
+/// Returns the damage taken from landing after falling `fall_distance` blocks, which is none if
+/// `fallDamage` is off.
+fn fall_damage(
+    rules: &GameRules,
+    fall_distance: f32,
+    safe_fall_distance: f64,
+    multiplier: f64,
+) -> f32 {
+    if !rules.get(GameRuleKeys::FALL_DAMAGE) {
+        return 0.0;
+    }
+    ((fall_distance as f64 - safe_fall_distance) * multiplier)
+        .ceil()
+        .max(0.0) as f32
+}
+
+impl ConnectionData {
+    /// Updates `FALL_DISTANCE` after the player moved, damaging them if they just landed.
+    pub(crate) fn update_fall(&mut self) -> ActorResult<()> {
+        let on_ground = self.get(PlayerComponents::ON_GROUND).unwrap_or(false);
+        let position = self.get(PlayerComponents::POSITION)?;
+        let Some(last_y) = self.associated_data.last_fall_y.replace(position[1]) else {
+            return Ok(());
+        };
+        let fall_distance = next_fall_distance(
+            self.get(PlayerComponents::FALL_DISTANCE).unwrap_or(0.0),
+            position[1] - last_y,
+        );
+
+        let immune = self.get(PlayerComponents::FLYING).unwrap_or(false)
+            || matches!(
+                self.get(PlayerComponents::GAMEMODE),
+                Ok(Gamemode::Creative | Gamemode::Spectator)
+            );
+        let Some(dimension) = self.associated_data.dimension.clone() else {
+            return Ok(());
+        };
+        let feet = position.floor().as_ivec3();
+        if immune || dimension.get_block(feet)?.name() == &id![minecraft:water] {
+            self.set(PlayerComponents::FALL_DISTANCE, 0.0);
+            return Ok(());
+        }
+
+        if !on_ground {
+            self.set(PlayerComponents::FALL_DISTANCE, fall_distance);
+            return Ok(());
+        }
+        self.set(PlayerComponents::FALL_DISTANCE, 0.0);
+        if fall_distance == 0.0 {
+            return Ok(());
+        }
+
+        // Slime blocks bounce players who land on them without sneaking.
+        let below = dimension.get_block(feet - IVec3::new(0, 1, 0))?;
+        if below.name() == &id![minecraft:slime_block]
+            && !self.get(PlayerComponents::SNEAKING).unwrap_or(false)
+        {
+            return Ok(());
+        }
+
+        let attributes = self.effective_attributes()?;
+        let attribute = |attribute: DataComponentType<f64>| {
+            attributes
+                .get(attribute.clone())
+                .unwrap_or_else(|_| default_attribute_value(attribute.name()))
+        };
+        let damage = fall_damage(
+            &dimension.game_rules()?,
+            fall_distance,
+            attribute(Attributes::SAFE_FALL_DISTANCE),
+            attribute(Attributes::FALL_DAMAGE_MULTIPLIER),
+        );
+        if damage > 0.0 {
+            // Damaging a player goes through their actor, so it can't happen on this thread.
+            let player = self.as_actor();
+            Runtime::spawn_task(async move {
+                player.damage(damage, DamageSource::new(id![minecraft:fall]))
+            });
+        }
+        Ok(())
+    }
+
+    /// Forgets where the player was before a teleport, so the jump in position isn't counted
+    /// as falling.
+    pub(crate) fn reset_fall(&mut self) {
+        self.associated_data.last_fall_y = None;
+        self.set(PlayerComponents::FALL_DISTANCE, 0.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dimension::{GameRuleKeys, GameRules};
+
+    use super::{fall_damage, next_fall_distance};
+
+    #[test]
+    fn fall_distance_is_measured_from_the_highest_point() {
+        let mut fall = 0.0;
+        fall = next_fall_distance(fall, 1.0);
+        assert_eq!(fall, 0.0);
+        fall = next_fall_distance(fall, -4.0);
+        fall = next_fall_distance(fall, 1.5);
+        assert_eq!(fall, 2.5);
+        fall = next_fall_distance(fall, -3.0);
+        assert_eq!(fall, 5.5);
+
+        let rules = GameRules::new();
+        assert_eq!(fall_damage(&rules, fall, 3.0, 1.0), 3.0);
+        assert_eq!(fall_damage(&rules, 2.0, 3.0, 1.0), 0.0);
+        assert_eq!(fall_damage(&rules, 10.0, 3.0, 0.5), 4.0);
+    }
+
+    #[test]
+    fn fall_damage_rule_prevents_damage() {
+        let rules = GameRules::new().with(GameRuleKeys::FALL_DAMAGE, false);
+        assert_eq!(fall_damage(&rules, 20.0, 3.0, 1.0), 0.0);
+
+        let rules = rules.with(GameRuleKeys::FALL_DAMAGE, true);
+        assert_eq!(fall_damage(&rules, 20.0, 3.0, 1.0), 17.0);
+    }
+}
@@ -0,0 +1,80 @@
+use wyvern_components::DataComponentHolder;
+use wyvern_values::{DVec3, IVec3, id};
+
+use crate::actors::ActorResult;
+
+use super::{ConnectionData, PlayerComponents};
+
+/// Half the width of a player's bounding box.
+const PLAYER_HALF_WIDTH: f64 = 0.3;
+/// How far below a player's feet the ground is looked for, to allow for rounding.
+const GROUND_MARGIN: f64 = 0.05;
+
+/// Returns the blocks under the corners of a player's bounding box standing at `position`,
+/// without duplicates. At least one of them has to be solid for the player to be on the ground.
+fn ground_samples(position: DVec3) -> Vec<IVec3> {
+    let y = (position[1] - GROUND_MARGIN).floor() as i32;
+    let mut samples = Vec::with_capacity(4);
+    for dx in [-PLAYER_HALF_WIDTH, PLAYER_HALF_WIDTH] {
+        for dz in [-PLAYER_HALF_WIDTH, PLAYER_HALF_WIDTH] {
+            let sample = IVec3::new(
+                (position[0] + dx).floor() as i32,
+                y,
+                (position[2] + dz).floor() as i32,
+            );
+            if !samples.contains(&sample) {
+                samples.push(sample);
+            }
+        }
+    }
+    samples
+}
+
+impl ConnectionData {
+    /// Sets `ON_GROUND` from what the client reported with a movement. Clients claiming to be on
+    /// the ground with nothing but air or fluids under them are treated as airborne, so they
+    /// can't avoid fall damage or fly by always claiming to be on the ground.
+    pub(crate) fn update_on_ground(&mut self, claimed: bool) -> ActorResult<()> {
+        let on_ground = claimed && self.has_ground_below()?;
+        self.set(PlayerComponents::ON_GROUND, on_ground);
+        Ok(())
+    }
+
+    fn has_ground_below(&mut self) -> ActorResult<bool> {
+        let Some(dimension) = self.associated_data.dimension.clone() else {
+            return Ok(true);
+        };
+        let position = self.get(PlayerComponents::POSITION)?;
+        for sample in ground_samples(position) {
+            let block = dimension.get_block(sample)?;
+            if !block.is_air()
+                && block.name() != &id![minecraft:water]
+                && block.name() != &id![minecraft:lava]
+            {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wyvern_values::{DVec3, IVec3};
+
+    use super::ground_samples;
+
+    #[test]
+    fn ground_is_sampled_under_each_corner() {
+        assert_eq!(
+            ground_samples(DVec3::new(0.5, 64.0, 0.5)),
+            vec![IVec3::new(0, 63, 0)]
+        );
+        assert_eq!(
+            ground_samples(DVec3::new(1.1, 64.0, 0.5)),
+            vec![IVec3::new(0, 63, 0), IVec3::new(1, 63, 0)]
+        );
+        assert_eq!(ground_samples(DVec3::new(0.0, 64.5, 0.0)).len(), 4);
+        assert_eq!(ground_samples(DVec3::new(0.0, 64.5, 0.0))[0][1], 64);
+    }
+}
@@ -0,0 +1,151 @@
+use voxidian_protocol::packet::c2s::play::InputFlags;
+use wyvern_components::DataComponentHolder;
+
+use crate::{
+    actors::{Actor, ActorResult},
+    entities::EntityComponents,
+    events::{PlayerJumpEvent, PlayerSneakEvent, PlayerSprintEvent},
+    runtime::Runtime,
+};
+
+use super::{ConnectionData, PlayerComponents};
+
+/// The movement keys a player is holding, as last reported by their client.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PlayerInputs {
+    pub forward: bool,
+    pub backward: bool,
+    pub left: bool,
+    pub right: bool,
+    pub jump: bool,
+    pub sneak: bool,
+    pub sprint: bool,
+}
+
+impl PlayerInputs {
+    /// Returns whether any of the keys that move the player horizontally are held.
+    pub fn is_moving(&self) -> bool {
+        self.forward != self.backward || self.left != self.right
+    }
+}
+
+impl From<InputFlags> for PlayerInputs {
+    fn from(flags: InputFlags) -> Self {
+        PlayerInputs {
+            forward: flags.forward,
+            backward: flags.backward,
+            left: flags.left,
+            right: flags.right,
+            jump: flags.jump,
+            sneak: flags.sneak,
+            sprint: flags.sprint,
+        }
+    }
+}
+
+impl ConnectionData {
+    /// Stores the keys the client is holding, updating whether the player is sneaking or
+    /// sprinting. Changes are fired as events and shown to other players, and pressing jump
+    /// fires a `PlayerJumpEvent`.
+    pub(crate) fn update_input(&mut self, flags: InputFlags) -> ActorResult<()> {
+        let inputs = PlayerInputs::from(flags);
+        let sneaking = inputs.sneak;
+        let sprinting = inputs.sprint && inputs.forward && !inputs.sneak;
+        let jumped = inputs.jump
+            && !self
+                .get(PlayerComponents::INPUTS)
+                .is_ok_and(|previous| previous.jump);
+        self.set(PlayerComponents::INPUT_FLAGS, flags);
+        self.set(PlayerComponents::INPUTS, inputs);
+
+        if jumped {
+            self.connected_server.spawn_event(PlayerJumpEvent {
+                player: self.as_actor(),
+            })?;
+        }
+
+        let sneak_changed = self.get(PlayerComponents::SNEAKING).unwrap_or(false) != sneaking;
+        let sprint_changed = self.get(PlayerComponents::SPRINTING).unwrap_or(false) != sprinting;
+        if !sneak_changed && !sprint_changed {
+            return Ok(());
+        }
+        self.set(PlayerComponents::SNEAKING, sneaking);
+        self.set(PlayerComponents::SPRINTING, sprinting);
+
+        if let Some(dim) = self.associated_data.dimension.clone() {
+            let uuid = self.get(PlayerComponents::UUID)?;
+            Runtime::spawn_task(async move {
+                let entity = dim.get_entity(uuid);
+                if sneak_changed {
+                    entity.set(EntityComponents::SNEAKING, sneaking)?;
+                }
+                if sprint_changed {
+                    entity.set(EntityComponents::SPRINTING, sprinting)?;
+                }
+                Ok(())
+            });
+        }
+
+        if sneak_changed && sneaking {
+            self.dismount_on_sneak()?;
+        }
+        if sneak_changed {
+            self.connected_server.spawn_event(PlayerSneakEvent {
+                player: self.as_actor(),
+                sneaking,
+            })?;
+        }
+        if sprint_changed {
+            self.connected_server.spawn_event(PlayerSprintEvent {
+                player: self.as_actor(),
+                sprinting,
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Stops the player riding their vehicle, the same as sneaking does in vanilla.
+    fn dismount_on_sneak(&mut self) -> ActorResult<()> {
+        let Some(dim) = self.associated_data.dimension.clone() else {
+            return Ok(());
+        };
+        let uuid = self.get(PlayerComponents::UUID)?;
+        Runtime::spawn_task(async move { dim.get_entity(uuid).dismount() });
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use voxidian_protocol::packet::c2s::play::InputFlags;
+
+    use super::PlayerInputs;
+
+    fn flags(forward: bool, backward: bool, jump: bool, sprint: bool) -> InputFlags {
+        InputFlags {
+            forward,
+            backward,
+            left: false,
+            right: false,
+            jump,
+            sneak: false,
+            sprint,
+        }
+    }
+
+    #[test]
+    fn input_flags_are_decoded() {
+        let inputs = PlayerInputs::from(flags(true, false, true, true));
+        assert_eq!(
+            inputs,
+            PlayerInputs {
+                forward: true,
+                jump: true,
+                sprint: true,
+                ..Default::default()
+            }
+        );
+        assert!(inputs.is_moving());
+        assert!(!PlayerInputs::from(flags(true, true, false, false)).is_moving());
+    }
+}
@@ -1,17 +1,31 @@
-use crate::{actors::ActorResult, inventory::Inventory};
+use crate::{actors::ActorResult, inventory::Inventory, item::ItemStack};
 
 use super::Player;
 
+/// The inventory of a player. Through the `Inventory` trait, reads and writes block until the
+/// player's actor answers; use the `_async` variants from async code.
 pub struct PlayerInventory {
     pub(crate) player: Player,
 }
 
+impl PlayerInventory {
+    /// Returns the item in a slot without blocking the current task.
+    pub async fn get_slot_async(&self, slot: usize) -> ActorResult<ItemStack> {
+        self.player.get_inv_slot_async(slot).await
+    }
+
+    /// Sets the item in a slot without blocking the current task.
+    pub async fn set_slot_async(&self, slot: usize, item: ItemStack) -> ActorResult<()> {
+        self.player.set_inv_slot_async(slot, item).await
+    }
+}
+
 impl Inventory for PlayerInventory {
-    fn get_slot(&self, slot: usize) -> ActorResult<crate::item::ItemStack> {
+    fn get_slot(&self, slot: usize) -> ActorResult<ItemStack> {
         self.player.get_inv_slot(slot)
     }
 
-    fn set_slot(&mut self, slot: usize, item: crate::item::ItemStack) -> ActorResult<()> {
+    fn set_slot(&mut self, slot: usize, item: ItemStack) -> ActorResult<()> {
         self.player.set_inv_slot(slot, item)
     }
 }
@@ -1,8 +1,22 @@
 use wyvern_actors::{ActorError, ActorResult};
 use wyvern_datatypes::{gamemode::Gamemode, text::Text};
-use wyvern_values::{DVec2, DVec3, Uuid};
+use wyvern_values::{DVec2, DVec3, DVec3Ext, Uuid};
 
-use super::{Player, PlayerComponents};
+use super::{Player, PlayerComponents, PlayerInputs, scoreboard::ScoreFormat};
+
+/// How far above a standing player's position their eyes are.
+pub const PLAYER_EYE_HEIGHT: f64 = 1.62;
+/// How far above a sneaking player's position their eyes are.
+pub const PLAYER_SNEAKING_EYE_HEIGHT: f64 = 1.27;
+
+/// Returns how far above a player's position their eyes are.
+pub(crate) fn eye_height(sneaking: bool) -> f64 {
+    if sneaking {
+        PLAYER_SNEAKING_EYE_HEIGHT
+    } else {
+        PLAYER_EYE_HEIGHT
+    }
+}
 
 impl Player {
     pub fn teleport(&self, position: DVec3) -> ActorResult<()> {
@@ -13,10 +27,43 @@ impl Player {
         self.set(PlayerComponents::TELEPORT_VELOCITY, position)
     }
 
+    /// Returns the movement keys the player is holding. Nothing is held until their client
+    /// first reports its input.
+    pub fn inputs(&self) -> ActorResult<PlayerInputs> {
+        Ok(self.get(PlayerComponents::INPUTS).unwrap_or_default())
+    }
+
+    /// Returns where the player's eyes are, which is lower while they sneak.
+    pub fn eye_position(&self) -> ActorResult<DVec3> {
+        let position = self.get(PlayerComponents::POSITION)?;
+        let sneaking = self.get(PlayerComponents::SNEAKING).unwrap_or(false);
+        Ok(position.with_y(position[1] + eye_height(sneaking)))
+    }
+
+    /// Turns the player to face `target`, looking from their eyes.
+    pub fn look_at(&self, target: DVec3) -> ActorResult<()> {
+        let eyes = self.eye_position()?;
+        self.set_rotation((target - eyes).to_rotation())
+    }
+
+    /// Returns whether an action put on cooldown with `set_action_cooldown` is still on cooldown.
+    pub fn is_action_on_cooldown(&self, key: impl Into<String>) -> ActorResult<bool> {
+        Ok(self.action_cooldown_remaining(key.into())? > 0)
+    }
+
     pub fn set_gamemode(&self, gamemode: Gamemode) -> ActorResult<()> {
         self.set(PlayerComponents::GAMEMODE, gamemode)
     }
 
+    pub fn set_allow_flight(&self, allow_flight: bool) -> ActorResult<()> {
+        self.set(PlayerComponents::ALLOW_FLIGHT, allow_flight)
+    }
+
+    /// Makes the player start or stop flying. Has no effect unless they are allowed to fly.
+    pub fn set_flying(&self, flying: bool) -> ActorResult<()> {
+        self.set(PlayerComponents::FLYING, flying)
+    }
+
     pub fn set_health(&self, health: f32) -> ActorResult<()> {
         let mut hp = self.get(PlayerComponents::HEALTH)?;
         hp.health = health;
@@ -43,6 +90,15 @@ impl Player {
         self.get(PlayerComponents::UUID)
     }
 
+    /// Returns the player's latest measured latency in milliseconds, or 0 if it hasn't been
+    /// measured yet.
+    pub fn ping(&self) -> ActorResult<u32> {
+        match self.get(PlayerComponents::PING) {
+            Err(ActorError::ComponentNotFound) => Ok(0),
+            result => result,
+        }
+    }
+
     pub fn sidebar(&self) -> PlayerSidebar<'_> {
         PlayerSidebar { player: self }
     }
@@ -65,16 +121,26 @@ impl PlayerSidebar<'_> {
         ))
     }
 
+    /// Replaces a single line, adding empty lines before it if needed. Only lines that
+    /// changed are sent to the player.
     pub fn set_line(&self, idx: usize, value: Text) -> ActorResult<()> {
-        let mut a = self.player.get(PlayerComponents::SIDEBAR_LINES)?;
-        while a.len() < idx {
-            a.push(Text::literal(""));
+        let mut a = self
+            .player
+            .get(PlayerComponents::SIDEBAR_LINES)
+            .unwrap_or_default();
+        if a.len() <= idx {
+            a.resize(idx + 1, Text::literal(""));
         }
-        a.push(value);
+        a[idx] = value;
         self.player.set(PlayerComponents::SIDEBAR_LINES, a)?;
         Ok(())
     }
 
+    /// Replaces every line at once. Only lines that changed are sent to the player.
+    pub fn set_lines(&self, lines: Vec<Text>) -> ActorResult<()> {
+        self.player.set(PlayerComponents::SIDEBAR_LINES, lines)
+    }
+
     pub fn add_line(&self, value: Text) -> ActorResult<()> {
         let mut a = self.player.get(PlayerComponents::SIDEBAR_LINES)?;
         a.push(value);
@@ -92,6 +158,26 @@ impl PlayerSidebar<'_> {
     pub fn get_name(&self) -> ActorResult<Text> {
         self.player.get(PlayerComponents::SIDEBAR_NAME)
     }
+
+    /// Sets how the numbers beside every line without its own format are shown.
+    pub fn set_number_format(&self, format: ScoreFormat) -> ActorResult<()> {
+        self.player
+            .set(PlayerComponents::SIDEBAR_NUMBER_FORMAT, format)
+    }
+
+    /// Sets how the number beside a single line is shown.
+    pub fn set_line_format(&self, idx: usize, format: ScoreFormat) -> ActorResult<()> {
+        let mut formats = self
+            .player
+            .get(PlayerComponents::SIDEBAR_LINE_FORMATS)
+            .unwrap_or_default();
+        if formats.len() <= idx {
+            formats.resize(idx + 1, None);
+        }
+        formats[idx] = Some(format);
+        self.player
+            .set(PlayerComponents::SIDEBAR_LINE_FORMATS, formats)
+    }
 }
 
 pub struct PlayerWorldBorder<'a> {
@@ -0,0 +1,151 @@
+use std::time::{Duration, Instant};
+
+use flume::{Receiver, Selector, Sender};
+use wyvern_datatypes::gamemode::Gamemode;
+use wyvern_values::Id;
+
+/// How long a joining player waits for a `PlayerJoinEvent` handler to choose their dimension.
+pub const JOIN_DIMENSION_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The dimension a joining player will be sent to. Set by a `PlayerJoinEvent` handler.
+#[derive(Debug, Clone)]
+pub struct DimensionChoice {
+    sender: Sender<Id>,
+}
+
+impl DimensionChoice {
+    pub(crate) fn new() -> (DimensionChoice, Receiver<Id>) {
+        let (sender, receiver) = flume::bounded(1);
+        (DimensionChoice { sender }, receiver)
+    }
+
+    /// Chooses the dimension for the joining player. Only the first choice is used.
+    pub fn set(&self, dimension: Id) {
+        let _ = self.sender.try_send(dimension);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum JoinError {
+    /// Every handler finished without choosing a dimension.
+    NoDimensionChosen,
+    /// No dimension was chosen before the timeout elapsed.
+    TimedOut,
+}
+
+enum Wake<M> {
+    Dimension(Id),
+    Message(M),
+    Abandoned,
+}
+
+/// Suspends until a dimension is chosen, handling actor messages as they arrive so handlers
+/// can still make requests to the joining player while it waits.
+pub(crate) fn await_dimension<M>(
+    choice: &Receiver<Id>,
+    messages: &Receiver<M>,
+    deadline: Instant,
+    mut handle_message: impl FnMut(M),
+) -> Result<Id, JoinError> {
+    loop {
+        let wake = Selector::new()
+            .recv(choice, |id| id.map_or(Wake::Abandoned, Wake::Dimension))
+            .recv(messages, |message| {
+                message.map_or(Wake::Abandoned, Wake::Message)
+            })
+            .wait_deadline(deadline);
+
+        match wake {
+            Ok(Wake::Dimension(id)) => return Ok(id),
+            Ok(Wake::Message(message)) => handle_message(message),
+            Ok(Wake::Abandoned) => {
+                return choice.try_recv().map_err(|_| JoinError::NoDimensionChosen);
+            }
+            Err(_) => return Err(JoinError::TimedOut),
+        }
+    }
+}
+
+/// Returns the gamemode a joining player starts with, given the one they have once their
+/// dimension is chosen. A gamemode set by a `PlayerJoinEvent` handler takes priority over the
+/// dimension's default, which takes priority over the server's.
+pub(crate) fn join_gamemode(
+    current: Gamemode,
+    set_by_handler: bool,
+    dimension_default: Option<Gamemode>,
+) -> Gamemode {
+    match dimension_default {
+        Some(gamemode) if !set_by_handler => gamemode,
+        _ => current,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use wyvern_datatypes::gamemode::Gamemode;
+    use wyvern_values::Id;
+
+    use super::{DimensionChoice, JoinError, await_dimension, join_gamemode};
+
+    #[test]
+    fn no_handler_sets_dimension() {
+        let (choice, receiver) = DimensionChoice::new();
+        let (_messages_tx, messages) = flume::unbounded::<()>();
+        let handler = std::thread::spawn(move || drop(choice));
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let result = await_dimension(&receiver, &messages, deadline, |_| {});
+        handler.join().unwrap();
+
+        assert_eq!(result, Err(JoinError::NoDimensionChosen));
+        assert!(Instant::now() < deadline);
+    }
+
+    #[test]
+    fn handler_chooses_dimension() {
+        let (choice, receiver) = DimensionChoice::new();
+        let (messages_tx, messages) = flume::unbounded();
+        let handler = std::thread::spawn(move || {
+            messages_tx.send(1).unwrap();
+            choice.set(Id::constant("example", "root"));
+            choice.set(Id::constant("example", "other"));
+        });
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let result = await_dimension(&receiver, &messages, deadline, |_: i32| {});
+        handler.join().unwrap();
+
+        assert_eq!(result, Ok(Id::constant("example", "root")));
+    }
+
+    #[test]
+    fn times_out_without_choice() {
+        let (_choice, receiver) = DimensionChoice::new();
+        let (_messages_tx, messages) = flume::unbounded::<()>();
+
+        let deadline = Instant::now() + Duration::from_millis(20);
+        let result = await_dimension(&receiver, &messages, deadline, |_| {});
+
+        assert_eq!(result, Err(JoinError::TimedOut));
+    }
+
+    #[test]
+    fn handler_gamemodes_beat_dimension_defaults() {
+        let creative = Some(Gamemode::Creative);
+        assert_eq!(
+            join_gamemode(Gamemode::Survival, false, creative.clone()),
+            Gamemode::Creative
+        );
+        // Setting the server's default explicitly still counts as choosing it.
+        assert_eq!(
+            join_gamemode(Gamemode::Survival, true, creative),
+            Gamemode::Survival
+        );
+        assert_eq!(
+            join_gamemode(Gamemode::Adventure, false, None),
+            Gamemode::Adventure
+        );
+    }
+}
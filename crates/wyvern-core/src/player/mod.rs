@@ -1,13 +1,20 @@
 use std::{
-    collections::VecDeque,
+    collections::{HashMap, HashSet, VecDeque},
     net::{IpAddr, TcpStream},
     sync::{Arc, Mutex},
+    time::{Duration, Instant},
 };
 
 use data::PlayerData;
 use flume::{Receiver, Sender};
 use inventory::PlayerInventory;
+use movement::movement_velocity;
 use net::ConnectionStoppedSignal;
+use packet_log::{PacketDirection, log_packet};
+use particles::particle_packet;
+use persistent::PersistentData;
+use scoreboard::Scoreboard;
+use sounds::{sound_packet, stop_sound_packet};
 use voxidian_protocol::{
     mojang::auth_verify::MojAuthProperty,
     packet::{
@@ -17,44 +24,89 @@ use voxidian_protocol::{
             config::DisconnectS2CConfigPacket,
             login::LoginDisconnectS2CLoginPacket,
             play::{
-                AddEntityS2CPlayPacket, ContainerSetSlotS2CPlayPacket, DisconnectS2CPlayPacket,
-                ForgetLevelChunkS2CPlayPacket, GameEvent, GameEventS2CPlayPacket,
-                Gamemode as PtcGamemode, LevelParticlesS2CPlayPacket, OpenScreenS2CPlayPacket,
+                ClearTitlesS2CPlayPacket, ContainerSetSlotS2CPlayPacket, CooldownS2CPlayPacket,
+                DisconnectS2CPlayPacket, ForgetLevelChunkS2CPlayPacket, GameEvent,
+                GameEventS2CPlayPacket, Gamemode as PtcGamemode, OpenScreenS2CPlayPacket,
                 PlayerPositionS2CPlayPacket, PlayerRotationS2CPlayPacket,
                 RemoveEntitiesS2CPlayPacket, RespawnDataKept, RespawnS2CPlayPacket,
-                ScreenWindowKind, SetEntityDataS2CPlayPacket, SoundCategory,
+                ScreenWindowKind, SetSubtitleTextS2CPlayPacket, SetTimeS2CPlayPacket,
+                SetTitleTextS2CPlayPacket, SetTitlesAnimationS2CPlayPacket,
                 SoundEntityS2CPlayPacket, SystemChatS2CPlayPacket, TeleportFlags,
             },
         },
     },
     registry::RegEntry,
-    value::{Angle, ProfileProperty, Text as PtcText, VarInt},
+    value::{ProfileProperty, Text as PtcText, VarInt},
+};
+use wyvern_components::{
+    ComponentElement, DataComponentHolder, DataComponentMap, DataComponentType,
+};
+use wyvern_datatypes::{
+    nbt::{Nbt, NbtCompound},
+    particle::{Particle, ParticleOptions},
+    sound::{Sound, SoundCategory},
+    text::Text,
+    title::Title,
+    weather::Weather,
+    window::InventoryKind,
 };
-use wyvern_components::{ComponentElement, DataComponentHolder, DataComponentMap};
-use wyvern_datatypes::{particle::Particle, sound::Sound, text::Text, window::InventoryKind};
 use wyvern_macros::{actor, message};
 
 use crate::{
     actors::{ActorError, ActorResult},
-    dimension::Dimension,
-    entities::EntityComponents,
-    inventory::{DataInventory, Inventory},
+    blocks::BlockState,
+    dimension::{
+        Dimension, GameRuleKeys, SectionUpdates, difficulty_packet, immediate_respawn_packet,
+    },
+    entities::{
+        ActiveEffects, AttributeContainer, Attributes, DEFAULT_MAX_HEALTH, DamageHistory,
+        DamageSource, Effect, Entity, EntityComponents, broadcast_effect_packets,
+        default_attribute_value, effect_packet, remove_effect_packet,
+    },
+    inventory::{DataInventory, Inventory, ResultSlot, is_empty},
     item::ItemStack,
+    runtime::Runtime,
     server::{Server, registries::RegistryKeys},
 };
 
-use wyvern_values::{DVec3, Id, Uuid, Vec2};
+use wyvern_values::{DVec3, IVec2, IVec3, Id, Uuid, Vec2};
 
 mod components;
 pub use components::*;
 
+mod attack;
+mod attributes;
+mod border;
 pub mod chunkload;
+mod click;
+mod cooldown;
 pub mod data;
+mod death;
+mod effects;
+mod fake_blocks;
+mod fall;
+mod ground;
+mod input;
+mod movement;
+mod particles;
+mod pickup;
+pub use input::PlayerInputs;
 pub mod inventory;
 pub mod itf;
+pub mod join;
 pub mod net;
+pub mod packet_log;
+pub mod persistent;
+mod protection;
+mod reach;
+mod recipes;
+pub mod scoreboard;
 pub mod skins;
+mod sounds;
+mod spawn;
 pub mod stages;
+mod triggers;
+mod vehicle;
 
 #[actor(Player, PlayerMessage)]
 pub(crate) struct ConnectionData {
@@ -72,6 +124,7 @@ pub(crate) struct ConnectionData {
     pub(crate) mojauth: Option<MojauthData>,
     pub(crate) last_saved_components: DataComponentMap,
     pub(crate) components: DataComponentMap,
+    pub(crate) persistent_data: NbtCompound,
 }
 
 impl DataComponentHolder for ConnectionData {
@@ -131,6 +184,26 @@ impl ConnectionData {
             .cloned()
     }
 
+    #[GetPersistentEntry]
+    pub(crate) fn get_persistent_entry(&mut self, key: String) -> ActorResult<Nbt> {
+        self.persistent_data
+            .get(key)
+            .cloned()
+            .ok_or(ActorError::ComponentNotFound)
+    }
+
+    #[SetPersistentEntry]
+    pub(crate) fn set_persistent_entry(&mut self, key: String, value: Nbt) -> ActorResult<()> {
+        self.persistent_data.set(key, value);
+        Ok(())
+    }
+
+    #[RemovePersistentEntry]
+    pub(crate) fn remove_persistent_entry(&mut self, key: String) -> ActorResult<()> {
+        self.persistent_data.remove(key);
+        Ok(())
+    }
+
     #[Disconnect]
     pub(crate) fn disconnect_internal(&mut self, message: Text) -> ActorResult<()> {
         let stage = *self.stage.lock().unwrap();
@@ -162,6 +235,13 @@ impl ConnectionData {
         }
     }
 
+    #[Kick]
+    #[doc = "Disconnects this player, showing them the reason."]
+    pub fn kick(&mut self, reason: Text) -> ActorResult<()> {
+        self.associated_data.kick = Some(reason);
+        Ok(())
+    }
+
     #[SetStage]
     pub fn set_stage(&mut self, stage: Stage) -> ActorResult<()> {
         *self.stage.lock().unwrap() = stage;
@@ -204,7 +284,7 @@ impl ConnectionData {
             .map(|x| ProfileProperty {
                 name: x.name.clone(),
                 value: x.value.clone(),
-                sig: Some(x.sig.clone()),
+                sig: (!x.sig.is_empty()).then(|| x.sig.clone()),
             })
             .collect::<Vec<_>>())
     }
@@ -218,8 +298,10 @@ impl ConnectionData {
             });
         }
 
-        self.associated_data.dimension = Some(dimension.clone());
+        self.leave_region_triggers()?;
+        let old_dimension = self.associated_data.dimension.replace(dimension.clone());
         self.associated_data.loaded_chunks.clear();
+        self.associated_data.fake_blocks.clear();
         self.set(PlayerComponents::POSITION, DVec3::new(0.0, 0.0, 0.0));
         self.set(PlayerComponents::DIRECTION, Vec2::new(0.0, 0.0));
 
@@ -278,45 +360,82 @@ impl ConnectionData {
             yaw: 0.0,
             pitch: 0.0,
         });
+        self.send_client_time()?;
+        self.send_client_weather()?;
+        self.send_difficulty()?;
+        self.send_world_border()?;
+        self.send_world_spawn()?;
+        self.resend_effects()?;
 
-        for entity in dimension.entities()? {
-            let position = entity.get(EntityComponents::POSITION)?;
-            let direction = entity.get(EntityComponents::DIRECTION)?;
-            let id = entity.get(EntityComponents::ENTITY_ID)?;
-            let ty = entity.get(EntityComponents::ENTITY_TYPE)?;
-            self.write_packet(AddEntityS2CPlayPacket {
-                id: id.into(),
-                uuid: *entity.uuid(),
-                kind: unsafe {
-                    RegEntry::new_unchecked(
-                        self.connected_server
-                            .registries()?
-                            .get(RegistryKeys::DIMENSION_TYPE)
-                            .get_entry(ty)
-                            .unwrap()
-                            .id(),
-                    )
-                },
-                x: position[0],
-                y: position[1],
-                z: position[2],
-                pitch: Angle::of_deg(direction[0]),
-                yaw: Angle::of_deg(direction[1]),
-                head_yaw: Angle::of_deg(direction[1]),
-                data: VarInt::from(0),
-                vel_x: 0,
-                vel_y: 0,
-                vel_z: 0,
-            });
-            self.write_packet(SetEntityDataS2CPlayPacket {
-                entity: id.into(),
-                data: entity.generate_metadata()?,
-            });
+        // Move the player's entity over, so they're sent the entities in range of it in the
+        // new dimension and its players are sent them.
+        let uuid = self.get(PlayerComponents::UUID)?;
+        let entity_id = self.associated_data.entity_id;
+        Runtime::spawn_task(async move {
+            if let Some(old) = old_dimension {
+                old.remove_entity(uuid)?;
+            }
+            dimension.spawn_player_entity(uuid, entity_id)?;
+            Ok(())
+        });
+
+        Ok(())
+    }
+
+    #[TakeDeathDrops]
+    pub(crate) fn take_death_drops(&mut self) -> ActorResult<Vec<ItemStack>> {
+        let mut drops = Vec::new();
+        for slot in 0..46 {
+            let item = self
+                .associated_data
+                .inventory
+                .get_slot(slot)
+                .unwrap_or_default();
+            if !is_empty(&item) {
+                drops.push(item);
+                self.associated_data
+                    .inventory
+                    .set_slot(slot, ItemStack::air())?;
+            }
+        }
+        let cursor = std::mem::replace(&mut self.associated_data.cursor_item, ItemStack::air());
+        if !is_empty(&cursor) {
+            drops.push(cursor);
         }
+        self.set(
+            PlayerComponents::EXPERIENCE,
+            ExperienceComponent {
+                level: 0,
+                progress: 0.0,
+            },
+        );
+        self.send_inventory();
+        Ok(drops)
+    }
+
+    #[GiveItem]
+    #[doc = "Puts as much of `item` into the player's inventory as fits, topping up stacks of the same item before filling empty slots, hotbar first. Returns what didn't fit, or air if everything did."]
+    pub fn give_item(&mut self, item: ItemStack) -> ActorResult<ItemStack> {
+        self.insert_item(item)
+    }
 
+    #[ForgetUnsentChunks]
+    #[doc = "Marks chunks from a batch that weren't sent as unloaded, so they're sent again."]
+    pub(crate) fn forget_unsent_chunks(
+        &mut self,
+        chunks: Vec<IVec2>,
+        batch_sent: bool,
+    ) -> ActorResult<()> {
+        self.forget_chunks(&chunks, batch_sent);
         Ok(())
     }
 
+    #[SpaceForItem]
+    #[doc = "Returns how many of `item` would fit in the player's inventory."]
+    pub(crate) fn space_for_item(&self, item: ItemStack) -> ActorResult<u16> {
+        self.space_for(&item)
+    }
+
     #[GetInvSlot]
     pub(crate) fn get_inv_slot(&self, slot: usize) -> ActorResult<ItemStack> {
         self.associated_data.inventory.get_slot(slot)
@@ -381,6 +500,361 @@ impl ConnectionData {
         Ok(())
     }
 
+    #[SetCooldown]
+    #[doc = "Puts an item on cooldown for the given duration. A zero duration clears the cooldown."]
+    pub fn set_cooldown(&mut self, item: Id, duration: Duration) -> ActorResult<()> {
+        self.write_packet(CooldownS2CPlayPacket {
+            group: item.clone().into(),
+            duration: VarInt::new((duration.as_millis() / 50).min(i32::MAX as u128) as i32),
+        });
+        if duration.is_zero() {
+            self.associated_data.cooldowns.remove(&item);
+        } else {
+            self.associated_data
+                .cooldowns
+                .insert(item, Instant::now() + duration);
+        }
+        Ok(())
+    }
+
+    #[IsOnCooldown]
+    pub fn is_on_cooldown(&mut self, item: Id) -> ActorResult<bool> {
+        Ok(self.associated_data.is_on_cooldown(&item))
+    }
+
+    #[GetVelocity]
+    #[doc = "Returns the player's velocity in blocks per tick, worked out from their latest movement. Players who haven't moved recently are standing still."]
+    pub fn velocity(&self) -> ActorResult<DVec3> {
+        Ok(movement_velocity(
+            self.associated_data.last_movement,
+            Instant::now(),
+        ))
+    }
+
+    #[SetActionCooldown]
+    #[doc = "Puts an action, such as a game's ability, on cooldown for the given number of ticks. Zero ticks clears the cooldown. Keys are arbitrary and separate from item cooldowns; use `set_cooldown` as well to show the cooldown on an item."]
+    pub fn set_action_cooldown(&mut self, key: String, ticks: u32) -> ActorResult<()> {
+        self.associated_data
+            .action_cooldowns
+            .set(key, ticks, Instant::now());
+        Ok(())
+    }
+
+    #[ActionCooldownRemaining]
+    #[doc = "Returns how many ticks are left on an action's cooldown, rounded up, or 0 if it isn't on cooldown."]
+    pub fn action_cooldown_remaining(&mut self, key: String) -> ActorResult<u32> {
+        Ok(self
+            .associated_data
+            .action_cooldowns
+            .remaining(&key, Instant::now()))
+    }
+
+    #[RecordDamage]
+    pub(crate) fn record_damage(&mut self, source: DamageSource, amount: f32) -> ActorResult<()> {
+        self.associated_data
+            .damage_history
+            .record(source, amount, Instant::now());
+        Ok(())
+    }
+
+    #[GetDamageHistory]
+    #[doc = "Returns the damage this player took recently, for attributing kills."]
+    pub fn damage_history(&mut self) -> ActorResult<DamageHistory> {
+        self.associated_data.damage_history.expire(Instant::now());
+        Ok(self.associated_data.damage_history.clone())
+    }
+
+    #[TakeDamageHistory]
+    pub(crate) fn take_damage_history(&mut self) -> ActorResult<DamageHistory> {
+        self.associated_data.damage_history.expire(Instant::now());
+        Ok(std::mem::take(&mut self.associated_data.damage_history))
+    }
+
+    #[EffectiveAttributes]
+    #[doc = "Returns the player's attributes with the modifiers of the items they hold and wear, and of their effects, applied."]
+    pub fn effective_attributes(&mut self) -> ActorResult<AttributeContainer> {
+        Ok(self
+            .get(PlayerComponents::ATTRIBUTES)
+            .unwrap_or_default()
+            .with_modifiers(&self.equipment_modifiers())
+            .with_modifiers(&self.associated_data.effects.attribute_modifiers()))
+    }
+
+    #[SyncAttributes]
+    #[doc = "Sends the player's attributes, with their equipment's modifiers applied, if they changed since they were last sent."]
+    pub(crate) fn sync_attributes(&mut self) -> ActorResult<()> {
+        let mut attributes = self.effective_attributes()?;
+        // The client keeps attributes it was sent, so ones no longer modified are reset.
+        for attribute in self.associated_data.sent_attributes.component_map().keys() {
+            if !attributes.component_map().contains(attribute) {
+                attributes.set(
+                    DataComponentType::<f64>::new(attribute.clone()),
+                    default_attribute_value(attribute),
+                );
+            }
+        }
+        if attributes == self.associated_data.sent_attributes {
+            return Ok(());
+        }
+        self.write_packet(attributes.into_packet(self.associated_data.entity_id));
+
+        // Other players only need to know the player's scale, which also sizes their hitbox.
+        let scale = attributes.get(Attributes::SCALE).ok();
+        if scale
+            != self
+                .associated_data
+                .sent_attributes
+                .get(Attributes::SCALE)
+                .ok()
+            && let Some(dimension) = self.associated_data.dimension.clone()
+        {
+            let uuid = self.get(PlayerComponents::UUID)?;
+            Runtime::spawn_task(async move {
+                let entity = dimension.get_entity(uuid);
+                let mut attributes = entity.get(EntityComponents::ATTRIBUTES).unwrap_or_default();
+                attributes.set(Attributes::SCALE, scale.unwrap_or(1.0));
+                entity.set(EntityComponents::ATTRIBUTES, attributes)
+            });
+        }
+        self.associated_data.sent_attributes = attributes;
+        Ok(())
+    }
+
+    #[SendFakeBlock]
+    #[doc = "Shows this player a block that isn't in their dimension, such as for a build preview. The fake block is tracked until it's cleared, the player changes dimension or their client unloads its chunk. Changes to the real block are still shown over it."]
+    pub fn send_fake_block(&mut self, position: IVec3, state: BlockState) -> ActorResult<()> {
+        self.write_packet(fake_blocks::block_update(position, &state));
+        self.associated_data.fake_blocks.insert(position, state);
+        Ok(())
+    }
+
+    #[ClearFakeBlock]
+    #[doc = "Shows this player the real block where a fake block was sent, if there is one."]
+    pub fn clear_fake_block(&mut self, position: IVec3) -> ActorResult<()> {
+        if self.associated_data.fake_blocks.remove(&position).is_some() {
+            self.correct_block(position)?;
+        }
+        Ok(())
+    }
+
+    #[SendFakeBlocks]
+    #[doc = "Shows this player many fake blocks at once, such as a preview of a structure, with one update per chunk section. Fake blocks are tracked the same way as with `send_fake_block`."]
+    pub fn send_fake_blocks(&mut self, blocks: Vec<(IVec3, BlockState)>) -> ActorResult<()> {
+        let mut updates = SectionUpdates::default();
+        for (position, state) in blocks {
+            updates.push(position, state.protocol_id() as u32, None);
+            self.associated_data.fake_blocks.insert(position, state);
+        }
+        self.write_section_updates(updates);
+        Ok(())
+    }
+
+    #[ClearFakeBlocksAt]
+    #[doc = "Shows this player the real blocks where fake blocks were sent at any of the given positions."]
+    pub fn clear_fake_blocks_at(&mut self, positions: Vec<IVec3>) -> ActorResult<()> {
+        self.revert_fake_blocks(positions)
+    }
+
+    #[ClearFakeBlocks]
+    #[doc = "Shows this player the real blocks where any fake blocks were sent."]
+    pub fn clear_fake_blocks(&mut self) -> ActorResult<()> {
+        let positions = self.associated_data.fake_blocks.keys().copied().collect();
+        self.revert_fake_blocks(positions)
+    }
+
+    #[FakeBlocks]
+    #[doc = "Returns the fake blocks this player is being shown, by position."]
+    pub fn fake_blocks(&mut self) -> ActorResult<HashMap<IVec3, BlockState>> {
+        Ok(self.associated_data.fake_blocks.clone())
+    }
+
+    #[AddEffect]
+    #[doc = "Gives this player an effect. If they already have it, a higher amplifier or, at the same amplifier, a longer duration wins."]
+    pub fn add_effect(&mut self, effect: Effect) -> ActorResult<()> {
+        let now = Instant::now();
+        let id = effect.id.clone();
+        if !self.associated_data.effects.add(effect, now) {
+            return Ok(());
+        }
+        let active = self.associated_data.effects.get(&id).unwrap();
+        let packet = effect_packet(self.associated_data.entity_id, active, now)?;
+        if let Some(dimension) = self.associated_data.dimension.clone() {
+            broadcast_effect_packets(
+                dimension,
+                self.get(PlayerComponents::UUID)?,
+                vec![packet],
+                Vec::new(),
+            );
+        }
+        Ok(())
+    }
+
+    #[RemoveEffect]
+    #[doc = "Removes an effect from this player, if they have it."]
+    pub fn remove_effect(&mut self, id: Id) -> ActorResult<()> {
+        if !self.associated_data.effects.remove(&id) {
+            return Ok(());
+        }
+        let packet = remove_effect_packet(self.associated_data.entity_id, &id)?;
+        if let Some(dimension) = self.associated_data.dimension.clone() {
+            broadcast_effect_packets(
+                dimension,
+                self.get(PlayerComponents::UUID)?,
+                Vec::new(),
+                vec![packet],
+            );
+        }
+        Ok(())
+    }
+
+    #[GetEffects]
+    #[doc = "Returns the effects this player has."]
+    pub fn effects(&mut self) -> ActorResult<ActiveEffects> {
+        Ok(self.associated_data.effects.clone())
+    }
+
+    #[TickEffects]
+    pub(crate) fn tick_effects(&mut self) -> ActorResult<()> {
+        self.associated_data.effect_ticks += 1;
+        if self
+            .associated_data
+            .effects
+            .regenerates(self.associated_data.effect_ticks)
+        {
+            let max = self
+                .effective_attributes()?
+                .get(Attributes::MAX_HEALTH)
+                .unwrap_or(DEFAULT_MAX_HEALTH as f64) as f32;
+            let mut health = self.get(PlayerComponents::HEALTH)?;
+            if health.health > 0.0 && health.health < max {
+                health.health = (health.health + 1.0).min(max);
+                self.set(PlayerComponents::HEALTH, health);
+            }
+        }
+
+        let expired = self.associated_data.effects.expire(Instant::now());
+        if expired.is_empty() {
+            return Ok(());
+        }
+        let removals = expired
+            .iter()
+            .map(|id| remove_effect_packet(self.associated_data.entity_id, id))
+            .collect::<ActorResult<Vec<_>>>()?;
+        if let Some(dimension) = self.associated_data.dimension.clone() {
+            broadcast_effect_packets(
+                dimension,
+                self.get(PlayerComponents::UUID)?,
+                Vec::new(),
+                removals,
+            );
+        }
+        Ok(())
+    }
+
+    #[ShowTitle]
+    pub fn show_title(&mut self, title: Title) -> ActorResult<()> {
+        self.write_packet(SetTitlesAnimationS2CPlayPacket {
+            fade_in: Title::ticks(title.fade_in),
+            stay: Title::ticks(title.stay),
+            fade_out: Title::ticks(title.fade_out),
+        });
+        self.write_packet(SetSubtitleTextS2CPlayPacket {
+            text: PtcText::from(title.subtitle).to_nbt(),
+        });
+        self.write_packet(SetTitleTextS2CPlayPacket {
+            text: PtcText::from(title.title).to_nbt(),
+        });
+        Ok(())
+    }
+
+    #[ClearTitle]
+    pub fn clear_title(&mut self) -> ActorResult<()> {
+        self.write_packet(ClearTitlesS2CPlayPacket { reset: true });
+        Ok(())
+    }
+
+    #[SetClientTime]
+    #[doc = "Shows this player a fixed time of day instead of their dimension's, until cleared."]
+    pub fn set_client_time(&mut self, time: i64) -> ActorResult<()> {
+        self.associated_data.client_time = Some(time);
+        self.send_client_time()
+    }
+
+    #[ClearClientTime]
+    pub fn clear_client_time(&mut self) -> ActorResult<()> {
+        self.associated_data.client_time = None;
+        self.send_client_time()
+    }
+
+    #[Respawn]
+    #[doc = "Respawns this player where they died if they are dead, closing their death screen. Does nothing if they are alive."]
+    pub fn respawn(&mut self) -> ActorResult<()> {
+        self.perform_respawn()
+    }
+
+    #[ShowDimensionTime]
+    #[doc = "Shows this player their dimension's time, unless they are shown a time of their own."]
+    pub(crate) fn show_dimension_time(
+        &mut self,
+        world_age: i64,
+        time: i64,
+        increasing: bool,
+    ) -> ActorResult<()> {
+        self.show_time(world_age, time, increasing);
+        Ok(())
+    }
+
+    #[SetClientWeather]
+    #[doc = "Shows this player the given weather instead of their dimension's, until cleared."]
+    pub fn set_client_weather(&mut self, weather: Weather) -> ActorResult<()> {
+        self.associated_data.client_weather = Some(weather);
+        self.send_client_weather()
+    }
+
+    #[ClearClientWeather]
+    pub fn clear_client_weather(&mut self) -> ActorResult<()> {
+        self.associated_data.client_weather = None;
+        self.send_client_weather()
+    }
+
+    #[ShowDimensionWeather]
+    #[doc = "Shows this player their dimension's weather, unless they are shown weather of their own."]
+    pub(crate) fn show_dimension_weather(&mut self, weather: Weather) -> ActorResult<()> {
+        self.show_weather(self.associated_data.client_weather.unwrap_or(weather));
+        Ok(())
+    }
+
+    #[SetRotation]
+    #[doc = "Forces the player to face the given yaw and pitch in degrees, keeping their position."]
+    pub fn set_rotation(&mut self, rotation: Vec2) -> ActorResult<()> {
+        let teleport_id = self.get(PlayerComponents::TELEPORT_SYNC_SENT).unwrap_or(10) + 1;
+        self.set(PlayerComponents::TELEPORT_SYNC_SENT, teleport_id);
+        self.set(PlayerComponents::DIRECTION, rotation);
+        self.write_packet(PlayerPositionS2CPlayPacket {
+            teleport_id: VarInt::from(teleport_id),
+            x: 0.0,
+            y: 0.0,
+            z: 0.0,
+            vx: 0.0,
+            vy: 0.0,
+            vz: 0.0,
+            adyaw_deg: rotation[0],
+            adpitch_deg: rotation[1],
+            flags: TeleportFlags {
+                relative_x: true,
+                relative_y: true,
+                relative_z: true,
+                relative_pitch: false,
+                relative_yaw: false,
+                relative_vx: true,
+                relative_vy: true,
+                relative_vz: true,
+                rotate_velocity: false,
+            },
+        });
+        Ok(())
+    }
+
     #[OpenScreen]
     pub fn open_screen(&mut self, kind: InventoryKind) -> ActorResult<()> {
         let id = if self.associated_data.window_id > 100 {
@@ -402,15 +876,64 @@ impl ConnectionData {
                 ItemStack::air,
             ),
         ));
+        self.associated_data.locked_slots.clear();
+        self.associated_data.drag = None;
+        self.associated_data.screen_result = None;
+        self.associated_data.anvil_rename = None;
+        Ok(())
+    }
+
+    #[SetScreenResult]
+    #[doc = "Sets how the result slot of the player's open anvil or crafting screen is computed, which happens in the player's actor whenever an input slot or the anvil's name changes. The callback must not make requests to this player. Cleared whenever a screen is opened."]
+    pub fn set_screen_result(&mut self, result: ResultSlot) -> ActorResult<()> {
+        let Some((kind, _)) = &self.associated_data.screen else {
+            return Err(ActorError::BadRequest);
+        };
+        if kind.result_slot().is_none() {
+            return Err(ActorError::BadRequest);
+        }
+        self.associated_data.screen_result = Some(result);
+        self.update_screen_result()
+    }
+
+    #[SetScreenLocked]
+    #[doc = "Locks or unlocks every slot of the player's open screen. See `set_screen_slot_locked`."]
+    pub fn set_screen_locked(&mut self, locked: bool) -> ActorResult<()> {
+        let Some((kind, _)) = &self.associated_data.screen else {
+            return Err(ActorError::BadRequest);
+        };
+        self.associated_data.locked_slots = if locked {
+            (0..ScreenWindowKind::from(*kind).container_slot_count()).collect()
+        } else {
+            HashSet::new()
+        };
+        Ok(())
+    }
+
+    #[SetScreenSlotLocked]
+    #[doc = "Sets whether a slot of the player's open screen is locked. Clicking a locked slot only fires a `MenuClickEvent`, and items are never moved into or out of it. Locks are cleared whenever a screen is opened or closed."]
+    pub fn set_screen_slot_locked(&mut self, slot: usize, locked: bool) -> ActorResult<()> {
+        let Some((kind, _)) = &self.associated_data.screen else {
+            return Err(ActorError::BadRequest);
+        };
+        if slot >= ScreenWindowKind::from(*kind).container_slot_count() {
+            return Err(ActorError::IndexOutOfBounds);
+        }
+        if locked {
+            self.associated_data.locked_slots.insert(slot);
+        } else {
+            self.associated_data.locked_slots.remove(&slot);
+        }
         Ok(())
     }
 
     #[SetScreenSlot]
     pub fn set_screen_slot(&mut self, slot: usize, item: ItemStack) -> ActorResult<()> {
-        let Some(inventory) = self.associated_data.screen.as_mut().map(|x| &mut x.1) else {
+        let Some((kind, inventory)) = self.associated_data.screen.as_mut() else {
             return Err(ActorError::BadRequest);
         };
         inventory.set_slot(slot, item.clone())?;
+        let is_input = kind.input_slots().contains(&slot);
 
         self.write_packet(ContainerSetSlotS2CPlayPacket {
             window_id: VarInt::new(self.associated_data.window_id as i32),
@@ -419,6 +942,9 @@ impl ConnectionData {
             slot_data: item.into(),
         });
 
+        if is_input {
+            self.update_screen_result()?;
+        }
         Ok(())
     }
 
@@ -431,10 +957,11 @@ impl ConnectionData {
     }
 
     #[PlaySound]
+    #[doc = "Plays a sound for this player that follows them as they move."]
     pub fn play_sound(&mut self, sound: Sound) -> ActorResult<()> {
         self.write_packet(SoundEntityS2CPlayPacket {
             sound: sound.clone().into(),
-            category: SoundCategory::Master,
+            category: sound.get_category().into(),
             entity: self.associated_data.entity_id.into(),
             volume: sound.get_volume(),
             pitch: sound.get_pitch(),
@@ -443,26 +970,44 @@ impl ConnectionData {
         Ok(())
     }
 
+    #[PlaySoundAt]
+    #[doc = "Plays a sound at a position for this player only."]
+    pub fn play_sound_at(&mut self, pos: DVec3, sound: Sound) -> ActorResult<()> {
+        self.write_packet(sound_packet(pos, sound));
+        Ok(())
+    }
+
+    #[StopSound]
+    #[doc = "Stops sounds playing for this player. Only sounds with the given name and in the given category are stopped, and every sound is stopped if neither is given."]
+    pub fn stop_sound(
+        &mut self,
+        sound: Option<Id>,
+        category: Option<SoundCategory>,
+    ) -> ActorResult<()> {
+        self.write_packet(stop_sound_packet(sound, category));
+        Ok(())
+    }
+
     #[EntityId]
     pub(crate) fn entity_id(&self) -> ActorResult<i32> {
         Ok(self.associated_data.entity_id)
     }
 
     #[PlayParticle]
+    #[doc = "Plays a single particle at a position for this player."]
     pub fn play_particle(&mut self, pos: DVec3, particle: Particle) -> ActorResult<()> {
-        self.write_packet(LevelParticlesS2CPlayPacket {
-            long_distance: true,
-            always_visible: true,
-            x: pos[0],
-            y: pos[1],
-            z: pos[2],
-            spread_x: 0.0,
-            spread_y: 0.0,
-            spread_z: 0.0,
-            max_speed: 0.0,
-            count: 1,
-            particle: particle.into(),
-        });
+        self.play_particle_ex(pos, particle, ParticleOptions::default())
+    }
+
+    #[PlayParticleEx]
+    #[doc = "Plays particles at a position for this player, spread out as described by `options`."]
+    pub fn play_particle_ex(
+        &mut self,
+        pos: DVec3,
+        particle: Particle,
+        options: ParticleOptions,
+    ) -> ActorResult<()> {
+        self.write_packet(particle_packet(pos, particle, options));
         Ok(())
     }
 
@@ -486,9 +1031,9 @@ impl Player {
         &self,
         packet: P,
     ) -> ActorResult<()> {
-        log::debug!("sending: {:#?}", packet);
         let mut buf = PacketBuf::new();
         packet.encode_prefixed(&mut buf).unwrap();
+        log_packet(PacketDirection::Clientbound, &packet, buf.as_slice());
 
         let mut len_buf: PacketBuf = PacketBuf::new();
         VarInt::from(buf.iter().count())
@@ -504,18 +1049,134 @@ impl Player {
         Ok(())
     }
 
+    /// Encodes a packet once and sends it to every player given, skipping players that have disconnected.
+    pub(crate) fn broadcast_packet<P: PrefixedPacketEncode + std::fmt::Debug>(
+        players: &[Player],
+        packet: P,
+    ) -> ActorResult<()> {
+        let mut buf = PacketBuf::new();
+        packet.encode_prefixed(&mut buf).unwrap();
+        log_packet(PacketDirection::Clientbound, &packet, buf.as_slice());
+        let bytes = buf.into_inner();
+
+        for player in players {
+            match player.send_packet_buf(PacketBuf::from(bytes.clone())) {
+                Ok(()) | Err(ActorError::ActorHasBeenDropped) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns a handle to this player's inventory. This does not contact the player's actor.
     pub fn inventory(&self) -> ActorResult<PlayerInventory> {
         Ok(PlayerInventory {
             player: self.clone(),
         })
     }
+
+    pub fn persistent_data(&self) -> ActorResult<PersistentData> {
+        Ok(PersistentData {
+            player: self.clone(),
+        })
+    }
+
+    pub fn scoreboard(&self) -> ActorResult<Scoreboard> {
+        Ok(Scoreboard {
+            player: self.clone(),
+        })
+    }
+
+    /// Returns the entity representing this player in their current dimension.
+    pub fn as_entity(&self) -> ActorResult<Entity> {
+        Ok(Entity {
+            dimension: self.dimension()?,
+            uuid: self.get(PlayerComponents::UUID)?,
+        })
+    }
 }
 
 impl ConnectionData {
+    /// Sends the time of day this player should see, which is their dimension's without an
+    /// override.
+    pub(crate) fn send_client_time(&mut self) -> ActorResult<()> {
+        let (world_age, time, increasing) = match self.associated_data.dimension.clone() {
+            Some(dimension) => (
+                dimension.world_age()?,
+                dimension.time()?,
+                dimension.game_rule(GameRuleKeys::DO_DAYLIGHT_CYCLE)?,
+            ),
+            None => (0, 0, false),
+        };
+        self.show_time(world_age, time, increasing);
+        Ok(())
+    }
+
+    /// Sends the player a time of day, or their override if they have one. Overrides don't
+    /// advance.
+    fn show_time(&mut self, world_age: i64, time: i64, increasing: bool) {
+        let (time, increasing) = match self.associated_data.client_time {
+            Some(time) => (time, false),
+            None => (time, increasing),
+        };
+        self.write_packet(SetTimeS2CPlayPacket {
+            world_age,
+            time,
+            time_increasing: increasing,
+        });
+    }
+
+    /// Sends the difficulty of this player's dimension, along with whether it skips the death screen.
+    pub(crate) fn send_difficulty(&mut self) -> ActorResult<()> {
+        if let Some(dimension) = self.associated_data.dimension.clone() {
+            self.write_packet(difficulty_packet(
+                dimension.difficulty()?,
+                dimension.is_difficulty_locked()?,
+            ));
+            let immediate = dimension.game_rule(GameRuleKeys::DO_IMMEDIATE_RESPAWN)?;
+            self.write_packet(immediate_respawn_packet(immediate));
+        }
+        Ok(())
+    }
+
+    /// Sends the weather this player should see, which is their dimension's without an override.
+    pub(crate) fn send_client_weather(&mut self) -> ActorResult<()> {
+        let weather = match (
+            self.associated_data.client_weather,
+            self.associated_data.dimension.clone(),
+        ) {
+            (Some(weather), _) => weather,
+            (None, Some(dimension)) => dimension.weather()?,
+            (None, None) => Weather::Clear,
+        };
+        self.show_weather(weather);
+        Ok(())
+    }
+
+    fn show_weather(&mut self, weather: Weather) {
+        let (rain, thunder) = weather.levels();
+        self.write_packet(GameEventS2CPlayPacket {
+            event: if weather == Weather::Clear {
+                GameEvent::EndRaining
+            } else {
+                GameEvent::BeginRaining
+            },
+            value: 0.0,
+        });
+        self.write_packet(GameEventS2CPlayPacket {
+            event: GameEvent::RainLevelChange,
+            value: rain,
+        });
+        self.write_packet(GameEventS2CPlayPacket {
+            event: GameEvent::ThunderLevelChange,
+            value: thunder,
+        });
+    }
+
     pub fn write_packet<P: PrefixedPacketEncode + std::fmt::Debug>(&mut self, packet: P) {
-        log::debug!("sending: {:#?}", packet);
         let mut buf = PacketBuf::new();
         packet.encode_prefixed(&mut buf).unwrap();
+        log_packet(PacketDirection::Clientbound, &packet, buf.as_slice());
 
         let mut len_buf = PacketBuf::new();
         VarInt::from(buf.iter().count())
@@ -0,0 +1,58 @@
+use std::time::{Duration, Instant};
+
+use wyvern_components::DataComponentHolder;
+use wyvern_values::DVec3;
+
+use crate::actors::ActorResult;
+
+use super::{ConnectionData, PlayerComponents};
+
+/// How long a movement counts towards the player's velocity. Clients send a movement every
+/// tick while they move, so a longer gap means they have stopped.
+const MOVEMENT_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// Returns the velocity, in blocks per tick, of a player whose last movement was by `delta`
+/// at `moved_at`.
+pub(crate) fn movement_velocity(last_movement: Option<(DVec3, Instant)>, now: Instant) -> DVec3 {
+    match last_movement {
+        Some((delta, moved_at)) if now.duration_since(moved_at) <= MOVEMENT_TIMEOUT => delta,
+        _ => DVec3::ZERO,
+    }
+}
+
+impl ConnectionData {
+    /// Records the player moving from their current position to `position`, so their velocity
+    /// can be worked out. Must be called before `POSITION` is updated.
+    pub(crate) fn record_movement(&mut self, position: DVec3) -> ActorResult<()> {
+        let delta = position - self.get(PlayerComponents::POSITION)?;
+        self.associated_data.last_movement = Some((delta, Instant::now()));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use wyvern_values::DVec3;
+
+    use super::movement_velocity;
+
+    #[test]
+    fn velocity_comes_from_recent_movements() {
+        let moved_at = Instant::now();
+        let delta = DVec3::new(0.2, -0.08, 0.0);
+        let last = Some((delta, moved_at));
+        assert_eq!(movement_velocity(last, moved_at), delta);
+        assert_eq!(
+            movement_velocity(last, moved_at + Duration::from_millis(50)),
+            delta
+        );
+        // A player who stopped sending movements is standing still.
+        assert_eq!(
+            movement_velocity(last, moved_at + Duration::from_secs(1)),
+            DVec3::ZERO
+        );
+        assert_eq!(movement_velocity(None, moved_at), DVec3::ZERO);
+    }
+}
@@ -9,19 +9,22 @@ use std::{
 
 use crate::{
     actors::{Actor, ActorResult},
+    player::packet_log::{PacketDirection, log_packet},
     runtime::Runtime,
 };
 use flume::{Receiver, Sender};
 use voxidian_protocol::{
     packet::{
-        DecodeError, PrefixedPacketDecode, Stage, c2s::handshake::C2SHandshakePackets,
-        processing::PacketProcessing, s2c::play::KeepAliveS2CPlayPacket,
+        DecodeError, PrefixedPacketDecode, Stage,
+        c2s::handshake::C2SHandshakePackets,
+        processing::PacketProcessing,
+        s2c::play::{KeepAliveS2CPlayPacket, PlayerActionEntry, PlayerInfoUpdateS2CPlayPacket},
     },
-    value::Uuid,
+    value::{Uuid, VarInt},
 };
 use wyvern_actors::ActorError;
 use wyvern_components::{DataComponentHolder, DataComponentMap};
-use wyvern_datatypes::gamemode::Gamemode;
+use wyvern_datatypes::{gamemode::Gamemode, nbt::NbtCompound, text::Text};
 
 use crate::{player::PlayerMessage, server::Server};
 
@@ -29,6 +32,12 @@ use super::{ConnectionData, ConnectionWithSignal, Player, PlayerComponents, data
 
 pub struct ConnectionStoppedSignal;
 
+/// How often players in the play stage are sent a keep-alive, unless the server says otherwise.
+pub(crate) const DEFAULT_KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(5);
+/// How long a player has to answer a keep-alive before they are disconnected, unless the
+/// server says otherwise.
+pub(crate) const DEFAULT_KEEP_ALIVE_TIMEOUT: Duration = Duration::from_secs(30);
+
 impl ConnectionData {
     pub fn connection_channel(
         stream: TcpStream,
@@ -68,6 +77,9 @@ impl ConnectionData {
         stage: Arc<Mutex<Stage>>,
     ) {
         stream.set_nonblocking(true).unwrap();
+        let (keep_alive_interval, keep_alive_timeout) = server
+            .keep_alive_settings()
+            .unwrap_or((DEFAULT_KEEP_ALIVE_INTERVAL, DEFAULT_KEEP_ALIVE_TIMEOUT));
         let conn = ConnectionData {
             stream,
             addr,
@@ -79,11 +91,16 @@ impl ConnectionData {
             signal,
             stage,
             connected_server: server,
-            associated_data: PlayerData::default(),
+            associated_data: PlayerData {
+                keep_alive_interval,
+                keep_alive_timeout,
+                ..Default::default()
+            },
             mojauth: None,
             components: DataComponentMap::new()
                 .with(PlayerComponents::GAMEMODE, Gamemode::Survival),
             last_saved_components: DataComponentMap::new(),
+            persistent_data: NbtCompound::new(),
         };
 
         conn.event_loop();
@@ -91,7 +108,9 @@ impl ConnectionData {
 
     pub fn event_loop(mut self) {
         loop {
-            let result = self.handle_incoming_bytes();
+            let result = self
+                .handle_incoming_bytes()
+                .and_then(|()| self.check_keep_alive());
             if result.is_err() {
                 log::info!("A player has disconnected. Stopping their connection data...");
 
@@ -99,6 +118,12 @@ impl ConnectionData {
                     let _ = dim
                         .remove_entity(self.get(PlayerComponents::UUID).unwrap_or(Uuid::new_v4()));
                 }
+                // Saved before the connection stops so a quick reconnect loads this data.
+                if let Ok(uuid) = self.get(PlayerComponents::UUID)
+                    && let Ok(store) = self.connected_server.player_data_store()
+                {
+                    store.save(uuid, std::mem::take(&mut self.persistent_data));
+                }
                 self.signal.send(ConnectionStoppedSignal).unwrap();
                 drop(self);
                 return;
@@ -106,15 +131,68 @@ impl ConnectionData {
             self.handle_messages();
             let _ = self.read_incoming_packets();
             self.write_outgoing_packets();
+        }
+    }
 
+    /// Sends a keep-alive if one is due, then closes the connection if the player has been
+    /// kicked, such as for leaving a keep-alive unanswered for too long.
+    fn check_keep_alive(&mut self) -> Result<(), ()> {
+        if *self.stage.lock().unwrap() == Stage::Play {
             let now = Instant::now();
-            if now > self.associated_data.last_sent_keep_alive + Duration::from_secs(5)
-                && *self.stage.lock().unwrap() == Stage::Play
-            {
-                self.write_packet(KeepAliveS2CPlayPacket(10));
-                self.associated_data.last_sent_keep_alive = Instant::now();
+            let data = &mut self.associated_data;
+            if let Some((_, sent)) = data.pending_keep_alive {
+                if now > sent + data.keep_alive_timeout {
+                    data.kick = Some(Text::literal("Timed out"));
+                }
+            } else if now > data.last_sent_keep_alive + data.keep_alive_interval {
+                let id = data.next_keep_alive_id;
+                data.next_keep_alive_id += 1;
+                data.pending_keep_alive = Some((id, now));
+                data.last_sent_keep_alive = now;
+                self.write_packet(KeepAliveS2CPlayPacket(id));
+            }
+        }
+
+        match self.associated_data.kick.take() {
+            Some(reason) => {
+                log::info!("Kicking a player: {}", reason.to_plain());
+                let _ = self.disconnect_internal(reason);
+                self.write_outgoing_packets();
+                Err(())
             }
+            None => Ok(()),
+        }
+    }
+
+    /// Measures the player's latency from the keep-alive they answered, and shows it to every
+    /// player in the player list.
+    pub(crate) fn receive_keep_alive(&mut self, id: u64) -> ActorResult<()> {
+        let Some((pending, sent)) = self.associated_data.pending_keep_alive else {
+            return Ok(());
+        };
+        if pending != id {
+            self.associated_data.kick = Some(Text::literal("Invalid keep-alive"));
+            return Ok(());
         }
+        self.associated_data.pending_keep_alive = None;
+
+        let ping = sent.elapsed().as_millis().min(i32::MAX as u128) as u32;
+        self.set(PlayerComponents::PING, ping);
+
+        let uuid = self.get(PlayerComponents::UUID)?;
+        let server = self.connected_server.clone();
+        Runtime::spawn_task(async move {
+            Player::broadcast_packet(
+                &server.players()?,
+                PlayerInfoUpdateS2CPlayPacket {
+                    actions: vec![(
+                        uuid,
+                        vec![PlayerActionEntry::Latency(VarInt::from(ping as i32))],
+                    )],
+                },
+            )
+        });
+        Ok(())
     }
 
     pub fn handle_incoming_bytes(&mut self) -> Result<(), ()> {
@@ -147,6 +225,7 @@ impl ConnectionData {
             Stage::Handshake => {
                 self.read_packets(|packet: C2SHandshakePackets, this: &mut Self| {
                     let C2SHandshakePackets::Intention(packet) = packet;
+                    this.associated_data.protocol_version = packet.protocol_version.as_i32();
                     *this.stage.lock().unwrap() = packet.intended_stage.into_stage();
                     Ok(())
                 })?;
@@ -209,6 +288,7 @@ impl ConnectionData {
                 let buf_copy = buf.clone();
                 match T::decode_prefixed(&mut buf) {
                     Ok(packet) => {
+                        log_packet(PacketDirection::Serverbound, &packet, buf_copy.as_slice());
                         f(packet, self)?;
                         Ok(())
                     }
@@ -0,0 +1,177 @@
+//! Logging of the packets sent to and received from players, for debugging the protocol.
+//!
+//! Packets are logged at the info level under the `wyvern::packets` target once a filter is set
+//! with `set_packet_log` or `ServerBuilder::packet_log`. While no filter is set, checking
+//! whether to log a packet costs a single atomic load.
+
+use std::{
+    fmt::{Debug, Write},
+    sync::{
+        RwLock,
+        atomic::{AtomicBool, Ordering},
+    },
+};
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static FILTER: RwLock<Option<PacketLogFilter>> = RwLock::new(None);
+
+/// Which way a packet was going.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PacketDirection {
+    /// Sent by a client to the server.
+    Serverbound,
+    /// Sent by the server to a client.
+    Clientbound,
+}
+
+/// Which packets are logged, and how.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PacketLogFilter {
+    pub serverbound: bool,
+    pub clientbound: bool,
+    /// Only packets whose type name contains one of these, ignoring case, are logged. Every
+    /// packet is logged if this is empty.
+    pub packets: Vec<String>,
+    /// Whether the encoded bytes of each packet are logged as hex after it.
+    pub hex: bool,
+}
+
+impl Default for PacketLogFilter {
+    fn default() -> Self {
+        PacketLogFilter {
+            serverbound: true,
+            clientbound: true,
+            packets: Vec::new(),
+            hex: false,
+        }
+    }
+}
+
+impl PacketLogFilter {
+    /// A filter that logs every packet in both directions, without hex.
+    pub fn new() -> PacketLogFilter {
+        PacketLogFilter::default()
+    }
+
+    pub fn serverbound(mut self, serverbound: bool) -> Self {
+        self.serverbound = serverbound;
+        self
+    }
+
+    pub fn clientbound(mut self, clientbound: bool) -> Self {
+        self.clientbound = clientbound;
+        self
+    }
+
+    /// Adds a packet type to log, such as `SetHealth` or `Chunk`. Once any are added, other
+    /// packets aren't logged.
+    pub fn packet(mut self, name: impl Into<String>) -> Self {
+        self.packets.push(name.into());
+        self
+    }
+
+    pub fn hex(mut self, hex: bool) -> Self {
+        self.hex = hex;
+        self
+    }
+
+    fn matches(&self, direction: PacketDirection, name: &str) -> bool {
+        let direction = match direction {
+            PacketDirection::Serverbound => self.serverbound,
+            PacketDirection::Clientbound => self.clientbound,
+        };
+        let name = name.to_lowercase();
+        direction
+            && (self.packets.is_empty()
+                || self
+                    .packets
+                    .iter()
+                    .any(|packet| name.contains(&packet.to_lowercase())))
+    }
+}
+
+/// Starts logging packets that pass `filter`, or stops logging packets if it's `None`. This
+/// can be changed while the server is running.
+pub fn set_packet_log(filter: Option<PacketLogFilter>) {
+    let mut current = FILTER.write().unwrap();
+    ENABLED.store(filter.is_some(), Ordering::Relaxed);
+    *current = filter;
+}
+
+/// Returns the filter packets are currently logged with, if they're being logged.
+pub fn packet_log() -> Option<PacketLogFilter> {
+    FILTER.read().unwrap().clone()
+}
+
+/// Returns the name of the packet type a packet's debug output starts with. Packets wrapped in
+/// an enum variant are named after the packet inside it.
+fn packet_name(debug: &str) -> &str {
+    let mut names = debug
+        .split(|c: char| !c.is_alphanumeric() && c != '_')
+        .filter(|x| !x.is_empty());
+    let first = names.clone().next().unwrap_or_default();
+    names
+        .take(2)
+        .find(|x| x.ends_with("Packet"))
+        .unwrap_or(first)
+}
+
+fn hex(bytes: &[u8]) -> String {
+    let mut hex = String::with_capacity(bytes.len() * 3);
+    for (index, byte) in bytes.iter().enumerate() {
+        if index > 0 {
+            hex.push(' ');
+        }
+        let _ = write!(hex, "{byte:02x}");
+    }
+    hex
+}
+
+/// Logs a packet if it passes the current filter. `bytes` are the packet's encoded id and data.
+pub(crate) fn log_packet<P: Debug>(direction: PacketDirection, packet: &P, bytes: &[u8]) {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return;
+    }
+    let filter = FILTER.read().unwrap();
+    let Some(filter) = &*filter else {
+        return;
+    };
+    let debug = format!("{packet:?}");
+    let name = packet_name(&debug);
+    if !filter.matches(direction, name) {
+        return;
+    }
+    let arrow = match direction {
+        PacketDirection::Serverbound => "C->S",
+        PacketDirection::Clientbound => "S->C",
+    };
+    if filter.hex {
+        log::info!(target: "wyvern::packets", "{arrow} {name}: {debug}\n{}", hex(bytes));
+    } else {
+        log::info!(target: "wyvern::packets", "{arrow} {name}: {debug}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PacketDirection, PacketLogFilter, hex, packet_name};
+
+    #[test]
+    fn packets_are_filtered_by_name_and_direction() {
+        assert_eq!(
+            packet_name("SetHealthS2CPlayPacket { health: 20.0 }"),
+            "SetHealthS2CPlayPacket"
+        );
+        assert_eq!(
+            packet_name("Chat(ChatC2SPlayPacket { message: \"hi\" })"),
+            "ChatC2SPlayPacket"
+        );
+        assert_eq!(packet_name("Pong"), "Pong");
+
+        let filter = PacketLogFilter::new().clientbound(false).packet("chat");
+        assert!(filter.matches(PacketDirection::Serverbound, "ChatC2SPlayPacket"));
+        assert!(!filter.matches(PacketDirection::Clientbound, "ChatS2CPlayPacket"));
+        assert!(!filter.matches(PacketDirection::Serverbound, "MovePlayerPosC2SPlayPacket"));
+        assert_eq!(hex(&[0x00, 0x1f, 0xff]), "00 1f ff");
+    }
+}
@@ -0,0 +1,45 @@
+use voxidian_protocol::packet::s2c::play::LevelParticlesS2CPlayPacket;
+use wyvern_datatypes::particle::{Particle, ParticleOptions};
+use wyvern_values::DVec3;
+
+use crate::{actors::ActorResult, dimension::Dimension};
+
+use super::Player;
+
+pub(crate) fn particle_packet(
+    pos: DVec3,
+    particle: Particle,
+    options: ParticleOptions,
+) -> LevelParticlesS2CPlayPacket {
+    LevelParticlesS2CPlayPacket {
+        long_distance: options.long_distance,
+        always_visible: true,
+        x: pos[0],
+        y: pos[1],
+        z: pos[2],
+        spread_x: options.offset[0] as f32,
+        spread_y: options.offset[1] as f32,
+        spread_z: options.offset[2] as f32,
+        max_speed: options.speed,
+        count: options.count,
+        particle: particle.into(),
+    }
+}
+
+impl Dimension {
+    /// Plays particles at a position for every player in this dimension.
+    pub fn play_particle(
+        &self,
+        pos: DVec3,
+        particle: Particle,
+        options: ParticleOptions,
+    ) -> ActorResult<()> {
+        let server = self.server()?;
+        let players = self
+            .players()?
+            .into_iter()
+            .filter_map(|uuid| server.player(uuid).ok())
+            .collect::<Vec<_>>();
+        Player::broadcast_packet(&players, particle_packet(pos, particle, options))
+    }
+}
@@ -0,0 +1,33 @@
+use datafix::serialization::{Codec, DefaultCodec};
+use wyvern_actors::{ActorError, ActorResult};
+use wyvern_datatypes::nbt::NbtOps;
+
+use super::Player;
+
+/// Data stored against a player's UUID that survives reconnects.
+///
+/// Values are encoded to NBT with their default codec. The data is loaded before
+/// `PlayerJoinEvent` fires and saved when the player disconnects.
+pub struct PersistentData {
+    pub(crate) player: Player,
+}
+
+impl PersistentData {
+    pub fn get<T: DefaultCodec<NbtOps>>(&self, key: &str) -> ActorResult<T> {
+        let value = self.player.get_persistent_entry(key.to_string())?;
+        T::codec()
+            .decode_start(&NbtOps, &value)
+            .map_err(|_| ActorError::BadRequest)
+    }
+
+    pub fn set<T: DefaultCodec<NbtOps>>(&self, key: &str, value: T) -> ActorResult<()> {
+        let value = T::codec()
+            .encode_start(&NbtOps, &value)
+            .map_err(|_| ActorError::BadRequest)?;
+        self.player.set_persistent_entry(key.to_string(), value)
+    }
+
+    pub fn remove(&self, key: &str) -> ActorResult<()> {
+        self.player.remove_persistent_entry(key.to_string())
+    }
+}
@@ -0,0 +1,214 @@
+use std::sync::{Arc, Mutex};
+
+use wyvern_components::DataComponentHolder;
+use wyvern_datatypes::gamemode::Gamemode;
+use wyvern_values::{DVec3, DVec3Ext, Vec2Ext};
+
+use crate::{
+    actors::ActorResult,
+    entities::{BoundingBox, EntityComponents, PLAYER_DROP_PICKUP_DELAY},
+    events::{DropItemEvent, PlayerPickupItemEvent},
+    inventory::{is_empty, max_stack_size, stackable},
+    item::{ItemComponents, ItemStack},
+    runtime::Runtime,
+    server::Server,
+};
+
+use super::{ConnectionData, Player, PlayerComponents, itf::eye_height};
+
+/// How far past a player's hitbox they can pick up items from.
+const PICKUP_REACH: DVec3 = DVec3::new(1.0, 0.5, 1.0);
+
+/// The inventory slots picked up items go in, hotbar first.
+fn pickup_slots() -> impl Iterator<Item = usize> {
+    (36..45).chain(9..36)
+}
+
+fn count(item: &ItemStack) -> u16 {
+    item.get(ItemComponents::ITEM_COUNT).unwrap_or(1)
+}
+
+/// Puts as much of `item` into `slots` as fits, topping up stacks of the same item before
+/// filling empty slots. Returns the indices of the slots that changed and how many items
+/// didn't fit.
+fn insert(slots: &mut [ItemStack], item: &ItemStack) -> (Vec<usize>, u16) {
+    let mut remaining = count(item);
+    let mut changed = Vec::new();
+    let max = max_stack_size(item);
+
+    for (index, slot) in slots.iter_mut().enumerate() {
+        if remaining == 0 {
+            break;
+        }
+        if is_empty(slot) || !stackable(slot, item) || count(slot) >= max {
+            continue;
+        }
+        let moved = remaining.min(max - count(slot));
+        *slot = slot
+            .clone()
+            .with(ItemComponents::ITEM_COUNT, count(slot) + moved);
+        remaining -= moved;
+        changed.push(index);
+    }
+    for (index, slot) in slots.iter_mut().enumerate() {
+        if remaining == 0 {
+            break;
+        }
+        if !is_empty(slot) {
+            continue;
+        }
+        let moved = remaining.min(max);
+        *slot = item.clone().with(ItemComponents::ITEM_COUNT, moved);
+        remaining -= moved;
+        changed.push(index);
+    }
+    (changed, remaining)
+}
+
+impl ConnectionData {
+    fn pickup_slot_items(&self) -> ActorResult<Vec<ItemStack>> {
+        pickup_slots()
+            .map(|slot| self.associated_data.inventory.get_slot(slot))
+            .collect()
+    }
+
+    /// Puts as much of `item` into the player's inventory as fits, returning what didn't fit,
+    /// or air if everything did.
+    pub(crate) fn insert_item(&mut self, item: ItemStack) -> ActorResult<ItemStack> {
+        let mut slots = self.pickup_slot_items()?;
+        let (changed, remaining) = insert(&mut slots, &item);
+        let indices = pickup_slots().collect::<Vec<_>>();
+        for index in changed {
+            self.set_inv_slot(indices[index], slots[index].clone())?;
+        }
+        Ok(match remaining {
+            0 => ItemStack::air(),
+            remaining => item.with(ItemComponents::ITEM_COUNT, remaining),
+        })
+    }
+
+    /// Returns how many of `item` would fit in the player's inventory.
+    pub(crate) fn space_for(&self, item: &ItemStack) -> ActorResult<u16> {
+        let mut slots = self.pickup_slot_items()?;
+        let (_, remaining) = insert(&mut slots, item);
+        Ok(count(item) - remaining)
+    }
+
+    /// Fires a `DropItemEvent` for `item`, then, unless a handler cancelled it, throws the item
+    /// out in front of the player as an item entity, which they can't pick back up for
+    /// `PLAYER_DROP_PICKUP_DELAY` ticks.
+    pub(crate) fn toss_item(&self, item: ItemStack) -> ActorResult<()> {
+        let Some(dimension) = self.associated_data.dimension.clone() else {
+            return Ok(());
+        };
+        if is_empty(&item) {
+            return Ok(());
+        }
+        let position = self.get(PlayerComponents::POSITION)?;
+        let sneaking = self.get(PlayerComponents::SNEAKING).unwrap_or(false);
+        let direction = self.get(PlayerComponents::DIRECTION)?.to_3d_direction();
+        let position = position.with_y(position[1] + eye_height(sneaking) - 0.3);
+        let velocity = direction * 0.3 + DVec3::new(0.0, 0.1, 0.0);
+        let player = self.as_actor();
+        let server = self.connected_server.clone();
+        Runtime::spawn_task(async move {
+            let cancelled = Arc::new(Mutex::new(false));
+            server
+                .fire_event_async(DropItemEvent {
+                    player,
+                    item: item.clone(),
+                    cancelled: cancelled.clone(),
+                })
+                .await?;
+            if *cancelled.lock().unwrap() {
+                return Ok(());
+            }
+            dimension.drop_item_with_delay(position, item, velocity, PLAYER_DROP_PICKUP_DELAY)?;
+            Ok(())
+        });
+        Ok(())
+    }
+}
+
+impl Player {
+    /// Picks up the item entities the player is touching, as much of each as fits in their
+    /// inventory, firing a `PlayerPickupItemEvent` for each. Dead and spectating players don't
+    /// pick anything up.
+    pub(crate) fn pick_up_items(&self) -> ActorResult<()> {
+        if self
+            .get(PlayerComponents::HEALTH)
+            .is_ok_and(|health| health.health <= 0.0)
+            || matches!(
+                self.get(PlayerComponents::GAMEMODE),
+                Ok(Gamemode::Spectator)
+            )
+        {
+            return Ok(());
+        }
+
+        let dimension = self.dimension()?;
+        // The player's entity is spawned shortly after they join.
+        let Ok(bounds) = dimension.get_entity(self.uuid()?).bounding_box() else {
+            return Ok(());
+        };
+        let area = BoundingBox {
+            min: bounds.min - PICKUP_REACH,
+            max: bounds.max + PICKUP_REACH,
+        };
+        for uuid in dimension.pickup_candidates(area)? {
+            let Ok(item) = dimension.get_entity(uuid).get(EntityComponents::ITEM) else {
+                continue;
+            };
+            let space = self.space_for_item(item)?;
+            let Some(taken) = dimension.take_item_entity(uuid, space)? else {
+                continue;
+            };
+
+            // Something else may have filled the inventory since the space was checked.
+            let leftover = self.give_item(taken.clone())?;
+            if !is_empty(&leftover) {
+                let position = self.get(PlayerComponents::POSITION)?;
+                dimension.drop_item(position, leftover, DVec3::ZERO)?;
+            }
+            Server::get()?.spawn_event(PlayerPickupItemEvent {
+                player: self.clone(),
+                item: taken,
+            })?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wyvern_components::DataComponentHolder;
+    use wyvern_values::id;
+
+    use crate::item::{ItemComponents, ItemStack};
+
+    use super::insert;
+
+    #[test]
+    fn items_top_up_stacks_before_empty_slots() {
+        let stone = |count: u16| {
+            ItemStack::new(id![minecraft:stone]).with(ItemComponents::ITEM_COUNT, count)
+        };
+        let mut slots = vec![
+            ItemStack::air(),
+            ItemStack::new(id![minecraft:dirt]),
+            stone(60),
+            ItemStack::air(),
+        ];
+
+        let (changed, remaining) = insert(&mut slots, &stone(70));
+        assert_eq!(changed, vec![2, 0, 3]);
+        assert_eq!(remaining, 0);
+        assert_eq!(slots[2], stone(64));
+        assert_eq!(slots[0], stone(64));
+        assert_eq!(slots[3], stone(2));
+
+        let (changed, remaining) = insert(&mut slots, &stone(100));
+        assert_eq!(changed, vec![3]);
+        assert_eq!(remaining, 38);
+    }
+}
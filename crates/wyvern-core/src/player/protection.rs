@@ -0,0 +1,33 @@
+use wyvern_components::DataComponentHolder;
+use wyvern_values::IVec3;
+
+use crate::actors::{ActorError, ActorResult};
+
+use super::{ConnectionData, PlayerComponents, fake_blocks::block_update};
+
+impl ConnectionData {
+    /// Returns whether the player can't place or break the block at `position` because of their
+    /// dimension's spawn protection.
+    pub(crate) fn is_spawn_protected(&self, position: IVec3) -> ActorResult<bool> {
+        if self.get(PlayerComponents::OP_LEVEL).unwrap_or(0) > 0 {
+            return Ok(false);
+        }
+        let Some(dimension) = &self.associated_data.dimension else {
+            return Ok(false);
+        };
+        Ok(dimension.spawn_protection()?.protects(position))
+    }
+
+    /// Sends the player the block actually at `position`, undoing a change their client
+    /// predicted.
+    pub(crate) fn correct_block(&mut self, position: IVec3) -> ActorResult<()> {
+        let dimension = self
+            .associated_data
+            .dimension
+            .clone()
+            .ok_or(ActorError::ActorIsNotLoaded)?;
+        let block = dimension.get_block(position)?;
+        self.write_packet(block_update(position, &block));
+        Ok(())
+    }
+}
@@ -0,0 +1,45 @@
+use wyvern_components::{DataComponentHolder, DataComponentType};
+use wyvern_datatypes::gamemode::Gamemode;
+use wyvern_values::DVec3;
+
+use crate::{
+    actors::ActorResult,
+    entities::{Attributes, default_attribute_value},
+};
+
+use super::{ConnectionData, PlayerComponents, itf::eye_height};
+
+impl ConnectionData {
+    /// Returns where the player's eyes are and how far from them they can attack entities.
+    pub(crate) fn entity_reach(&mut self) -> ActorResult<(DVec3, f64)> {
+        self.reach(Attributes::ENTITY_INTERACTION_RANGE, 2.0)
+    }
+
+    /// Returns where the player's eyes are and how far from them they can use blocks.
+    pub(crate) fn block_reach(&mut self) -> ActorResult<(DVec3, f64)> {
+        self.reach(Attributes::BLOCK_INTERACTION_RANGE, 0.5)
+    }
+
+    /// Returns where the player's eyes are and how far from them they can interact by the
+    /// given range attribute, which is longer in creative mode, plus the server's leeway.
+    fn reach(
+        &mut self,
+        attribute: DataComponentType<f64>,
+        creative_bonus: f64,
+    ) -> ActorResult<(DVec3, f64)> {
+        let position = self.get(PlayerComponents::POSITION)?;
+        let sneaking = self.get(PlayerComponents::SNEAKING).unwrap_or(false);
+        let eyes = position.with_y(position[1] + eye_height(sneaking));
+        let mut range = self
+            .effective_attributes()?
+            .get(attribute.clone())
+            .unwrap_or_else(|_| default_attribute_value(attribute.name()));
+        if self.get(PlayerComponents::GAMEMODE) == Ok(Gamemode::Creative) {
+            range += creative_bonus;
+        }
+        Ok((
+            eyes,
+            range + self.connected_server.interaction_range_leeway()?,
+        ))
+    }
+}
@@ -0,0 +1,40 @@
+use voxidian_protocol::{
+    packet::s2c::play::{RecipeBookAddEntry, RecipeBookAddS2CPlayPacket},
+    value::{RecipeBookCategory, RecipeDisplayEntry, VarInt},
+};
+
+use crate::{actors::ActorResult, server::registries::RegistryKeys};
+
+use super::ConnectionData;
+
+impl ConnectionData {
+    /// Fills the player's recipe book with every recipe registered on the server, replacing
+    /// what it had before. Recipes are numbered by their position in the registry.
+    ///
+    /// The recipe book only shows recipes. Clicking one doesn't fill the crafting grid, since
+    /// no crafting requirements are sent and recipe placement requests aren't handled.
+    pub(crate) fn send_recipes(&mut self) -> ActorResult<()> {
+        let registries = self.connected_server.registries()?;
+        let entries = registries
+            .get(RegistryKeys::RECIPE)
+            .entries()
+            .enumerate()
+            .map(|(index, (_, recipe))| RecipeBookAddEntry {
+                display: RecipeDisplayEntry {
+                    id: VarInt::new(index as i32),
+                    display: recipe.display(),
+                    group: None,
+                    category: RecipeBookCategory::CraftingMisc,
+                    // Without requirements, the client doesn't offer to place the recipe.
+                    crafting_requirements: None,
+                },
+                flags: 0,
+            })
+            .collect::<Vec<_>>();
+        self.write_packet(RecipeBookAddS2CPlayPacket {
+            entries: entries.into(),
+            replace: true,
+        });
+        Ok(())
+    }
+}
@@ -0,0 +1,176 @@
+use voxidian_protocol::{
+    packet::s2c::play::{
+        NumberFormat, ObjectiveKind, ObjectiveLocation, ResetScoreS2CPlayPacket,
+        SetDisplayObjectiveS2CPlayPacket, SetObjectiveS2CPlayPacket, SetScoreS2CPlayPacket,
+        UpdateObjectiveAction,
+    },
+    value::{Text as PtcText, VarInt},
+};
+use wyvern_datatypes::text::Text;
+
+use crate::actors::ActorResult;
+
+use super::Player;
+
+/// Where on the client's screen an objective is shown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DisplaySlot {
+    Sidebar,
+    BelowName,
+    PlayerList,
+}
+
+impl From<DisplaySlot> for ObjectiveLocation {
+    fn from(value: DisplaySlot) -> Self {
+        match value {
+            DisplaySlot::Sidebar => ObjectiveLocation::Sidebar,
+            DisplaySlot::BelowName => ObjectiveLocation::BelowName,
+            DisplaySlot::PlayerList => ObjectiveLocation::List,
+        }
+    }
+}
+
+/// How the client renders the scores of an objective.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ScoreRender {
+    Integer,
+    Hearts,
+}
+
+impl From<ScoreRender> for ObjectiveKind {
+    fn from(value: ScoreRender) -> Self {
+        match value {
+            ScoreRender::Integer => ObjectiveKind::Integer,
+            ScoreRender::Hearts => ObjectiveKind::Hearts,
+        }
+    }
+}
+
+/// How the number beside a score is shown.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScoreFormat {
+    /// The score itself, in the client's default red.
+    Number,
+    /// Nothing at all.
+    Blank,
+    /// The score itself, styled like the given text. The content of the text is ignored.
+    Styled(Text),
+    /// The given text instead of the score.
+    Fixed(Text),
+}
+
+impl ScoreFormat {
+    pub(crate) fn to_protocol(&self) -> Option<NumberFormat> {
+        match self {
+            ScoreFormat::Number => None,
+            ScoreFormat::Blank => Some(NumberFormat::Blank),
+            ScoreFormat::Styled(text) => Some(NumberFormat::Styled {
+                style: PtcText::from(text.clone())
+                    .into_components()
+                    .into_iter()
+                    .next()
+                    .map(|component| component.style)
+                    .unwrap_or_default(),
+            }),
+            ScoreFormat::Fixed(text) => Some(NumberFormat::Fixed {
+                content: PtcText::from(text.clone()).to_nbt(),
+            }),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Objective {
+    pub name: String,
+    pub display_name: Text,
+    pub render: ScoreRender,
+    /// How the scores of this objective are shown, unless a score has its own format.
+    pub number_format: ScoreFormat,
+}
+
+impl Objective {
+    pub fn new(name: impl Into<String>, display_name: Text) -> Objective {
+        Objective {
+            name: name.into(),
+            display_name,
+            render: ScoreRender::Integer,
+            number_format: ScoreFormat::Number,
+        }
+    }
+
+    pub fn render(mut self, render: ScoreRender) -> Self {
+        self.render = render;
+        self
+    }
+
+    pub fn number_format(mut self, number_format: ScoreFormat) -> Self {
+        self.number_format = number_format;
+        self
+    }
+}
+
+/// The objectives and scores shown to a single player.
+///
+/// The sidebar helpers on `PlayerComponents` use an objective named `wyvern_objective`,
+/// so objectives created here should use a different name.
+pub struct Scoreboard {
+    pub(crate) player: Player,
+}
+
+impl Scoreboard {
+    pub fn create_objective(&self, objective: &Objective) -> ActorResult<()> {
+        self.player.write_packet(SetObjectiveS2CPlayPacket {
+            name: objective.name.clone(),
+            action: UpdateObjectiveAction::Create {
+                value: PtcText::from(objective.display_name.clone()).to_nbt(),
+                kind: objective.render.into(),
+                format: objective.number_format.to_protocol(),
+            },
+        })
+    }
+
+    pub fn update_objective(&self, objective: &Objective) -> ActorResult<()> {
+        self.player.write_packet(SetObjectiveS2CPlayPacket {
+            name: objective.name.clone(),
+            action: UpdateObjectiveAction::Update {
+                value: PtcText::from(objective.display_name.clone()).to_nbt(),
+                kind: objective.render.into(),
+                format: objective.number_format.to_protocol(),
+            },
+        })
+    }
+
+    pub fn remove_objective(&self, objective: &str) -> ActorResult<()> {
+        self.player.write_packet(SetObjectiveS2CPlayPacket {
+            name: objective.into(),
+            action: UpdateObjectiveAction::Remove,
+        })
+    }
+
+    /// Shows an objective in the given slot, replacing whatever was shown there.
+    pub fn display(&self, slot: DisplaySlot, objective: &str) -> ActorResult<()> {
+        self.player.write_packet(SetDisplayObjectiveS2CPlayPacket {
+            to: slot.into(),
+            name: objective.into(),
+        })
+    }
+
+    /// Sets the score of an entry, such as a player's username, in an objective.
+    pub fn set_score(&self, objective: &str, entry: &str, value: i32) -> ActorResult<()> {
+        self.player.write_packet(SetScoreS2CPlayPacket {
+            entity_name: entry.into(),
+            objective_name: objective.into(),
+            value: VarInt::new(value),
+            display_name: None,
+            number_format: None,
+        })
+    }
+
+    /// Removes the score of an entry from an objective.
+    pub fn reset_score(&self, objective: &str, entry: &str) -> ActorResult<()> {
+        self.player.write_packet(ResetScoreS2CPlayPacket {
+            entity_name: entry.into(),
+            objective_name: Some(objective.into()),
+        })
+    }
+}
@@ -0,0 +1,86 @@
+use voxidian_protocol::packet::s2c::play::{SoundS2CPlayPacket, StopSoundS2CPlayPacket};
+use wyvern_datatypes::sound::{Sound, SoundCategory};
+use wyvern_values::{DVec3, Id};
+
+use crate::{actors::ActorResult, dimension::Dimension};
+
+use super::{Player, PlayerComponents};
+
+/// Returns how far away a sound can be heard, which grows with volumes louder than 1.
+fn audible_range(volume: f32) -> f64 {
+    16.0 * volume.max(1.0) as f64
+}
+
+pub(crate) fn sound_packet(pos: DVec3, sound: Sound) -> SoundS2CPlayPacket {
+    // Sound positions are sent in eighths of a block.
+    SoundS2CPlayPacket {
+        category: sound.get_category().into(),
+        x: (pos[0] * 8.0) as i32,
+        y: (pos[1] * 8.0) as i32,
+        z: (pos[2] * 8.0) as i32,
+        volume: sound.get_volume(),
+        pitch: sound.get_pitch(),
+        seed: 0,
+        sound: sound.into(),
+    }
+}
+
+pub(crate) fn stop_sound_packet(
+    sound: Option<Id>,
+    category: Option<SoundCategory>,
+) -> StopSoundS2CPlayPacket {
+    StopSoundS2CPlayPacket {
+        category: category.map(Into::into),
+        sound: sound.map(Into::into),
+    }
+}
+
+impl Dimension {
+    /// Plays a sound at a position for every player in this dimension close enough to hear it.
+    /// Sounds can be heard from 16 blocks away, or further for volumes louder than 1.
+    pub fn play_sound_at(&self, pos: DVec3, sound: Sound) -> ActorResult<()> {
+        let range = audible_range(sound.get_volume());
+        let server = self.server()?;
+        let mut players = Vec::new();
+        for uuid in self.players()? {
+            let Ok(player) = server.player(uuid) else {
+                continue;
+            };
+            if player
+                .get(PlayerComponents::POSITION)
+                .is_ok_and(|position| position.distance(pos) <= range)
+            {
+                players.push(player);
+            }
+        }
+        Player::broadcast_packet(&players, sound_packet(pos, sound))
+    }
+
+    /// Stops sounds playing for every player in this dimension. Only sounds with the given name
+    /// and in the given category are stopped, and every sound is stopped if neither is given.
+    pub fn stop_sound(
+        &self,
+        sound: Option<Id>,
+        category: Option<SoundCategory>,
+    ) -> ActorResult<()> {
+        let server = self.server()?;
+        let players = self
+            .players()?
+            .into_iter()
+            .filter_map(|uuid| server.player(uuid).ok())
+            .collect::<Vec<_>>();
+        Player::broadcast_packet(&players, stop_sound_packet(sound, category))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::audible_range;
+
+    #[test]
+    fn loud_sounds_travel_further() {
+        assert_eq!(audible_range(0.5), 16.0);
+        assert_eq!(audible_range(1.0), 16.0);
+        assert_eq!(audible_range(4.0), 64.0);
+    }
+}
@@ -0,0 +1,13 @@
+use crate::actors::ActorResult;
+
+use super::ConnectionData;
+
+impl ConnectionData {
+    /// Tells the player where their dimension's spawn point is, so their compasses point to it.
+    pub(crate) fn send_world_spawn(&mut self) -> ActorResult<()> {
+        if let Some(dimension) = self.associated_data.dimension.clone() {
+            self.write_packet(dimension.world_spawn()?.to_packet());
+        }
+        Ok(())
+    }
+}
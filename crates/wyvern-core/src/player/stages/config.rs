@@ -8,7 +8,10 @@ use voxidian_protocol::{
         },
     },
     registry::RegEntry,
-    value::{PaintingVariant as PtcPaintingVariant, VarInt, WolfVariant as PtcWolfVariant},
+    value::{
+        BannerPattern as PtcBannerPattern, PaintingVariant as PtcPaintingVariant, VarInt,
+        WolfVariant as PtcWolfVariant,
+    },
 };
 use wyvern_values::Id;
 
@@ -22,7 +25,6 @@ use crate::{
 impl ConnectionData {
     pub fn configuration_stage(&mut self) -> ActorResult<()> {
         self.read_packets(|packet: C2SConfigPackets, this: &mut Self| {
-            log::debug!("Packet: {:?}", packet);
             {
                 match packet {
                     C2SConfigPackets::CustomPayload(_packet) => {}
@@ -97,6 +99,8 @@ impl ConnectionData {
                                     rotate_velocity: false,
                                 },
                             })?;
+
+                            p.write_packet(Server::get()?.commands()?.to_packet())?;
                             Ok(())
                         });
                     }
@@ -151,6 +155,15 @@ impl ConnectionData {
                                 .to_registry_data_packet(),
                         );
 
+                        this.write_packet(
+                            this.connected_server
+                                .registries()?
+                                .get(RegistryKeys::BANNER_PATTERN)
+                                .map(|x| PtcBannerPattern::from(x.clone()))
+                                .inner()
+                                .to_registry_data_packet(),
+                        );
+
                         this.write_packet(
                             this.connected_server
                                 .registries()?
@@ -0,0 +1,148 @@
+use std::net::IpAddr;
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use wyvern_values::Uuid;
+
+pub(crate) const VELOCITY_TRANSACTION_ID: i32 = 0x5659;
+pub(crate) const VELOCITY_FORWARDING_VERSION: u8 = 1;
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ForwardedProperty {
+    pub(crate) name: String,
+    pub(crate) value: String,
+    pub(crate) signature: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ForwardedPlayer {
+    pub(crate) address: IpAddr,
+    pub(crate) uuid: Uuid,
+    pub(crate) username: String,
+    pub(crate) properties: Vec<ForwardedProperty>,
+}
+
+/// Verifies the signature of a Velocity `player_info` response against the shared
+/// secret, then parses the forwarded player information out of it.
+pub(crate) fn read_velocity_response(secret: &[u8], response: &[u8]) -> Option<ForwardedPlayer> {
+    if response.len() < 32 {
+        return None;
+    }
+    let (signature, data) = response.split_at(32);
+
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret).ok()?;
+    mac.update(data);
+    mac.verify_slice(signature).ok()?;
+
+    let mut reader = ForwardingReader { data };
+    let version = reader.read_varint()?;
+    if version < VELOCITY_FORWARDING_VERSION as i32 {
+        return None;
+    }
+    let address = reader.read_string()?.parse().ok()?;
+    let uuid = Uuid::from_u128(u128::from_be_bytes(reader.read_bytes(16)?.try_into().ok()?));
+    let username = reader.read_string()?;
+
+    let property_count = reader.read_varint()?;
+    let mut properties = Vec::new();
+    for _ in 0..property_count {
+        let name = reader.read_string()?;
+        let value = reader.read_string()?;
+        let signature = if reader.read_bytes(1)?[0] != 0 {
+            Some(reader.read_string()?)
+        } else {
+            None
+        };
+        properties.push(ForwardedProperty {
+            name,
+            value,
+            signature,
+        });
+    }
+
+    Some(ForwardedPlayer {
+        address,
+        uuid,
+        username,
+        properties,
+    })
+}
+
+struct ForwardingReader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> ForwardingReader<'a> {
+    fn read_bytes(&mut self, len: usize) -> Option<&'a [u8]> {
+        if self.data.len() < len {
+            return None;
+        }
+        let (bytes, rest) = self.data.split_at(len);
+        self.data = rest;
+        Some(bytes)
+    }
+
+    fn read_varint(&mut self) -> Option<i32> {
+        let mut value = 0i32;
+        for i in 0..5 {
+            let byte = self.read_bytes(1)?[0];
+            value |= ((byte & 0x7F) as i32) << (i * 7);
+            if byte & 0x80 == 0 {
+                return Some(value);
+            }
+        }
+        None
+    }
+
+    fn read_string(&mut self) -> Option<String> {
+        let len = self.read_varint()?;
+        let bytes = self.read_bytes(usize::try_from(len).ok()?)?;
+        String::from_utf8(bytes.to_vec()).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hmac::{Hmac, Mac};
+    use sha2::Sha256;
+    use wyvern_values::Uuid;
+
+    use super::read_velocity_response;
+
+    fn write_string(buf: &mut Vec<u8>, value: &str) {
+        buf.push(value.len() as u8);
+        buf.extend_from_slice(value.as_bytes());
+    }
+
+    fn signed_response(secret: &[u8]) -> Vec<u8> {
+        let mut data = vec![1];
+        write_string(&mut data, "10.0.0.5");
+        data.extend_from_slice(&0x1234u128.to_be_bytes());
+        write_string(&mut data, "Endistic");
+        data.push(1);
+        write_string(&mut data, "textures");
+        write_string(&mut data, "abc");
+        data.push(0);
+
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret).unwrap();
+        mac.update(&data);
+        let mut response = mac.finalize().into_bytes().to_vec();
+        response.extend(data);
+        response
+    }
+
+    #[test]
+    fn valid_signature_is_accepted() {
+        let player = read_velocity_response(b"secret", &signed_response(b"secret")).unwrap();
+        assert_eq!(player.address.to_string(), "10.0.0.5");
+        assert_eq!(player.uuid, Uuid::from_u128(0x1234));
+        assert_eq!(player.username, "Endistic");
+        assert_eq!(player.properties.len(), 1);
+        assert_eq!(player.properties[0].signature, None);
+    }
+
+    #[test]
+    fn invalid_signature_is_rejected() {
+        assert!(read_velocity_response(b"other", &signed_response(b"secret")).is_none());
+    }
+}
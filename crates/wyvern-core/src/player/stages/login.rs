@@ -1,5 +1,7 @@
+use std::net::Shutdown;
+
 use voxidian_protocol::{
-    mojang::auth_verify::{MojAuth, MojAuthError},
+    mojang::auth_verify::{MojAuth, MojAuthError, MojAuthProperty},
     packet::{
         Stage,
         c2s::login::C2SLoginPackets,
@@ -7,26 +9,75 @@ use voxidian_protocol::{
         s2c::{
             config::{KnownPack, SelectKnownPacksS2CConfigPacket},
             login::{
-                HelloS2CLoginPacket, LoginCompressionS2CLoginPacket, LoginFinishedS2CLoginPacket,
+                CustomQueryS2CLoginPacket, HelloS2CLoginPacket, LoginCompressionS2CLoginPacket,
+                LoginFinishedS2CLoginPacket,
             },
         },
     },
-    value::{LengthPrefixHashMap, VarInt},
+    value::{Identifier, LengthPrefixHashMap, VarInt},
 };
 use wyvern_components::DataComponentHolder;
+use wyvern_datatypes::text::Text;
 
 use crate::{
     actors::{ActorError, ActorResult},
     player::{ConnectionData, MojauthData, PlayerComponents},
-    server::Server,
+    server::{Server, status::ProtocolCheck},
+};
+
+use super::forwarding::{
+    VELOCITY_FORWARDING_VERSION, VELOCITY_TRANSACTION_ID, read_velocity_response,
 };
 
 impl ConnectionData {
     pub fn login_stage(&mut self) -> ActorResult<()> {
         self.read_packets(|packet: C2SLoginPackets, this: &mut Self| {
-            log::debug!("Packet: {:?}", packet);
             match packet {
-                C2SLoginPackets::CustomQueryAnswer(_packet) => todo!(),
+                C2SLoginPackets::CustomQueryAnswer(packet) => {
+                    if packet.transaction_id.as_i32() != VELOCITY_TRANSACTION_ID {
+                        return Ok(());
+                    }
+                    let Some(secret) = Server::get()?.velocity_secret()? else {
+                        return Ok(());
+                    };
+
+                    let forwarded = packet.data.as_ref().and_then(|data| {
+                        read_velocity_response(secret.as_bytes(), data.as_slice())
+                    });
+                    let Some(forwarded) = forwarded else {
+                        log::warn!("Failed to verify forwarded player information from proxy");
+                        this.disconnect_internal(Text::literal(
+                            "Unable to verify player details, please connect through the proxy.",
+                        ))?;
+                        this.write_outgoing_packets();
+                        let _ = this.stream.shutdown(Shutdown::Both);
+                        return Err(ActorError::BadRequest);
+                    };
+
+                    this.addr = forwarded.address;
+                    this.set(PlayerComponents::USERNAME, forwarded.username);
+                    this.set(PlayerComponents::UUID, forwarded.uuid);
+                    this.mojauth = Some(MojauthData {
+                        private_key: None,
+                        verify_token: Vec::new(),
+                        public_key: None,
+                        props: forwarded
+                            .properties
+                            .into_iter()
+                            .map(|property| MojAuthProperty {
+                                name: property.name,
+                                value: property.value,
+                                sig: property.signature.unwrap_or_default(),
+                            })
+                            .collect(),
+                    });
+
+                    this.write_packet(LoginFinishedS2CLoginPacket {
+                        uuid: this.get(PlayerComponents::UUID)?,
+                        username: this.get(PlayerComponents::USERNAME)?,
+                        props: LengthPrefixHashMap::new(),
+                    });
+                }
                 C2SLoginPackets::LoginAcknowledged(_packet) => {
                     *this.stage.lock().unwrap() = Stage::Config;
                     this.write_packet(SelectKnownPacksS2CConfigPacket {
@@ -112,15 +163,39 @@ impl ConnectionData {
                     });
                 }
                 C2SLoginPackets::Hello(packet) => {
-                    this.write_packet(LoginCompressionS2CLoginPacket {
-                        threshold: VarInt::from(128),
-                    });
-                    this.packet_processing.compression = CompressionMode::ZLib { threshold: 128 };
+                    let accepted = Server::get()?.accepted_protocol_versions()?;
+                    let check =
+                        ProtocolCheck::new(this.associated_data.protocol_version, &accepted);
+                    if let Some(message) = check.message() {
+                        log::info!(
+                            "Rejected {} using protocol version {}",
+                            packet.username,
+                            this.associated_data.protocol_version
+                        );
+                        this.disconnect_internal(message)?;
+                        this.write_outgoing_packets();
+                        let _ = this.stream.shutdown(Shutdown::Both);
+                        return Err(ActorError::BadRequest);
+                    }
+
+                    if let Some(threshold) = Server::get()?.compression_threshold()? {
+                        let threshold = threshold as i32;
+                        this.write_packet(LoginCompressionS2CLoginPacket {
+                            threshold: VarInt::from(threshold),
+                        });
+                        this.packet_processing.compression = compression_mode(threshold);
+                    }
 
                     this.set(PlayerComponents::USERNAME, packet.username);
                     this.set(PlayerComponents::UUID, packet.uuid);
 
-                    if Server::get()?.mojauth_enabled()? {
+                    if Server::get()?.velocity_secret()?.is_some() {
+                        this.write_packet(CustomQueryS2CLoginPacket {
+                            transaction_id: VarInt::from(VELOCITY_TRANSACTION_ID),
+                            channel: Identifier::new_const("velocity", "player_info"),
+                            data: vec![VELOCITY_FORWARDING_VERSION].into(),
+                        });
+                    } else if Server::get()?.mojauth_enabled()? {
                         this.mojauth = Some(MojauthData {
                             private_key: None,
                             verify_token: Vec::new(),
@@ -180,3 +255,71 @@ impl ConnectionData {
         })
     }
 }
+
+/// Returns how packets are framed once the client has been sent `threshold` in the login
+/// compression packet. Packets at least `threshold` bytes long are compressed, and, as in
+/// vanilla, a negative threshold disables compression.
+fn compression_mode(threshold: i32) -> CompressionMode {
+    match usize::try_from(threshold) {
+        Ok(threshold) => CompressionMode::ZLib { threshold },
+        Err(_) => CompressionMode::None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use voxidian_protocol::packet::{PacketBuf, processing::PacketProcessing};
+
+    use super::compression_mode;
+
+    /// Frames a packet of `len` bytes for a client sent `threshold` at login.
+    fn frame(threshold: i32, len: usize) -> Vec<u8> {
+        let mut processing = PacketProcessing::NONE;
+        processing.compression = compression_mode(threshold);
+        processing
+            .encode_encrypt(PacketBuf::from(vec![7; len]))
+            .unwrap()
+            .as_slice()
+            .to_vec()
+    }
+
+    /// Reads a VarInt from the start of `bytes`, returning it and the bytes after it.
+    fn read_varint(bytes: &[u8]) -> (i32, &[u8]) {
+        let mut value = 0;
+        for (index, byte) in bytes.iter().enumerate() {
+            value |= ((byte & 0x7f) as i32) << (7 * index);
+            if byte & 0x80 == 0 {
+                return (value, &bytes[index + 1..]);
+            }
+        }
+        panic!("unterminated VarInt");
+    }
+
+    #[test]
+    fn packets_at_the_threshold_are_compressed() {
+        let framed = frame(256, 256);
+        let (packet_len, rest) = read_varint(&framed);
+        assert_eq!(packet_len as usize, rest.len());
+        let (data_len, data) = read_varint(rest);
+        assert_eq!(data_len, 256);
+        assert_ne!(data, [7; 256].as_slice());
+    }
+
+    #[test]
+    fn packets_below_the_threshold_are_sent_uncompressed() {
+        let framed = frame(256, 255);
+        let (packet_len, rest) = read_varint(&framed);
+        assert_eq!(packet_len, 256);
+        let (data_len, data) = read_varint(rest);
+        assert_eq!(data_len, 0);
+        assert_eq!(data, [7; 255].as_slice());
+    }
+
+    #[test]
+    fn negative_thresholds_disable_compression() {
+        let framed = frame(-1, 300);
+        let (packet_len, data) = read_varint(&framed);
+        assert_eq!(packet_len, 300);
+        assert_eq!(data, [7; 300].as_slice());
+    }
+}
@@ -1,4 +1,5 @@
 pub mod config;
+pub(crate) mod forwarding;
 pub mod login;
 pub mod play;
 pub mod status;
@@ -1,16 +1,15 @@
+use std::time::Instant;
+
 use voxidian_protocol::{
     packet::{
         c2s::play::{BlockFace, C2SPlayPackets, CommandAction, InteractAction, PlayerStatus},
         s2c::play::{
-            AddEntityS2CPlayPacket, AnimateS2CPlayPacket, BlockChangedAckS2CPlayPacket,
-            ContainerSlotGroup, DisconnectS2CPlayPacket, EntityAnimation, GameEvent,
-            GameEventS2CPlayPacket, Hand, PlayerActionEntry, PlayerInfoUpdateS2CPlayPacket,
-            PongResponseS2CPlayPacket, RespawnDataKept, RespawnS2CPlayPacket, ScreenWindowKind,
-            SetEntityDataS2CPlayPacket,
+            AnimateS2CPlayPacket, BlockChangedAckS2CPlayPacket, DisconnectS2CPlayPacket,
+            EntityAnimation, GameEvent, GameEventS2CPlayPacket, Hand, PlayerActionEntry,
+            PlayerInfoUpdateS2CPlayPacket, PongResponseS2CPlayPacket,
         },
     },
-    registry::RegEntry,
-    value::{Angle, ProfileProperty, Text as PtcText, TextComponent, VarInt},
+    value::{ProfileProperty, Text as PtcText, TextComponent},
 };
 use wyvern_components::DataComponentHolder;
 use wyvern_datatypes::{gamemode::Gamemode, text::Text};
@@ -18,38 +17,46 @@ use wyvern_datatypes::{gamemode::Gamemode, text::Text};
 use crate::{
     actors::{Actor, ActorError, ActorResult},
     blocks::BlockState,
-    entities::EntityComponents,
+    entities::{BoundingBox, EntityComponents},
     events::{
-        BreakBlockEvent, ChangeHeldSlotEvent, ChatMessageEvent, DropItemEvent, PlaceBlockEvent,
-        PlayerAttackEntityEvent, PlayerAttackPlayerEvent, PlayerCommandEvent, PlayerJoinEvent,
-        PlayerLeftClickEvent, PlayerLoadEvent, PlayerMoveEvent, PlayerRespawnEvent,
-        RightClickEvent, StartBreakBlockEvent, SwapHandsEvent,
+        BreakBlockEvent, ChangeHeldSlotEvent, ChatMessageEvent, PlaceBlockEvent,
+        PlayerAttackEntityEvent, PlayerAttackPlayerEvent, PlayerJoinEvent, PlayerLeftClickEvent,
+        PlayerLoadEvent, PlayerMoveEvent, RightClickEvent, StartBreakBlockEvent, SwapHandsEvent,
     },
     inventory::Inventory,
     item::{ITEM_REGISTRY, ItemComponents, ItemStack},
-    player::{ConnectionData, HealthComponent, PlayerComponents},
+    player::{
+        ConnectionData, PlayerComponents, PlayerMessage,
+        join::{
+            DimensionChoice, JOIN_DIMENSION_TIMEOUT, JoinError, await_dimension, join_gamemode,
+        },
+        update::{ABILITY_FLYING, default_allow_flight},
+    },
     runtime::Runtime,
-    server::{Server, registries::RegistryKeys},
+    server::Server,
 };
 
-use wyvern_values::{DVec3, IVec3, Id, Vec2, cell::Token, id};
+use wyvern_values::{DVec3, IVec3, Id, Vec2};
+
+/// The bit of a movement packet's flags set when the client is on the ground.
+const ON_GROUND_FLAG: u8 = 0x01;
 
 impl ConnectionData {
     pub fn play_phase(&mut self) -> ActorResult<()> {
         self.read_packets(
             |packet: C2SPlayPackets, this: &mut Self| -> ActorResult<()> {
-                log::debug!(
-                    "Player {:?} has sent packet: {:?}",
-                    this.get(PlayerComponents::USERNAME)?,
-                    packet
-                );
-
                 match packet {
                     C2SPlayPackets::ChatCommand(packet) => {
-                        this.connected_server.spawn_event(PlayerCommandEvent {
-                            player: this.as_actor(),
-                            command: packet.command,
-                        })?;
+                        this.connected_server
+                            .commands()?
+                            .dispatch(this.as_actor(), packet.command);
+                    }
+                    C2SPlayPackets::CommandSuggestion(packet) => {
+                        this.connected_server.commands()?.suggest(
+                            this.as_actor(),
+                            packet.id,
+                            packet.command,
+                        );
                     }
                     C2SPlayPackets::PlayerAction(packet) => {
                         let block =
@@ -62,7 +69,11 @@ impl ConnectionData {
                                     player: this.as_actor(),
                                     position: block,
                                 })?;
-                                if this.get(PlayerComponents::GAMEMODE) == Ok(Gamemode::Creative) {
+                                let creative =
+                                    this.get(PlayerComponents::GAMEMODE) == Ok(Gamemode::Creative);
+                                if creative && this.is_spawn_protected(block)? {
+                                    this.correct_block(block)?;
+                                } else if creative {
                                     let old_block = this
                                         .associated_data
                                         .dimension
@@ -83,7 +94,11 @@ impl ConnectionData {
                             }
                             PlayerStatus::CancelledDigging => {}
                             PlayerStatus::FinishedDigging => {
-                                if this.get(PlayerComponents::GAMEMODE) != Ok(Gamemode::Creative) {
+                                let creative =
+                                    this.get(PlayerComponents::GAMEMODE) == Ok(Gamemode::Creative);
+                                if !creative && this.is_spawn_protected(block)? {
+                                    this.correct_block(block)?;
+                                } else if !creative {
                                     let old_block = this
                                         .associated_data
                                         .dimension
@@ -109,10 +124,7 @@ impl ConnectionData {
                                     this.associated_data.held_slot as usize,
                                     ItemStack::air(),
                                 )?;
-                                this.connected_server.spawn_event(DropItemEvent {
-                                    player: this.as_actor(),
-                                    item,
-                                })?;
+                                this.toss_item(item)?;
                             }
                             PlayerStatus::DropItem => {
                                 let item =
@@ -121,10 +133,7 @@ impl ConnectionData {
                                     this.associated_data.held_slot as usize,
                                     ItemStack::air(),
                                 )?;
-                                this.connected_server.spawn_event(DropItemEvent {
-                                    player: this.as_actor(),
-                                    item,
-                                })?;
+                                this.toss_item(item)?;
                             }
                             PlayerStatus::FinishUsingItem => {}
                             PlayerStatus::SwapItems => {
@@ -135,6 +144,7 @@ impl ConnectionData {
                         }
                     }
                     C2SPlayPackets::AcceptTeleportation(packet) => {
+                        this.reset_fall();
                         if packet.teleport_id.as_i32() == 0 {
                             this.connect_to_new_dimension()?;
                         } else if packet.teleport_id.as_i32() != -1 {
@@ -154,10 +164,10 @@ impl ConnectionData {
                         {
                             return Ok(());
                         }
-                        this.set(
-                            PlayerComponents::POSITION,
-                            DVec3::new(packet.x, packet.y, packet.z),
-                        );
+                        let position = DVec3::new(packet.x, packet.y, packet.z);
+                        this.record_movement(position)?;
+                        this.set(PlayerComponents::POSITION, position);
+                        this.enforce_world_border()?;
 
                         this.send_chunks()?;
 
@@ -168,6 +178,10 @@ impl ConnectionData {
                         })?;
 
                         this.update_self_entity()?;
+                        this.update_region_triggers()?;
+                        this.check_void()?;
+                        this.update_on_ground(packet.flags & ON_GROUND_FLAG != 0)?;
+                        this.update_fall()?;
                     }
                     C2SPlayPackets::MovePlayerPosRot(packet) => {
                         if this.get(PlayerComponents::TELEPORT_SYNC_SENT).unwrap_or(0)
@@ -177,10 +191,10 @@ impl ConnectionData {
                         {
                             return Ok(());
                         }
-                        this.set(
-                            PlayerComponents::POSITION,
-                            DVec3::new(packet.x, packet.y, packet.z),
-                        );
+                        let position = DVec3::new(packet.x, packet.y, packet.z);
+                        this.record_movement(position)?;
+                        this.set(PlayerComponents::POSITION, position);
+                        this.enforce_world_border()?;
                         this.set(
                             PlayerComponents::DIRECTION,
                             Vec2::new(packet.pitch, packet.yaw),
@@ -193,6 +207,10 @@ impl ConnectionData {
                         })?;
 
                         this.update_self_entity()?;
+                        this.update_region_triggers()?;
+                        this.check_void()?;
+                        this.update_on_ground(packet.flags & ON_GROUND_FLAG != 0)?;
+                        this.update_fall()?;
                         this.send_chunks()?;
                     }
                     C2SPlayPackets::MovePlayerRot(packet) => {
@@ -215,18 +233,49 @@ impl ConnectionData {
                         })?;
 
                         this.update_self_entity()?;
+                        this.update_on_ground(packet.flags & ON_GROUND_FLAG != 0)?;
+                        this.update_fall()?;
+                    }
+                    C2SPlayPackets::MoveVehicle(packet) => {
+                        this.move_vehicle(
+                            DVec3::new(packet.x, packet.y, packet.z),
+                            packet.yaw,
+                            packet.pitch,
+                        )?;
                     }
                     C2SPlayPackets::ClientInformation(packet) => {
                         this.associated_data.render_distance = packet.info.view_distance as i32;
                     }
                     C2SPlayPackets::PlayerInput(packet) => {
-                        this.set(PlayerComponents::INPUT_FLAGS, packet.flags);
+                        this.update_input(packet.flags)?;
                     }
                     C2SPlayPackets::ClientTickEnd(_) => {}
+                    C2SPlayPackets::PlayerAbilities(packet) => {
+                        let allow_flight = this.get(PlayerComponents::ALLOW_FLIGHT).unwrap_or(
+                            this.get(PlayerComponents::GAMEMODE)
+                                .is_ok_and(|mode| default_allow_flight(&mode)),
+                        );
+                        let flying = packet.flags & ABILITY_FLYING != 0;
+                        // The client already knows whether it is flying, so this is only sent back
+                        // to it if it started flying without being allowed to.
+                        this.set(PlayerComponents::FLYING, flying && allow_flight);
+                        this.last_saved_components
+                            .set(PlayerComponents::FLYING, flying);
+                    }
                     C2SPlayPackets::PingRequest(packet) => {
                         this.write_packet(PongResponseS2CPlayPacket(packet.id as u64));
                     }
-                    C2SPlayPackets::ChunkBatchReceived(_packet) => {}
+                    C2SPlayPackets::KeepAlive(packet) => {
+                        this.receive_keep_alive(packet.0)?;
+                    }
+                    C2SPlayPackets::ChunkBatchReceived(packet) => {
+                        this.associated_data.unacknowledged_chunk_batches = this
+                            .associated_data
+                            .unacknowledged_chunk_batches
+                            .saturating_sub(1);
+                        this.associated_data.client_chunks_per_tick = Some(packet.chunks_per_tick);
+                        this.send_chunks()?;
+                    }
                     C2SPlayPackets::SetCreativeModeSlot(packet) => {
                         let item_id = ITEM_REGISTRY.lookup(&packet.new_item.id).unwrap();
                         let stack = ItemStack::from(packet.new_item)
@@ -240,6 +289,7 @@ impl ConnectionData {
                     }
                     C2SPlayPackets::SetCarriedItem(packet) => {
                         this.associated_data.held_slot = packet.slot + 36;
+                        this.associated_data.last_attack_reset = Some(Instant::now());
 
                         this.connected_server.spawn_event(ChangeHeldSlotEvent {
                             player: this.as_actor(),
@@ -247,14 +297,16 @@ impl ConnectionData {
                         })?;
                     }
                     C2SPlayPackets::UseItem(packet) => {
-                        if packet.hand == Hand::Mainhand {
+                        if packet.hand == Hand::Mainhand && !this.held_item_on_cooldown()? {
                             this.connected_server.spawn_event(RightClickEvent {
                                 player: this.as_actor(),
                             })?;
                         }
                     }
                     C2SPlayPackets::UseItemOn(packet) => {
-                        if packet.hand == Hand::Mainhand {
+                        if packet.hand == Hand::Mainhand && this.held_item_on_cooldown()? {
+                            this.write_packet(BlockChangedAckS2CPlayPacket(packet.sequence));
+                        } else if packet.hand == Hand::Mainhand {
                             let face: IVec3 = match packet.face {
                                 BlockFace::Down => IVec3::new(0, -1, 0),
                                 BlockFace::Up => IVec3::new(0, 1, 0),
@@ -271,7 +323,22 @@ impl ConnectionData {
                                 .inventory
                                 .get_slot(this.associated_data.held_slot as usize)?;
 
+                            let (eyes, range) = this.block_reach()?;
+                            let block = BoundingBox {
+                                min: target.as_dvec3(),
+                                max: (target + IVec3::ONE).as_dvec3(),
+                            };
                             let state = BlockState::new(held.kind());
+                            // Undo what the client predicted for blocks out of reach or in spawn
+                            // protection.
+                            if block.distance_to(eyes) > range
+                                || (state.id_is_valid() && this.is_spawn_protected(final_pos)?)
+                            {
+                                this.write_packet(BlockChangedAckS2CPlayPacket(packet.sequence));
+                                this.correct_block(final_pos)?;
+                                this.set_inv_slot(this.associated_data.held_slot as usize, held)?;
+                                return Ok(());
+                            }
                             let state_clone = state.clone();
                             let dim = this
                                 .associated_data
@@ -323,45 +390,39 @@ impl ConnectionData {
                         })?;
                     }
                     C2SPlayPackets::ContainerClick(packet) => {
-                        this.associated_data.cursor_item = packet.cursor_item.into();
-
-                        if let Some((screen, open_inventory)) = &mut this.associated_data.screen {
-                            for slot in packet.changed_slots.iter() {
-                                match ScreenWindowKind::from(*screen)
-                                    .get_slot_index_group(slot.slot as usize)
-                                    .unwrap()
-                                {
-                                    ContainerSlotGroup::PlayerHotbar(hotbar) => {
-                                        this.associated_data
-                                            .inventory
-                                            .set_slot(36 + hotbar, slot.data.clone().into())?;
-                                    }
-                                    ContainerSlotGroup::PlayerUpper(upper) => {
-                                        this.associated_data
-                                            .inventory
-                                            .set_slot(9 + upper, slot.data.clone().into())?;
-                                    }
-                                    ContainerSlotGroup::Container(slot_idx) => {
-                                        open_inventory
-                                            .set_slot(slot_idx, slot.data.clone().into())?;
-                                    }
-                                    _ => todo!(),
-                                }
-                            }
-                        } else {
-                            for slot in packet.changed_slots.iter() {
-                                this.associated_data
-                                    .inventory
-                                    .set_slot(slot.slot as usize, slot.data.clone().into())?;
-                            }
-                        }
+                        let changed_slots = packet
+                            .changed_slots
+                            .iter()
+                            .map(|slot| (slot.slot, slot.data.clone().into()))
+                            .collect();
+                        this.container_click(
+                            packet.slot,
+                            packet.button,
+                            packet.mode.as_i32(),
+                            changed_slots,
+                            packet.cursor_item.into(),
+                        )?;
                     }
                     C2SPlayPackets::ContainerClose(_) => {
                         this.associated_data.cursor_item = ItemStack::air();
                         this.associated_data.screen = None;
+                        this.associated_data.locked_slots.clear();
+                        this.associated_data.drag = None;
+                        this.associated_data.screen_result = None;
+                        this.associated_data.anvil_rename = None;
+                    }
+                    C2SPlayPackets::RenameItem(packet) => {
+                        this.associated_data.anvil_rename = Some(packet.name);
+                        this.update_screen_result()?;
                     }
                     C2SPlayPackets::Interact(packet) => {
                         let player = this.as_actor();
+                        let (eyes, range) = this.entity_reach()?;
+                        let attack_strength = match packet.action {
+                            InteractAction::Attack => this.take_attack_strength()?,
+                            _ => 0.0,
+                        };
+                        let min_attack_strength = this.connected_server.min_attack_strength()?;
                         Runtime::spawn_task(async move {
                             match packet.action {
                                 InteractAction::Interact(_hand) => {}
@@ -369,15 +430,23 @@ impl ConnectionData {
                                     let victim = player
                                         .dimension()?
                                         .get_entity_by_id(packet.entity_id.into())?;
-                                    if let Ok(victim) = Server::get()?.player(*victim.uuid()) {
+                                    // Hacked clients can claim to hit entities across the map.
+                                    if victim.bounding_box()?.distance_to(eyes) > range
+                                        || attack_strength < min_attack_strength
+                                    {
+                                        return Ok(());
+                                    }
+                                    if let Some(victim) = victim.as_player() {
                                         Server::get()?.spawn_event(PlayerAttackPlayerEvent {
                                             attacker: player,
                                             victim,
+                                            attack_strength,
                                         })?;
                                     } else {
                                         Server::get()?.spawn_event(PlayerAttackEntityEvent {
                                             attacker: player,
                                             victim,
+                                            attack_strength,
                                         })?;
                                     }
                                 }
@@ -394,14 +463,11 @@ impl ConnectionData {
                             player: this.as_actor(),
                         })?;
                         Runtime::spawn_task(async move {
-                            let players = player.dimension()?.players()?;
+                            let players = player.dimension()?.entity_trackers(uuid)?;
 
                             match packet.hand {
                                 Hand::Mainhand => {
                                     for player in players {
-                                        if player == uuid {
-                                            continue;
-                                        }
                                         let player = Server::get()?.player(player)?;
                                         player.write_packet(AnimateS2CPlayPacket {
                                             id: eid.into(),
@@ -411,9 +477,6 @@ impl ConnectionData {
                                 }
                                 Hand::Offhand => {
                                     for player in players {
-                                        if player == uuid {
-                                            continue;
-                                        }
                                         let player = Server::get()?.player(player)?;
                                         player.write_packet(AnimateS2CPlayPacket {
                                             id: eid.into(),
@@ -432,44 +495,7 @@ impl ConnectionData {
                     }
                     C2SPlayPackets::ClientCommand(packet) => match packet.action {
                         CommandAction::PerformRespawn => {
-                            this.write_packet(RespawnS2CPlayPacket {
-                                dim: unsafe { RegEntry::new_unchecked(0) },
-                                dim_name: id![wyvern:fake].into(),
-                                seed: 0,
-                                gamemode: Gamemode::Survival.into(),
-                                is_debug: false,
-                                is_flat: false,
-                                death_loc: None,
-                                portal_cooldown: VarInt::from(0),
-                                sea_level: VarInt::from(64),
-                                prev_gamemode: Gamemode::Survival.into(),
-                                data_kept: RespawnDataKept {
-                                    keep_attributes: true,
-                                    keep_metadata: true,
-                                },
-                            });
-                            this.associated_data.loaded_chunks.clear();
-
-                            this.set(
-                                PlayerComponents::HEALTH,
-                                HealthComponent {
-                                    food: 20,
-                                    saturation: 20.0,
-                                    health: 20.0,
-                                },
-                            );
-                            this.write_packet(GameEventS2CPlayPacket {
-                                event: GameEvent::WaitForChunks,
-                                value: 0.0,
-                            });
-
-                            this.set(
-                                PlayerComponents::TELEPORT_POSITION,
-                                this.get(PlayerComponents::POSITION)? + DVec3::new(0.0, 0.1, 0.0),
-                            );
-                            Server::get()?.spawn_event(PlayerRespawnEvent {
-                                player: this.as_actor(),
-                            })?;
+                            this.perform_respawn()?;
                         }
                         CommandAction::RequestStats => {
                             // todo: send statistics packet
@@ -488,44 +514,91 @@ impl ConnectionData {
         )
     }
 
+    /// Returns true if the held item is on cooldown and actions using it should be ignored.
+    pub(crate) fn held_item_on_cooldown(&mut self) -> ActorResult<bool> {
+        let held = self
+            .associated_data
+            .inventory
+            .get_slot(self.associated_data.held_slot as usize)?;
+        let suppress = self.connected_server.suppress_cooldown_actions()?;
+        Ok(self
+            .associated_data
+            .item_use_suppressed(&held.kind(), suppress, Instant::now()))
+    }
+
     pub fn connect_to_new_dimension(&mut self) -> ActorResult<()> {
         log::debug!("Setting dimension...");
 
-        let key = Id::constant("null", "null");
-        let token = Token::new(Id::constant("null", "null"));
-        let token_copy = token.clone();
+        let (choice, receiver) = DimensionChoice::new();
+
+        self.persistent_data = self
+            .connected_server
+            .player_data_store()?
+            .load(self.get(PlayerComponents::UUID)?)?;
+
+        let default_gamemode = self.connected_server.default_gamemode()?;
+        self.set(PlayerComponents::GAMEMODE, default_gamemode);
 
         self.connected_server.spawn_event(PlayerJoinEvent {
             player: self.as_actor(),
-            new_dimension: token_copy,
+            new_dimension: choice,
         })?;
 
-        loop {
-            self.handle_messages();
-
-            if token.get() != key {
-                break;
+        let messages = self.receiver.clone();
+        let deadline = Instant::now() + JOIN_DIMENSION_TIMEOUT;
+        let mut gamemode_set = false;
+        let chosen = await_dimension(&receiver, &messages, deadline, |message| {
+            if let PlayerMessage::SetComponent(id, _, _) = &message
+                && id == PlayerComponents::GAMEMODE.name()
+            {
+                gamemode_set = true;
             }
-        }
+            self.handle_message(message)
+        });
 
-        self.associated_data.dimension = self.connected_server.dimension(token.get()).ok();
+        let reason = match &chosen {
+            Ok(id) => {
+                self.associated_data.dimension = self.connected_server.dimension(id.clone()).ok();
+                format!("The dimension {id} chosen in PlayerJoinEvent does not exist")
+            }
+            Err(JoinError::NoDimensionChosen) => {
+                "No dimension was chosen in PlayerJoinEvent".to_string()
+            }
+            Err(JoinError::TimedOut) => {
+                "Timed out waiting for a dimension to be chosen in PlayerJoinEvent".to_string()
+            }
+        };
 
         if self.associated_data.dimension.is_none() {
+            log::warn!("Disconnecting joining player: {reason}");
             let mut text = PtcText::new();
-            text.push(TextComponent::of_literal(
-                "Failed to set dimension in PlayerJoinEvent",
-            ));
+            text.push(TextComponent::of_literal(reason));
             self.write_packet(DisconnectS2CPlayPacket {
                 reason: text.to_nbt(),
             });
             return Err(ActorError::ActorIsNotLoaded);
         }
 
+        if let Some(dimension) = &self.associated_data.dimension {
+            let gamemode = join_gamemode(
+                self.get(PlayerComponents::GAMEMODE)?,
+                gamemode_set,
+                dimension.default_gamemode()?,
+            );
+            self.set(PlayerComponents::GAMEMODE, gamemode);
+        }
+
         log::debug!("Sending game events chunk packet...");
         self.write_packet(GameEventS2CPlayPacket {
             event: GameEvent::WaitForChunks,
             value: 0.0,
         });
+        self.send_client_time()?;
+        self.send_client_weather()?;
+        self.send_difficulty()?;
+        self.send_world_border()?;
+        self.send_world_spawn()?;
+        self.send_recipes()?;
 
         log::debug!("Broadcasting this player info...");
         for player in self.connected_server.connections()? {
@@ -538,7 +611,7 @@ impl ConnectionData {
                     .map(|x| ProfileProperty {
                         name: x.name.clone(),
                         value: x.value.clone(),
-                        sig: Some(x.sig.clone()),
+                        sig: (!x.sig.is_empty()).then(|| x.sig.clone()),
                     })
                     .collect::<Vec<_>>()
             } else {
@@ -613,23 +686,21 @@ impl ConnectionData {
             }
         }
 
+        log::debug!("Sending teams...");
+        for packet in self.connected_server.team_packets()? {
+            self.write_packet(packet);
+        }
+
         let entities = self
             .associated_data
             .dimension
             .as_ref()
             .unwrap()
             .all_entities()?;
-        log::debug!("Sending all entities...");
+        // Entities are sent once they're in range of the player's entity, but the client has
+        // to know about human entities' profiles before they can be added.
+        log::debug!("Sending human entity profiles...");
         for entity in entities {
-            let position = entity
-                .get(EntityComponents::POSITION)
-                .unwrap_or(DVec3::new(0.0, 0.0, 0.0));
-            let direction = entity
-                .get(EntityComponents::DIRECTION)
-                .unwrap_or(Vec2::new(0.0, 0.0));
-            let id = entity.get(EntityComponents::ENTITY_ID)?;
-            let ty = entity.get(EntityComponents::ENTITY_TYPE)?;
-
             if let Ok(skin) = entity.get(EntityComponents::PLAYER_SKIN) {
                 let name = format!("NPC_{:?}", entity.get(EntityComponents::ENTITY_ID)?);
                 let props = vec![ProfileProperty {
@@ -647,30 +718,6 @@ impl ConnectionData {
                     )],
                 });
             }
-            self.write_packet(AddEntityS2CPlayPacket {
-                id: id.into(),
-                uuid: *entity.uuid(),
-                kind: self
-                    .connected_server
-                    .registries()?
-                    .get(RegistryKeys::ENTITY_TYPE)
-                    .get_entry(ty)
-                    .unwrap(),
-                x: position[0],
-                y: position[1],
-                z: position[2],
-                pitch: Angle::of_deg(direction[0]),
-                yaw: Angle::of_deg(direction[1]),
-                head_yaw: Angle::of_deg(direction[1]),
-                data: VarInt::from(0),
-                vel_x: 0,
-                vel_y: 0,
-                vel_z: 0,
-            });
-            self.write_packet(SetEntityDataS2CPlayPacket {
-                entity: id.into(),
-                data: entity.generate_metadata()?,
-            });
         }
 
         log::debug!("Spawning human...");
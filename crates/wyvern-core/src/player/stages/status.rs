@@ -1,35 +1,51 @@
+use base64::{Engine, engine::general_purpose::STANDARD};
 use voxidian_protocol::{
     packet::{
         c2s::status::C2SStatusPackets,
         s2c::status::{
-            PongResponseS2CStatusPacket, StatusResponse, StatusResponsePlayers,
-            StatusResponseVersion,
+            PongResponseS2CStatusPacket, StatusResponse as PtcStatusResponse, StatusResponsePlayer,
+            StatusResponsePlayers, StatusResponseVersion,
         },
     },
-    value::Text,
+    value::Text as PtcText,
 };
 
-use crate::{actors::ActorResult, player::ConnectionData};
+use crate::{actors::ActorResult, player::ConnectionData, server::status::StatusRequest};
 
 impl ConnectionData {
     pub fn status_stage(&mut self) -> ActorResult<()> {
         self.read_packets(|packet: C2SStatusPackets, this| {
-            log::debug!("Packet: {:?}", packet);
             match packet {
                 C2SStatusPackets::StatusRequest(_packet) => {
+                    let handler = this.connected_server.status_handler()?;
+                    let status = handler(StatusRequest {
+                        protocol_version: this.associated_data.protocol_version,
+                        online_players: this.connected_server.players()?.len() as u32,
+                    });
+
                     this.write_packet(
-                        StatusResponse {
+                        PtcStatusResponse {
                             version: StatusResponseVersion {
-                                name: "1.21.4".to_string(),
-                                protocol: 769,
+                                name: status.version_name,
+                                protocol: status.protocol_version,
                             },
                             players: Some(StatusResponsePlayers {
-                                online: 0,
-                                max: 100,
-                                sample: vec![],
+                                online: status.online_players,
+                                max: status.max_players,
+                                sample: status
+                                    .sample
+                                    .into_iter()
+                                    .map(|player| StatusResponsePlayer {
+                                        name: player.name,
+                                        id: player.uuid,
+                                    })
+                                    .collect(),
                             }),
-                            desc: Text::new(),
-                            favicon_png_b64: "".to_string(),
+                            desc: PtcText::from(status.description),
+                            favicon_png_b64: status
+                                .favicon
+                                .map(|png| STANDARD.encode(png))
+                                .unwrap_or_default(),
                             enforce_chat_reports: false,
                             prevent_chat_reports: true,
                         }
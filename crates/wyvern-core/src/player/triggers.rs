@@ -0,0 +1,247 @@
+use std::{
+    collections::{HashMap, HashSet},
+    time::{Duration, Instant},
+};
+
+use wyvern_components::DataComponentHolder;
+
+use wyvern_values::{Id, id};
+
+use crate::{
+    actors::ActorResult,
+    dimension::VoidBehavior,
+    entities::DamageSource,
+    events::{PlayerEnterRegionEvent, PlayerLeaveRegionEvent, PlayerVoidEvent},
+    runtime::Runtime,
+};
+
+use super::{ConnectionData, PlayerComponents};
+
+/// Which region triggers a player is inside of, and when they last entered each one.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct RegionPresence {
+    inside: HashSet<Id>,
+    entered_at: HashMap<Id, Instant>,
+}
+
+/// The region triggers a player entered and left with a single movement.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct RegionTransitions {
+    pub(crate) entered: Vec<Id>,
+    pub(crate) left: Vec<Id>,
+}
+
+impl RegionPresence {
+    /// Moves the player to be inside exactly the triggers in `at`, paired with their cooldowns.
+    /// Entering a trigger that is still on cooldown is ignored until the cooldown is over.
+    pub(crate) fn update(
+        &mut self,
+        at: impl IntoIterator<Item = (Id, Duration)>,
+        now: Instant,
+    ) -> RegionTransitions {
+        let mut transitions = RegionTransitions::default();
+        let mut inside = HashSet::new();
+        for (id, cooldown) in at {
+            if self.inside.contains(&id) {
+                inside.insert(id);
+                continue;
+            }
+            let on_cooldown = self
+                .entered_at
+                .get(&id)
+                .is_some_and(|entered| now.duration_since(*entered) < cooldown);
+            if on_cooldown {
+                continue;
+            }
+
+            self.entered_at.insert(id.clone(), now);
+            inside.insert(id.clone());
+            transitions.entered.push(id);
+        }
+
+        let previous = std::mem::replace(&mut self.inside, inside);
+        transitions.left = previous
+            .into_iter()
+            .filter(|id| !self.inside.contains(id))
+            .collect();
+        transitions
+    }
+
+    /// Leaves every trigger the player is inside of, returning them.
+    pub(crate) fn leave_all(&mut self) -> Vec<Id> {
+        std::mem::take(&mut self.inside).into_iter().collect()
+    }
+}
+
+impl ConnectionData {
+    /// Fires enter and leave events for the region triggers this player moved into or out of
+    /// since their last movement. Entering a trigger that is still on cooldown for this player
+    /// is ignored until the cooldown is over.
+    pub(crate) fn update_region_triggers(&mut self) -> ActorResult<()> {
+        let Some(dimension) = self.associated_data.dimension.clone() else {
+            return Ok(());
+        };
+        let position = self.get(PlayerComponents::POSITION)?;
+        let triggers = dimension
+            .region_triggers_at(position)?
+            .into_iter()
+            .map(|(id, trigger)| (id, trigger.cooldown));
+        let transitions = self
+            .associated_data
+            .region_triggers
+            .update(triggers, Instant::now());
+
+        for id in transitions.entered {
+            self.connected_server.spawn_event(PlayerEnterRegionEvent {
+                player: self.as_actor(),
+                dimension: dimension.clone(),
+                region: id,
+            })?;
+        }
+        for id in transitions.left {
+            self.connected_server.spawn_event(PlayerLeaveRegionEvent {
+                player: self.as_actor(),
+                dimension: dimension.clone(),
+                region: id,
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Fires leave events for every region trigger this player is inside of, for when they
+    /// leave their dimension.
+    pub(crate) fn leave_region_triggers(&mut self) -> ActorResult<()> {
+        let previous = self.associated_data.region_triggers.leave_all();
+        let Some(dimension) = self.associated_data.dimension.clone() else {
+            return Ok(());
+        };
+        for id in previous {
+            self.connected_server.spawn_event(PlayerLeaveRegionEvent {
+                player: self.as_actor(),
+                dimension: dimension.clone(),
+                region: id,
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Fires a `PlayerVoidEvent` when this player falls below the void height of their
+    /// dimension, and hurts them at most once per tick while they stay below it if the
+    /// dimension's void behavior deals damage.
+    pub(crate) fn check_void(&mut self) -> ActorResult<()> {
+        let Some(dimension) = self.associated_data.dimension.clone() else {
+            return Ok(());
+        };
+        let behavior = dimension.void_behavior()?;
+        let position = self.get(PlayerComponents::POSITION)?;
+        if behavior == VoidBehavior::Disabled || position[1] >= dimension.void_height()? {
+            self.associated_data.void_ticked_at = None;
+            return Ok(());
+        }
+
+        let now = Instant::now();
+        match self.associated_data.void_ticked_at {
+            None => {
+                self.connected_server.spawn_event(PlayerVoidEvent {
+                    player: self.as_actor(),
+                    dimension,
+                    position,
+                })?;
+            }
+            Some(ticked_at) if now.duration_since(ticked_at) < Duration::from_millis(50) => {
+                return Ok(());
+            }
+            Some(_) => {}
+        }
+        self.associated_data.void_ticked_at = Some(now);
+
+        if let VoidBehavior::Damage(amount) = behavior {
+            // Damaging a player goes through their actor, so it can't happen on this thread.
+            let player = self.as_actor();
+            Runtime::spawn_task(async move {
+                player.damage(amount, DamageSource::new(id![minecraft:out_of_world]))
+            });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use wyvern_values::id;
+
+    use super::{RegionPresence, RegionTransitions};
+
+    #[test]
+    fn entering_and_leaving_fire_once() {
+        let start = Instant::now();
+        let mut presence = RegionPresence::default();
+        let spawn = || [(id![game:spawn], Duration::ZERO)];
+
+        assert_eq!(
+            presence.update(spawn(), start),
+            RegionTransitions {
+                entered: vec![id![game:spawn]],
+                left: vec![],
+            }
+        );
+        // Moving around inside a trigger doesn't enter it again.
+        assert_eq!(
+            presence.update(spawn(), start),
+            RegionTransitions::default()
+        );
+        assert_eq!(
+            presence.update([], start),
+            RegionTransitions {
+                entered: vec![],
+                left: vec![id![game:spawn]],
+            }
+        );
+        assert_eq!(presence.update([], start), RegionTransitions::default());
+    }
+
+    #[test]
+    fn reentering_waits_for_the_cooldown() {
+        let start = Instant::now();
+        let mut presence = RegionPresence::default();
+        let portal = || [(id![game:portal], Duration::from_secs(1))];
+
+        presence.update(portal(), start);
+        presence.update([], start + Duration::from_millis(200));
+        // Still on cooldown, so the player isn't counted as inside and leaving fires nothing.
+        assert_eq!(
+            presence.update(portal(), start + Duration::from_millis(400)),
+            RegionTransitions::default()
+        );
+        assert_eq!(
+            presence.update([], start + Duration::from_millis(600)),
+            RegionTransitions::default()
+        );
+        assert_eq!(
+            presence.update(portal(), start + Duration::from_secs(1)),
+            RegionTransitions {
+                entered: vec![id![game:portal]],
+                left: vec![],
+            }
+        );
+    }
+
+    #[test]
+    fn changing_regions_leaves_the_old_one() {
+        let start = Instant::now();
+        let mut presence = RegionPresence::default();
+
+        presence.update([(id![game:lobby], Duration::ZERO)], start);
+        assert_eq!(
+            presence.update([(id![game:arena], Duration::ZERO)], start),
+            RegionTransitions {
+                entered: vec![id![game:arena]],
+                left: vec![id![game:lobby]],
+            }
+        );
+        assert_eq!(presence.leave_all(), vec![id![game:arena]]);
+        assert_eq!(presence.update([], start), RegionTransitions::default());
+    }
+}
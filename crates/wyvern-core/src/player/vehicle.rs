@@ -0,0 +1,39 @@
+use wyvern_components::DataComponentHolder;
+use wyvern_values::{DVec3, Vec2};
+
+use crate::{actors::ActorResult, entities::EntityComponents, runtime::Runtime};
+
+use super::{ConnectionData, PlayerComponents};
+
+impl ConnectionData {
+    /// Moves the vehicle the player is steering to where their client moved it. Clients only
+    /// steer vehicles they can control, such as boats and saddled horses, and only the first
+    /// passenger of a vehicle is allowed to move it.
+    pub(crate) fn move_vehicle(
+        &mut self,
+        position: DVec3,
+        yaw: f32,
+        pitch: f32,
+    ) -> ActorResult<()> {
+        let Some(dimension) = self.associated_data.dimension.clone() else {
+            return Ok(());
+        };
+        let uuid = self.get(PlayerComponents::UUID)?;
+        Runtime::spawn_task(async move {
+            let Some(vehicle) = dimension.get_entity(uuid).vehicle()? else {
+                return Ok(());
+            };
+            let driver = vehicle
+                .get(EntityComponents::PASSENGERS)
+                .unwrap_or_default()
+                .first()
+                .copied();
+            if driver != Some(uuid) {
+                return Ok(());
+            }
+            vehicle.set(EntityComponents::POSITION, position)?;
+            vehicle.set(EntityComponents::DIRECTION, Vec2::new(pitch, yaw))
+        });
+        Ok(())
+    }
+}
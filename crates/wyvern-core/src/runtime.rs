@@ -1,6 +1,7 @@
 #![allow(unused)]
 
 use std::{
+    cell::RefCell,
     collections::VecDeque,
     pin::Pin,
     sync::{LazyLock, Mutex, OnceLock},
@@ -12,7 +13,7 @@ use async_executor::{Executor, Task};
 use flume::{Receiver, Sender};
 use lockfree::queue::Queue;
 
-use crate::actors::ActorResult;
+use crate::{actors::ActorResult, server::Server};
 
 pub(crate) static GLOBAL_RUNTIME: Runtime = Runtime {
     executor: Executor::new(),
@@ -22,12 +23,33 @@ pub struct Runtime {
     pub(crate) executor: Executor<'static>,
 }
 
+thread_local! {
+    static CURRENT_SERVER: RefCell<Option<Server>> = const { RefCell::new(None) };
+}
+
 impl Runtime {
     pub fn spawn_actor<F>(func: F, name: impl Into<String>) -> JoinHandle<()>
     where
         F: FnOnce() + Send + 'static,
     {
-        Builder::new().name(name.into()).spawn(func).unwrap()
+        let server = Self::current_server();
+        Builder::new()
+            .name(name.into())
+            .spawn(move || {
+                CURRENT_SERVER.set(server);
+                func()
+            })
+            .unwrap()
+    }
+
+    /// Returns the server that owns the current actor thread or task, if any.
+    pub(crate) fn current_server() -> Option<Server> {
+        CURRENT_SERVER.with_borrow(|server| server.clone())
+    }
+
+    /// Marks the current thread as belonging to the given server.
+    pub(crate) fn enter_server(server: Server) {
+        CURRENT_SERVER.set(Some(server));
     }
 
     pub fn executor(&self) -> &'static Executor<'static> {
@@ -38,14 +60,42 @@ impl Runtime {
     where
         F: Future<Output = ActorResult<()>> + Send + Sync + 'static,
     {
-        GLOBAL_RUNTIME.executor.spawn(fut).detach();
+        Self::spawn_server_task(Self::current_server(), fut);
+    }
+
+    /// Spawns a task that belongs to the given server, so `Server::get` returns it from inside the task.
+    pub(crate) fn spawn_server_task<F>(server: Option<Server>, fut: F)
+    where
+        F: Future<Output = ActorResult<()>> + Send + Sync + 'static,
+    {
+        GLOBAL_RUNTIME
+            .executor
+            .spawn(ServerScoped {
+                server,
+                future: Box::pin(fut),
+            })
+            .detach();
     }
 
     pub fn run_async<T: Send + 'static, F>(fut: F) -> Task<ActorResult<T>>
     where
         F: Future<Output = ActorResult<T>> + Send + Sync + 'static,
     {
-        GLOBAL_RUNTIME.executor.spawn(fut)
+        Self::run_server_async(Self::current_server(), fut)
+    }
+
+    /// Runs a task that belongs to the given server, returning a handle to its result.
+    pub(crate) fn run_server_async<T: Send + 'static, F>(
+        server: Option<Server>,
+        fut: F,
+    ) -> Task<ActorResult<T>>
+    where
+        F: Future<Output = ActorResult<T>> + Send + Sync + 'static,
+    {
+        GLOBAL_RUNTIME.executor.spawn(ServerScoped {
+            server,
+            future: Box::pin(fut),
+        })
     }
 
     pub async fn yield_now() {
@@ -53,6 +103,23 @@ impl Runtime {
     }
 }
 
+/// Makes the server a task was spawned from current while the task is being polled.
+struct ServerScoped<F> {
+    server: Option<Server>,
+    future: Pin<Box<F>>,
+}
+
+impl<F: Future> Future for ServerScoped<F> {
+    type Output = F::Output;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let previous = CURRENT_SERVER.replace(self.server.clone());
+        let result = self.future.as_mut().poll(cx);
+        CURRENT_SERVER.set(previous);
+        result
+    }
+}
+
 // thanks async-std
 pub struct YieldNow(bool);
 
@@ -82,3 +149,97 @@ impl Future for NeverYield {
         Poll::Pending
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use flume::Sender;
+
+    use crate::{
+        actor,
+        actors::{Actor, ActorError, ActorResult},
+        message,
+        server::Server,
+    };
+
+    use super::{Runtime, ServerScoped};
+
+    #[actor(Counter, CounterMessage)]
+    struct CounterData {
+        sender: Sender<CounterMessage>,
+        count: u32,
+    }
+
+    #[message(Counter, CounterMessage)]
+    impl CounterData {
+        #[Increment]
+        pub fn increment(&mut self) -> ActorResult<u32> {
+            self.count += 1;
+            Ok(self.count)
+        }
+
+        #[IncrementTwice]
+        pub fn increment_twice(&mut self) -> ActorResult<u32> {
+            self.as_actor().increment()?;
+            self.as_actor().increment()
+        }
+    }
+
+    fn spawn_counter() -> (Counter, Sender<()>) {
+        let (sender, receiver) = flume::unbounded();
+        let (stop_tx, stop_rx) = flume::bounded(1);
+        let mut data = CounterData {
+            sender,
+            receiver,
+            count: 0,
+        };
+        let counter = data.as_actor();
+        std::thread::spawn(move || {
+            while stop_rx.try_recv().is_err() {
+                data.handle_messages();
+                std::thread::sleep(Duration::from_millis(1));
+            }
+        });
+        (counter, stop_tx)
+    }
+
+    #[test]
+    fn self_request_does_not_deadlock() {
+        let (counter, stop) = spawn_counter();
+        assert_eq!(counter.increment_twice(), Err(ActorError::WouldDeadlock));
+        assert_eq!(counter.increment(), Ok(1));
+        let _ = stop.send(());
+    }
+
+    #[test]
+    fn async_requests_are_answered() {
+        let (counter, stop) = spawn_counter();
+        let result = futures::executor::block_on(async {
+            counter.increment_async().await?;
+            counter.increment_async().await
+        });
+        assert_eq!(result, Ok(2));
+        let _ = stop.send(());
+    }
+
+    #[test]
+    fn tasks_see_their_own_server() {
+        let (first_tx, _first_rx) = flume::unbounded();
+        let (second_tx, _second_rx) = flume::unbounded();
+        let second = Server {
+            sender: second_tx.downgrade(),
+        };
+
+        let found = futures::executor::block_on(ServerScoped {
+            server: Some(second),
+            future: Box::pin(async { Server::get() }),
+        })
+        .unwrap();
+
+        let found = found.sender.upgrade().unwrap();
+        assert!(found.same_channel(&second_tx));
+        assert!(!found.same_channel(&first_tx));
+        assert!(Runtime::current_server().is_none());
+    }
+}
@@ -1,20 +1,43 @@
-use std::{collections::HashMap, ops::Deref, sync::Arc, thread::Builder, time::Instant};
+use std::{
+    collections::HashMap,
+    net::{Ipv4Addr, SocketAddr, SocketAddrV4},
+    ops::Deref,
+    path::PathBuf,
+    sync::Arc,
+    thread::Builder,
+    time::{Duration, Instant},
+};
 
 use voxidian_protocol::autogenerated::block_states::{
     BLOCK_STATE_DEFAULTS, BLOCK_STATE_TO_ID, BLOCK_STATES, ID_TO_BLOCK_STATE,
 };
+use wyvern_datatypes::gamemode::Gamemode;
 use wyvern_textures::TexturePack;
 use wyvern_values::Id;
 
 use crate::{
     actors::ActorResult,
-    blocks::BLOCK_STATE_KEYS,
+    blocks::{BLOCK_STATE_KEYS, INTERNED_STATES},
+    commands::{CommandNode, CommandTree},
     events::{Event, EventBus},
+    player::{
+        net::{DEFAULT_KEEP_ALIVE_INTERVAL, DEFAULT_KEEP_ALIVE_TIMEOUT},
+        packet_log::{PacketLogFilter, set_packet_log},
+    },
     plugin::Plugin,
     runtime::{GLOBAL_RUNTIME, NeverYield},
 };
 
-use super::{ServerData, dimensions::DimensionContainer, registries::RegistryContainer};
+use super::{
+    ServerData,
+    dimensions::DimensionContainer,
+    player_data::PlayerDataStore,
+    registries::RegistryContainer,
+    status::{PROTOCOL_VERSION, StatusHandler, StatusRequest, StatusResponse},
+};
+
+/// The largest compression threshold the login compression packet can carry.
+const MAX_COMPRESSION_THRESHOLD: usize = i32::MAX as usize;
 
 pub struct ServerBuilder {
     events: EventBus,
@@ -23,6 +46,23 @@ pub struct ServerBuilder {
     mojauth_enabled: bool,
     texture_pack: Option<TexturePack>,
     task_threads: usize,
+    compression_threshold: Option<usize>,
+    velocity_secret: Option<String>,
+    status: StatusHandler,
+    commands: CommandTree,
+    player_data_directory: Option<PathBuf>,
+    suppress_cooldown_actions: bool,
+    chunk_batch_size: usize,
+    target_chunks_per_tick: f32,
+    interaction_range_leeway: f64,
+    min_attack_strength: f32,
+    packet_log: Option<PacketLogFilter>,
+    address: SocketAddr,
+    default_gamemode: Gamemode,
+    console_input: bool,
+    keep_alive_interval: Duration,
+    keep_alive_timeout: Duration,
+    accepted_protocol_versions: Vec<i32>,
 }
 
 impl Default for ServerBuilder {
@@ -42,9 +82,33 @@ impl ServerBuilder {
             mojauth_enabled: true,
             texture_pack: None,
             task_threads: 1,
+            compression_threshold: Some(128),
+            velocity_secret: None,
+            status: Arc::new(|request| StatusResponse::new(&request)),
+            commands: CommandTree::default(),
+            player_data_directory: None,
+            suppress_cooldown_actions: false,
+            chunk_batch_size: 16,
+            target_chunks_per_tick: 16.0,
+            interaction_range_leeway: 1.0,
+            min_attack_strength: 0.0,
+            packet_log: None,
+            address: SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::new(127, 0, 0, 1), 25565)),
+            default_gamemode: Gamemode::Survival,
+            console_input: false,
+            keep_alive_interval: DEFAULT_KEEP_ALIVE_INTERVAL,
+            keep_alive_timeout: DEFAULT_KEEP_ALIVE_TIMEOUT,
+            accepted_protocol_versions: vec![PROTOCOL_VERSION],
         }
     }
 
+    /// Registers a handler for an event. Each handler is run as its own task on the task pool.
+    ///
+    /// Actor methods are safe to call from handlers, but the blocking variants hold onto a pool
+    /// thread until the actor responds. Handlers should prefer the `_async` variant of actor
+    /// methods (such as `Server::dimension_async`), which yield the thread while waiting.
+    /// Blocking calls made on an actor's own thread return `ActorError::WouldDeadlock` instead
+    /// of hanging.
     pub fn event<
         E: Event + 'static,
         F: 'static + Future<Output = ActorResult<()>> + Send + Sync,
@@ -58,6 +122,24 @@ impl ServerBuilder {
         self
     }
 
+    /// Registers a command with the given name. The builder receives the literal node for
+    /// the command, which arguments and executors can be attached to.
+    pub fn command<F: FnOnce(CommandNode) -> CommandNode>(
+        mut self,
+        name: &str,
+        builder: F,
+    ) -> Self {
+        self.commands.register(builder(CommandNode::literal(name)));
+        self
+    }
+
+    /// Sets the address the server listens on. Defaults to `127.0.0.1:25565`.
+    /// Each server running in the same process needs its own address.
+    pub fn address(mut self, address: impl Into<SocketAddr>) -> Self {
+        self.address = address.into();
+        self
+    }
+
     pub fn task_threads(mut self, threads: usize) -> Self {
         self.task_threads = threads;
         self
@@ -67,11 +149,122 @@ impl ServerBuilder {
         plugin.build(self)
     }
 
+    /// Sets the gamemode players are given when they join, before `PlayerJoinEvent` fires.
+    /// Defaults to survival.
+    pub fn default_gamemode(mut self, gamemode: Gamemode) -> Self {
+        self.default_gamemode = gamemode;
+        self
+    }
+
+    /// Sets whether commands typed into standard input are run as the console. Useful when
+    /// running the server in a terminal. Disabled by default.
+    pub fn console_input(mut self, enabled: bool) -> Self {
+        self.console_input = enabled;
+        self
+    }
+
     pub fn mojauth_enabled(mut self, status: bool) -> Self {
         self.mojauth_enabled = status;
         self
     }
 
+    /// Sets the minimum size in bytes a packet must be before it is compressed.
+    /// Packets smaller than the threshold are still framed, but sent uncompressed.
+    /// Passing `None` disables compression entirely. Thresholds above `i32::MAX` can't be sent
+    /// to the client and are lowered to it.
+    pub fn compression_threshold(mut self, threshold: Option<usize>) -> Self {
+        self.compression_threshold =
+            threshold.map(|threshold| threshold.min(MAX_COMPRESSION_THRESHOLD));
+        self
+    }
+
+    /// Enables Velocity modern forwarding, verifying forwarded player information with the
+    /// provided secret. Mojang authentication is skipped while this is enabled, since the proxy
+    /// has already authenticated the player.
+    pub fn velocity_secret(mut self, secret: String) -> Self {
+        self.velocity_secret = Some(secret);
+        self
+    }
+
+    /// Sets the function used to build the server list entry shown to clients.
+    pub fn status<F: Fn(StatusRequest) -> StatusResponse + Send + Sync + 'static>(
+        mut self,
+        f: F,
+    ) -> Self {
+        self.status = Arc::new(f);
+        self
+    }
+
+    /// Sets the directory persistent player data is saved to when players disconnect.
+    /// Without one, player data only lasts for as long as the server is running.
+    pub fn player_data_directory(mut self, directory: impl Into<PathBuf>) -> Self {
+        self.player_data_directory = Some(directory.into());
+        self
+    }
+
+    /// Sets whether using an item that is on cooldown is ignored. When enabled, the server
+    /// won't place blocks or fire `RightClickEvent` for items on cooldown. Disabled by default.
+    pub fn suppress_cooldown_actions(mut self, suppress: bool) -> Self {
+        self.suppress_cooldown_actions = suppress;
+        self
+    }
+
+    /// Sets how often players are sent a keep-alive, which is also how often their latency is
+    /// measured. Defaults to 5 seconds.
+    pub fn keep_alive_interval(mut self, interval: Duration) -> Self {
+        self.keep_alive_interval = interval;
+        self
+    }
+
+    /// Sets how long a player has to answer a keep-alive before they are disconnected.
+    /// Defaults to 30 seconds.
+    pub fn keep_alive_timeout(mut self, timeout: Duration) -> Self {
+        self.keep_alive_timeout = timeout;
+        self
+    }
+
+    /// Sets the most chunks sent to a player in a single chunk batch. Defaults to 16.
+    pub fn chunk_batch_size(mut self, size: usize) -> Self {
+        self.chunk_batch_size = size.max(1);
+        self
+    }
+
+    /// Sets how many chunks per tick the server aims to send each player. Clients report how
+    /// many they can keep up with, and the lower of the two is used. Defaults to 16.
+    pub fn target_chunks_per_tick(mut self, chunks: f32) -> Self {
+        self.target_chunks_per_tick = chunks;
+        self
+    }
+
+    /// Sets how many blocks past their interaction range attributes players can attack entities
+    /// and use blocks, to allow for latency. Interactions further away are ignored. Defaults to 1.
+    pub fn interaction_range_leeway(mut self, leeway: f64) -> Self {
+        self.interaction_range_leeway = leeway;
+        self
+    }
+
+    /// Sets how charged an attack has to be, from 0 to 1, for its attack event to fire. Attacks
+    /// still restart the attacker's cooldown when ignored. Defaults to 0, firing every attack.
+    pub fn min_attack_strength(mut self, strength: f32) -> Self {
+        self.min_attack_strength = strength;
+        self
+    }
+
+    /// Sets which protocol versions clients can log in with. Other clients are disconnected with
+    /// an outdated client or server message. Defaults to only `PROTOCOL_VERSION`, the version the
+    /// server implements, since other versions need a proxy to translate packets.
+    pub fn accepted_protocol_versions(mut self, versions: Vec<i32>) -> Self {
+        self.accepted_protocol_versions = versions;
+        self
+    }
+
+    /// Logs packets that pass the filter from the moment the server starts. Packet logging can
+    /// also be turned on, off, or changed later with `set_packet_log`. Defaults to off.
+    pub fn packet_log(mut self, filter: PacketLogFilter) -> Self {
+        self.packet_log = Some(filter);
+        self
+    }
+
     pub fn registries<F: FnOnce(&mut RegistryContainer)>(mut self, f: F) -> Self {
         f(&mut self.registries);
         self
@@ -83,6 +276,10 @@ impl ServerBuilder {
     }
 
     pub fn run(self) {
+        if self.packet_log.is_some() {
+            set_packet_log(self.packet_log.clone());
+        }
+
         let chan = flume::unbounded();
         let server = ServerData {
             connections: Vec::new(),
@@ -96,9 +293,26 @@ impl ServerBuilder {
 
             last_entity_id: 0,
             mojauth_enabled: self.mojauth_enabled,
+            compression_threshold: self.compression_threshold,
+            velocity_secret: self.velocity_secret,
+            status: self.status,
+            commands: Arc::new(self.commands),
+            player_data: PlayerDataStore::new(self.player_data_directory),
+            suppress_cooldown_actions: self.suppress_cooldown_actions,
+            chunk_batch_size: self.chunk_batch_size,
+            target_chunks_per_tick: self.target_chunks_per_tick,
+            interaction_range_leeway: self.interaction_range_leeway,
+            min_attack_strength: self.min_attack_strength,
             texture_pack: self.texture_pack.map(Arc::new),
 
             default_dimension: Id::constant("minecraft", "overworld"),
+            default_gamemode: self.default_gamemode,
+            address: self.address,
+            teams: HashMap::new(),
+            console_input: self.console_input,
+            keep_alive_interval: self.keep_alive_interval,
+            keep_alive_timeout: self.keep_alive_timeout,
+            accepted_protocol_versions: self.accepted_protocol_versions,
         };
 
         log::info!("Initializing some lazy values...");
@@ -108,6 +322,7 @@ impl ServerBuilder {
         let _ = BLOCK_STATE_TO_ID.deref();
         let _ = ID_TO_BLOCK_STATE.deref();
         let _ = BLOCK_STATE_KEYS.deref();
+        let _ = INTERNED_STATES.deref();
 
         for _ in 0..self.task_threads {
             let _ = Builder::new().name("AsyncEventLoop".into()).spawn(|| {
@@ -118,3 +333,33 @@ impl ServerBuilder {
         server.start();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{MAX_COMPRESSION_THRESHOLD, ServerBuilder};
+
+    fn threshold(threshold: usize) -> Option<usize> {
+        ServerBuilder::new()
+            .compression_threshold(Some(threshold))
+            .compression_threshold
+    }
+
+    #[test]
+    fn compression_thresholds_fit_the_login_packet() {
+        assert_eq!(threshold(256), Some(256));
+        assert_eq!(
+            threshold(MAX_COMPRESSION_THRESHOLD),
+            Some(MAX_COMPRESSION_THRESHOLD)
+        );
+        assert_eq!(
+            threshold(MAX_COMPRESSION_THRESHOLD + 1),
+            Some(MAX_COMPRESSION_THRESHOLD)
+        );
+        assert_eq!(
+            ServerBuilder::new()
+                .compression_threshold(None)
+                .compression_threshold,
+            None
+        );
+    }
+}
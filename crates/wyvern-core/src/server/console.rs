@@ -0,0 +1,27 @@
+use std::io::BufRead;
+
+use crate::commands::CommandSender;
+
+use super::Server;
+
+/// Reads commands from standard input a line at a time, running each one as the console.
+/// Stops once standard input is closed.
+pub(crate) fn read_console(server: Server) {
+    for line in std::io::stdin().lock().lines() {
+        let line = match line {
+            Ok(line) => line,
+            Err(e) => {
+                log::error!("Failed to read console input: {e}");
+                break;
+            }
+        };
+        let command = line.trim();
+        if command.is_empty() {
+            continue;
+        }
+        if let Err(e) = server.dispatch_command(CommandSender::Console, command) {
+            log::error!("Failed to run console command `{command}`: {e:?}");
+        }
+    }
+    log::info!("Console input has closed, no more commands will be read from it.");
+}
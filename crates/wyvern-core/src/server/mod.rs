@@ -1,5 +1,7 @@
 use std::{
-    net::{Ipv4Addr, SocketAddrV4},
+    collections::{BTreeSet, HashMap},
+    net::SocketAddr,
+    ops::Range,
     sync::{Arc, Mutex, OnceLock},
     time::{Duration, Instant},
 };
@@ -7,27 +9,44 @@ use std::{
 use crate::{
     actor,
     actors::{ActorError, ActorResult},
+    commands::{CommandResult, CommandSender, CommandTree},
     message,
     player::PlayerComponents,
 };
 use crate::{actors::Actor, runtime::Runtime};
 use dimensions::DimensionContainer;
 use flume::Sender;
+use player_data::PlayerDataStore;
 use registries::RegistryContainer;
-use voxidian_protocol::{packet::Stage, value::Uuid};
+use status::StatusHandler;
+use teams::{TeamData, TeamOptions};
+use voxidian_protocol::{
+    packet::{
+        Stage,
+        s2c::play::{SetPlayerTeamS2CPlayPacket, SystemChatS2CPlayPacket},
+    },
+    value::{Text as PtcText, Uuid},
+};
+use wyvern_datatypes::{gamemode::Gamemode, text::Text};
 use wyvern_textures::TexturePack;
 
 use crate::{
     dimension::{Dimension, DimensionData},
-    events::{DimensionCreateEvent, Event, EventBus, ServerStartEvent, ServerTickEvent},
+    events::{
+        DimensionCreateEvent, Event, EventBus, EventStats, ServerStartEvent, ServerTickEvent,
+    },
     player::{ConnectionData, ConnectionWithSignal, Player},
 };
 use wyvern_values::Id;
 
 mod builder;
 pub use builder::*;
+mod console;
 pub mod dimensions;
+mod player_data;
 pub mod registries;
+pub mod status;
+pub mod teams;
 
 static SERVER_INSTANCE: OnceLock<Server> = OnceLock::new();
 
@@ -41,26 +60,99 @@ pub(crate) struct ServerData {
     pub(crate) events: Arc<EventBus>,
     pub(crate) last_entity_id: i32,
     pub(crate) mojauth_enabled: bool,
+    pub(crate) compression_threshold: Option<usize>,
+    pub(crate) velocity_secret: Option<String>,
+    pub(crate) status: StatusHandler,
+    pub(crate) commands: Arc<CommandTree>,
+    pub(crate) player_data: Arc<PlayerDataStore>,
+    pub(crate) suppress_cooldown_actions: bool,
+    pub(crate) chunk_batch_size: usize,
+    pub(crate) target_chunks_per_tick: f32,
+    pub(crate) interaction_range_leeway: f64,
+    pub(crate) min_attack_strength: f32,
     pub(crate) texture_pack: Option<Arc<TexturePack>>,
     pub(crate) default_dimension: Id,
+    pub(crate) default_gamemode: Gamemode,
+    pub(crate) address: SocketAddr,
+    pub(crate) teams: HashMap<String, TeamData>,
+    pub(crate) console_input: bool,
+    pub(crate) keep_alive_interval: Duration,
+    pub(crate) keep_alive_timeout: Duration,
+    pub(crate) accepted_protocol_versions: Vec<i32>,
 }
 
 impl Server {
+    /// Returns the server that owns the current thread or task. Outside of any server,
+    /// this falls back to the first server that was started.
     pub fn get() -> ActorResult<Server> {
-        SERVER_INSTANCE
-            .get()
+        Runtime::current_server()
+            .or_else(|| SERVER_INSTANCE.get().cloned())
             .ok_or(ActorError::ActorDoesNotExist)
-            .cloned()
+    }
+
+    /// Runs `f` for every player in the play stage.
+    pub fn broadcast<F: FnMut(&Player) -> ActorResult<()>>(&self, mut f: F) -> ActorResult<()> {
+        for player in self.players()? {
+            match f(&player) {
+                Ok(()) | Err(ActorError::ActorHasBeenDropped) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+
+    /// Runs a command as if `sender` had typed it, waiting for it to finish. A leading `/` is
+    /// optional. Unlike commands typed by players, commands that aren't registered are never
+    /// passed on to [`PlayerCommandEvent`](crate::events::PlayerCommandEvent).
+    pub fn dispatch_command(
+        &self,
+        sender: CommandSender,
+        command: &str,
+    ) -> ActorResult<CommandResult> {
+        futures::executor::block_on(self.dispatch_command_async(sender, command))
+    }
+
+    pub async fn dispatch_command_async(
+        &self,
+        sender: CommandSender,
+        command: &str,
+    ) -> ActorResult<CommandResult> {
+        let commands = self.commands_async().await?;
+        let input = command.strip_prefix('/').unwrap_or(command).to_string();
+        Runtime::run_server_async(Some(self.clone()), async move {
+            commands.execute(sender, input).await
+        })
+        .await
+    }
+
+    /// Sends a chat message to every player in the play stage.
+    pub fn broadcast_message(&self, message: Text) -> ActorResult<()> {
+        Player::broadcast_packet(
+            &self.players()?,
+            SystemChatS2CPlayPacket {
+                content: PtcText::from(message).to_nbt(),
+                is_actionbar: false,
+            },
+        )
     }
 
     pub fn spawn_event<E: Event + Send + Sync + 'static>(&self, event: E) -> ActorResult<()> {
         let server = self.clone();
-        Runtime::spawn_task(async move {
+        Runtime::spawn_server_task(Some(self.clone()), async move {
             event.dispatch(server.event_bus().unwrap());
             Ok(())
         });
         Ok(())
     }
+
+    /// Fires an event, finishing once every handler has run. Handlers are run one at a time.
+    pub async fn fire_event_async<E: Event + Send + Sync + 'static>(
+        &self,
+        event: E,
+    ) -> ActorResult<()> {
+        let bus = self.event_bus_async().await?;
+        event.dispatch_and_wait(bus).await
+    }
 }
 
 #[message(Server, ServerMessage)]
@@ -76,6 +168,17 @@ impl ServerData {
         Ok(())
     }
 
+    #[DefaultGamemode]
+    pub fn default_gamemode(&self) -> ActorResult<Gamemode> {
+        Ok(self.default_gamemode.clone())
+    }
+
+    #[SetDefaultGamemode]
+    pub fn set_default_gamemode(&mut self, gamemode: Gamemode) -> ActorResult<()> {
+        self.default_gamemode = gamemode;
+        Ok(())
+    }
+
     #[ResourcePack]
     pub fn resource_pack(&self) -> ActorResult<Arc<TexturePack>> {
         self.texture_pack.clone().ok_or(ActorError::BadRequest)
@@ -86,6 +189,145 @@ impl ServerData {
         Ok(self.mojauth_enabled)
     }
 
+    #[CompressionThreshold]
+    pub fn compression_threshold(&self) -> ActorResult<Option<usize>> {
+        Ok(self.compression_threshold)
+    }
+
+    #[SuppressCooldownActions]
+    pub fn suppress_cooldown_actions(&self) -> ActorResult<bool> {
+        Ok(self.suppress_cooldown_actions)
+    }
+
+    #[KeepAliveSettings]
+    pub(crate) fn keep_alive_settings(&self) -> ActorResult<(Duration, Duration)> {
+        Ok((self.keep_alive_interval, self.keep_alive_timeout))
+    }
+
+    #[ChunkBatchSize]
+    pub fn chunk_batch_size(&self) -> ActorResult<usize> {
+        Ok(self.chunk_batch_size)
+    }
+
+    #[TargetChunksPerTick]
+    pub fn target_chunks_per_tick(&self) -> ActorResult<f32> {
+        Ok(self.target_chunks_per_tick)
+    }
+
+    #[InteractionRangeLeeway]
+    pub fn interaction_range_leeway(&self) -> ActorResult<f64> {
+        Ok(self.interaction_range_leeway)
+    }
+
+    #[MinAttackStrength]
+    pub fn min_attack_strength(&self) -> ActorResult<f32> {
+        Ok(self.min_attack_strength)
+    }
+
+    #[GetCommands]
+    pub fn commands(&self) -> ActorResult<Arc<CommandTree>> {
+        Ok(self.commands.clone())
+    }
+
+    #[GetStatusHandler]
+    pub(crate) fn status_handler(&self) -> ActorResult<StatusHandler> {
+        Ok(self.status.clone())
+    }
+
+    #[AcceptedProtocolVersions]
+    #[doc = "Returns the protocol versions clients are allowed to log in with."]
+    pub fn accepted_protocol_versions(&self) -> ActorResult<Vec<i32>> {
+        Ok(self.accepted_protocol_versions.clone())
+    }
+
+    #[VelocitySecret]
+    pub fn velocity_secret(&self) -> ActorResult<Option<String>> {
+        Ok(self.velocity_secret.clone())
+    }
+
+    #[GetPlayerDataStore]
+    #[doc = "Returns the store holding the persistent data of every player."]
+    pub(crate) fn player_data_store(&self) -> ActorResult<Arc<PlayerDataStore>> {
+        Ok(self.player_data.clone())
+    }
+
+    #[RegisterTeam]
+    #[doc = "Registers a team with the given options, keeping its members if it already exists. Returns whether it already existed."]
+    pub(crate) fn register_team(
+        &mut self,
+        name: String,
+        options: TeamOptions,
+    ) -> ActorResult<bool> {
+        match self.teams.get_mut(&name) {
+            Some(team) => {
+                team.options = options;
+                Ok(true)
+            }
+            None => {
+                self.teams.insert(
+                    name,
+                    TeamData {
+                        options,
+                        members: BTreeSet::new(),
+                    },
+                );
+                Ok(false)
+            }
+        }
+    }
+
+    #[UnregisterTeam]
+    pub(crate) fn unregister_team(&mut self, name: String) -> ActorResult<()> {
+        self.teams
+            .remove(&name)
+            .map(|_| ())
+            .ok_or(ActorError::BadRequest)
+    }
+
+    #[GetTeamData]
+    pub(crate) fn team_data(&self, name: String) -> ActorResult<TeamData> {
+        self.teams.get(&name).cloned().ok_or(ActorError::BadRequest)
+    }
+
+    #[GetTeamNameOf]
+    pub(crate) fn team_name_of(&self, entry: String) -> ActorResult<Option<String>> {
+        Ok(self
+            .teams
+            .iter()
+            .find(|(_, team)| team.members.contains(&entry))
+            .map(|(name, _)| name.clone()))
+    }
+
+    #[AddTeamMember]
+    #[doc = "Adds an entry to a team, removing it from every other team since an entry can only be in one team at a time."]
+    pub(crate) fn add_team_member(&mut self, name: String, entry: String) -> ActorResult<()> {
+        if !self.teams.contains_key(&name) {
+            return Err(ActorError::BadRequest);
+        }
+        for team in self.teams.values_mut() {
+            team.members.remove(&entry);
+        }
+        self.teams.get_mut(&name).unwrap().members.insert(entry);
+        Ok(())
+    }
+
+    #[RemoveTeamMember]
+    #[doc = "Removes an entry from a team, returning whether it was a member."]
+    pub(crate) fn remove_team_member(&mut self, name: String, entry: String) -> ActorResult<bool> {
+        let team = self.teams.get_mut(&name).ok_or(ActorError::BadRequest)?;
+        Ok(team.members.remove(&entry))
+    }
+
+    #[TeamPackets]
+    #[doc = "Returns the packets that create every team and its members, sent to players as they join."]
+    pub(crate) fn team_packets(&self) -> ActorResult<Vec<SetPlayerTeamS2CPlayPacket>> {
+        Ok(self
+            .teams
+            .iter()
+            .map(|(name, team)| team.create_packet(name))
+            .collect())
+    }
+
     #[NewEntityId]
     pub fn new_entity_id(&mut self) -> ActorResult<i32> {
         self.last_entity_id += 1;
@@ -93,11 +335,39 @@ impl ServerData {
         Ok(self.last_entity_id)
     }
 
+    #[NewEntityIds]
+    #[doc = "Returns `count` new, consecutive entity ids."]
+    pub fn new_entity_ids(&mut self, count: usize) -> ActorResult<Range<i32>> {
+        let start = self.last_entity_id + 1;
+        self.last_entity_id += count as i32;
+        Ok(start..self.last_entity_id + 1)
+    }
+
     #[GetEventBus]
     pub fn event_bus(&mut self) -> ActorResult<Arc<EventBus>> {
         Ok(self.events.clone())
     }
 
+    #[GetEventStats]
+    #[doc = "Returns the handler execution times of every event type that has been fired, keyed by the name of the event type."]
+    pub fn event_stats(&self) -> ActorResult<HashMap<String, EventStats>> {
+        Ok(self
+            .events
+            .stats
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(name, stats)| (name.to_string(), *stats))
+            .collect())
+    }
+
+    #[ResetEventStats]
+    #[doc = "Clears all recorded event handler timings."]
+    pub fn reset_event_stats(&self) -> ActorResult<()> {
+        self.events.stats.lock().unwrap().clear();
+        Ok(())
+    }
+
     #[SpawnConnectionInternal]
     pub fn spawn_connection_internal(&mut self, conn: ConnectionWithSignal) -> ActorResult<()> {
         self.connections.push(conn);
@@ -191,9 +461,8 @@ impl ServerData {
             });
         }
 
-        SERVER_INSTANCE.set(snd.clone()).unwrap_or_else(|_| {
-            log::error!("WyvernMC does not support running two servers at once. Bugs may occur.");
-        });
+        let _ = SERVER_INSTANCE.set(snd.clone());
+        Runtime::enter_server(snd.clone());
         let snd_clone = snd.clone();
         Runtime::spawn_task(async move {
             snd_clone
@@ -205,8 +474,14 @@ impl ServerData {
         });
         let snd_clone = snd.clone();
 
+        if self.console_input {
+            let server = snd.clone();
+            Runtime::spawn_actor(move || console::read_console(server), "ConsoleInputThread");
+        }
+
+        let address = self.address;
         Runtime::spawn_actor(
-            move || Self::networking_loop(snd_clone),
+            move || Self::networking_loop(snd_clone, address),
             "ServerNetworkingThread",
         );
         self.handle_loops(snd);
@@ -236,12 +511,10 @@ impl ServerData {
         }
     }
 
-    pub fn networking_loop(server: Server) {
-        let listener =
-            std::net::TcpListener::bind(SocketAddrV4::new(Ipv4Addr::new(127, 0, 0, 1), 25565))
-                .unwrap();
+    pub fn networking_loop(server: Server, address: SocketAddr) {
+        let listener = std::net::TcpListener::bind(address).unwrap();
 
-        log::info!("A server is now listening on: 127.0.0.1:25565");
+        log::info!("A server is now listening on: {address}");
         loop {
             let new_client = listener.accept();
             match new_client {
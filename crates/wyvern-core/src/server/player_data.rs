@@ -0,0 +1,154 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    thread::Builder,
+};
+
+use flume::Sender;
+use voxidian_protocol::{
+    packet::PacketBuf,
+    value::{Nbt as PtcNbt, Uuid},
+};
+use wyvern_datatypes::nbt::NbtCompound;
+
+use crate::actors::{ActorError, ActorResult};
+
+enum PlayerDataJob {
+    Load(Uuid, Sender<NbtCompound>),
+    Save(Uuid, NbtCompound),
+}
+
+/// Holds the persistent data of players, reading and writing player data files on a thread of
+/// its own so the server actor never waits on the disk. Files are read and written in the order
+/// they're requested, so a load waits for every save of the same player requested before it.
+pub(crate) struct PlayerDataStore {
+    cache: Mutex<HashMap<Uuid, NbtCompound>>,
+    jobs: Option<Sender<PlayerDataJob>>,
+}
+
+impl PlayerDataStore {
+    /// Creates a store saving to `directory`. Without one, player data only lasts for as long as
+    /// the store does.
+    pub(crate) fn new(directory: Option<PathBuf>) -> Arc<PlayerDataStore> {
+        let jobs = directory.map(|directory| {
+            let (sender, receiver) = flume::unbounded();
+            Builder::new()
+                .name("player-data".into())
+                .spawn(move || {
+                    for job in receiver.iter() {
+                        match job {
+                            PlayerDataJob::Load(uuid, reply) => {
+                                let _ = reply.send(read_player_data(&directory, uuid));
+                            }
+                            PlayerDataJob::Save(uuid, data) => {
+                                if let Err(e) = write_player_data(&directory, uuid, data) {
+                                    log::error!("Failed to save player data for {uuid}: {e}");
+                                }
+                            }
+                        }
+                    }
+                })
+                .unwrap();
+            sender
+        });
+        Arc::new(PlayerDataStore {
+            cache: Mutex::new(HashMap::new()),
+            jobs,
+        })
+    }
+
+    /// Returns the persistent data stored for the player with the given UUID, reading it from the
+    /// player data directory if it is not already loaded.
+    pub(crate) fn load(&self, uuid: Uuid) -> ActorResult<NbtCompound> {
+        if let Some(data) = self.cache.lock().unwrap().get(&uuid) {
+            return Ok(data.clone());
+        }
+        let Some(jobs) = &self.jobs else {
+            return Ok(NbtCompound::new());
+        };
+
+        let (reply, receiver) = flume::bounded(1);
+        jobs.send(PlayerDataJob::Load(uuid, reply))
+            .map_err(|_| ActorError::ActorHasBeenDropped)?;
+        let data = receiver
+            .recv()
+            .map_err(|_| ActorError::ActorHasBeenDropped)?;
+
+        // A save made while the file was being read is newer than what was read.
+        Ok(self
+            .cache
+            .lock()
+            .unwrap()
+            .entry(uuid)
+            .or_insert(data)
+            .clone())
+    }
+
+    /// Stores the persistent data of the player with the given UUID, queueing it to be written to
+    /// the player data directory if one is configured.
+    pub(crate) fn save(&self, uuid: Uuid, data: NbtCompound) {
+        self.cache.lock().unwrap().insert(uuid, data.clone());
+        if let Some(jobs) = &self.jobs {
+            let _ = jobs.send(PlayerDataJob::Save(uuid, data));
+        }
+    }
+}
+
+fn player_data_path(directory: &Path, uuid: Uuid) -> PathBuf {
+    directory.join(format!("{uuid}.nbt"))
+}
+
+fn read_player_data(directory: &Path, uuid: Uuid) -> NbtCompound {
+    std::fs::read(player_data_path(directory, uuid))
+        .ok()
+        .and_then(|bytes| NbtCompound::try_from(bytes).ok())
+        .unwrap_or_default()
+}
+
+fn write_player_data(
+    directory: &Path,
+    uuid: Uuid,
+    data: NbtCompound,
+) -> Result<(), std::io::Error> {
+    let mut buf = PacketBuf::new();
+    PtcNbt {
+        name: String::new(),
+        root: data.into(),
+    }
+    .write_named(&mut buf)
+    .map_err(|_| std::io::Error::other("player data could not be encoded"))?;
+
+    std::fs::create_dir_all(directory)?;
+    std::fs::write(player_data_path(directory, uuid), buf.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use voxidian_protocol::value::Uuid;
+    use wyvern_datatypes::compound;
+
+    use super::PlayerDataStore;
+
+    #[test]
+    fn saved_data_is_loaded_back() {
+        let directory = std::env::temp_dir().join(format!("wyvern-player-data-{}", Uuid::new_v4()));
+        let uuid = Uuid::new_v4();
+        let data = compound! {
+            coins: 12
+        };
+
+        let store = PlayerDataStore::new(Some(directory.clone()));
+        assert_eq!(store.load(uuid), Ok(Default::default()));
+        store.save(uuid, data.clone());
+        assert_eq!(store.load(uuid), Ok(data.clone()));
+        // Files are read after every save before them is written, so once an uncached player is
+        // loaded, the save above is on disk.
+        assert_eq!(store.load(Uuid::new_v4()), Ok(Default::default()));
+
+        // A fresh store has nothing cached, so this reads the file written by the first one.
+        let store = PlayerDataStore::new(Some(directory.clone()));
+        assert_eq!(store.load(uuid), Ok(data));
+        let _ = std::fs::remove_dir_all(directory);
+    }
+}
@@ -4,9 +4,11 @@ use voxidian_protocol::value::{
     Biome, CatVariant, ChickenVariant, CowVariant, DamageType, EntityModelType, EntityType,
     FrogVariant, PigVariant, SoundEvent, WolfSoundVariant,
 };
-use wyvern_datatypes::regval::{DimensionType, PaintingVariant, WolfVariant};
+use wyvern_datatypes::regval::{BannerPattern, DimensionType, PaintingVariant, WolfVariant};
 use wyvern_values::{Id, Registry, id};
 
+use crate::item::Recipe;
+
 #[allow(dead_code)]
 pub struct RegistryContainer {
     pub(crate) registries: HashMap<Id, Box<dyn Send + Sync + Any>>,
@@ -43,7 +45,15 @@ impl RegistryContainer {
             .unwrap()
     }
 
+    /// Registers a crafting recipe under the given id. Registered recipes are crafted in
+    /// players' crafting grids and shown in their recipe books, though the recipe book can't
+    /// fill the grid with them.
+    pub fn add_recipe(&mut self, id: Id, recipe: impl Into<Recipe>) {
+        self.get_mut(RegistryKeys::RECIPE).insert(id, recipe.into());
+    }
+
     pub fn add_defaults(&mut self) {
+        self.insert(RegistryKeys::BANNER_PATTERN);
         self.insert(RegistryKeys::BIOME);
         self.insert(RegistryKeys::CAT_VARIANT);
         self.insert(RegistryKeys::CHICKEN_VARIANT);
@@ -56,6 +66,7 @@ impl RegistryContainer {
         self.insert(RegistryKeys::PIG_VARIANT);
         self.insert(RegistryKeys::WOLF_SOUND_VARIANT);
         self.insert(RegistryKeys::WOLF_VARIANT);
+        self.insert(RegistryKeys::RECIPE);
 
         for entry in DamageType::vanilla_registry().entries() {
             self.get_mut(RegistryKeys::DAMAGE_TYPE).insert(
@@ -83,6 +94,16 @@ impl RegistryContainer {
         self.get_mut(RegistryKeys::DIMENSION_TYPE)
             .insert(id![minecraft:overworld], DimensionType::default());
 
+        for pattern in VANILLA_BANNER_PATTERNS {
+            self.get_mut(RegistryKeys::BANNER_PATTERN).insert(
+                Id::new("minecraft", pattern),
+                BannerPattern {
+                    asset: Id::new("minecraft", pattern),
+                    translation_key: format!("block.minecraft.banner.{}", pattern),
+                },
+            );
+        }
+
         self.get_mut(RegistryKeys::WOLF_VARIANT).insert(
             Id::empty(),
             WolfVariant {
@@ -170,6 +191,52 @@ impl RegistryContainer {
     }
 }
 
+const VANILLA_BANNER_PATTERNS: [&str; 43] = [
+    "base",
+    "border",
+    "bricks",
+    "circle",
+    "creeper",
+    "cross",
+    "curly_border",
+    "diagonal_left",
+    "diagonal_right",
+    "diagonal_up_left",
+    "diagonal_up_right",
+    "flow",
+    "flower",
+    "globe",
+    "gradient",
+    "gradient_up",
+    "guster",
+    "half_horizontal",
+    "half_horizontal_bottom",
+    "half_vertical",
+    "half_vertical_right",
+    "mojang",
+    "piglin",
+    "rhombus",
+    "skull",
+    "small_stripes",
+    "square_bottom_left",
+    "square_bottom_right",
+    "square_top_left",
+    "square_top_right",
+    "straight_cross",
+    "stripe_bottom",
+    "stripe_center",
+    "stripe_downleft",
+    "stripe_downright",
+    "stripe_left",
+    "stripe_middle",
+    "stripe_right",
+    "stripe_top",
+    "triangle_bottom",
+    "triangle_top",
+    "triangles_bottom",
+    "triangles_top",
+];
+
 impl Default for RegistryContainer {
     fn default() -> Self {
         Self::new()
@@ -215,6 +282,9 @@ impl RegistryKeys {
         RegistryKey::new(id![minecraft:frog_variant]);
     pub const WOLF_SOUND_VARIANT: RegistryKey<WolfSoundVariant> =
         RegistryKey::new(id![minecraft:wolf_sound_variant]);
+    pub const BANNER_PATTERN: RegistryKey<BannerPattern> =
+        RegistryKey::new(id![minecraft:banner_pattern]);
+    pub const RECIPE: RegistryKey<Recipe> = RegistryKey::new(id![minecraft:recipe]);
 }
 
 impl std::fmt::Debug for RegistryContainer {
@@ -0,0 +1,161 @@
+use std::sync::Arc;
+
+use wyvern_datatypes::text::Text;
+use wyvern_values::Uuid;
+
+pub const PROTOCOL_VERSION: i32 = 770;
+pub const VERSION_NAME: &str = "1.21.5";
+
+pub(crate) type StatusHandler = Arc<dyn Fn(StatusRequest) -> StatusResponse + Send + Sync>;
+
+/// Information about a client pinging the server from the multiplayer server list.
+#[derive(Debug, Clone)]
+pub struct StatusRequest {
+    pub protocol_version: i32,
+    pub online_players: u32,
+}
+
+impl StatusRequest {
+    /// Returns true if the client's protocol version matches the server's.
+    pub fn is_compatible(&self) -> bool {
+        self.protocol_version == PROTOCOL_VERSION
+    }
+}
+
+/// Whether a client's protocol version can join the server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProtocolCheck {
+    Accepted,
+    /// The client is older than every version the server accepts.
+    OutdatedClient,
+    /// The client is newer than every version the server accepts.
+    OutdatedServer,
+}
+
+impl ProtocolCheck {
+    /// Checks a client's protocol version against the versions the server accepts. Clients in
+    /// between accepted versions are told their client is outdated.
+    pub fn new(protocol_version: i32, accepted: &[i32]) -> ProtocolCheck {
+        if accepted.contains(&protocol_version) {
+            ProtocolCheck::Accepted
+        } else if accepted.iter().all(|x| protocol_version > *x) {
+            ProtocolCheck::OutdatedServer
+        } else {
+            ProtocolCheck::OutdatedClient
+        }
+    }
+
+    /// Returns the message a rejected client is disconnected with.
+    pub fn message(&self) -> Option<Text> {
+        match self {
+            ProtocolCheck::Accepted => None,
+            ProtocolCheck::OutdatedClient => Some(Text::literal(format!(
+                "Outdated client! Please use {VERSION_NAME}"
+            ))),
+            ProtocolCheck::OutdatedServer => Some(Text::literal(format!(
+                "Outdated server! I'm still on {VERSION_NAME}"
+            ))),
+        }
+    }
+}
+
+/// A player shown when hovering over the player count in the server list.
+#[derive(Debug, Clone)]
+pub struct StatusPlayer {
+    pub name: String,
+    pub uuid: Uuid,
+}
+
+/// The server list entry shown to a client.
+#[derive(Debug, Clone)]
+pub struct StatusResponse {
+    pub description: Text,
+    pub max_players: u32,
+    pub online_players: u32,
+    pub sample: Vec<StatusPlayer>,
+    /// The raw bytes of a 64x64 PNG image, which will be base64-encoded when sent.
+    pub favicon: Option<Vec<u8>>,
+    pub version_name: String,
+    pub protocol_version: i32,
+}
+
+impl StatusResponse {
+    pub fn new(request: &StatusRequest) -> StatusResponse {
+        StatusResponse {
+            description: Text::literal(""),
+            max_players: 100,
+            online_players: request.online_players,
+            sample: Vec::new(),
+            favicon: None,
+            version_name: VERSION_NAME.to_string(),
+            protocol_version: PROTOCOL_VERSION,
+        }
+    }
+
+    pub fn description(mut self, description: Text) -> Self {
+        self.description = description;
+        self
+    }
+
+    pub fn max_players(mut self, max_players: u32) -> Self {
+        self.max_players = max_players;
+        self
+    }
+
+    pub fn online_players(mut self, online_players: u32) -> Self {
+        self.online_players = online_players;
+        self
+    }
+
+    pub fn sample(mut self, sample: Vec<StatusPlayer>) -> Self {
+        self.sample = sample;
+        self
+    }
+
+    pub fn favicon(mut self, png: Vec<u8>) -> Self {
+        self.favicon = Some(png);
+        self
+    }
+
+    pub fn version(mut self, name: impl Into<String>, protocol_version: i32) -> Self {
+        self.version_name = name.into();
+        self.protocol_version = protocol_version;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PROTOCOL_VERSION, ProtocolCheck};
+
+    #[test]
+    fn mismatched_protocol_versions_are_rejected() {
+        let accepted = [PROTOCOL_VERSION];
+        assert_eq!(
+            ProtocolCheck::new(PROTOCOL_VERSION, &accepted),
+            ProtocolCheck::Accepted
+        );
+        assert_eq!(
+            ProtocolCheck::new(PROTOCOL_VERSION - 1, &accepted),
+            ProtocolCheck::OutdatedClient
+        );
+        assert_eq!(
+            ProtocolCheck::new(PROTOCOL_VERSION + 1, &accepted),
+            ProtocolCheck::OutdatedServer
+        );
+        assert!(
+            ProtocolCheck::new(PROTOCOL_VERSION + 1, &accepted)
+                .message()
+                .is_some()
+        );
+        assert_eq!(
+            ProtocolCheck::new(770, &[768, 769, 770]),
+            ProtocolCheck::Accepted
+        );
+        assert_eq!(
+            ProtocolCheck::new(2, &[1, 3]),
+            ProtocolCheck::OutdatedClient
+        );
+        assert_eq!(ProtocolCheck::new(5, &[]), ProtocolCheck::OutdatedServer);
+    }
+}
@@ -0,0 +1,267 @@
+use std::collections::BTreeSet;
+
+use voxidian_protocol::{
+    packet::s2c::play::{SetPlayerTeamS2CPlayPacket, TeamMethod, TeamParameters},
+    value::{Text as PtcText, VarInt},
+};
+use wyvern_datatypes::text::Text;
+
+use crate::{actors::ActorResult, player::Player};
+
+use super::Server;
+
+/// The named colors a team can give the names of its members.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TeamColor {
+    Black,
+    DarkBlue,
+    DarkGreen,
+    DarkAqua,
+    DarkRed,
+    DarkPurple,
+    Gold,
+    Gray,
+    DarkGray,
+    Blue,
+    Green,
+    Aqua,
+    Red,
+    LightPurple,
+    Yellow,
+    White,
+    Reset,
+}
+
+impl TeamColor {
+    fn protocol_id(self) -> i32 {
+        match self {
+            TeamColor::Reset => 21,
+            color => color as i32,
+        }
+    }
+}
+
+/// Which entities members of a team push when colliding with them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CollisionRule {
+    Always,
+    Never,
+    PushOtherTeams,
+    PushOwnTeam,
+}
+
+impl CollisionRule {
+    fn as_str(self) -> &'static str {
+        match self {
+            CollisionRule::Always => "always",
+            CollisionRule::Never => "never",
+            CollisionRule::PushOtherTeams => "pushOtherTeams",
+            CollisionRule::PushOwnTeam => "pushOwnTeam",
+        }
+    }
+}
+
+/// Who can see the name tags of a team's members.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NameTagVisibility {
+    Always,
+    Never,
+    HideForOtherTeams,
+    HideForOwnTeam,
+}
+
+impl NameTagVisibility {
+    fn as_str(self) -> &'static str {
+        match self {
+            NameTagVisibility::Always => "always",
+            NameTagVisibility::Never => "never",
+            NameTagVisibility::HideForOtherTeams => "hideForOtherTeams",
+            NameTagVisibility::HideForOwnTeam => "hideForOwnTeam",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TeamOptions {
+    pub display_name: Text,
+    pub color: TeamColor,
+    pub prefix: Text,
+    pub suffix: Text,
+    /// Whether members can attack each other. This is sent to clients, but it is up to
+    /// attack handlers to check it with `Server::team_of`.
+    pub friendly_fire: bool,
+    pub see_invisible_teammates: bool,
+    pub collision_rule: CollisionRule,
+    pub name_tag_visibility: NameTagVisibility,
+}
+
+impl Default for TeamOptions {
+    fn default() -> Self {
+        TeamOptions {
+            display_name: Text::literal(""),
+            color: TeamColor::Reset,
+            prefix: Text::literal(""),
+            suffix: Text::literal(""),
+            friendly_fire: true,
+            see_invisible_teammates: false,
+            collision_rule: CollisionRule::Always,
+            name_tag_visibility: NameTagVisibility::Always,
+        }
+    }
+}
+
+impl TeamOptions {
+    fn parameters(&self) -> TeamParameters {
+        let mut flags = 0;
+        if self.friendly_fire {
+            flags |= 0x01;
+        }
+        if self.see_invisible_teammates {
+            flags |= 0x02;
+        }
+        TeamParameters {
+            display_name: PtcText::from(self.display_name.clone()).to_nbt(),
+            options: flags,
+            name_tag_visibility: self.name_tag_visibility.as_str().into(),
+            collision_rule: self.collision_rule.as_str().into(),
+            color: VarInt::new(self.color.protocol_id()),
+            prefix: PtcText::from(self.prefix.clone()).to_nbt(),
+            suffix: PtcText::from(self.suffix.clone()).to_nbt(),
+        }
+    }
+}
+
+/// The options and members of a team, as stored by the server.
+#[derive(Debug, Clone)]
+pub(crate) struct TeamData {
+    pub(crate) options: TeamOptions,
+    /// The entries in this team, such as player usernames or entity UUIDs.
+    pub(crate) members: BTreeSet<String>,
+}
+
+impl TeamData {
+    pub(crate) fn create_packet(&self, name: &str) -> SetPlayerTeamS2CPlayPacket {
+        SetPlayerTeamS2CPlayPacket {
+            name: name.into(),
+            method: TeamMethod::Create {
+                parameters: self.options.parameters(),
+                entities: self.members.iter().cloned().collect::<Vec<_>>().into(),
+            },
+        }
+    }
+}
+
+/// A handle to a team registered on a server. Teams persist until removed, and are sent
+/// to players as they join.
+#[derive(Debug, Clone)]
+pub struct Team {
+    pub(crate) server: Server,
+    pub(crate) name: String,
+}
+
+impl Team {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn options(&self) -> ActorResult<TeamOptions> {
+        Ok(self.server.team_data(self.name.clone())?.options)
+    }
+
+    pub fn set_options(&self, options: TeamOptions) -> ActorResult<()> {
+        self.server
+            .register_team(self.name.clone(), options.clone())?;
+        self.broadcast(TeamMethod::Update {
+            parameters: options.parameters(),
+        })
+    }
+
+    /// Returns the entries in this team, such as player usernames or entity UUIDs.
+    pub fn members(&self) -> ActorResult<Vec<String>> {
+        Ok(self
+            .server
+            .team_data(self.name.clone())?
+            .members
+            .into_iter()
+            .collect())
+    }
+
+    /// Adds an entry to this team, removing it from any team it was in before.
+    /// Players are added by username, other entities by UUID.
+    pub fn add_member(&self, entry: impl Into<String>) -> ActorResult<()> {
+        let entry = entry.into();
+        self.server
+            .add_team_member(self.name.clone(), entry.clone())?;
+        self.broadcast(TeamMethod::AddEntities {
+            entities: vec![entry].into(),
+        })
+    }
+
+    pub fn remove_member(&self, entry: impl Into<String>) -> ActorResult<()> {
+        let entry = entry.into();
+        if self
+            .server
+            .remove_team_member(self.name.clone(), entry.clone())?
+        {
+            self.broadcast(TeamMethod::RemoveEntities {
+                entities: vec![entry].into(),
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Unregisters this team, removing it for every player.
+    pub fn remove(self) -> ActorResult<()> {
+        self.server.unregister_team(self.name.clone())?;
+        self.broadcast(TeamMethod::Remove)
+    }
+
+    fn broadcast(&self, method: TeamMethod) -> ActorResult<()> {
+        Player::broadcast_packet(
+            &self.server.players()?,
+            SetPlayerTeamS2CPlayPacket {
+                name: self.name.clone(),
+                method,
+            },
+        )
+    }
+}
+
+impl Server {
+    /// Registers a team and sends it to every player. If a team with this name already
+    /// exists, its options are replaced and its members are kept.
+    pub fn create_team(&self, name: impl Into<String>, options: TeamOptions) -> ActorResult<Team> {
+        let team = Team {
+            server: self.clone(),
+            name: name.into(),
+        };
+        if self.register_team(team.name.clone(), options.clone())? {
+            team.broadcast(TeamMethod::Update {
+                parameters: options.parameters(),
+            })?;
+        } else {
+            team.broadcast(TeamMethod::Create {
+                parameters: options.parameters(),
+                entities: Vec::new().into(),
+            })?;
+        }
+        Ok(team)
+    }
+
+    pub fn team(&self, name: impl Into<String>) -> ActorResult<Team> {
+        let name = name.into();
+        self.team_data(name.clone())?;
+        Ok(Team {
+            server: self.clone(),
+            name,
+        })
+    }
+
+    /// Returns the team an entry, such as a player's username, is a member of.
+    pub fn team_of(&self, entry: impl Into<String>) -> ActorResult<Option<Team>> {
+        Ok(self.team_name_of(entry.into())?.map(|name| Team {
+            server: self.clone(),
+            name,
+        }))
+    }
+}
@@ -0,0 +1,32 @@
+/// How hard the game is, which changes how much damage players take and whether hostile mobs
+/// spawn.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, Default)]
+pub enum Difficulty {
+    Peaceful,
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    /// Returns the id the protocol uses for this difficulty.
+    pub fn id(self) -> u8 {
+        match self {
+            Difficulty::Peaceful => 0,
+            Difficulty::Easy => 1,
+            Difficulty::Normal => 2,
+            Difficulty::Hard => 3,
+        }
+    }
+
+    /// Scales damage dealt to players by mobs the way vanilla does for this difficulty.
+    pub fn scale_mob_damage(self, damage: f32) -> f32 {
+        match self {
+            Difficulty::Peaceful => 0.0,
+            Difficulty::Easy => (damage / 2.0 + 1.0).min(damage),
+            Difficulty::Normal => damage,
+            Difficulty::Hard => damage * 1.5,
+        }
+    }
+}
@@ -8,6 +8,18 @@ pub enum Gamemode {
     Spectator,
 }
 
+impl Gamemode {
+    /// Returns the id the protocol uses for this gamemode.
+    pub fn id(&self) -> u8 {
+        match self {
+            Gamemode::Survival => 0,
+            Gamemode::Creative => 1,
+            Gamemode::Adventure => 2,
+            Gamemode::Spectator => 3,
+        }
+    }
+}
+
 impl From<Gamemode> for PtcGamemode {
     fn from(value: Gamemode) -> Self {
         match value {
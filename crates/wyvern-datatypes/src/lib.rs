@@ -1,7 +1,10 @@
+pub mod difficulty;
 pub mod gamemode;
 pub mod nbt;
 pub mod particle;
 pub mod regval;
 pub mod sound;
 pub mod text;
+pub mod title;
+pub mod weather;
 pub mod window;
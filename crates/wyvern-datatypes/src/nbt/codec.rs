@@ -0,0 +1,98 @@
+use datafix::{
+    result::{DataError, DataResult},
+    serialization::{Codec, CodecOps, DefaultCodec, ListView, MapView},
+};
+
+use super::{Nbt, NbtArray, NbtCompound};
+
+/// Encodes any NBT value as-is, so data with no fixed shape (such as block entity data) can
+/// be kept by other codecs.
+pub struct NbtCodec;
+
+impl<O: CodecOps> Codec<Nbt, O> for NbtCodec {
+    fn encode(&self, ops: &O, value: &Nbt) -> DataResult<O::T> {
+        Ok(match value {
+            Nbt::Byte(value) => ops.create_byte(value),
+            Nbt::Boolean(value) => ops.create_boolean(value),
+            Nbt::Short(value) => ops.create_short(value),
+            Nbt::Int(value) => ops.create_int(value),
+            Nbt::Long(value) => ops.create_long(value),
+            Nbt::Float(value) => ops.create_float(value),
+            Nbt::Double(value) => ops.create_double(value),
+            Nbt::String(value) => ops.create_string(value),
+            Nbt::Array(array) => ops.create_list(
+                array
+                    .iter()
+                    .map(|element| self.encode(ops, &element))
+                    .collect::<DataResult<Vec<_>>>()?,
+            ),
+            Nbt::Compound(compound) => ops.create_map(
+                compound
+                    .keys()
+                    .into_iter()
+                    .map(|key| {
+                        let element = self.encode(ops, compound.get(&key).unwrap())?;
+                        Ok((key, element))
+                    })
+                    .collect::<DataResult<Vec<_>>>()?,
+            ),
+        })
+    }
+
+    fn decode(&self, ops: &O, value: &O::T) -> DataResult<Nbt> {
+        if let Ok(map) = ops.get_map(value) {
+            let mut compound = NbtCompound::new();
+            for key in map.keys() {
+                let element = self.decode(ops, map.get(&key)?)?;
+                compound.set(key, element);
+            }
+            return Ok(Nbt::Compound(compound));
+        }
+        if let Ok(list) = ops.get_list(value) {
+            let mut array = NbtArray::new();
+            for element in list.into_iter() {
+                array
+                    .push(self.decode(ops, &element)?)
+                    .map_err(|_| DataError::unexpected_type("list of one type"))?;
+            }
+            return Ok(Nbt::Array(array));
+        }
+        ops.get_string(value)
+            .map(Nbt::String)
+            .or_else(|_| ops.get_byte(value).map(Nbt::Byte))
+            .or_else(|_| ops.get_short(value).map(Nbt::Short))
+            .or_else(|_| ops.get_int(value).map(Nbt::Int))
+            .or_else(|_| ops.get_long(value).map(Nbt::Long))
+            .or_else(|_| ops.get_float(value).map(Nbt::Float))
+            .or_else(|_| ops.get_double(value).map(Nbt::Double))
+            .or_else(|_| ops.get_boolean(value).map(Nbt::Boolean))
+    }
+}
+
+impl<O: CodecOps> DefaultCodec<O> for Nbt {
+    fn codec() -> impl Codec<Self, O> {
+        NbtCodec
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use datafix::serialization::{Codec, DefaultCodec};
+
+    use crate::nbt::{Nbt, NbtArray, NbtCompound, NbtOps};
+
+    #[test]
+    fn nbt_round_trips() {
+        let mut items = NbtArray::new();
+        items.push(Nbt::Int(1)).unwrap();
+        items.push(Nbt::Int(2)).unwrap();
+        let mut compound = NbtCompound::new();
+        compound.set("CustomName", Nbt::String("Loot".into()));
+        compound.set("Items", Nbt::Array(items));
+        compound.set("Lock", Nbt::Byte(1));
+        let nbt = Nbt::Compound(compound);
+
+        let encoded = Nbt::codec().encode(&NbtOps, &nbt).unwrap();
+        assert_eq!(Nbt::codec().decode(&NbtOps, &encoded).unwrap(), nbt);
+    }
+}
@@ -1,3 +1,5 @@
+mod codec;
+pub use codec::*;
 mod ops;
 pub use ops::*;
 mod snbt;
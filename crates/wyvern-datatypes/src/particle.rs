@@ -1,10 +1,20 @@
 use voxidian_protocol::{
     registry::RegEntry,
-    value::{ParticleData, ParticleInstance, ParticleType},
+    value::{ParticleData, ParticleInstance, ParticleType, VarInt},
 };
 use wyvern_components::{DataComponentHolder, DataComponentMap, DataComponentType};
-use wyvern_values::{Id, id};
+use wyvern_values::{DVec3, Id, id};
 
+/// The particles that show a block, set with `Particle::block_state`.
+const BLOCK_PARTICLES: [Id; 5] = [
+    id![minecraft:block],
+    id![minecraft:block_marker],
+    id![minecraft:block_crumble],
+    id![minecraft:dust_pillar],
+    id![minecraft:falling_dust],
+];
+
+#[derive(Clone, Debug)]
 pub struct Particle {
     components: DataComponentMap,
 }
@@ -15,12 +25,35 @@ impl Particle {
             components: DataComponentMap::new().with(ParticleComponents::TYPE, id),
         }
     }
+
+    /// Sets the block shown by block particles such as `minecraft:block` and
+    /// `minecraft:falling_dust`, as a protocol block state id.
+    pub fn block_state(mut self, state: i32) -> Self {
+        self.components.set(ParticleComponents::BLOCK_STATE, state);
+        self
+    }
+
+    /// Sets the RGB color of `minecraft:dust` particles. Defaults to red.
+    pub fn color(mut self, rgb: i32) -> Self {
+        self.components.set(ParticleComponents::COLOR, rgb);
+        self
+    }
+
+    /// Sets the size of `minecraft:dust` particles. Defaults to 1.
+    pub fn scale(mut self, scale: f32) -> Self {
+        self.components.set(ParticleComponents::SCALE, scale);
+        self
+    }
 }
 
 pub struct ParticleComponents;
 
 impl ParticleComponents {
     pub const TYPE: DataComponentType<Id> = DataComponentType::new(id![minecraft:particle_type]);
+    pub const BLOCK_STATE: DataComponentType<i32> =
+        DataComponentType::new(id![minecraft:block_state]);
+    pub const COLOR: DataComponentType<i32> = DataComponentType::new(id![minecraft:color]);
+    pub const SCALE: DataComponentType<f32> = DataComponentType::new(id![minecraft:scale]);
 }
 
 impl DataComponentHolder for Particle {
@@ -40,7 +73,27 @@ impl From<Particle> for ParticleInstance {
             .get(ParticleComponents::TYPE)
             .unwrap_or(Id::empty());
 
-        let data = ParticleData::None;
+        let data = if BLOCK_PARTICLES.contains(&id) {
+            ParticleData::Block(VarInt::from(
+                value
+                    .components
+                    .get(ParticleComponents::BLOCK_STATE)
+                    .unwrap_or(0),
+            ))
+        } else if id == id![minecraft:dust] {
+            ParticleData::Dust {
+                color: value
+                    .components
+                    .get(ParticleComponents::COLOR)
+                    .unwrap_or(0xFF0000),
+                scale: value
+                    .components
+                    .get(ParticleComponents::SCALE)
+                    .unwrap_or(1.0),
+            }
+        } else {
+            ParticleData::None
+        };
         ParticleInstance {
             base: ParticleType::vanilla_registry()
                 .get_entry(&id.into())
@@ -49,3 +102,54 @@ impl From<Particle> for ParticleInstance {
         }
     }
 }
+
+/// How a batch of particles is spread out when played.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ParticleOptions {
+    /// How many particles are played.
+    pub count: i32,
+    /// How far particles can be spread from the position on each axis. With a count of 0, a
+    /// single particle is played moving in this direction instead.
+    pub offset: DVec3,
+    /// How fast the particles move.
+    pub speed: f32,
+    /// Whether the particles are shown to players up to 512 blocks away instead of 32.
+    pub long_distance: bool,
+}
+
+impl Default for ParticleOptions {
+    fn default() -> Self {
+        ParticleOptions {
+            count: 1,
+            offset: DVec3::new(0.0, 0.0, 0.0),
+            speed: 0.0,
+            long_distance: true,
+        }
+    }
+}
+
+impl ParticleOptions {
+    pub fn new() -> ParticleOptions {
+        ParticleOptions::default()
+    }
+
+    pub fn count(mut self, count: i32) -> Self {
+        self.count = count;
+        self
+    }
+
+    pub fn offset(mut self, offset: DVec3) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    pub fn speed(mut self, speed: f32) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    pub fn long_distance(mut self, long_distance: bool) -> Self {
+        self.long_distance = long_distance;
+        self
+    }
+}
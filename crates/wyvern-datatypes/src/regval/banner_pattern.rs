@@ -0,0 +1,17 @@
+use voxidian_protocol::value::BannerPattern as PtcBannerPattern;
+use wyvern_values::Id;
+
+#[derive(Debug, Clone)]
+pub struct BannerPattern {
+    pub asset: Id,
+    pub translation_key: String,
+}
+
+impl From<BannerPattern> for PtcBannerPattern {
+    fn from(value: BannerPattern) -> Self {
+        PtcBannerPattern {
+            asset_id: value.asset.into(),
+            translation_key: value.translation_key,
+        }
+    }
+}
@@ -4,3 +4,5 @@ mod wolf_variant;
 pub use wolf_variant::*;
 mod dimtype;
 pub use dimtype::*;
+mod banner_pattern;
+pub use banner_pattern::*;
@@ -9,15 +9,34 @@ use wyvern_macros::generate_sounds_types;
 use wyvern_values::Id;
 
 static SOUND_REGISTRY: LazyLock<Registry<SoundEvent>> = LazyLock::new(SoundEvent::vanilla_registry);
-#[derive(Debug, Clone)]
+/// The volume slider in the client's sound options that a sound is controlled by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SoundCategory {
     Master,
+    Music,
+    Records,
+    Weather,
+    Blocks,
+    Hostile,
+    Neutral,
+    Players,
+    Ambient,
+    Voice,
 }
 
 impl From<SoundCategory> for PtcSoundCategory {
     fn from(value: SoundCategory) -> Self {
         match value {
             SoundCategory::Master => PtcSoundCategory::Master,
+            SoundCategory::Music => PtcSoundCategory::Music,
+            SoundCategory::Records => PtcSoundCategory::Records,
+            SoundCategory::Weather => PtcSoundCategory::Weather,
+            SoundCategory::Blocks => PtcSoundCategory::Blocks,
+            SoundCategory::Hostile => PtcSoundCategory::Hostile,
+            SoundCategory::Neutral => PtcSoundCategory::Neutral,
+            SoundCategory::Players => PtcSoundCategory::Players,
+            SoundCategory::Ambient => PtcSoundCategory::Ambient,
+            SoundCategory::Voice => PtcSoundCategory::Voice,
         }
     }
 }
@@ -53,6 +72,14 @@ impl Sound {
         self.category = category;
         self
     }
+
+    pub fn get_category(&self) -> SoundCategory {
+        self.category
+    }
+
+    pub fn get_name(&self) -> &Id {
+        &self.name
+    }
 }
 
 impl From<Sound> for PtcSound {
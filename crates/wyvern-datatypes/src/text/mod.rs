@@ -55,4 +55,12 @@ impl Text {
             content: TextContent::Group(vec![self, other]),
         }
     }
+
+    /// Returns the content of this text without any formatting.
+    pub fn to_plain(&self) -> String {
+        match &self.content {
+            TextContent::Literal(literal) => literal.clone(),
+            TextContent::Group(texts) => texts.iter().map(Text::to_plain).collect(),
+        }
+    }
 }
@@ -0,0 +1,56 @@
+use std::time::Duration;
+
+use crate::text::Text;
+
+/// A title shown in the middle of a player's screen, with an optional subtitle beneath it.
+#[derive(Debug, Clone)]
+pub struct Title {
+    pub title: Text,
+    pub subtitle: Text,
+    pub fade_in: Duration,
+    pub stay: Duration,
+    pub fade_out: Duration,
+}
+
+impl Title {
+    pub fn new(title: Text) -> Title {
+        Title {
+            title,
+            subtitle: Text::literal(""),
+            fade_in: Duration::from_millis(500),
+            stay: Duration::from_millis(3500),
+            fade_out: Duration::from_secs(1),
+        }
+    }
+
+    pub fn subtitle(mut self, subtitle: Text) -> Self {
+        self.subtitle = subtitle;
+        self
+    }
+
+    pub fn timings(mut self, fade_in: Duration, stay: Duration, fade_out: Duration) -> Self {
+        self.fade_in = fade_in;
+        self.stay = stay;
+        self.fade_out = fade_out;
+        self
+    }
+
+    /// Converts a duration into game ticks, rounding down.
+    pub fn ticks(duration: Duration) -> i32 {
+        (duration.as_millis() / 50).min(i32::MAX as u128) as i32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::Title;
+
+    #[test]
+    fn converts_durations_to_ticks() {
+        assert_eq!(Title::ticks(Duration::from_secs(1)), 20);
+        assert_eq!(Title::ticks(Duration::from_millis(75)), 1);
+        assert_eq!(Title::ticks(Duration::MAX), i32::MAX);
+    }
+}
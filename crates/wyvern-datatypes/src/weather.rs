@@ -0,0 +1,19 @@
+/// The weather shown to players.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, Default)]
+pub enum Weather {
+    #[default]
+    Clear,
+    Rain,
+    Thunder,
+}
+
+impl Weather {
+    /// Returns the rain and thunder levels the client fades its sky towards.
+    pub fn levels(self) -> (f32, f32) {
+        match self {
+            Weather::Clear => (0.0, 0.0),
+            Weather::Rain => (1.0, 0.0),
+            Weather::Thunder => (1.0, 1.0),
+        }
+    }
+}
@@ -1,3 +1,5 @@
+use std::ops::Range;
+
 use voxidian_protocol::packet::s2c::play::ScreenWindowKind;
 
 #[derive(Clone, Debug, PartialEq, Hash, Copy)]
@@ -8,6 +10,31 @@ pub enum InventoryKind {
     Chest4Row,
     Chest5Row,
     Chest6Row,
+    /// An anvil, with two input slots followed by the result slot.
+    Anvil,
+    /// A crafting table, with the result slot followed by the 3x3 grid of input slots.
+    Crafting,
+}
+
+impl InventoryKind {
+    /// Returns the index of the slot showing what the inputs make, if this kind of screen has
+    /// one.
+    pub fn result_slot(&self) -> Option<usize> {
+        match self {
+            InventoryKind::Anvil => Some(2),
+            InventoryKind::Crafting => Some(0),
+            _ => None,
+        }
+    }
+
+    /// Returns the indices of the slots the result slot is computed from.
+    pub fn input_slots(&self) -> Range<usize> {
+        match self {
+            InventoryKind::Anvil => 0..2,
+            InventoryKind::Crafting => 1..10,
+            _ => 0..0,
+        }
+    }
 }
 
 impl From<InventoryKind> for ScreenWindowKind {
@@ -19,6 +46,8 @@ impl From<InventoryKind> for ScreenWindowKind {
             InventoryKind::Chest4Row => ScreenWindowKind::Generic9x4,
             InventoryKind::Chest5Row => ScreenWindowKind::Generic9x5,
             InventoryKind::Chest6Row => ScreenWindowKind::Generic9x6,
+            InventoryKind::Anvil => ScreenWindowKind::Anvil,
+            InventoryKind::Crafting => ScreenWindowKind::Crafting,
         }
     }
 }
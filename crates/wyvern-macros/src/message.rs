@@ -1,7 +1,7 @@
 use core::panic;
 
 use proc_macro2::TokenStream;
-use quote::{ToTokens, quote};
+use quote::{ToTokens, format_ident, quote};
 use syn::{FnArg, Ident, ImplItem, ImplItemFn, ItemImpl, Meta, ReturnType, Type};
 
 use crate::actor::ActorInput;
@@ -100,19 +100,24 @@ pub fn message(attr: TokenStream, item: TokenStream) -> TokenStream {
         }
 
         impl crate::actors::Actor for #target_type {
+            type Message = #attr_message_type;
+
             fn handle_messages(&mut self) {
+                crate::actors::enter_actor(|| self.sender.clone());
                 for _ in 0..512 {
                     match self.receiver.try_recv() {
-                        Ok(v) => {
-                            match v {
-                                #(#enum_arms)*
-                            }
-                        },
+                        Ok(v) => self.handle_message(v),
                         Err(flume::TryRecvError::Empty) => { return; },
                         Err(flume::TryRecvError::Disconnected) => { return; }
                     }
                 }
             }
+
+            fn handle_message(&mut self, message: #attr_message_type) {
+                match message {
+                    #(#enum_arms)*
+                }
+            }
         }
 
         impl #target_type {
@@ -209,12 +214,19 @@ fn create_fn_from_variant(variant: &MessageVariant) -> TokenStream {
     let enum_type = variant.enum_name.clone();
     let enum_variant = variant.name.clone();
 
+    let async_name = format_ident!("{}_async", name);
+    let async_doc_attr = doc_attr_opt.iter();
+    let async_param_types = param_types.clone();
+
     let r = quote! {
         #(#doc_attr)*
         #fn_vis fn #name(&self, #(#param_names: #param_types),*) -> #rt {
             let Some(sender) = self.sender.upgrade() else {
                 return Err(ActorError::ActorHasBeenDropped);
             };
+            if crate::actors::is_current_actor(|current: &flume::Sender<#enum_type>| current.same_channel(&sender)) {
+                return Err(ActorError::WouldDeadlock);
+            }
             let (tx, mut rx) = flume::bounded(1);
             match sender.try_send(#enum_type::#enum_variant(#(#param_names,)* tx)) {
                 Ok(v) => {
@@ -239,6 +251,21 @@ fn create_fn_from_variant(variant: &MessageVariant) -> TokenStream {
                 };
             };
         }
+
+        #(#async_doc_attr)*
+        #fn_vis async fn #async_name(&self, #(#param_names: #async_param_types),*) -> #rt {
+            let Some(sender) = self.sender.upgrade() else {
+                return Err(ActorError::ActorHasBeenDropped);
+            };
+            let (tx, rx) = flume::bounded(1);
+            if sender.try_send(#enum_type::#enum_variant(#(#param_names,)* tx)).is_err() {
+                return Err(ActorError::ActorHasBeenDropped);
+            }
+            drop(sender);
+            rx.into_recv_async()
+                .await
+                .unwrap_or(Err(ActorError::ActorHasBeenDropped))
+        }
     };
     r
 }
@@ -11,6 +11,12 @@ pub trait Vec2Ext {
     fn to_3d_direction(&self) -> DVec3;
 }
 
+pub trait DVec3Ext {
+    /// Returns the yaw and pitch in degrees of something facing along this direction.
+    /// This is the inverse of `Vec2Ext::to_3d_direction`.
+    fn to_rotation(&self) -> Vec2;
+}
+
 impl Vec2Ext for Vec2 {
     fn to_3d_direction(&self) -> DVec3 {
         let yaw = (self[0].to_radians() as f64) + (PI / 2.0);
@@ -24,3 +30,33 @@ impl Vec2Ext for Vec2 {
         DVec3::new(cos_pitch * cos_yaw, -sin_pitch, cos_pitch * sin_yaw)
     }
 }
+
+impl DVec3Ext for DVec3 {
+    fn to_rotation(&self) -> Vec2 {
+        let horizontal = (self[0] * self[0] + self[2] * self[2]).sqrt();
+        let yaw = self[2].atan2(self[0]) - (PI / 2.0);
+        let pitch = (-self[1]).atan2(horizontal);
+        Vec2::new(yaw.to_degrees() as f32, pitch.to_degrees() as f32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DVec3, DVec3Ext, Vec2, Vec2Ext};
+
+    #[test]
+    fn rotation_round_trips() {
+        let rotation = DVec3::new(1.0, 0.0, 0.0).to_rotation();
+        assert!((rotation[0] + 90.0).abs() < 1.0e-4);
+        assert!(rotation[1].abs() < 1.0e-4);
+
+        let rotation = DVec3::new(0.0, -1.0, 1.0).to_rotation();
+        assert!(rotation[0].abs() < 1.0e-4);
+        assert!((rotation[1] - 45.0).abs() < 1.0e-4);
+
+        let direction = Vec2::new(30.0, -20.0).to_3d_direction();
+        let rotation = direction.to_rotation();
+        assert!((rotation[0] - 30.0).abs() < 1.0e-3);
+        assert!((rotation[1] + 20.0).abs() < 1.0e-3);
+    }
+}
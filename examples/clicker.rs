@@ -18,7 +18,6 @@ use wyvern_mc::{
         DimensionCreateEvent, PlayerJoinEvent, RightClickEvent, ServerStartEvent, ServerTickEvent,
         SwapHandsEvent,
     },
-    inventory::Inventory,
     item::{ItemComponents, ItemStack, Items},
     player::PlayerComponents,
     server::Server,
@@ -67,16 +66,20 @@ async fn on_dim_init(event: Arc<DimensionCreateEvent>) -> ActorResult<()> {
 async fn on_join(event: Arc<PlayerJoinEvent>) -> ActorResult<()> {
     event.new_dimension.set(id!(clicker:root));
 
-    event.player.inventory()?.set_slot(
-        40,
-        ItemStack::new(Items::DIAMOND)
-            .with(ItemComponents::CUSTOM_DATA, {
-                let mut compound = NbtCompound::new();
-                compound.set("clicker_data", 10.into());
-                compound
-            })
-            .with(ItemComponents::ITEM_NAME, Text::literal("Click me!")),
-    )?;
+    event
+        .player
+        .inventory()?
+        .set_slot_async(
+            40,
+            ItemStack::new(Items::DIAMOND)
+                .with(ItemComponents::CUSTOM_DATA, {
+                    let mut compound = NbtCompound::new();
+                    compound.set("clicker_data", 10.into());
+                    compound
+                })
+                .with(ItemComponents::ITEM_NAME, Text::literal("Click me!")),
+        )
+        .await?;
 
     Ok(())
 }
@@ -136,7 +139,7 @@ async fn on_right_click(event: Arc<RightClickEvent>) -> ActorResult<()> {
             .category(SoundCategory::Master),
     )?;
 
-    let item = event.player.inventory()?.get_slot(40)?;
+    let item = event.player.inventory()?.get_slot_async(40).await?;
     log::error!("{:?}", item.get(ItemComponents::CUSTOM_DATA));
     log::error!("{:?}", item.get(ItemComponents::ITEM_NAME));
     log::error!("{:?}", item.component_map());
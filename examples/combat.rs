@@ -5,17 +5,16 @@ use wyvern_mc::{
     blocks::{BlockComponents, BlockState, Blocks},
     components::DataComponentHolder,
     datatypes::{particle::Particle, sound::Sounds, text::Text},
-    entities::{AttributeContainer, Attributes, EntityComponents},
+    entities::{AttributeContainer, Attributes, DamageSource, EntityComponents, EntitySpawn},
     events::{
         DimensionCreateEvent, PlayerAttackEntityEvent, PlayerAttackPlayerEvent, PlayerJoinEvent,
         ServerStartEvent,
     },
-    inventory::Inventory,
     item::{ItemComponents, ItemStack, Items},
     player::{Player, PlayerComponents},
     runtime::Runtime,
     server::Server,
-    values::{DVec3, IVec3, Vec2Ext, id},
+    values::{DVec3, IVec3, id},
 };
 
 fn main() {
@@ -41,14 +40,11 @@ async fn on_server_start(event: Arc<ServerStartEvent>) -> ActorResult<()> {
 }
 
 async fn on_dim_init(event: Arc<DimensionCreateEvent>) -> ActorResult<()> {
-    for x in -20..20 {
-        for z in -20..20 {
-            event.dimension.set_block(
-                IVec3::new(x, 0, z),
-                BlockState::new(Blocks::GRASS_BLOCK).with(BlockComponents::SNOWY, false),
-            )?;
-        }
-    }
+    event.dimension.fill_region(
+        IVec3::new(-20, 0, -20),
+        IVec3::new(19, 0, 19),
+        BlockState::new(Blocks::GRASS_BLOCK).with(BlockComponents::SNOWY, false),
+    )?;
 
     Ok(())
 }
@@ -59,12 +55,16 @@ async fn on_join(event: Arc<PlayerJoinEvent>) -> ActorResult<()> {
         .player
         .set(PlayerComponents::POSITION, DVec3::new(0.0, 1.0, 0.0))?;
 
-    event.player.inventory()?.set_slot(
-        36,
-        ItemStack::new(Items::DIAMOND_SWORD)
-            .with(ItemComponents::ITEM_NAME, Text::literal("Diamond Sword"))
-            .with(ItemComponents::CAN_BLOCK, true),
-    )?;
+    event
+        .player
+        .inventory()?
+        .set_slot_async(
+            36,
+            ItemStack::new(Items::DIAMOND_SWORD)
+                .with(ItemComponents::ITEM_NAME, Text::literal("Diamond Sword"))
+                .with(ItemComponents::CAN_BLOCK, true),
+        )
+        .await?;
 
     event.player.set(
         PlayerComponents::ATTRIBUTES,
@@ -74,12 +74,18 @@ async fn on_join(event: Arc<PlayerJoinEvent>) -> ActorResult<()> {
     Runtime::spawn_task(async move {
         let uuid = Player::uuid_to_username("platinaknife");
         let skin = Player::get_skin_for_uuid(&uuid);
-        let entity = event.player.dimension()?.spawn_human_entity(skin)?;
-        entity.set(EntityComponents::POSITION, DVec3::new(3.0, 10.0, 3.0))?;
-        entity.set(EntityComponents::PHYSICS_ENABLED, true)?;
-        entity.set(EntityComponents::GRAVITY_ENABLED, true)?;
-
-        // entity.set(
+        // Everything the entity spawns with is sent along with it, instead of as separate
+        // updates after it appears.
+        let spawn = EntitySpawn::new(id![minecraft:player])
+            .position(DVec3::new(3.0, 10.0, 3.0))
+            .with(EntityComponents::PHYSICS_ENABLED, true)
+            .with(EntityComponents::GRAVITY_ENABLED, true)
+            .equipment(
+                EntityComponents::MAINHAND_ITEM,
+                ItemStack::new(id![minecraft:iron_axe]),
+            );
+
+        // let spawn = spawn.equipment(
         //     EntityComponents::HELMET_ITEM,
         //     ItemStack::new(Items::GOLDEN_HELMET).with(
         //         ItemComponents::EQUIPPABLE,
@@ -89,8 +95,8 @@ async fn on_join(event: Arc<PlayerJoinEvent>) -> ActorResult<()> {
         //             model: Id::new("minecraft", "gold"),
         //         },
         //     ),
-        // )?;
-        // entity.set(
+        // );
+        // let spawn = spawn.equipment(
         //     EntityComponents::CHESTPLATE_ITEM,
         //     ItemStack::new(Items::CHAINMAIL_CHESTPLATE).with(
         //         ItemComponents::EQUIPPABLE,
@@ -100,8 +106,8 @@ async fn on_join(event: Arc<PlayerJoinEvent>) -> ActorResult<()> {
         //             model: Id::new("minecraft", "chainmail"),
         //         },
         //     ),
-        // )?;
-        // entity.set(
+        // );
+        // let spawn = spawn.equipment(
         //     EntityComponents::LEGGINGS_ITEM,
         //     ItemStack::new(Items::DIAMOND_LEGGINGS).with(
         //         ItemComponents::EQUIPPABLE,
@@ -111,8 +117,8 @@ async fn on_join(event: Arc<PlayerJoinEvent>) -> ActorResult<()> {
         //             model: Id::new("minecraft", "chainmail"),
         //         },
         //     ),
-        // )?;
-        // entity.set(
+        // );
+        // let spawn = spawn.equipment(
         //     EntityComponents::BOOTS_ITEM,
         //     ItemStack::new(Items::DIAMOND_BOOTS).with(
         //         ItemComponents::EQUIPPABLE,
@@ -122,11 +128,11 @@ async fn on_join(event: Arc<PlayerJoinEvent>) -> ActorResult<()> {
         //             model: Id::new("minecraft", "diamond"),
         //         },
         //     ),
-        // )?;
-        entity.set(
-            EntityComponents::MAINHAND_ITEM,
-            ItemStack::new(id![minecraft:iron_axe]),
-        )?;
+        // );
+        event
+            .player
+            .dimension()?
+            .spawn_human_entity_with(skin, spawn)?;
 
         Ok(())
     });
@@ -135,15 +141,13 @@ async fn on_join(event: Arc<PlayerJoinEvent>) -> ActorResult<()> {
 }
 
 async fn on_attack(event: Arc<PlayerAttackEntityEvent>) -> ActorResult<()> {
-    let dir = event
-        .attacker
-        .get(PlayerComponents::DIRECTION)?
-        .to_3d_direction()
-        .map(|x| x / 2.0);
-
     event
         .victim
-        .set(EntityComponents::VELOCITY, dir.with_y(0.3))?;
+        .apply_knockback(event.attacker.get(PlayerComponents::POSITION)?, 0.4)?;
+    event.victim.damage(
+        2.0,
+        DamageSource::player_attack(event.attacker.as_entity()?),
+    )?;
 
     event
         .attacker
@@ -162,7 +166,10 @@ async fn on_attack_player(event: Arc<PlayerAttackPlayerEvent>) -> ActorResult<()
         .attacker
         .play_sound(Sounds::ENTITY_PLAYER_ATTACK_CRIT)?;
 
-    event.victim.play_sound(Sounds::ENTITY_PLAYER_HURT)?;
+    event.victim.damage(
+        2.0,
+        DamageSource::player_attack(event.attacker.as_entity()?),
+    )?;
 
     Ok(())
 }
@@ -12,7 +12,6 @@ use wyvern_mc::{
     events::{
         BreakBlockEvent, DimensionCreateEvent, PlaceBlockEvent, PlayerJoinEvent, ServerStartEvent,
     },
-    inventory::Inventory,
     item::{ItemComponents, ItemStack, Items},
     player::PlayerComponents,
     runtime::Runtime,
@@ -64,20 +63,19 @@ async fn on_dim_init(event: Arc<DimensionCreateEvent>) -> ActorResult<()> {
 async fn on_join(event: Arc<PlayerJoinEvent>) -> ActorResult<()> {
     event.new_dimension.set(id![example:root]);
     event.player.set_gamemode(Gamemode::Survival)?;
-    event
-        .player
-        .inventory()?
-        .set_slot(36, ItemStack::new(Items::DIAMOND_PICKAXE))?;
-
-    event
-        .player
-        .inventory()?
-        .set_slot(37, ItemStack::new(Items::DIAMOND_SHOVEL))?;
-
-    event.player.inventory()?.set_slot(
-        38,
-        ItemStack::new(Items::COBBLESTONE).with(ItemComponents::ITEM_COUNT, 64),
-    )?;
+    let inventory = event.player.inventory()?;
+    inventory
+        .set_slot_async(36, ItemStack::new(Items::DIAMOND_PICKAXE))
+        .await?;
+    inventory
+        .set_slot_async(37, ItemStack::new(Items::DIAMOND_SHOVEL))
+        .await?;
+    inventory
+        .set_slot_async(
+            38,
+            ItemStack::new(Items::COBBLESTONE).with(ItemComponents::ITEM_COUNT, 64),
+        )
+        .await?;
 
     Runtime::spawn_task(async move {
         std::thread::sleep(Duration::from_millis(10000));
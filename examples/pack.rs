@@ -7,7 +7,6 @@ use wyvern_mc::{
     datatypes::gamemode::Gamemode,
     entities::{AttributeContainer, Attributes},
     events::{DimensionCreateEvent, PlayerJoinEvent, ServerStartEvent},
-    inventory::Inventory,
     item::{ItemComponents, ItemStack},
     macros::server,
     player::PlayerComponents,
@@ -53,20 +52,19 @@ async fn on_dim_init(event: Arc<DimensionCreateEvent>) -> ActorResult<()> {
 async fn on_join(event: Arc<PlayerJoinEvent>) -> ActorResult<()> {
     event.new_dimension.set(id![example:root]);
     event.player.set_gamemode(Gamemode::Survival)?;
-    event
-        .player
-        .inventory()?
-        .set_slot(36, ItemStack::new(id![minecraft:diamond_pickaxe]))?;
-
-    event
-        .player
-        .inventory()?
-        .set_slot(37, ItemStack::new(id![minecraft:diamond_shovel]))?;
-
-    event.player.inventory()?.set_slot(
-        38,
-        ItemStack::new(id![minecraft:cobblestone]).with(ItemComponents::ITEM_COUNT, 64),
-    )?;
+    let inventory = event.player.inventory()?;
+    inventory
+        .set_slot_async(36, ItemStack::new(id![minecraft:diamond_pickaxe]))
+        .await?;
+    inventory
+        .set_slot_async(37, ItemStack::new(id![minecraft:diamond_shovel]))
+        .await?;
+    inventory
+        .set_slot_async(
+            38,
+            ItemStack::new(id![minecraft:cobblestone]).with(ItemComponents::ITEM_COUNT, 64),
+        )
+        .await?;
 
     Runtime::spawn_task(async move {
         std::thread::sleep(Duration::from_millis(10000));
@@ -9,7 +9,11 @@ use wyvern_mc::{
         ServerStartEvent, ServerTickEvent,
     },
     player::{HealthComponent, PlayerComponents},
-    server::{Server, registries::RegistryKeys},
+    server::{
+        Server,
+        registries::RegistryKeys,
+        teams::{CollisionRule, TeamColor, TeamOptions},
+    },
     values::{DVec3, IVec3, Id, id},
 };
 
@@ -37,6 +41,14 @@ async fn on_server_start(event: Arc<ServerStartEvent>) -> ActorResult<()> {
         .server
         .create_dimension(id!(example:root), id![minecraft:overworld])?;
     event.server.set_default_dimension(id![example:root])?;
+    event.server.create_team(
+        "runners",
+        TeamOptions {
+            color: TeamColor::Aqua,
+            collision_rule: CollisionRule::Never,
+            ..Default::default()
+        },
+    )?;
 
     Ok(())
 }
@@ -127,6 +139,11 @@ async fn on_join(event: Arc<PlayerJoinEvent>) -> ActorResult<()> {
         PlayerComponents::TELEPORT_POSITION,
         DVec3::new(0.0, 11.0, 0.0),
     )?;
+    event
+        .player
+        .server()?
+        .team("runners")?
+        .add_member(event.player.get(PlayerComponents::USERNAME)?)?;
     Ok(())
 }
 
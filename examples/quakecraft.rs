@@ -7,7 +7,7 @@ use wyvern_mc::{
     components::DataComponentHolder,
     datatypes::{
         nbt::{Nbt, NbtCompound, NbtOps},
-        particle::Particle,
+        particle::{Particle, ParticleOptions},
         regval::DimensionType,
         text::{Text, TextColor},
     },
@@ -16,10 +16,9 @@ use wyvern_mc::{
         BreakBlockEvent, ChatMessageEvent, DimensionCreateEvent, PlaceBlockEvent, PlayerJoinEvent,
         PlayerLeftClickEvent, PlayerLoadEvent, RightClickEvent, ServerStartEvent, ServerTickEvent,
     },
-    inventory::Inventory,
     item::{ItemComponents, ItemStack},
     macros::server,
-    player::{Player, PlayerComponents},
+    player::{Player, PlayerComponents, itf::PLAYER_EYE_HEIGHT},
     server::{Server, ServerBuilder, registries::RegistryKeys},
     values::{DVec3, IVec3, Vec2Ext, id},
 };
@@ -68,11 +67,15 @@ async fn on_dim_init(event: Arc<DimensionCreateEvent>) -> ActorResult<()> {
 
 async fn on_join(event: Arc<PlayerJoinEvent>) -> ActorResult<()> {
     event.new_dimension.set(id![example:root]);
-    event.player.inventory()?.set_slot(
-        36,
-        ItemStack::new(id![minecraft:iron_hoe])
-            .with(ItemComponents::ITEM_NAME, Text::literal("Railgun")),
-    )?;
+    event
+        .player
+        .inventory()?
+        .set_slot_async(
+            36,
+            ItemStack::new(id![minecraft:iron_hoe])
+                .with(ItemComponents::ITEM_NAME, Text::literal("Railgun")),
+        )
+        .await?;
     event.player.set(
         PlayerComponents::ATTRIBUTES,
         AttributeContainer::new().with(Attributes::ATTACK_SPEED, 1000.0),
@@ -100,14 +103,11 @@ async fn on_place(event: Arc<PlaceBlockEvent>) -> ActorResult<()> {
 }
 
 async fn on_chat(event: Arc<ChatMessageEvent>) -> ActorResult<()> {
-    for player in Server::get()?.players()? {
-        player.send_message(Text::literal(format!(
-            "<{}> {}",
-            event.player.get(PlayerComponents::USERNAME)?,
-            event.message
-        )))?;
-    }
-    Ok(())
+    Server::get()?.broadcast_message(Text::literal(format!(
+        "<{}> {}",
+        event.player.get(PlayerComponents::USERNAME)?,
+        event.message
+    )))
 }
 
 async fn on_tick(_event: Arc<ServerTickEvent>) -> ActorResult<()> {
@@ -136,62 +136,43 @@ async fn on_tick(_event: Arc<ServerTickEvent>) -> ActorResult<()> {
 
 async fn on_shoot(event: Arc<RightClickEvent>) -> ActorResult<()> {
     let position = event.player.get(PlayerComponents::POSITION)?;
+    let eyes = position.with_y(position[1] + PLAYER_EYE_HEIGHT);
     let direction = event
         .player
         .get(PlayerComponents::DIRECTION)?
-        .to_3d_direction()
-        .map(|x| x / 2.0);
-    let mut step = position.with_y(position[1] + 1.8);
-    let players = event.player.dimension()?.players()?;
-    for _ in 1..120 {
-        step = step
-            .with_x(step[0] + direction[0])
-            .with_y(step[1] + direction[1])
-            .with_z(step[2] + direction[2]);
-
-        for subplayer in &players {
-            let subplayer = Server::get()?.player(*subplayer)?;
-            subplayer.play_particle(step, Particle::new(id![minecraft:electric_spark]))?;
-        }
-
-        if *event
-            .player
-            .dimension()?
-            .get_block(step.floor().as_ivec3())?
-            .name()
-            != id![minecraft:air]
-        {
-            break;
-        }
-
-        for player in &players {
-            let player = Server::get()?.player(*player)?;
-            let position = player.get(PlayerComponents::POSITION)?;
-            if player.get(PlayerComponents::USERNAME)
-                == event.player.get(PlayerComponents::USERNAME)
-            {
-                continue;
-            }
-
-            let dx = (step[0] - position[0]).abs();
-            let dy = (step[1] - (position[1] + 1.0)).abs();
-            let dz = (step[2] - position[2]).abs();
-
-            if dx <= 0.5 && dz <= 0.5 && dy <= 1.0 {
-                respawn_player(&player)?;
+        .to_3d_direction();
+    let dimension = event.player.dimension()?;
+    let players = dimension.players()?;
+
+    let hit = dimension.raycast_entities(eyes, direction, 60.0)?;
+    let length = match &hit {
+        Some(hit) => hit.distance,
+        None => dimension
+            .raycast(eyes, direction, 60.0)?
+            .map_or(60.0, |hit| hit.distance),
+    };
 
-                for subplayer in &players {
-                    let subplayer = Server::get()?.player(*subplayer)?;
-                    subplayer.send_message(Text::literal(format!(
-                        "{} pommed {}",
-                        event.player.get(PlayerComponents::USERNAME)?,
-                        player.get(PlayerComponents::USERNAME)?
-                    )))?;
-                }
+    let mut step = 0.0;
+    while step < length {
+        dimension.play_particle(
+            eyes + direction * step,
+            Particle::new(id![minecraft:electric_spark]),
+            ParticleOptions::new(),
+        )?;
+        step += 0.5;
+    }
 
-                return Ok(());
-            }
-        }
+    let Some(player) = hit.and_then(|hit| hit.entity.as_player()) else {
+        return Ok(());
+    };
+    respawn_player(&player)?;
+    for subplayer in &players {
+        let subplayer = Server::get()?.player(*subplayer)?;
+        subplayer.send_message(Text::literal(format!(
+            "{} pommed {}",
+            event.player.get(PlayerComponents::USERNAME)?,
+            player.get(PlayerComponents::USERNAME)?
+        )))?;
     }
     Ok(())
 }